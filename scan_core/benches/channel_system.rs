@@ -0,0 +1,123 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use rand::{SeedableRng, rngs::SmallRng};
+use scan_core::channel_system::*;
+use scan_core::*;
+
+#[inline(always)]
+fn run_to_completion(mut cs: ChannelSystem<SmallRng>) {
+    while let Some((pg_id, action, post)) = cs
+        .possible_transitions()
+        .filter_map(|(pg_id, action, iter)| {
+            iter.into_iter()
+                .map(|v| v.last())
+                .collect::<Option<Vec<_>>>()
+                .map(|l| (pg_id, action, l))
+        })
+        .last()
+    {
+        assert!(cs.transition(pg_id, action, post.as_slice()).is_ok());
+    }
+}
+
+// A producer sends a counter to a consumer,
+// both executing a chain of internal steps between communications,
+// as FSMs do when executing their executable content.
+#[inline(always)]
+fn producer_consumer_cs(compression: bool) -> ChannelSystem<SmallRng> {
+    let mut cs = ChannelSystemBuilder::new_with_rng(SmallRng::seed_from_u64(0));
+    cs.set_compression(compression);
+    let channel = cs.new_channel(Type::Integer, Some(1));
+
+    let producer = cs.new_program_graph();
+    let counter = cs
+        .new_var(producer, Expression::Const(Val::Integer(0)))
+        .unwrap();
+    let initial = cs.new_initial_location(producer).unwrap();
+    let mut pre = initial;
+    for step in 0..10 {
+        let action = cs.new_action(producer).unwrap();
+        cs.add_effect(
+            producer,
+            action,
+            counter,
+            Expression::Sum(vec![
+                Expression::Var(counter, Type::Integer),
+                Expression::Const(Val::Integer(1)),
+            ]),
+        )
+        .unwrap();
+        let post = cs.new_location(producer).unwrap();
+        let guard = (step == 0).then(|| {
+            Expression::Less(Box::new((
+                Expression::Var(counter, Type::Integer),
+                Expression::Const(Val::Integer(100)),
+            )))
+        });
+        cs.add_transition(producer, pre, action, post, guard)
+            .unwrap();
+        pre = post;
+    }
+    let send = cs
+        .new_send(producer, channel, Expression::Var(counter, Type::Integer))
+        .unwrap();
+    cs.add_transition(producer, pre, send, initial, None)
+        .unwrap();
+
+    let consumer = cs.new_program_graph();
+    let msg = cs
+        .new_var(consumer, Expression::Const(Val::Integer(0)))
+        .unwrap();
+    let sum = cs
+        .new_var(consumer, Expression::Const(Val::Integer(0)))
+        .unwrap();
+    let initial = cs.new_initial_location(consumer).unwrap();
+    let receive = cs.new_receive(consumer, channel, msg).unwrap();
+    let mut pre = cs.new_location(consumer).unwrap();
+    cs.add_transition(consumer, initial, receive, pre, None)
+        .unwrap();
+    for step in 0..10 {
+        let action = cs.new_action(consumer).unwrap();
+        cs.add_effect(
+            consumer,
+            action,
+            sum,
+            Expression::Sum(vec![
+                Expression::Var(sum, Type::Integer),
+                Expression::Var(msg, Type::Integer),
+            ]),
+        )
+        .unwrap();
+        let post = if step == 9 {
+            initial
+        } else {
+            cs.new_location(consumer).unwrap()
+        };
+        cs.add_transition(consumer, pre, action, post, None)
+            .unwrap();
+        pre = post;
+    }
+
+    cs.build()
+}
+
+fn run(c: &mut Criterion) {
+    let css = [
+        (producer_consumer_cs(false), "producer-consumer cs"),
+        (
+            producer_consumer_cs(true),
+            "compressed producer-consumer cs",
+        ),
+    ];
+    for (cs, name) in css.iter() {
+        c.bench_with_input(
+            BenchmarkId::new("execute to termination", name),
+            cs,
+            |b, cs| {
+                b.iter(|| run_to_completion(cs.clone()));
+            },
+        );
+    }
+}
+
+criterion_group!(benches, run);
+criterion_main!(benches);
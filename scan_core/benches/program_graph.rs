@@ -1,11 +1,11 @@
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
-use rand::rngs::mock::StepRng;
+use rand::{SeedableRng, rngs::SmallRng};
 use scan_core::program_graph::*;
 use scan_core::*;
 
 #[inline(always)]
-fn run_to_completion(mut pg: ProgramGraph<StepRng>) {
-    let mut rng = StepRng::new(0, 1);
+fn run_to_completion(mut pg: ProgramGraph<SmallRng>) {
+    let mut rng = SmallRng::seed_from_u64(0);
     while let Some((action, post)) = pg
         .possible_transitions()
         .filter_map(|(a, iter)| {
@@ -21,7 +21,7 @@ fn run_to_completion(mut pg: ProgramGraph<StepRng>) {
 }
 
 #[inline(always)]
-fn simple_pg() -> ProgramGraph<StepRng> {
+fn simple_pg() -> ProgramGraph<SmallRng> {
     let mut pg = ProgramGraphBuilder::new();
     let pre = pg.new_initial_location();
     let action = pg.new_action();
@@ -31,7 +31,7 @@ fn simple_pg() -> ProgramGraph<StepRng> {
 }
 
 #[inline(always)]
-fn condition_pg() -> ProgramGraph<StepRng> {
+fn condition_pg() -> ProgramGraph<SmallRng> {
     let mut pg = ProgramGraphBuilder::new();
     let pre = pg.new_initial_location();
     let action = pg.new_action();
@@ -62,7 +62,7 @@ fn condition_pg() -> ProgramGraph<StepRng> {
 }
 
 #[inline(always)]
-fn long_pg() -> ProgramGraph<StepRng> {
+fn long_pg() -> ProgramGraph<SmallRng> {
     let mut pg = ProgramGraphBuilder::new();
     let mut pre = pg.new_initial_location();
     let action = pg.new_action();
@@ -75,7 +75,31 @@ fn long_pg() -> ProgramGraph<StepRng> {
 }
 
 #[inline(always)]
-fn counter_pg() -> ProgramGraph<StepRng> {
+fn chain_pg(compression: bool) -> ProgramGraph<SmallRng> {
+    let mut pg = ProgramGraphBuilder::new();
+    pg.set_compression(compression);
+    let mut pre = pg.new_initial_location();
+    let var = pg.new_var(Expression::Const(Val::Integer(0))).unwrap();
+    for _ in 0..10 {
+        let action = pg.new_action();
+        pg.add_effect(
+            action,
+            var,
+            Expression::Sum(vec![
+                Expression::Var(var, Type::Integer),
+                Expression::Const(Val::Integer(1)),
+            ]),
+        )
+        .unwrap();
+        let post = pg.new_location();
+        pg.add_transition(pre, action, post, None).unwrap();
+        pre = post;
+    }
+    pg.build()
+}
+
+#[inline(always)]
+fn counter_pg() -> ProgramGraph<SmallRng> {
     let mut pg = ProgramGraphBuilder::new();
     let initial = pg.new_initial_location();
     let action = pg.new_action();
@@ -100,12 +124,84 @@ fn counter_pg() -> ProgramGraph<StepRng> {
     pg.build()
 }
 
+#[inline(always)]
+fn expression_pg() -> ProgramGraph<SmallRng> {
+    let mut pg = ProgramGraphBuilder::new();
+    let initial = pg.new_initial_location();
+    let action = pg.new_action();
+    let var = pg.new_var(Expression::Const(Val::Integer(0))).unwrap();
+    let list = pg
+        .new_var(Expression::List(Type::Integer, Vec::new()))
+        .unwrap();
+    let list_type = Type::List(Box::new(Type::Integer));
+    pg.add_effect(
+        action,
+        var,
+        Expression::Sum(vec![
+            Expression::Var(var, Type::Integer),
+            Expression::Const(Val::Integer(1)),
+        ]),
+    )
+    .unwrap();
+    pg.add_effect(
+        action,
+        list,
+        Expression::Append(Box::new((
+            Expression::Var(list, list_type.clone()),
+            Expression::Var(var, Type::Integer),
+        ))),
+    )
+    .unwrap();
+    // Guards with constant subexpressions and repeated subexpressions.
+    let offset = || {
+        Expression::Sum(vec![
+            Expression::Var(var, Type::Integer),
+            Expression::Mult(vec![
+                Expression::Const(Val::Integer(2)),
+                Expression::Const(Val::Integer(3)),
+            ]),
+        ])
+    };
+    for counter in 0..10 {
+        let guard = Expression::And(vec![
+            Expression::Less(Box::new((
+                offset(),
+                Expression::Sum(vec![
+                    Expression::Const(Val::Integer(10)),
+                    Expression::Const(Val::Integer(counter)),
+                ]),
+            ))),
+            Expression::Or(vec![
+                Expression::Equal(Box::new((
+                    Expression::Mod(Box::new((offset(), Expression::Const(Val::Integer(2))))),
+                    Expression::Const(Val::Integer(0)),
+                ))),
+                Expression::Greater(Box::new((offset(), Expression::Const(Val::Integer(5))))),
+            ]),
+            Expression::Not(Box::new(Expression::Contains(Box::new((
+                Expression::Var(list, list_type.clone()),
+                Expression::Const(Val::Integer(-1)),
+            ))))),
+            Expression::LessEq(Box::new((
+                Expression::Len(Box::new(Expression::Var(list, list_type.clone()))),
+                Expression::Var(var, Type::Integer),
+            ))),
+        ]);
+        pg.add_transition(initial, action, initial, Some(guard))
+            .unwrap();
+    }
+    pg.build()
+}
+
 fn possible_transitions(c: &mut Criterion) {
     let pgs = [
         (simple_pg(), "simple pg"),
         (condition_pg(), "condition pg"),
         (long_pg(), "long pg"),
+        (chain_pg(false), "chain pg"),
+        (chain_pg(true), "compressed chain pg"),
         (counter_pg(), "counter pg"),
+        (expression_pg(), "expression pg"),
     ];
     for (pg, name) in pgs.iter() {
         c.bench_with_input(
@@ -123,7 +219,10 @@ fn run(c: &mut Criterion) {
         (simple_pg(), "simple pg"),
         (condition_pg(), "condition pg"),
         (long_pg(), "long pg"),
+        (chain_pg(false), "chain pg"),
+        (chain_pg(true), "compressed chain pg"),
         (counter_pg(), "counter pg"),
+        (expression_pg(), "expression pg"),
     ];
     for (pg, name) in pgs.iter() {
         c.bench_with_input(
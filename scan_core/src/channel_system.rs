@@ -15,6 +15,10 @@
 //!     - `receive` actions pop the value in front of the channel queue and write it onto a given PG variable;
 //!     - `probe_empty_queue` actions can only be executed if the given channel has an empty queue;
 //!     - `probe_full_queue` actions can only be executed if the given channel has a full queue;
+//! - Channels can be unreliable, according to their [`FaultModel`]:
+//!   messages can be lost, duplicated, delayed and received out of order.
+//! - Broadcast channels have no queue of their own,
+//!   and deliver the messages sent to them to all of their subscribed channels at once.
 //!
 //! Analogously to PGs, a CS is defined through a [`ChannelSystemBuilder`],
 //! by adding new PGs and channels.
@@ -108,19 +112,25 @@
 //!     .expect("transition is possible");
 //! ```
 
+mod analysis;
 mod builder;
+mod dot;
+mod symbols;
 
 use crate::program_graph::{
     Action as PgAction, Clock as PgClock, Location as PgLocation, Var as PgVar, *,
 };
 use crate::{Time, grammar::*};
+pub use analysis::CsDiagnostic;
 pub use builder::*;
+use log::{debug, trace};
 use rand::rngs::SmallRng;
 use rand::seq::{IteratorRandom, SliceRandom};
 use rand::{Rng, SeedableRng};
 use smallvec::SmallVec;
 use std::collections::VecDeque;
 use std::sync::Arc;
+pub use symbols::CsSymbolTable;
 use thiserror::Error;
 
 /// An indexing object for PGs in a CS.
@@ -188,15 +198,49 @@ pub enum Message {
     ProbeFullQueue,
 }
 
+/// A model of the faults affecting the delivery of the messages sent through a channel,
+/// used to verify a CS against unreliable communication.
+///
+/// The default model is that of a reliable channel.
+/// Faults cannot affect handshake channels.
+///
+/// See [`ChannelSystemBuilder::set_fault_model`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FaultModel {
+    /// Probability that a sent message is lost.
+    pub drop: f64,
+    /// Probability that a sent message is delivered twice,
+    /// provided that the channel has room for both copies.
+    pub duplicate: f64,
+    /// Whether messages can be received in a different order than they were sent.
+    pub reorder: bool,
+    /// Maximum delay before a sent message can be received,
+    /// chosen uniformly at random for each message.
+    /// Messages in transit still occupy the channel.
+    pub delay: Time,
+}
+
+impl FaultModel {
+    // Whether the model is well-defined, i.e., its probabilities are between 0 and 1.
+    fn is_valid(&self) -> bool {
+        (0f64..=1f64).contains(&self.drop) && (0f64..=1f64).contains(&self.duplicate)
+    }
+
+    // Whether the model admits no faults at all.
+    fn is_reliable(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 /// The error type for operations with [`ChannelSystemBuilder`]s and [`ChannelSystem`]s.
-#[derive(Debug, Clone, Copy, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum CsError {
     /// A PG within the CS returned an error of its own.
-    #[error("error from program graph {0:?}")]
-    ProgramGraph(PgId, #[source] PgError),
+    #[error("error from program graph {0}")]
+    ProgramGraph(Named<PgId>, #[source] PgError),
     /// There is no such PG in the CS.
-    #[error("program graph {0:?} does not belong to the channel system")]
-    MissingPg(PgId),
+    #[error("program graph {0} does not belong to the channel system")]
+    MissingPg(Named<PgId>),
     /// The channel is at full capacity and can accept no more incoming messages.
     #[error("channel {0:?} is at full capacity")]
     OutOfCapacity(Channel),
@@ -209,24 +253,30 @@ pub enum CsError {
     /// The channel is not empty.
     #[error("channel {0:?} is not empty")]
     NotEmpty(Channel),
+    /// The messages in the channel have been delayed and cannot be received yet.
+    #[error("messages in channel {0:?} are still in transit")]
+    InTransit(Channel),
+    /// No PG is ready to receive from the handshake channel.
+    #[error("no program graph is ready to receive from handshake {0:?}")]
+    NoReceiver(Channel),
     /// There is no such communication action in the CS.
-    #[error("communication {0:?} has not been defined")]
-    NoCommunication(Action),
+    #[error("communication {0} has not been defined")]
+    NoCommunication(Named<Action>),
     /// The action does not belong to the PG.
-    #[error("action {0:?} does not belong to program graph {1:?}")]
-    ActionNotInPg(Action, PgId),
+    #[error("action {0} does not belong to program graph {1}")]
+    ActionNotInPg(Named<Action>, Named<PgId>),
     /// The variable does not belong to the PG.
-    #[error("variable {0:?} does not belong to program graph {1:?}")]
-    VarNotInPg(Var, PgId),
+    #[error("variable {0} does not belong to program graph {1}")]
+    VarNotInPg(Named<Var>, Named<PgId>),
     /// The location does not belong to the PG.
-    #[error("location {0:?} does not belong to program graph {1:?}")]
-    LocationNotInPg(Location, PgId),
+    #[error("location {0} does not belong to program graph {1}")]
+    LocationNotInPg(Named<Location>, Named<PgId>),
     /// The clock does not belong to the PG.
-    #[error("clock {0:?} does not belong to program graph {1:?}")]
-    ClockNotInPg(Clock, PgId),
+    #[error("clock {0} does not belong to program graph {1}")]
+    ClockNotInPg(Named<Clock>, Named<PgId>),
     /// The given PGs do not match.
-    #[error("program graphs {0:?} and {1:?} do not match")]
-    DifferentPgs(PgId, PgId),
+    #[error("program graphs {0} and {1} do not match")]
+    DifferentPgs(Named<PgId>, Named<PgId>),
     /// Action is a communication.
     ///
     /// Is returned when trying to associate an effect to a communication action.
@@ -238,6 +288,25 @@ pub enum CsError {
     /// Cannot probe an handshake channel
     #[error("cannot probe handshake {0:?}")]
     ProbingHandshakeChannel(Channel),
+    /// Faults cannot affect a handshake channel.
+    #[error("handshake {0:?} cannot be unreliable")]
+    FaultyHandshakeChannel(Channel),
+    /// The fault model's probabilities are not between 0 and 1.
+    #[error("invalid fault model for {0:?}")]
+    InvalidFaultModel(Channel),
+    /// The channel is not a broadcast channel.
+    #[error("channel {0:?} is not a broadcast")]
+    NotBroadcast(Channel),
+    /// Cannot receive from or probe a broadcast channel,
+    /// but only its subscribers.
+    #[error("cannot receive from or probe broadcast {0:?}")]
+    BroadcastChannel(Channel),
+    /// Broadcast, handshake and unreliable channels cannot subscribe to a broadcast channel.
+    #[error("channel {0:?} cannot subscribe to a broadcast")]
+    InvalidSubscriber(Channel),
+    /// Faults cannot affect a broadcast channel or its subscribers.
+    #[error("broadcast {0:?} and its subscribers cannot be unreliable")]
+    UnreliableBroadcast(Channel),
     /// Cannot probe for fullness an infinite capacity channel
     #[error("cannot probe for fullness the infinite capacity {0:?}")]
     ProbingInfiniteQueue(Channel),
@@ -268,13 +337,31 @@ pub enum EventType {
     ProbeEmptyQueue,
     /// Checking whether a channel is full.
     ProbeFullQueue,
+    /// Passing a value through a handshake channel,
+    /// from the PG producing the event to the given receiving PG.
+    Handshake(PgId, Val),
+    /// Sending a value to an unreliable channel, which loses it.
+    Dropped(Val),
+    /// Sending a value to an unreliable channel, which delivers it twice.
+    Duplicated(Val),
+    /// Sending a value to an unreliable channel, which delivers it after the given delay.
+    Delayed(Val, Time),
+    /// Retrieving a value out of an unreliable channel, before messages that were sent earlier.
+    Reordered(Val),
 }
 
 #[derive(Clone)]
 struct ChannelSystemDef {
     channels: Vec<(Type, Option<usize>)>,
+    // Fault models of the unreliable channels
+    faults: Vec<Option<FaultModel>>,
+    // Subscribers of the broadcast channels
+    broadcasts: Vec<Option<Vec<Channel>>>,
     communications: Vec<(PgAction, Channel, Message)>,
     communications_pg_idxs: Vec<u16>,
+    // Whether any PG has transitions with non-default priority
+    prioritized: bool,
+    symbols: CsSymbolTable,
 }
 
 impl ChannelSystemDef {
@@ -292,6 +379,18 @@ impl ChannelSystemDef {
             })
             .ok()
     }
+
+    #[inline(always)]
+    fn is_broadcast(&self, channel: Channel) -> bool {
+        self.broadcasts[channel.0 as usize].is_some()
+    }
+
+    #[inline(always)]
+    fn subscribers(&self, channel: Channel) -> &[Channel] {
+        self.broadcasts[channel.0 as usize]
+            .as_deref()
+            .unwrap_or_default()
+    }
 }
 
 /// Representation of a CS that can be executed transition-by-transition.
@@ -308,7 +407,8 @@ pub struct ChannelSystem<R: Rng> {
     rng: R,
     time: Time,
     program_graphs: Vec<ProgramGraph<R>>,
-    message_queue: Vec<VecDeque<Val>>,
+    // Messages in the channels, with the time at which they can be received
+    message_queue: Vec<VecDeque<(Val, Time)>>,
     def: Arc<ChannelSystemDef>,
 }
 
@@ -331,12 +431,30 @@ impl<R: Rng> ChannelSystem<R> {
         self.time
     }
 
+    /// Returns the channels subscribed to the given broadcast channel,
+    /// or none if the channel is not a broadcast.
+    ///
+    /// See [`ChannelSystemBuilder::subscribe`].
+    pub fn subscribers(&self, channel: Channel) -> &[Channel] {
+        self.def.subscribers(channel)
+    }
+
+    /// Returns the symbols of the CS's objects.
+    #[inline(always)]
+    pub fn symbols(&self) -> &CsSymbolTable {
+        &self.def.symbols
+    }
+
     /// Iterates over all transitions that can be admitted in the current state.
     ///
     /// An admittable transition is characterized by the PG it executes on, the required action and the post-state
     /// (the pre-state being necessarily the current state of the machine).
     /// The (eventual) guard is guaranteed to be satisfied.
     ///
+    /// Only the admittable transitions with the highest [`Priority`] among all PGs are returned.
+    /// If any PG is in a committed location (see [`Urgency::Committed`]),
+    /// only the transitions of PGs in committed locations are returned.
+    ///
     /// See also [`ProgramGraph::possible_transitions`].
     pub fn possible_transitions(
         &self,
@@ -347,22 +465,65 @@ impl<R: Rng> ChannelSystem<R> {
             impl Iterator<Item = impl Iterator<Item = Location> + '_> + '_,
         ),
     > + '_ {
+        let level = self.priority_level();
+        let committed = self.is_committed();
         self.program_graphs
             .iter()
             .enumerate()
+            .filter(move |(_, pg)| !committed || pg.is_committed())
             .flat_map(move |(id, pg)| {
                 let pg_id = PgId(id as u16);
-                pg.possible_transitions().filter_map(move |(action, post)| {
-                    let action = Action(pg_id, action);
-                    self.check_communication(pg_id, action).ok().map(move |()| {
-                        let post = post.map(move |locs| locs.map(move |loc| Location(pg_id, loc)));
-                        (pg_id, action, post)
+                pg.possible_transitions_at(level)
+                    .filter_map(move |(action, post)| {
+                        let action = Action(pg_id, action);
+                        self.check_communication(pg_id, action).ok().map(move |()| {
+                            let post =
+                                post.map(move |locs| locs.map(move |loc| Location(pg_id, loc)));
+                            (pg_id, action, post)
+                        })
                     })
-                })
             })
     }
 
-    pub(crate) fn montecarlo_execution(&mut self, duration: Time) -> Option<Event> {
+    // The highest priority among the transitions that can be admitted in the current state, across all PGs.
+    fn priority_level(&self) -> Priority {
+        if self.def.prioritized {
+            let committed = self.is_committed();
+            self.program_graphs
+                .iter()
+                .enumerate()
+                .filter(|(_, pg)| !committed || pg.is_committed())
+                .filter_map(|(id, pg)| {
+                    let pg_id = PgId(id as u16);
+                    pg.priority_level(|action| {
+                        self.check_communication(pg_id, Action(pg_id, action))
+                            .is_ok()
+                    })
+                })
+                .max()
+                .unwrap_or_default()
+        } else {
+            0
+        }
+    }
+
+    /// Whether any PG is currently in a committed location.
+    ///
+    /// See also [`ProgramGraph::is_committed`].
+    pub fn is_committed(&self) -> bool {
+        self.program_graphs.iter().any(ProgramGraph::is_committed)
+    }
+
+    // Whether the PG is allowed to move,
+    // i.e., either it is in a committed location or no other PG is.
+    fn may_move(&self, pg_id: PgId) -> bool {
+        self.program_graphs[pg_id.0 as usize].is_committed() || !self.is_committed()
+    }
+
+    pub(crate) fn montecarlo_execution(
+        &mut self,
+        duration: Time,
+    ) -> Result<Option<Event>, CsError> {
         let mut pg_vec =
             SmallVec::<[_; 8]>::from_iter((0..self.program_graphs.len() as u16).map(PgId));
         let mut rand = SmallRng::from_rng(&mut self.rng);
@@ -373,67 +534,141 @@ impl<R: Rng> ChannelSystem<R> {
                 let (select, remainder) = pg_list.partial_shuffle(&mut self.rng, 1);
                 pg_list = remainder;
                 let pg_id = select[0];
-                while let Some((action, post_states)) = self.program_graphs[pg_id.0 as usize]
-                    .possible_transitions()
-                    .filter_map(|(action, post_state)| {
-                        if self.def.communication(Action(pg_id, action)).is_none_or(
-                            |(channel, message)| {
-                                let (_, capacity) = self.def.channels[channel.0 as usize];
-                                let queue = &self.message_queue[channel.0 as usize];
-                                // Channel capacity must never be exeeded!
-                                assert!(capacity.is_none_or(|cap| queue.len() <= cap));
-                                // NOTE FIXME currently handshake is unsupported
-                                !matches!(capacity, Some(0))
-                                    && match message {
+                while self.may_move(pg_id)
+                    && let Some((action, post_states)) = self.program_graphs[pg_id.0 as usize]
+                        .possible_transitions_at(self.priority_level())
+                        .filter_map(|(action, post_state)| {
+                            if self.def.communication(Action(pg_id, action)).is_none_or(
+                                |(channel, message)| {
+                                    let (_, capacity) = self.def.channels[channel.0 as usize];
+                                    let queue = &self.message_queue[channel.0 as usize];
+                                    // Channel capacity must never be exeeded!
+                                    assert!(capacity.is_none_or(|cap| queue.len() <= cap));
+                                    match message {
+                                        Message::Send if self.def.is_broadcast(channel) => {
+                                            Self::full_subscriber(
+                                                &self.def,
+                                                &self.message_queue,
+                                                channel,
+                                            )
+                                            .is_none()
+                                        }
+                                        Message::Send if capacity == Some(0) => {
+                                            Self::handshake_receivers(
+                                                &self.program_graphs,
+                                                &self.def,
+                                                &self.message_queue,
+                                                self.time,
+                                                pg_id,
+                                                channel,
+                                            )
+                                            .next()
+                                            .is_some()
+                                        }
                                         Message::Send => {
                                             capacity.is_none_or(|cap| queue.len() < cap)
                                         }
-                                        Message::Receive => !queue.is_empty(),
+                                        // Handshake receptions are executed together with sends
+                                        Message::Receive => Self::is_delivered(
+                                            queue,
+                                            self.def.faults[channel.0 as usize],
+                                            self.time,
+                                        ),
+                                        // Handshake channels cannot be probed
+                                        _ if capacity == Some(0) => false,
                                         Message::ProbeFullQueue => {
                                             capacity.is_some_and(|cap| queue.len() == cap)
                                         }
                                         Message::ProbeEmptyQueue => queue.is_empty(),
                                     }
-                            },
-                        ) {
-                            post_state
-                                .map(|locs| locs.choose(&mut self.rng).map(|l| Location(pg_id, l)))
-                                .collect::<Option<SmallVec<[Location; 4]>>>()
-                                .map(|s| (action, s))
-                        } else {
-                            None
-                        }
-                    })
-                    .choose(&mut rand)
+                                },
+                            ) {
+                                post_state
+                                    .map(|locs| {
+                                        locs.choose(&mut self.rng).map(|l| Location(pg_id, l))
+                                    })
+                                    .collect::<Option<SmallVec<[Location; 4]>>>()
+                                    .map(|s| (action, s))
+                            } else {
+                                None
+                            }
+                        })
+                        .choose(&mut rand)
                 {
+                    let action = Action(pg_id, action);
+                    trace!(
+                        "{} executes action {} towards {}",
+                        self.symbols().describe_pg(pg_id),
+                        self.symbols().describe_action(action),
+                        post_states
+                            .iter()
+                            .map(|loc| self.symbols().describe_location(*loc))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
                     let event = self
-                        .transition(pg_id, Action(pg_id, action), post_states.as_slice())
-                        .expect("successful transition");
+                        .transition(pg_id, action, post_states.as_slice())
+                        .inspect_err(|err| {
+                            debug!(
+                                "{} failed to execute action {}: {err}",
+                                self.symbols().describe_pg(pg_id),
+                                self.symbols().describe_action(action),
+                            )
+                        })?;
                     if event.is_some() {
-                        return event;
+                        return Ok(event);
                     }
                 }
             }
-            self.wait(1).ok()?;
+            if self.wait(1).is_err() {
+                return Ok(None);
+            }
         }
-        None
+        Ok(None)
     }
 
     fn check_communication(&self, pg_id: PgId, action: Action) -> Result<(), CsError> {
         if pg_id.0 >= self.program_graphs.len() as u16 {
-            Err(CsError::MissingPg(pg_id))
+            Err(CsError::MissingPg(self.def.symbols.named_pg(pg_id)))
         } else if action.0 != pg_id {
-            Err(CsError::ActionNotInPg(action, pg_id))
+            Err(CsError::ActionNotInPg(
+                self.def.symbols.named_action(action),
+                self.def.symbols.named_pg(pg_id),
+            ))
         } else if let Some((channel, message)) = self.def.communication(action) {
             let (_, capacity) = self.def.channels[channel.0 as usize];
             let queue = &self.message_queue[channel.0 as usize];
             // Channel capacity must never be exeeded!
             assert!(capacity.is_none_or(|cap| queue.len() <= cap));
             match message {
+                Message::Send if self.def.is_broadcast(channel) => {
+                    Self::full_subscriber(&self.def, &self.message_queue, channel)
+                        .map_or(Ok(()), |subscriber| Err(CsError::OutOfCapacity(subscriber)))
+                }
+                Message::Send if capacity == Some(0) => Self::handshake_receivers(
+                    &self.program_graphs,
+                    &self.def,
+                    &self.message_queue,
+                    self.time,
+                    pg_id,
+                    channel,
+                )
+                .next()
+                .map(|_| ())
+                .ok_or(CsError::NoReceiver(channel)),
                 Message::Send if capacity.is_some_and(|cap| queue.len() >= cap) => {
                     Err(CsError::OutOfCapacity(channel))
                 }
                 Message::Receive if queue.is_empty() => Err(CsError::Empty(channel)),
+                Message::Receive
+                    if !Self::is_delivered(
+                        queue,
+                        self.def.faults[channel.0 as usize],
+                        self.time,
+                    ) =>
+                {
+                    Err(CsError::InTransit(channel))
+                }
                 Message::ProbeEmptyQueue | Message::ProbeFullQueue
                     if matches!(capacity, Some(0)) =>
                 {
@@ -454,10 +689,107 @@ impl<R: Rng> ChannelSystem<R> {
         }
     }
 
+    // Whether some message in the queue can be received at the given time:
+    // the front message, or any message if the channel can reorder them.
+    fn is_delivered(queue: &VecDeque<(Val, Time)>, faults: Option<FaultModel>, time: Time) -> bool {
+        if faults.is_some_and(|faults| faults.reorder) {
+            queue.iter().any(|(_, delivery)| *delivery <= time)
+        } else {
+            queue.front().is_some_and(|(_, delivery)| *delivery <= time)
+        }
+    }
+
+    // A subscriber of the broadcast channel with no room for further messages, if any.
+    fn full_subscriber(
+        def: &ChannelSystemDef,
+        message_queue: &[VecDeque<(Val, Time)>],
+        channel: Channel,
+    ) -> Option<Channel> {
+        def.subscribers(channel).iter().copied().find(|subscriber| {
+            let (_, capacity) = def.channels[subscriber.0 as usize];
+            capacity.is_some_and(|cap| message_queue[subscriber.0 as usize].len() >= cap)
+        })
+    }
+
+    // Receiving transitions from the handshake channel admissible by PGs other than the sender,
+    // given by the receiving PG, action and possible post-states of each of its processes.
+    // Receiving PGs only take transitions with their own highest priority among the enabled ones.
+    fn handshake_receivers<'a>(
+        program_graphs: &'a [ProgramGraph<R>],
+        def: &'a ChannelSystemDef,
+        message_queue: &'a [VecDeque<(Val, Time)>],
+        time: Time,
+        sender: PgId,
+        channel: Channel,
+    ) -> impl Iterator<Item = (PgId, PgAction, Vec<Vec<PgLocation>>)> + 'a {
+        program_graphs
+            .iter()
+            .enumerate()
+            .filter(move |(id, _)| *id != sender.0 as usize)
+            .flat_map(move |(id, pg)| {
+                let pg_id = PgId(id as u16);
+                let receives = move |action: PgAction| {
+                    def.communication(Action(pg_id, action)) == Some((channel, Message::Receive))
+                };
+                let level = if def.prioritized {
+                    pg.priority_level(|action| {
+                        receives(action)
+                            || Self::is_enabled(def, message_queue, time, Action(pg_id, action))
+                    })
+                    .unwrap_or_default()
+                } else {
+                    0
+                };
+                pg.possible_transitions_at(level)
+                    .filter(move |(action, _)| receives(*action))
+                    .filter_map(move |(action, post)| {
+                        let post = post.map(Vec::from_iter).collect::<Vec<_>>();
+                        post.iter()
+                            .all(|locs| !locs.is_empty())
+                            .then_some((pg_id, action, post))
+                    })
+            })
+    }
+
+    // Whether the communication of the action (if any) can take place in the current state.
+    // Sends to handshake channels are not considered enabled,
+    // as they depend on the state of the other PGs.
+    fn is_enabled(
+        def: &ChannelSystemDef,
+        message_queue: &[VecDeque<(Val, Time)>],
+        time: Time,
+        action: Action,
+    ) -> bool {
+        def.communication(action).is_none_or(|(channel, message)| {
+            let (_, capacity) = def.channels[channel.0 as usize];
+            let queue = &message_queue[channel.0 as usize];
+            match message {
+                Message::Send if def.is_broadcast(channel) => {
+                    Self::full_subscriber(def, message_queue, channel).is_none()
+                }
+                _ if capacity == Some(0) => false,
+                Message::Send => capacity.is_none_or(|cap| queue.len() < cap),
+                Message::Receive => Self::is_delivered(queue, def.faults[channel.0 as usize], time),
+                Message::ProbeFullQueue => capacity.is_some_and(|cap| queue.len() == cap),
+                Message::ProbeEmptyQueue => queue.is_empty(),
+            }
+        })
+    }
+
     /// Executes a transition on the given PG characterized by the argument action and post-state.
     ///
     /// Fails if the requested transition is not admissible.
     ///
+    /// Sending to a handshake channel also executes a receiving transition from the channel,
+    /// chosen at random among those admissible by the other PGs,
+    /// and produces a single [`EventType::Handshake`] event.
+    ///
+    /// Communications through unreliable channels are subject to faults according to their [`FaultModel`],
+    /// and produce the corresponding events.
+    ///
+    /// Sending to a broadcast channel pushes the message to the queues of all its subscribers,
+    /// and produces a single [`EventType::Send`] event on the broadcast channel.
+    ///
     /// See also [`ProgramGraph::transition`].
     pub fn transition(
         &mut self,
@@ -467,16 +799,86 @@ impl<R: Rng> ChannelSystem<R> {
     ) -> Result<Option<Event>, CsError> {
         // If action is a communication, check it is legal
         if pg_id.0 >= self.program_graphs.len() as u16 {
-            return Err(CsError::MissingPg(pg_id));
+            return Err(CsError::MissingPg(self.def.symbols.named_pg(pg_id)));
         } else if action.0 != pg_id {
-            return Err(CsError::ActionNotInPg(action, pg_id));
+            return Err(CsError::ActionNotInPg(
+                self.def.symbols.named_action(action),
+                self.def.symbols.named_pg(pg_id),
+            ));
         } else if let Some(post) = post.iter().find(|l| l.0 != pg_id) {
-            return Err(CsError::LocationNotInPg(*post, pg_id));
+            return Err(CsError::LocationNotInPg(
+                self.def.symbols.named_location(*post),
+                self.def.symbols.named_pg(pg_id),
+            ));
         }
         // If the action is a communication, send/receive the message
         if let Some((channel, message)) = self.def.communication(action) {
             let (_, capacity) = self.def.channels[channel.0 as usize];
             let event_type = match message {
+                Message::Send if self.def.is_broadcast(channel) => {
+                    if let Some(subscriber) =
+                        Self::full_subscriber(&self.def, &self.message_queue, channel)
+                    {
+                        return Err(CsError::OutOfCapacity(subscriber));
+                    }
+                    let val = self.program_graphs[pg_id.0 as usize]
+                        .send(
+                            action.1,
+                            post.iter()
+                                .map(|loc| loc.1)
+                                .collect::<SmallVec<[PgLocation; 8]>>()
+                                .as_slice(),
+                            &mut self.rng,
+                        )
+                        .map_err(|err| {
+                            CsError::ProgramGraph(self.def.symbols.named_pg(pg_id), err)
+                        })?;
+                    // Subscribers are reliable, so messages are delivered right away
+                    for subscriber in self.def.subscribers(channel) {
+                        self.message_queue[subscriber.0 as usize]
+                            .push_back((val.clone(), self.time));
+                    }
+                    EventType::Send(val)
+                }
+                Message::Send if capacity == Some(0) => {
+                    let (receiver, receive, receiver_post) = Self::handshake_receivers(
+                        &self.program_graphs,
+                        &self.def,
+                        &self.message_queue,
+                        self.time,
+                        pg_id,
+                        channel,
+                    )
+                    .choose(&mut self.rng)
+                    .ok_or(CsError::NoReceiver(channel))?;
+                    let receiver_post = receiver_post
+                        .iter()
+                        .map(|locs| {
+                            *locs
+                                .iter()
+                                .choose(&mut self.rng)
+                                .expect("post-states exist")
+                        })
+                        .collect::<SmallVec<[PgLocation; 8]>>();
+                    let val = self.program_graphs[pg_id.0 as usize]
+                        .send(
+                            action.1,
+                            post.iter()
+                                .map(|loc| loc.1)
+                                .collect::<SmallVec<[PgLocation; 8]>>()
+                                .as_slice(),
+                            &mut self.rng,
+                        )
+                        .map_err(|err| {
+                            CsError::ProgramGraph(self.def.symbols.named_pg(pg_id), err)
+                        })?;
+                    self.program_graphs[receiver.0 as usize]
+                        .receive(receive, receiver_post.as_slice(), val.clone())
+                        .map_err(|err| {
+                            CsError::ProgramGraph(self.def.symbols.named_pg(receiver), err)
+                        })?;
+                    EventType::Handshake(receiver, val)
+                }
                 Message::Send
                     if capacity
                         .is_some_and(|cap| self.message_queue[channel.0 as usize].len() >= cap) =>
@@ -493,16 +895,58 @@ impl<R: Rng> ChannelSystem<R> {
                                 .as_slice(),
                             &mut self.rng,
                         )
-                        .map_err(|err| CsError::ProgramGraph(pg_id, err))?;
-                    self.message_queue[channel.0 as usize].push_back(val.clone());
-                    EventType::Send(val)
+                        .map_err(|err| {
+                            CsError::ProgramGraph(self.def.symbols.named_pg(pg_id), err)
+                        })?;
+                    let queue = &mut self.message_queue[channel.0 as usize];
+                    if let Some(faults) = self.def.faults[channel.0 as usize] {
+                        if self.rng.random_bool(faults.drop) {
+                            EventType::Dropped(val)
+                        } else {
+                            let delay = self.rng.random_range(0..=faults.delay);
+                            queue.push_back((val.clone(), self.time + delay));
+                            // The copy is lost if there is no room for it
+                            if capacity.is_none_or(|cap| queue.len() < cap)
+                                && self.rng.random_bool(faults.duplicate)
+                            {
+                                let delay = self.rng.random_range(0..=faults.delay);
+                                queue.push_back((val.clone(), self.time + delay));
+                                EventType::Duplicated(val)
+                            } else if delay > 0 {
+                                EventType::Delayed(val, delay)
+                            } else {
+                                EventType::Send(val)
+                            }
+                        }
+                    } else {
+                        queue.push_back((val.clone(), self.time));
+                        EventType::Send(val)
+                    }
                 }
                 Message::Receive if self.message_queue[channel.0 as usize].is_empty() => {
                     return Err(CsError::Empty(channel));
                 }
                 Message::Receive => {
-                    let val = self.message_queue[channel.0 as usize]
-                        .pop_front()
+                    let time = self.time;
+                    let queue = &mut self.message_queue[channel.0 as usize];
+                    let idx = if self.def.faults[channel.0 as usize]
+                        .is_some_and(|faults| faults.reorder)
+                    {
+                        queue
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, (_, delivery))| *delivery <= time)
+                            .map(|(idx, _)| idx)
+                            .choose(&mut self.rng)
+                    } else {
+                        queue
+                            .front()
+                            .filter(|(_, delivery)| *delivery <= time)
+                            .map(|_| 0)
+                    }
+                    .ok_or(CsError::InTransit(channel))?;
+                    let (val, _) = queue
+                        .remove(idx)
                         .expect("communication has been verified before");
                     self.program_graphs[pg_id.0 as usize]
                         .receive(
@@ -514,7 +958,11 @@ impl<R: Rng> ChannelSystem<R> {
                             val.clone(),
                         )
                         .expect("communication has been verified before");
-                    EventType::Receive(val)
+                    if idx > 0 {
+                        EventType::Reordered(val)
+                    } else {
+                        EventType::Receive(val)
+                    }
                 }
                 Message::ProbeEmptyQueue | Message::ProbeFullQueue
                     if matches!(capacity, Some(0)) =>
@@ -534,7 +982,9 @@ impl<R: Rng> ChannelSystem<R> {
                                 .as_slice(),
                             &mut self.rng,
                         )
-                        .map_err(|err| CsError::ProgramGraph(pg_id, err))?;
+                        .map_err(|err| {
+                            CsError::ProgramGraph(self.def.symbols.named_pg(pg_id), err)
+                        })?;
                     EventType::ProbeEmptyQueue
                 }
                 Message::ProbeFullQueue
@@ -556,7 +1006,9 @@ impl<R: Rng> ChannelSystem<R> {
                                 .as_slice(),
                             &mut self.rng,
                         )
-                        .map_err(|err| CsError::ProgramGraph(pg_id, err))?;
+                        .map_err(|err| {
+                            CsError::ProgramGraph(self.def.symbols.named_pg(pg_id), err)
+                        })?;
                     EventType::ProbeFullQueue
                 }
             };
@@ -576,27 +1028,27 @@ impl<R: Rng> ChannelSystem<R> {
                         .as_slice(),
                     &mut self.rng,
                 )
-                .map_err(|err| CsError::ProgramGraph(pg_id, err))
+                .map_err(|err| CsError::ProgramGraph(self.def.symbols.named_pg(pg_id), err))
                 .map(|()| None)
         }
     }
 
     /// Tries waiting for the given delta of time.
-    /// Returns error if any of the PG cannot wait due to some time invariant.
+    /// Returns error if any of the PG cannot wait due to some time invariant or urgent location.
     pub fn wait(&mut self, delta: Time) -> Result<(), CsError> {
-        if let Some(pg) = self
-            .program_graphs
+        self.program_graphs
             .iter()
-            .position(|pg| !pg.can_wait(delta))
-        {
-            Err(CsError::ProgramGraph(PgId(pg as u16), PgError::Invariant))
-        } else {
-            self.program_graphs.iter_mut().for_each(|pg| {
-                pg.wait(delta).expect("wait");
-            });
-            self.time += delta;
-            Ok(())
-        }
+            .enumerate()
+            .try_for_each(|(id, pg)| {
+                pg.check_wait(delta).map_err(|err| {
+                    CsError::ProgramGraph(self.def.symbols.named_pg(PgId(id as u16)), err)
+                })
+            })?;
+        self.program_graphs.iter_mut().for_each(|pg| {
+            pg.wait(delta).expect("wait");
+        });
+        self.time += delta;
+        Ok(())
     }
 }
 
@@ -677,6 +1129,167 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn add_probabilistic_transition() -> Result<(), CsError> {
+        let mut cs = ChannelSystemBuilder::new();
+        let ch = cs.new_channel(Type::Boolean, None);
+        let pg1 = cs.new_program_graph();
+        let pg2 = cs.new_program_graph();
+        let initial = cs.new_initial_location(pg1)?;
+        let post = cs.new_location(pg1)?;
+        let other_post = cs.new_location(pg2)?;
+        let action = cs.new_action(pg1)?;
+        let var = cs.new_var(pg1, Expression::Const(Val::Integer(0)))?;
+        let send = cs.new_send(pg1, ch, CsExpression::Const(Val::Boolean(true)))?;
+        let branch = |post| (CsExpression::Const(Val::Integer(1)), post, Vec::new());
+        cs.add_probabilistic_transition(pg1, initial, send, None, vec![branch(post)])
+            .expect_err("communications cannot be probabilistic");
+        cs.add_probabilistic_transition(pg1, initial, action, None, vec![branch(other_post)])
+            .expect_err("post-location belongs to another PG");
+        cs.add_probabilistic_transition(
+            pg1,
+            initial,
+            action,
+            None,
+            vec![(
+                CsExpression::Const(Val::Float(0.5)),
+                post,
+                vec![(var, CsExpression::Const(Val::Integer(1)))],
+            )],
+        )?;
+        let mut cs = cs.build();
+        let (pg, action, post_states) = cs.possible_transitions().next().expect("transition");
+        let post_states = post_states
+            .map(|mut locs| locs.next().expect("post-state"))
+            .collect::<Vec<_>>();
+        cs.transition(pg, action, &post_states)?;
+        assert_eq!(cs.program_graphs[0].current_states().as_slice(), &[post.1]);
+        Ok(())
+    }
+
+    #[test]
+    fn priorities() -> Result<(), CsError> {
+        let mut cs = ChannelSystemBuilder::new();
+        let ch = cs.new_channel(Type::Boolean, Some(1));
+        let pg1 = cs.new_program_graph();
+        let initial1 = cs.new_initial_location(pg1)?;
+        let post1 = cs.new_location(pg1)?;
+        let send = cs.new_send(pg1, ch, CsExpression::Const(Val::Boolean(true)))?;
+        cs.add_transition(pg1, initial1, send, post1, None)?;
+        cs.set_priority(pg1, initial1, post1, 1)?;
+        let pg2 = cs.new_program_graph();
+        let initial2 = cs.new_initial_location(pg2)?;
+        let action = cs.new_action(pg2)?;
+        cs.add_transition(pg2, initial2, action, initial2, None)?;
+        cs.set_priority(pg2, initial2, post1, 1)
+            .expect_err("location belongs to another PG");
+        let mut cs = cs.build();
+        let possible_transitions = |cs: &ChannelSystem<SmallRng>| {
+            cs.possible_transitions()
+                .filter_map(|(pg_id, action, mut post_states)| {
+                    post_states
+                        .all(|mut locs| locs.next().is_some())
+                        .then_some((pg_id, action))
+                })
+                .collect::<Vec<_>>()
+        };
+        // Sending has higher priority across PGs
+        assert_eq!(possible_transitions(&cs), vec![(pg1, send)]);
+        cs.transition(pg1, send, &[post1])?;
+        assert_eq!(possible_transitions(&cs), vec![(pg2, action)]);
+        Ok(())
+    }
+
+    #[test]
+    fn urgency() -> Result<(), CsError> {
+        let mut cs = ChannelSystemBuilder::new();
+        let pg1 = cs.new_program_graph();
+        let initial1 = cs.new_initial_location(pg1)?;
+        let committed = cs.new_location(pg1)?;
+        let action1 = cs.new_action(pg1)?;
+        cs.add_transition(pg1, initial1, action1, committed, None)?;
+        cs.add_transition(pg1, committed, action1, initial1, None)?;
+        cs.set_urgency(pg1, committed, Urgency::Committed)?;
+        let pg2 = cs.new_program_graph();
+        let initial2 = cs.new_initial_location(pg2)?;
+        let urgent = cs.new_location(pg2)?;
+        let action2 = cs.new_action(pg2)?;
+        cs.add_transition(pg2, initial2, action2, urgent, None)?;
+        cs.add_transition(pg2, urgent, action2, initial2, None)?;
+        cs.set_urgency(pg2, urgent, Urgency::Urgent)?;
+        cs.set_urgency(pg2, committed, Urgency::Urgent)
+            .expect_err("location belongs to another PG");
+        let mut cs = cs.build();
+        let possible_transitions = |cs: &ChannelSystem<SmallRng>| {
+            cs.possible_transitions()
+                .map(|(pg_id, action, _)| (pg_id, action))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            possible_transitions(&cs),
+            vec![(pg1, action1), (pg2, action2)]
+        );
+        cs.wait(1)?;
+        cs.transition(pg2, action2, &[urgent])?;
+        assert!(matches!(
+            cs.wait(1),
+            Err(CsError::ProgramGraph(_, PgError::Urgent))
+        ));
+        cs.transition(pg1, action1, &[committed])?;
+        // Only the PG in the committed location can move
+        assert!(cs.is_committed());
+        assert_eq!(possible_transitions(&cs), vec![(pg1, action1)]);
+        cs.transition(pg1, action1, &[initial1])?;
+        assert_eq!(
+            possible_transitions(&cs),
+            vec![(pg1, action1), (pg2, action2)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn symbols() -> Result<(), CsError> {
+        let mut cs = ChannelSystemBuilder::new();
+        let pg = cs.new_program_graph();
+        let ch = cs.new_channel(Type::Integer, None);
+        let initial = cs.new_initial_location(pg)?;
+        let send = cs.new_send(pg, ch, CsExpression::from(0))?;
+        cs.add_transition(pg, initial, send, initial, None)?;
+        cs.set_pg_symbol(pg, "sender")?;
+        cs.set_channel_symbol(ch, "queue")?;
+        cs.set_location_symbol(initial, "idle")?;
+        cs.set_action_symbol(send, "send")?;
+        assert!(matches!(
+            cs.set_pg_symbol(PgId(1), "missing"),
+            Err(CsError::MissingPg(Named {
+                object: PgId(1),
+                ..
+            }))
+        ));
+        let receiver = cs.new_program_graph();
+        cs.set_pg_symbol(receiver, "receiver")?;
+        let waiting = cs.new_initial_location(receiver)?;
+        let err = cs
+            .add_transition(receiver, waiting, send, waiting, None)
+            .expect_err("the action belongs to another PG");
+        assert_eq!(
+            err.to_string(),
+            "action 'send' does not belong to program graph 'receiver'"
+        );
+        let cs = cs.build();
+        assert_eq!(cs.symbols().describe_pg(pg), "'sender'");
+        assert_eq!(cs.symbols().describe_location(initial), "'idle'");
+        assert_eq!(cs.symbols().describe_action(send), "'send'");
+        assert_eq!(
+            cs.analyze()
+                .iter()
+                .map(|diagnostic| diagnostic.describe(cs.symbols()))
+                .collect::<Vec<_>>(),
+            vec!["messages sent to 'queue' are never received"]
+        );
+        Ok(())
+    }
+
     #[test]
     fn add_communication() -> Result<(), CsError> {
         let mut cs = ChannelSystemBuilder::new();
@@ -713,4 +1326,388 @@ mod tests {
         assert_eq!(cs.possible_transitions().count(), 0);
         Ok(())
     }
+
+    #[test]
+    fn record_messages() -> Result<(), CsError> {
+        let point = Arc::new(Record::new(
+            String::from("Point"),
+            vec![
+                (String::from("x"), Type::Integer),
+                (String::from("y"), Type::Integer),
+            ],
+        ));
+        let mut cs = ChannelSystemBuilder::new();
+        let ch = cs.new_channel(Type::Record(point.clone()), Some(1));
+
+        let pg1 = cs.new_program_graph();
+        let initial1 = cs.new_initial_location(pg1)?;
+        let post1 = cs.new_location(pg1)?;
+        let msg = CsExpression::Record(
+            point.clone(),
+            vec![Expression::from(1), Expression::from(2)],
+        );
+        let send = cs.new_send(pg1, ch, msg)?;
+        cs.add_transition(pg1, initial1, send, post1, None)?;
+
+        let pg2 = cs.new_program_graph();
+        let initial2 = cs.new_initial_location(pg2)?;
+        let post2 = cs.new_location(pg2)?;
+        let wrong = cs.new_var(pg2, Expression::from(0))?;
+        cs.new_receive(pg2, ch, wrong).expect_err("type mismatch");
+        let var = cs.new_var(
+            pg2,
+            Expression::Const(Type::Record(point.clone()).default_value()),
+        )?;
+        let receive = cs.new_receive(pg2, ch, var)?;
+        cs.add_transition(pg2, initial2, receive, post2, None)?;
+        let field = cs.new_var(pg2, Expression::from(0))?;
+        let read = cs.new_action(pg2)?;
+        cs.add_effect(
+            pg2,
+            read,
+            field,
+            CsExpression::Var(var, Type::Record(point.clone()))
+                .field("y")
+                .map_err(CsError::Type)?,
+        )?;
+        cs.add_transition(pg2, post2, read, post2, None)?;
+
+        let mut cs = cs.build();
+        cs.transition(pg1, send, &[post1])?;
+        cs.transition(pg2, receive, &[post2])?;
+        assert_eq!(cs.possible_transitions().count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn handshake() -> Result<(), CsError> {
+        let mut cs = ChannelSystemBuilder::new();
+        let ch = cs.new_channel(Type::Integer, Some(0));
+
+        let pg1 = cs.new_program_graph();
+        let initial1 = cs.new_initial_location(pg1)?;
+        let post1 = cs.new_location(pg1)?;
+        let send = cs.new_send(pg1, ch, CsExpression::from(7))?;
+        cs.add_transition(pg1, initial1, send, post1, None)?;
+        cs.new_probe_empty_queue(pg1, ch)
+            .expect_err("handshake channels cannot be probed");
+
+        let pg2 = cs.new_program_graph();
+        let initial2 = cs.new_initial_location(pg2)?;
+        let post2 = cs.new_location(pg2)?;
+        let var = cs.new_var(pg2, CsExpression::from(0))?;
+        let receive = cs.new_receive(pg2, ch, var)?;
+        cs.add_transition(pg2, initial2, receive, post2, None)?;
+
+        let mut cs = cs.build();
+        // Receptions are only executed together with sends
+        assert_eq!(
+            cs.possible_transitions()
+                .map(|(pg_id, action, _)| (pg_id, action))
+                .collect::<Vec<_>>(),
+            vec![(pg1, send)]
+        );
+        cs.transition(pg2, receive, &[post2])
+            .expect_err("no message to receive");
+        let event = cs.montecarlo_execution(0)?.expect("handshake event");
+        assert_eq!(
+            event,
+            Event {
+                pg_id: pg1,
+                channel: ch,
+                event_type: EventType::Handshake(pg2, Val::Integer(7)),
+            }
+        );
+        assert_eq!(cs.program_graphs[0].current_states().as_slice(), &[post1.1]);
+        assert_eq!(cs.program_graphs[1].current_states().as_slice(), &[post2.1]);
+        assert_eq!(
+            cs.program_graphs[1].val(var.1).expect("receiving var"),
+            &Val::Integer(7)
+        );
+        // Without a receiver, nothing can be sent
+        assert_eq!(cs.montecarlo_execution(0)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn handshake_receivers() -> Result<(), CsError> {
+        let mut cs = ChannelSystemBuilder::new_with_rng(SmallRng::seed_from_u64(0));
+        let ch = cs.new_channel(Type::Integer, Some(0));
+
+        let pg1 = cs.new_program_graph();
+        let initial1 = cs.new_initial_location(pg1)?;
+        let send = cs.new_send(pg1, ch, CsExpression::from(1))?;
+        cs.add_transition(pg1, initial1, send, initial1, None)?;
+
+        // Receives from the channel only after taking the higher-priority action
+        let pg2 = cs.new_program_graph();
+        let initial2 = cs.new_initial_location(pg2)?;
+        let ready2 = cs.new_location(pg2)?;
+        let var2 = cs.new_var(pg2, CsExpression::from(0))?;
+        let receive2 = cs.new_receive(pg2, ch, var2)?;
+        let action2 = cs.new_action(pg2)?;
+        cs.add_transition(pg2, initial2, receive2, initial2, None)?;
+        cs.add_transition(pg2, initial2, action2, ready2, None)?;
+        cs.set_priority(pg2, initial2, ready2, 1)?;
+        cs.add_transition(pg2, ready2, receive2, ready2, None)?;
+
+        let pg3 = cs.new_program_graph();
+        let initial3 = cs.new_initial_location(pg3)?;
+        let var3 = cs.new_var(pg3, CsExpression::from(0))?;
+        let receive3 = cs.new_receive(pg3, ch, var3)?;
+        cs.add_transition(pg3, initial3, receive3, initial3, None)?;
+
+        let mut cs = cs.build();
+        // The low-priority receive of pg2 cannot fire
+        for _ in 0..10 {
+            let event = cs.transition(pg1, send, &[initial1])?;
+            assert_eq!(
+                event.map(|event| event.event_type),
+                Some(EventType::Handshake(pg3, Val::Integer(1)))
+            );
+        }
+        cs.transition(pg2, action2, &[ready2])?;
+        // Both PGs can now receive
+        let mut receivers = std::collections::HashSet::new();
+        for _ in 0..10 {
+            if let Some(Event {
+                event_type: EventType::Handshake(receiver, _),
+                ..
+            }) = cs.transition(pg1, send, &[initial1])?
+            {
+                receivers.insert(receiver);
+            }
+        }
+        assert_eq!(receivers, std::collections::HashSet::from([pg2, pg3]));
+        Ok(())
+    }
+
+    #[test]
+    fn committed_handshake() -> Result<(), CsError> {
+        let mut cs = ChannelSystemBuilder::new();
+        let ch = cs.new_channel(Type::Integer, Some(0));
+
+        let pg1 = cs.new_program_graph();
+        let initial1 = cs.new_initial_location(pg1)?;
+        let committed1 = cs.new_location(pg1)?;
+        let post1 = cs.new_location(pg1)?;
+        let action1 = cs.new_action(pg1)?;
+        let send = cs.new_send(pg1, ch, CsExpression::from(1))?;
+        cs.add_transition(pg1, initial1, action1, committed1, None)?;
+        cs.add_transition(pg1, committed1, send, post1, None)?;
+        cs.set_urgency(pg1, committed1, Urgency::Committed)?;
+
+        let pg2 = cs.new_program_graph();
+        let initial2 = cs.new_initial_location(pg2)?;
+        let var = cs.new_var(pg2, CsExpression::from(0))?;
+        let receive = cs.new_receive(pg2, ch, var)?;
+        let action2 = cs.new_action(pg2)?;
+        cs.add_transition(pg2, initial2, receive, initial2, None)?;
+        cs.add_transition(pg2, initial2, action2, initial2, None)?;
+
+        let mut cs = cs.build();
+        cs.transition(pg1, action1, &[committed1])?;
+        // Only the committed PG can move, but its send can still be received
+        assert_eq!(
+            cs.possible_transitions()
+                .map(|(pg_id, action, _)| (pg_id, action))
+                .collect::<Vec<_>>(),
+            vec![(pg1, send)]
+        );
+        let event = cs.montecarlo_execution(0)?.expect("handshake event");
+        assert_eq!(event.event_type, EventType::Handshake(pg2, Val::Integer(1)));
+        assert!(!cs.is_committed());
+        Ok(())
+    }
+
+    #[test]
+    fn faults() -> Result<(), CsError> {
+        let mut cs = ChannelSystemBuilder::new_with_rng(SmallRng::seed_from_u64(0));
+        let lossy = cs.new_channel(Type::Integer, None);
+        cs.set_fault_model(
+            lossy,
+            FaultModel {
+                drop: 1.0,
+                ..Default::default()
+            },
+        )?;
+        let duplicating = cs.new_channel(Type::Integer, Some(2));
+        cs.set_fault_model(
+            duplicating,
+            FaultModel {
+                duplicate: 1.0,
+                ..Default::default()
+            },
+        )?;
+        let delaying = cs.new_channel(Type::Integer, None);
+        cs.set_fault_model(
+            delaying,
+            FaultModel {
+                delay: 10,
+                ..Default::default()
+            },
+        )?;
+        cs.set_fault_model(
+            delaying,
+            FaultModel {
+                drop: 2.0,
+                ..Default::default()
+            },
+        )
+        .expect_err("drop probability is greater than 1");
+
+        let pg1 = cs.new_program_graph();
+        let initial1 = cs.new_initial_location(pg1)?;
+        let send_lossy = cs.new_send(pg1, lossy, CsExpression::from(1))?;
+        let send_duplicating = cs.new_send(pg1, duplicating, CsExpression::from(2))?;
+        let send_delaying = cs.new_send(pg1, delaying, CsExpression::from(3))?;
+        for send in [send_lossy, send_duplicating, send_delaying] {
+            cs.add_transition(pg1, initial1, send, initial1, None)?;
+        }
+        let pg2 = cs.new_program_graph();
+        let initial2 = cs.new_initial_location(pg2)?;
+        let var = cs.new_var(pg2, CsExpression::from(0))?;
+        let receive_duplicating = cs.new_receive(pg2, duplicating, var)?;
+        let receive_delaying = cs.new_receive(pg2, delaying, var)?;
+        for receive in [receive_duplicating, receive_delaying] {
+            cs.add_transition(pg2, initial2, receive, initial2, None)?;
+        }
+        let mut cs = cs.build();
+
+        let event = cs.transition(pg1, send_lossy, &[initial1])?;
+        assert_eq!(
+            event.map(|event| event.event_type),
+            Some(EventType::Dropped(Val::Integer(1)))
+        );
+        assert!(cs.message_queue[lossy.0 as usize].is_empty());
+
+        let event = cs.transition(pg1, send_duplicating, &[initial1])?;
+        assert_eq!(
+            event.map(|event| event.event_type),
+            Some(EventType::Duplicated(Val::Integer(2)))
+        );
+        // Both copies are received
+        cs.transition(pg2, receive_duplicating, &[initial2])?;
+        cs.transition(pg2, receive_duplicating, &[initial2])?;
+        cs.transition(pg2, receive_duplicating, &[initial2])
+            .expect_err("channel is empty");
+
+        let event = cs.transition(pg1, send_delaying, &[initial1])?;
+        match event.map(|event| event.event_type) {
+            Some(EventType::Delayed(Val::Integer(3), delay)) => {
+                assert!((1..=10).contains(&delay));
+                assert!(matches!(
+                    cs.transition(pg2, receive_delaying, &[initial2]),
+                    Err(CsError::InTransit(_))
+                ));
+                cs.wait(delay)?;
+            }
+            Some(EventType::Send(Val::Integer(3))) => {}
+            event => panic!("unexpected event {event:?}"),
+        }
+        cs.transition(pg2, receive_delaying, &[initial2])?;
+        Ok(())
+    }
+
+    #[test]
+    fn reorder() -> Result<(), CsError> {
+        let mut cs = ChannelSystemBuilder::new_with_rng(SmallRng::seed_from_u64(0));
+        let ch = cs.new_channel(Type::Integer, None);
+        cs.set_fault_model(
+            ch,
+            FaultModel {
+                reorder: true,
+                ..Default::default()
+            },
+        )?;
+        let pg1 = cs.new_program_graph();
+        let initial1 = cs.new_initial_location(pg1)?;
+        let send1 = cs.new_send(pg1, ch, CsExpression::from(1))?;
+        let send2 = cs.new_send(pg1, ch, CsExpression::from(2))?;
+        cs.add_transition(pg1, initial1, send1, initial1, None)?;
+        cs.add_transition(pg1, initial1, send2, initial1, None)?;
+        let pg2 = cs.new_program_graph();
+        let initial2 = cs.new_initial_location(pg2)?;
+        let var = cs.new_var(pg2, CsExpression::from(0))?;
+        let receive = cs.new_receive(pg2, ch, var)?;
+        cs.add_transition(pg2, initial2, receive, initial2, None)?;
+        let mut cs = cs.build();
+
+        let mut reordered = false;
+        for _ in 0..100 {
+            cs.transition(pg1, send1, &[initial1])?;
+            cs.transition(pg1, send2, &[initial1])?;
+            let first = cs.transition(pg2, receive, &[initial2])?;
+            let second = cs.transition(pg2, receive, &[initial2])?;
+            match (
+                first.map(|event| event.event_type),
+                second.map(|event| event.event_type),
+            ) {
+                (
+                    Some(EventType::Receive(Val::Integer(1))),
+                    Some(EventType::Receive(Val::Integer(2))),
+                ) => {}
+                (
+                    Some(EventType::Reordered(Val::Integer(2))),
+                    Some(EventType::Receive(Val::Integer(1))),
+                ) => reordered = true,
+                events => panic!("unexpected events {events:?}"),
+            }
+        }
+        assert!(reordered);
+        Ok(())
+    }
+
+    #[test]
+    fn broadcast() -> Result<(), CsError> {
+        let mut cs = ChannelSystemBuilder::new();
+        let broadcast = cs.new_broadcast(Type::Integer);
+        let queue1 = cs.new_channel(Type::Integer, Some(1));
+        let queue2 = cs.new_channel(Type::Integer, None);
+        cs.subscribe(broadcast, queue1)?;
+        cs.subscribe(broadcast, queue2)?;
+        cs.subscribe(queue1, queue2)
+            .expect_err("only broadcast channels have subscribers");
+        cs.set_fault_model(queue1, FaultModel::default())
+            .expect_err("subscribers cannot be unreliable");
+        let boolean = cs.new_channel(Type::Boolean, None);
+        cs.subscribe(broadcast, boolean)
+            .expect_err("types must match");
+
+        let pg0 = cs.new_program_graph();
+        let initial0 = cs.new_initial_location(pg0)?;
+        let send = cs.new_send(pg0, broadcast, CsExpression::from(3))?;
+        cs.add_transition(pg0, initial0, send, initial0, None)?;
+
+        let pg1 = cs.new_program_graph();
+        let initial1 = cs.new_initial_location(pg1)?;
+        let var1 = cs.new_var(pg1, CsExpression::from(0))?;
+        cs.new_receive(pg1, broadcast, var1)
+            .expect_err("cannot receive from broadcast channels");
+        let receive1 = cs.new_receive(pg1, queue1, var1)?;
+        cs.add_transition(pg1, initial1, receive1, initial1, None)?;
+
+        let mut cs = cs.build();
+        assert_eq!(cs.subscribers(broadcast), &[queue1, queue2]);
+        assert!(cs.subscribers(queue1).is_empty());
+        cs.transition(pg0, send, &[initial0])?;
+        assert_eq!(cs.message_queue[queue1.0 as usize].len(), 1);
+        assert_eq!(cs.message_queue[queue2.0 as usize].len(), 1);
+        // The first subscriber has no room left, so the broadcast is blocked
+        assert!(matches!(
+            cs.transition(pg0, send, &[initial0]),
+            Err(CsError::OutOfCapacity(channel)) if channel == queue1
+        ));
+        cs.transition(pg1, receive1, &[initial1])?;
+        assert_eq!(
+            cs.transition(pg0, send, &[initial0])?,
+            Some(Event {
+                pg_id: pg0,
+                channel: broadcast,
+                event_type: EventType::Send(Val::Integer(3)),
+            })
+        );
+        assert_eq!(cs.message_queue[queue2.0 as usize].len(), 2);
+        Ok(())
+    }
 }
@@ -0,0 +1,249 @@
+// Static analysis of channel systems, to catch modelling mistakes before simulation.
+//
+// On top of the analysis of the single PGs,
+// receiving from a channel is deemed possible only if some reachable transition sends to it
+// (or to a broadcast channel it subscribes to),
+// which in turn can make further locations unreachable, until a fixpoint is reached.
+
+use super::*;
+
+/// A potential modelling mistake found by the static analysis of a [`ChannelSystem`].
+///
+/// See [`ChannelSystem::analyze`].
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum CsDiagnostic {
+    /// The location cannot be reached from the initial locations.
+    UnreachableLocation(Location),
+    /// The guard of the transition, given by its pre-location, action and post-location,
+    /// is always false.
+    FalseGuard(Location, Action, Location),
+    /// The variable is written but its value is never read.
+    UnreadVar(Var),
+    /// Messages are sent to the channel, but never received from it.
+    UnreceivedChannel(Channel),
+    /// The action receiving from the channel can never be executed,
+    /// as no message is ever sent to the channel.
+    UnfedReceive(Action, Channel),
+}
+
+impl CsDiagnostic {
+    /// The PG the diagnostic refers to, if any.
+    pub fn pg_id(&self) -> Option<PgId> {
+        match self {
+            CsDiagnostic::UnreachableLocation(loc) | CsDiagnostic::FalseGuard(loc, ..) => {
+                Some(loc.0)
+            }
+            CsDiagnostic::UnreadVar(var) => Some(var.0),
+            CsDiagnostic::UnfedReceive(action, _) => Some(action.0),
+            CsDiagnostic::UnreceivedChannel(_) => None,
+        }
+    }
+
+    fn from_pg(pg_id: PgId, diagnostic: PgDiagnostic) -> Self {
+        match diagnostic {
+            PgDiagnostic::UnreachableLocation(loc) => {
+                CsDiagnostic::UnreachableLocation(Location(pg_id, loc))
+            }
+            PgDiagnostic::FalseGuard(pre, action, post) => CsDiagnostic::FalseGuard(
+                Location(pg_id, pre),
+                Action(pg_id, action),
+                Location(pg_id, post),
+            ),
+            PgDiagnostic::UnreadVar(var) => CsDiagnostic::UnreadVar(Var(pg_id, var)),
+        }
+    }
+
+    /// Describes the diagnostic, referring to the CS's objects by their symbols when possible.
+    ///
+    /// See also [`ChannelSystem::symbols`].
+    pub fn describe(&self, symbols: &CsSymbolTable) -> String {
+        match self {
+            CsDiagnostic::UnreachableLocation(loc) => {
+                format!(
+                    "location {} is unreachable",
+                    symbols.describe_location(*loc)
+                )
+            }
+            CsDiagnostic::FalseGuard(pre, action, post) => format!(
+                "transition from {} to {} with action {} has a guard that is always false",
+                symbols.describe_location(*pre),
+                symbols.describe_location(*post),
+                symbols.describe_action(*action),
+            ),
+            CsDiagnostic::UnreadVar(var) => {
+                format!(
+                    "variable {} is written but never read",
+                    symbols.describe_var(*var)
+                )
+            }
+            CsDiagnostic::UnreceivedChannel(channel) => format!(
+                "messages sent to {} are never received",
+                symbols.describe_channel(*channel)
+            ),
+            CsDiagnostic::UnfedReceive(action, channel) => format!(
+                "action {} receiving from {} can never be executed, as no message is sent to it",
+                symbols.describe_action(*action),
+                symbols.describe_channel(*channel),
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for CsDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.describe(&CsSymbolTable::default()))
+    }
+}
+
+impl<R: Rng> ChannelSystem<R> {
+    /// Statically analyzes the CS's definition, looking for potential modelling mistakes:
+    ///
+    /// - locations that cannot be reached from the initial locations;
+    /// - transitions whose guard is always false;
+    /// - variables that are written but never read;
+    /// - channels that are sent to, but never received from;
+    /// - receive actions on channels that no PG ever sends to.
+    ///
+    /// See also [`ProgramGraph::analyze`].
+    pub fn analyze(&self) -> Vec<CsDiagnostic> {
+        let false_guards = self
+            .program_graphs
+            .iter()
+            .map(ProgramGraph::false_guards)
+            .collect::<Vec<_>>();
+        // Channels some reachable transition sends to
+        let mut fed = vec![false; self.def.channels.len()];
+        let mut reachable;
+        loop {
+            reachable = self
+                .program_graphs
+                .iter()
+                .zip(&false_guards)
+                .enumerate()
+                .map(|(id, (pg, false_guards))| {
+                    pg.reachable_locations(false_guards, |action| {
+                        match self.def.communication(Action(PgId(id as u16), action)) {
+                            Some((channel, Message::Receive | Message::ProbeFullQueue)) => {
+                                fed[channel.0 as usize]
+                            }
+                            _ => true,
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            let mut updated = fed.clone();
+            self.live_communications(&false_guards, &reachable)
+                .filter(|(_, message)| *message == Message::Send)
+                .for_each(|(channel, _)| {
+                    updated[channel.0 as usize] = true;
+                    for subscriber in self.def.subscribers(channel) {
+                        updated[subscriber.0 as usize] = true;
+                    }
+                });
+            if updated == fed {
+                break;
+            }
+            fed = updated;
+        }
+        let mut received = vec![false; self.def.channels.len()];
+        self.live_communications(&false_guards, &reachable)
+            .filter(|(_, message)| *message == Message::Receive)
+            .for_each(|(channel, _)| received[channel.0 as usize] = true);
+        let mut diagnostics = self
+            .program_graphs
+            .iter()
+            .zip(false_guards.iter().zip(&reachable))
+            .enumerate()
+            .flat_map(|(id, (pg, (false_guards, reachable)))| {
+                pg.diagnostics(false_guards, reachable)
+                    .into_iter()
+                    .map(move |diagnostic| CsDiagnostic::from_pg(PgId(id as u16), diagnostic))
+            })
+            .collect::<Vec<_>>();
+        diagnostics.extend(
+            fed.iter()
+                .zip(&received)
+                .enumerate()
+                // Broadcast channels are received from through their subscribers
+                .filter(|(channel, (fed, received))| {
+                    **fed && !**received && !self.def.is_broadcast(Channel(*channel as u16))
+                })
+                .map(|(channel, _)| CsDiagnostic::UnreceivedChannel(Channel(channel as u16))),
+        );
+        diagnostics.extend(
+            self.def
+                .communications_pg_idxs
+                .windows(2)
+                .enumerate()
+                .flat_map(|(id, idxs)| {
+                    self.def.communications[idxs[0] as usize..idxs[1] as usize]
+                        .iter()
+                        .filter(|(_, channel, message)| {
+                            *message == Message::Receive && !fed[channel.0 as usize]
+                        })
+                        .map(move |(action, channel, _)| {
+                            CsDiagnostic::UnfedReceive(Action(PgId(id as u16), *action), *channel)
+                        })
+                }),
+        );
+        diagnostics
+    }
+
+    // Communications of the transitions from reachable locations whose guard is not always false.
+    fn live_communications<'a>(
+        &'a self,
+        false_guards: &'a [Vec<Vec<bool>>],
+        reachable: &'a [Vec<bool>],
+    ) -> impl Iterator<Item = (Channel, Message)> + 'a {
+        self.program_graphs
+            .iter()
+            .zip(false_guards.iter().zip(reachable))
+            .enumerate()
+            .flat_map(move |(id, (pg, (false_guards, reachable)))| {
+                pg.live_actions(false_guards, reachable)
+                    .filter_map(move |action| {
+                        self.def.communication(Action(PgId(id as u16), action))
+                    })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analyze() -> Result<(), CsError> {
+        let mut cs = ChannelSystemBuilder::new();
+        let fed = cs.new_channel(Type::Integer, None);
+        let unfed = cs.new_channel(Type::Integer, None);
+        let unreceived = cs.new_channel(Type::Integer, None);
+        let pg1 = cs.new_program_graph();
+        let initial1 = cs.new_initial_location(pg1)?;
+        let sent1 = cs.new_location(pg1)?;
+        let send_fed = cs.new_send(pg1, fed, CsExpression::from(0))?;
+        let send_unreceived = cs.new_send(pg1, unreceived, CsExpression::from(0))?;
+        cs.add_transition(pg1, initial1, send_fed, sent1, None)?;
+        cs.add_transition(pg1, sent1, send_unreceived, initial1, None)?;
+        let pg2 = cs.new_program_graph();
+        let initial2 = cs.new_initial_location(pg2)?;
+        let received2 = cs.new_location(pg2)?;
+        let starved2 = cs.new_location(pg2)?;
+        let var = cs.new_var(pg2, CsExpression::from(0))?;
+        let receive_fed = cs.new_receive(pg2, fed, var)?;
+        let receive_unfed = cs.new_receive(pg2, unfed, var)?;
+        cs.add_transition(pg2, initial2, receive_fed, received2, None)?;
+        cs.add_transition(pg2, initial2, receive_unfed, starved2, None)?;
+        let cs = cs.build();
+        assert_eq!(
+            cs.analyze(),
+            vec![
+                CsDiagnostic::UnreachableLocation(starved2),
+                CsDiagnostic::UnreadVar(var),
+                CsDiagnostic::UnreceivedChannel(unreceived),
+                CsDiagnostic::UnfedReceive(receive_unfed, unfed),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -1,18 +1,25 @@
 use super::{
-    Action, Channel, ChannelSystem, ChannelSystemDef, Clock, CsError, Location, Message, PgError,
-    PgExpression, PgId, ProgramGraph, ProgramGraphBuilder, TimeConstraint, Var,
+    Action, Channel, ChannelSystem, ChannelSystemDef, Clock, CsError, CsSymbolTable, FaultModel,
+    Location, Message, Named, PgError, PgExpression, PgId, PgVar, Priority, ProgramGraph,
+    ProgramGraphBuilder, Symbol, SymbolTable, TimeConstraint, Urgency, Var,
 };
-use crate::Expression;
-use crate::grammar::Type;
+use crate::grammar::{Bounds, Float, Overflow, Type};
+use crate::{Expected, Expression, TypeError};
 use log::info;
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
 use std::collections::{HashMap, VecDeque};
+use std::fmt::Formatter;
 use std::sync::Arc;
 
 /// An expression using CS's [`Var`] as variables.
 pub type CsExpression = Expression<Var>;
 
+/// A branch of a probabilistic transition, given by its weight, post-location and effects.
+///
+/// See [`ChannelSystemBuilder::add_probabilistic_transition`].
+pub type CsBranch = (CsExpression, Location, Vec<(Var, CsExpression)>);
+
 // WARN: This method should probably not be exposed to the public API.
 // TODO: Turn into a private method.
 impl TryFrom<(PgId, CsExpression)> for PgExpression {
@@ -22,7 +29,10 @@ impl TryFrom<(PgId, CsExpression)> for PgExpression {
         match expr {
             Expression::Const(val) => Ok(Expression::Const(val)),
             Expression::Var(cs_var, t) if cs_var.0 == pg_id => Ok(Expression::Var(cs_var.1, t)),
-            Expression::Var(cs_var, _t) => Err(CsError::VarNotInPg(cs_var, pg_id)),
+            Expression::Var(cs_var, _t) => Err(CsError::VarNotInPg(
+                Named::new(cs_var, None),
+                Named::new(pg_id, None),
+            )),
             Expression::Tuple(comps) => Ok(Expression::Tuple(
                 comps
                     .into_iter()
@@ -92,18 +102,131 @@ impl TryFrom<(PgId, CsExpression)> for PgExpression {
             Expression::Truncate(comp) => {
                 Ok(Expression::Truncate(Box::new((pg_id, *comp).try_into()?)))
             }
+            Expression::Record(record, fields) => Ok(Expression::Record(
+                record,
+                fields
+                    .into_iter()
+                    .map(|field| (pg_id, field).try_into())
+                    .collect::<Result<Vec<PgExpression>, CsError>>()?,
+            )),
+            Expression::Field(index, comp) => Ok(Expression::Field(
+                index,
+                Box::new((pg_id, *comp).try_into()?),
+            )),
+            Expression::SetField(index, comps) => Ok(Expression::SetField(
+                index,
+                Box::new(((pg_id, comps.0).try_into()?, (pg_id, comps.1).try_into()?)),
+            )),
             Expression::Len(comp) => Ok(Expression::Len(Box::new((pg_id, *comp).try_into()?))),
+            Expression::List(t, elements) => Ok(Expression::List(
+                t,
+                elements
+                    .into_iter()
+                    .map(|element| (pg_id, element).try_into())
+                    .collect::<Result<Vec<PgExpression>, CsError>>()?,
+            )),
+            Expression::Array(t, elements) => Ok(Expression::Array(
+                t,
+                elements
+                    .into_iter()
+                    .map(|element| (pg_id, element).try_into())
+                    .collect::<Result<Vec<PgExpression>, CsError>>()?,
+            )),
+            Expression::Index(comps) => Ok(Expression::Index(Box::new((
+                (pg_id, comps.0).try_into()?,
+                (pg_id, comps.1).try_into()?,
+            )))),
+            Expression::Update(comps) => Ok(Expression::Update(Box::new((
+                (pg_id, comps.0).try_into()?,
+                (pg_id, comps.1).try_into()?,
+                (pg_id, comps.2).try_into()?,
+            )))),
+            Expression::PushFront(comps) => Ok(Expression::PushFront(Box::new((
+                (pg_id, comps.0).try_into()?,
+                (pg_id, comps.1).try_into()?,
+            )))),
+            Expression::PopFront(comp) => {
+                Ok(Expression::PopFront(Box::new((pg_id, *comp).try_into()?)))
+            }
+            Expression::Contains(comps) => Ok(Expression::Contains(Box::new((
+                (pg_id, comps.0).try_into()?,
+                (pg_id, comps.1).try_into()?,
+            )))),
+            Expression::Slice(comps) => Ok(Expression::Slice(Box::new((
+                (pg_id, comps.0).try_into()?,
+                (pg_id, comps.1).try_into()?,
+                (pg_id, comps.2).try_into()?,
+            )))),
+            Expression::Concat(comps) => Ok(Expression::Concat(
+                comps
+                    .into_iter()
+                    .map(|comp| (pg_id, comp).try_into())
+                    .collect::<Result<Vec<PgExpression>, CsError>>()?,
+            )),
             Expression::Mod(comps) => Ok(Expression::Mod(Box::new((
                 (pg_id, comps.0).try_into()?,
                 (pg_id, comps.1).try_into()?,
             )))),
+            Expression::Cast(comp, t) => {
+                Ok(Expression::Cast(Box::new((pg_id, *comp).try_into()?), t))
+            }
             Expression::Div(comps) => Ok(Expression::Div(Box::new((
                 (pg_id, comps.0).try_into()?,
                 (pg_id, comps.1).try_into()?,
             )))),
+            Expression::Min(comps) => Ok(Expression::Min(Box::new((
+                (pg_id, comps.0).try_into()?,
+                (pg_id, comps.1).try_into()?,
+            )))),
+            Expression::Max(comps) => Ok(Expression::Max(Box::new((
+                (pg_id, comps.0).try_into()?,
+                (pg_id, comps.1).try_into()?,
+            )))),
+            Expression::Pow(comps) => Ok(Expression::Pow(Box::new((
+                (pg_id, comps.0).try_into()?,
+                (pg_id, comps.1).try_into()?,
+            )))),
+            Expression::Log(comps) => Ok(Expression::Log(Box::new((
+                (pg_id, comps.0).try_into()?,
+                (pg_id, comps.1).try_into()?,
+            )))),
+            Expression::Abs(comp) => Ok(Expression::Abs(Box::new((pg_id, *comp).try_into()?))),
+            Expression::Floor(comp) => Ok(Expression::Floor(Box::new((pg_id, *comp).try_into()?))),
+            Expression::Ceil(comp) => Ok(Expression::Ceil(Box::new((pg_id, *comp).try_into()?))),
+            Expression::Round(comp) => Ok(Expression::Round(Box::new((pg_id, *comp).try_into()?))),
+            Expression::Exp(comp) => Ok(Expression::Exp(Box::new((pg_id, *comp).try_into()?))),
+            Expression::Sqrt(comp) => Ok(Expression::Sqrt(Box::new((pg_id, *comp).try_into()?))),
+            Expression::Sin(comp) => Ok(Expression::Sin(Box::new((pg_id, *comp).try_into()?))),
+            Expression::Cos(comp) => Ok(Expression::Cos(Box::new((pg_id, *comp).try_into()?))),
+            Expression::Tan(comp) => Ok(Expression::Tan(Box::new((pg_id, *comp).try_into()?))),
             Expression::RandBool(p) => Ok(Expression::RandBool(p)),
             Expression::RandInt(l, u) => Ok(Expression::RandInt(l, u)),
             Expression::RandFloat(l, u) => Ok(Expression::RandFloat(l, u)),
+            Expression::RandUniform(params) => Ok(Expression::RandUniform(Box::new((
+                (pg_id, params.0).try_into()?,
+                (pg_id, params.1).try_into()?,
+            )))),
+            Expression::RandNormal(params) => Ok(Expression::RandNormal(Box::new((
+                (pg_id, params.0).try_into()?,
+                (pg_id, params.1).try_into()?,
+            )))),
+            Expression::RandExp(param) => {
+                Ok(Expression::RandExp(Box::new((pg_id, *param).try_into()?)))
+            }
+            Expression::RandPoisson(param) => Ok(Expression::RandPoisson(Box::new(
+                (pg_id, *param).try_into()?,
+            ))),
+            Expression::RandGeometric(param) => Ok(Expression::RandGeometric(Box::new(
+                (pg_id, *param).try_into()?,
+            ))),
+            Expression::RandChoice(choices) => Ok(Expression::RandChoice(
+                choices
+                    .into_iter()
+                    .map(|(weight, val)| {
+                        Ok(((pg_id, weight).try_into()?, (pg_id, val).try_into()?))
+                    })
+                    .collect::<Result<Vec<_>, CsError>>()?,
+            )),
             Expression::Ite(exprs) => Ok(Expression::Ite(Box::new((
                 (pg_id, exprs.0).try_into()?,
                 (pg_id, exprs.1).try_into()?,
@@ -118,7 +241,12 @@ impl TryFrom<(PgId, CsExpression)> for PgExpression {
 pub struct ChannelSystemBuilder<R: Rng> {
     program_graphs: Vec<ProgramGraphBuilder>,
     channels: Vec<(Type, Option<usize>)>,
+    faults: HashMap<Channel, FaultModel>,
+    broadcasts: HashMap<Channel, Vec<Channel>>,
     communications: HashMap<Action, (Channel, Message)>,
+    overflow: Overflow,
+    compression: bool,
+    symbols: CsSymbolTable,
     rng: R,
 }
 
@@ -143,15 +271,91 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
         Self {
             program_graphs: Vec::new(),
             channels: Vec::new(),
+            faults: HashMap::new(),
+            broadcasts: HashMap::new(),
             communications: HashMap::new(),
+            overflow: Overflow::default(),
+            compression: false,
+            symbols: CsSymbolTable::default(),
             rng,
         }
     }
 
+    /// Sets the semantics of sized integer overflow for all the PGs in the CS.
+    ///
+    /// See [`ProgramGraphBuilder::set_overflow`] for more info.
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.overflow = overflow;
+        self.program_graphs
+            .iter_mut()
+            .for_each(|pg| pg.set_overflow(overflow));
+    }
+
+    /// Sets whether all the PGs in the CS are compressed when they are built.
+    ///
+    /// Since only communications are observable in a CS,
+    /// compression does not alter the CS's observable behavior.
+    ///
+    /// See [`ProgramGraphBuilder::set_compression`] for more info.
+    pub fn set_compression(&mut self, compression: bool) {
+        self.compression = compression;
+        self.program_graphs
+            .iter_mut()
+            .for_each(|pg| pg.set_compression(compression));
+    }
+
+    // Turns a CsExpression into a PgExpression for the given PG,
+    // naming the variables not belonging to it in the error.
+    fn pg_expression(&self, pg_id: PgId, expr: CsExpression) -> Result<PgExpression, CsError> {
+        PgExpression::try_from((pg_id, expr)).map_err(|err| match err {
+            CsError::VarNotInPg(var, _) => {
+                CsError::VarNotInPg(self.named_var(var.object), self.symbols.named_pg(pg_id))
+            }
+            err => err,
+        })
+    }
+
+    // The symbols of the objects of a PG live in its builder until the CS is built.
+    fn pg_symbols(&self, pg_id: PgId) -> Option<&SymbolTable> {
+        self.program_graphs
+            .get(pg_id.0 as usize)
+            .map(ProgramGraphBuilder::symbols)
+    }
+
+    fn named_location(&self, location: Location) -> Named<Location> {
+        let symbol = self
+            .pg_symbols(location.0)
+            .and_then(|symbols| symbols.location(location.1));
+        Named::new(location, symbol)
+    }
+
+    fn named_action(&self, action: Action) -> Named<Action> {
+        let symbol = self
+            .pg_symbols(action.0)
+            .and_then(|symbols| symbols.action(action.1));
+        Named::new(action, symbol)
+    }
+
+    fn named_var(&self, var: Var) -> Named<Var> {
+        let symbol = self
+            .pg_symbols(var.0)
+            .and_then(|symbols| symbols.var(var.1));
+        Named::new(var, symbol)
+    }
+
+    fn named_clock(&self, clock: Clock) -> Named<Clock> {
+        let symbol = self
+            .pg_symbols(clock.0)
+            .and_then(|symbols| symbols.clock(clock.1));
+        Named::new(clock, symbol)
+    }
+
     /// Add a new PG to the CS.
     pub fn new_program_graph(&mut self) -> PgId {
         let pg_id = PgId(self.program_graphs.len() as u16);
-        let pg = ProgramGraphBuilder::new();
+        let mut pg = ProgramGraphBuilder::new();
+        pg.set_overflow(self.overflow);
+        pg.set_compression(self.compression);
         self.program_graphs.push(pg);
         pg_id
     }
@@ -162,17 +366,43 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
     ///
     /// See [`ProgramGraphBuilder::new_var`] for more info.
     pub fn new_var(&mut self, pg_id: PgId, init: CsExpression) -> Result<Var, CsError> {
+        let init = self.pg_expression(pg_id, init)?;
         let pg = self
             .program_graphs
             .get_mut(pg_id.0 as usize)
-            .ok_or(CsError::MissingPg(pg_id))?;
-        let init = PgExpression::try_from((pg_id, init))?;
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?;
         let var = pg
             .new_var_with_rng(init, &mut self.rng)
-            .map_err(|err| CsError::ProgramGraph(pg_id, err))?;
+            .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))?;
         Ok(Var(pg_id, var))
     }
 
+    /// Bounds the range of values of an integer variable, making it a bounded integer variable.
+    ///
+    /// It fails if the CS contains no such PG, or if the variable cannot be bounded.
+    ///
+    /// See [`ProgramGraphBuilder::set_bounds`] for more info.
+    pub fn set_bounds(&mut self, var: Var, bounds: Bounds) -> Result<(), CsError> {
+        let pg_id = var.0;
+        self.program_graphs
+            .get_mut(pg_id.0 as usize)
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
+            .set_bounds(var.1, bounds)
+            .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+    }
+
+    /// Gets the bounds of a variable.
+    ///
+    /// See [`ProgramGraphBuilder::var_bounds`] for more info.
+    pub fn var_bounds(&self, var: Var) -> Result<Bounds, CsError> {
+        let pg_id = var.0;
+        self.program_graphs
+            .get(pg_id.0 as usize)
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
+            .var_bounds(var.1)
+            .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+    }
+
     /// Adds a new clock to the given PG and returns a [`Clock`] id object.
     ///
     /// It fails if the CS contains no such PG.
@@ -181,7 +411,7 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
     pub fn new_clock(&mut self, pg_id: PgId) -> Result<Clock, CsError> {
         self.program_graphs
             .get_mut(pg_id.0 as usize)
-            .ok_or(CsError::MissingPg(pg_id))
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
             .map(|pg| Clock(pg_id, pg.new_clock()))
     }
 
@@ -193,7 +423,7 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
     pub fn new_action(&mut self, pg_id: PgId) -> Result<Action, CsError> {
         self.program_graphs
             .get_mut(pg_id.0 as usize)
-            .ok_or(CsError::MissingPg(pg_id))
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
             .map(|pg| Action(pg_id, pg.new_action()))
     }
 
@@ -205,17 +435,23 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
     /// See also [`ProgramGraphBuilder::add_reset`].
     pub fn add_reset(&mut self, pg_id: PgId, action: Action, clock: Clock) -> Result<(), CsError> {
         if action.0 != pg_id {
-            return Err(CsError::ActionNotInPg(action, pg_id));
+            return Err(CsError::ActionNotInPg(
+                self.named_action(action),
+                self.symbols.named_pg(pg_id),
+            ));
         }
         if clock.0 != pg_id {
-            return Err(CsError::ClockNotInPg(clock, pg_id));
+            return Err(CsError::ClockNotInPg(
+                self.named_clock(clock),
+                self.symbols.named_pg(pg_id),
+            ));
         }
         self.program_graphs
             .get_mut(pg_id.0 as usize)
-            .ok_or(CsError::MissingPg(pg_id))
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
             .and_then(|pg| {
                 pg.add_reset(action.1, clock.1)
-                    .map_err(|err| CsError::ProgramGraph(pg_id, err))
+                    .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
             })
     }
 
@@ -263,20 +499,26 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
         effect: CsExpression,
     ) -> Result<(), CsError> {
         if action.0 != pg_id {
-            Err(CsError::ActionNotInPg(action, pg_id))
+            Err(CsError::ActionNotInPg(
+                self.named_action(action),
+                self.symbols.named_pg(pg_id),
+            ))
         } else if var.0 != pg_id {
-            Err(CsError::VarNotInPg(var, pg_id))
+            Err(CsError::VarNotInPg(
+                self.named_var(var),
+                self.symbols.named_pg(pg_id),
+            ))
         } else if self.communications.contains_key(&action) {
             // Communications cannot have effects
             Err(CsError::ActionIsCommunication(action))
         } else {
-            let effect = PgExpression::try_from((pg_id, effect))?;
+            let effect = self.pg_expression(pg_id, effect)?;
             self.program_graphs
                 .get_mut(pg_id.0 as usize)
-                .ok_or(CsError::MissingPg(pg_id))
+                .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
                 .and_then(|pg| {
                     pg.add_effect(action.1, var.1, effect)
-                        .map_err(|err| CsError::ProgramGraph(pg_id, err))
+                        .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
                 })
         }
     }
@@ -289,7 +531,7 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
     pub fn new_location(&mut self, pg_id: PgId) -> Result<Location, CsError> {
         self.program_graphs
             .get_mut(pg_id.0 as usize)
-            .ok_or(CsError::MissingPg(pg_id))
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
             .map(|pg| Location(pg_id, pg.new_location()))
     }
 
@@ -310,17 +552,20 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
                 if c.0 == pg_id {
                     Ok((c.1, *l, *u))
                 } else {
-                    Err(CsError::DifferentPgs(pg_id, c.0))
+                    Err(CsError::DifferentPgs(
+                        self.symbols.named_pg(pg_id),
+                        self.symbols.named_pg(c.0),
+                    ))
                 }
             })
             .collect::<Result<Vec<_>, CsError>>()?;
         self.program_graphs
             .get_mut(pg_id.0 as usize)
-            .ok_or(CsError::MissingPg(pg_id))
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
             .and_then(|pg| {
                 pg.new_timed_location(invariants)
                     .map(|loc| Location(pg_id, loc))
-                    .map_err(|err| CsError::ProgramGraph(pg_id, err))
+                    .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
             })
     }
 
@@ -331,14 +576,17 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
     /// See also [`ProgramGraphBuilder::new_process`].
     pub fn new_process(&mut self, pg_id: PgId, location: Location) -> Result<(), CsError> {
         if location.0 != pg_id {
-            Err(CsError::LocationNotInPg(location, pg_id))
+            Err(CsError::LocationNotInPg(
+                self.named_location(location),
+                self.symbols.named_pg(pg_id),
+            ))
         } else {
             self.program_graphs
                 .get_mut(pg_id.0 as usize)
-                .ok_or(CsError::MissingPg(pg_id))
+                .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
                 .and_then(|pg| {
                     pg.new_process(location.1)
-                        .map_err(|err| CsError::ProgramGraph(pg_id, err))
+                        .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
                 })
         }
     }
@@ -369,17 +617,20 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
                 if c.0 == pg_id {
                     Ok((c.1, *l, *u))
                 } else {
-                    Err(CsError::DifferentPgs(pg_id, c.0))
+                    Err(CsError::DifferentPgs(
+                        self.symbols.named_pg(pg_id),
+                        self.symbols.named_pg(c.0),
+                    ))
                 }
             })
             .collect::<Result<Vec<_>, CsError>>()?;
         self.program_graphs
             .get_mut(pg_id.0 as usize)
-            .ok_or(CsError::MissingPg(pg_id))
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
             .and_then(|pg| {
                 pg.new_initial_timed_location(invariants)
                     .map(|loc| Location(pg_id, loc))
-                    .map_err(|err| CsError::ProgramGraph(pg_id, err))
+                    .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
             })
     }
 
@@ -397,22 +648,31 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
         guard: Option<CsExpression>,
     ) -> Result<(), CsError> {
         if action.0 != pg_id {
-            Err(CsError::ActionNotInPg(action, pg_id))
+            Err(CsError::ActionNotInPg(
+                self.named_action(action),
+                self.symbols.named_pg(pg_id),
+            ))
         } else if pre.0 != pg_id {
-            Err(CsError::LocationNotInPg(pre, pg_id))
+            Err(CsError::LocationNotInPg(
+                self.named_location(pre),
+                self.symbols.named_pg(pg_id),
+            ))
         } else if post.0 != pg_id {
-            Err(CsError::LocationNotInPg(post, pg_id))
+            Err(CsError::LocationNotInPg(
+                self.named_location(post),
+                self.symbols.named_pg(pg_id),
+            ))
         } else {
             // Turn CsExpression into a PgExpression for Program Graph pg_id
             let guard = guard
-                .map(|guard| PgExpression::try_from((pg_id, guard)))
+                .map(|guard| self.pg_expression(pg_id, guard))
                 .transpose()?;
             self.program_graphs
                 .get_mut(pg_id.0 as usize)
-                .ok_or(CsError::MissingPg(pg_id))
+                .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
                 .and_then(|pg| {
                     pg.add_transition(pre.1, action.1, post.1, guard)
-                        .map_err(|err| CsError::ProgramGraph(pg_id, err))
+                        .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
                 })
         }
     }
@@ -432,15 +692,24 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
         constraints: &[TimeConstraint],
     ) -> Result<(), CsError> {
         if action.0 != pg_id {
-            Err(CsError::ActionNotInPg(action, pg_id))
+            Err(CsError::ActionNotInPg(
+                self.named_action(action),
+                self.symbols.named_pg(pg_id),
+            ))
         } else if pre.0 != pg_id {
-            Err(CsError::LocationNotInPg(pre, pg_id))
+            Err(CsError::LocationNotInPg(
+                self.named_location(pre),
+                self.symbols.named_pg(pg_id),
+            ))
         } else if post.0 != pg_id {
-            Err(CsError::LocationNotInPg(post, pg_id))
+            Err(CsError::LocationNotInPg(
+                self.named_location(post),
+                self.symbols.named_pg(pg_id),
+            ))
         } else {
             // Turn CsExpression into a PgExpression for Program Graph pg_id
             let guard = guard
-                .map(|guard| PgExpression::try_from((pg_id, guard)))
+                .map(|guard| self.pg_expression(pg_id, guard))
                 .transpose()?;
             let constraints = constraints
                 .iter()
@@ -448,16 +717,19 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
                     if c.0 == pg_id {
                         Ok((c.1, *l, *u))
                     } else {
-                        Err(CsError::DifferentPgs(pg_id, c.0))
+                        Err(CsError::DifferentPgs(
+                            self.symbols.named_pg(pg_id),
+                            self.symbols.named_pg(c.0),
+                        ))
                     }
                 })
                 .collect::<Result<Vec<_>, CsError>>()?;
             self.program_graphs
                 .get_mut(pg_id.0 as usize)
-                .ok_or(CsError::MissingPg(pg_id))
+                .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
                 .and_then(|pg| {
                     pg.add_timed_transition(pre.1, action.1, post.1, guard, constraints)
-                        .map_err(|err| CsError::ProgramGraph(pg_id, err))
+                        .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
                 })
         }
     }
@@ -475,20 +747,26 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
         guard: Option<CsExpression>,
     ) -> Result<(), CsError> {
         if pre.0 != pg_id {
-            Err(CsError::LocationNotInPg(pre, pg_id))
+            Err(CsError::LocationNotInPg(
+                self.named_location(pre),
+                self.symbols.named_pg(pg_id),
+            ))
         } else if post.0 != pg_id {
-            Err(CsError::LocationNotInPg(post, pg_id))
+            Err(CsError::LocationNotInPg(
+                self.named_location(post),
+                self.symbols.named_pg(pg_id),
+            ))
         } else {
             // Turn CsExpression into a PgExpression for Program Graph pg_id
             let guard = guard
-                .map(|guard| PgExpression::try_from((pg_id, guard)))
+                .map(|guard| self.pg_expression(pg_id, guard))
                 .transpose()?;
             self.program_graphs
                 .get_mut(pg_id.0 as usize)
-                .ok_or(CsError::MissingPg(pg_id))
+                .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
                 .and_then(|pg| {
                     pg.add_autonomous_transition(pre.1, post.1, guard)
-                        .map_err(|err| CsError::ProgramGraph(pg_id, err))
+                        .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
                 })
         }
     }
@@ -507,13 +785,19 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
         constraints: &[TimeConstraint],
     ) -> Result<(), CsError> {
         if pre.0 != pg_id {
-            Err(CsError::LocationNotInPg(pre, pg_id))
+            Err(CsError::LocationNotInPg(
+                self.named_location(pre),
+                self.symbols.named_pg(pg_id),
+            ))
         } else if post.0 != pg_id {
-            Err(CsError::LocationNotInPg(post, pg_id))
+            Err(CsError::LocationNotInPg(
+                self.named_location(post),
+                self.symbols.named_pg(pg_id),
+            ))
         } else {
             // Turn CsExpression into a PgExpression for Program Graph pg_id
             let guard = guard
-                .map(|guard| PgExpression::try_from((pg_id, guard)))
+                .map(|guard| self.pg_expression(pg_id, guard))
                 .transpose()?;
             let constraints = constraints
                 .iter()
@@ -521,30 +805,356 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
                     if c.0 == pg_id {
                         Ok((c.1, *l, *u))
                     } else {
-                        Err(CsError::DifferentPgs(pg_id, c.0))
+                        Err(CsError::DifferentPgs(
+                            self.symbols.named_pg(pg_id),
+                            self.symbols.named_pg(c.0),
+                        ))
                     }
                 })
                 .collect::<Result<Vec<_>, CsError>>()?;
             self.program_graphs
                 .get_mut(pg_id.0 as usize)
-                .ok_or(CsError::MissingPg(pg_id))
+                .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
                 .and_then(|pg| {
                     pg.add_autonomous_timed_transition(pre.1, post.1, guard, constraints)
-                        .map_err(|err| CsError::ProgramGraph(pg_id, err))
+                        .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
                 })
         }
     }
 
+    /// Adds a probabilistic transition to the PG,
+    /// whose branches are given by their weight, post-location and effects.
+    ///
+    /// Fails if the CS contains no such PG, if the given action, variables or locations do not belong to it,
+    /// or if the action is a communication.
+    ///
+    /// See also [`ProgramGraphBuilder::add_probabilistic_transition`].
+    pub fn add_probabilistic_transition(
+        &mut self,
+        pg_id: PgId,
+        pre: Location,
+        action: Action,
+        guard: Option<CsExpression>,
+        branches: Vec<CsBranch>,
+    ) -> Result<Location, CsError> {
+        if action.0 != pg_id {
+            Err(CsError::ActionNotInPg(
+                self.named_action(action),
+                self.symbols.named_pg(pg_id),
+            ))
+        } else if pre.0 != pg_id {
+            Err(CsError::LocationNotInPg(
+                self.named_location(pre),
+                self.symbols.named_pg(pg_id),
+            ))
+        } else if self.communications.contains_key(&action) {
+            Err(CsError::ActionIsCommunication(action))
+        } else {
+            // Turn CsExpressions into PgExpressions for Program Graph pg_id
+            let guard = guard
+                .map(|guard| self.pg_expression(pg_id, guard))
+                .transpose()?;
+            let branches = branches
+                .into_iter()
+                .map(|(weight, post, effects)| {
+                    if post.0 != pg_id {
+                        return Err(CsError::LocationNotInPg(
+                            self.named_location(post),
+                            self.symbols.named_pg(pg_id),
+                        ));
+                    }
+                    let effects = effects
+                        .into_iter()
+                        .map(|(var, effect)| {
+                            if var.0 == pg_id {
+                                Ok((var.1, self.pg_expression(pg_id, effect)?))
+                            } else {
+                                Err(CsError::VarNotInPg(
+                                    self.named_var(var),
+                                    self.symbols.named_pg(pg_id),
+                                ))
+                            }
+                        })
+                        .collect::<Result<Vec<_>, CsError>>()?;
+                    Ok((self.pg_expression(pg_id, weight)?, post.1, effects))
+                })
+                .collect::<Result<Vec<_>, CsError>>()?;
+            self.program_graphs
+                .get_mut(pg_id.0 as usize)
+                .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
+                .and_then(|pg| {
+                    pg.add_probabilistic_transition(pre.1, action.1, guard, branches)
+                        .map(|loc| Location(pg_id, loc))
+                        .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+                })
+        }
+    }
+
+    /// Sets the priority of all transitions of the PG from the `pre` location to the `post` location.
+    ///
+    /// Priorities are compared across all PGs in the CS,
+    /// so that only the enabled transitions with the highest priority among all PGs can be executed.
+    ///
+    /// Fails if the CS contains no such PG, if the given locations do not belong to it,
+    /// or if there is no such transition.
+    ///
+    /// See also [`ProgramGraphBuilder::set_priority`].
+    pub fn set_priority(
+        &mut self,
+        pg_id: PgId,
+        pre: Location,
+        post: Location,
+        priority: Priority,
+    ) -> Result<(), CsError> {
+        if pre.0 != pg_id {
+            Err(CsError::LocationNotInPg(
+                self.named_location(pre),
+                self.symbols.named_pg(pg_id),
+            ))
+        } else if post.0 != pg_id {
+            Err(CsError::LocationNotInPg(
+                self.named_location(post),
+                self.symbols.named_pg(pg_id),
+            ))
+        } else {
+            self.program_graphs
+                .get_mut(pg_id.0 as usize)
+                .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
+                .and_then(|pg| {
+                    pg.set_priority(pre.1, post.1, priority)
+                        .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+                })
+        }
+    }
+
+    /// Sets the [`Urgency`] of the given location of the PG.
+    ///
+    /// While a PG is in a committed location,
+    /// only PGs in committed locations can execute transitions.
+    ///
+    /// Fails if the CS contains no such PG, or if the given location does not belong to it.
+    ///
+    /// See also [`ProgramGraphBuilder::set_urgency`].
+    pub fn set_urgency(
+        &mut self,
+        pg_id: PgId,
+        location: Location,
+        urgency: Urgency,
+    ) -> Result<(), CsError> {
+        if location.0 != pg_id {
+            Err(CsError::LocationNotInPg(
+                self.named_location(location),
+                self.symbols.named_pg(pg_id),
+            ))
+        } else {
+            self.program_graphs
+                .get_mut(pg_id.0 as usize)
+                .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))
+                .and_then(|pg| {
+                    pg.set_urgency(location.1, urgency)
+                        .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+                })
+        }
+    }
+
+    /// Makes a float variable a continuous variable changing over time at the given rate.
+    ///
+    /// It fails if the CS contains no such PG, or if the variable is not of float type.
+    ///
+    /// See [`ProgramGraphBuilder::set_rate`] for more info.
+    pub fn set_rate(&mut self, var: Var, rate: Float) -> Result<(), CsError> {
+        let pg_id = var.0;
+        self.program_graphs
+            .get_mut(pg_id.0 as usize)
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
+            .set_rate(var.1, rate)
+            .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+    }
+
+    /// Sets the rate at which a continuous variable changes over time while in the given location.
+    ///
+    /// It fails if the location and the variable do not belong to the same PG,
+    /// or if the variable is not of float type.
+    ///
+    /// See [`ProgramGraphBuilder::set_location_rate`] for more info.
+    pub fn set_location_rate(
+        &mut self,
+        location: Location,
+        var: Var,
+        rate: Float,
+    ) -> Result<(), CsError> {
+        let pg_id = location.0;
+        if var.0 != pg_id {
+            return Err(CsError::VarNotInPg(
+                self.named_var(var),
+                self.symbols.named_pg(pg_id),
+            ));
+        }
+        self.program_graphs
+            .get_mut(pg_id.0 as usize)
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
+            .set_location_rate(location.1, var.1, rate)
+            .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+    }
+
+    /// Stops the clock while in the given location, making it a stopwatch.
+    ///
+    /// It fails if the location and the clock do not belong to the same PG.
+    ///
+    /// See [`ProgramGraphBuilder::stop_clock`] for more info.
+    pub fn stop_clock(&mut self, location: Location, clock: Clock) -> Result<(), CsError> {
+        let pg_id = location.0;
+        if clock.0 != pg_id {
+            return Err(CsError::ClockNotInPg(
+                self.named_clock(clock),
+                self.symbols.named_pg(pg_id),
+            ));
+        }
+        self.program_graphs
+            .get_mut(pg_id.0 as usize)
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
+            .stop_clock(location.1, clock.1)
+            .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+    }
+
+    /// Sets an invariant of the given location, restricting the passing of time.
+    ///
+    /// It fails if the invariant refers to variables of other PGs,
+    /// or if it is not a well-typed boolean expression.
+    ///
+    /// See [`ProgramGraphBuilder::set_invariant`] for more info.
+    pub fn set_invariant(
+        &mut self,
+        location: Location,
+        invariant: CsExpression,
+    ) -> Result<(), CsError> {
+        let pg_id = location.0;
+        let invariant = self.pg_expression(pg_id, invariant)?;
+        self.program_graphs
+            .get_mut(pg_id.0 as usize)
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
+            .set_invariant(location.1, invariant)
+            .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+    }
+
     /// Adds a new channel of the given type and capacity to the CS.
     ///
     /// - [`None`] capacity means that the channel's capacity is unlimited.
-    /// - [`Some(0)`] capacity means the channel uses the handshake protocol (NOT YET IMPLEMENTED!)
+    /// - [`Some(0)`] capacity means the channel uses the handshake protocol:
+    ///   a send and a receive by two different PGs are executed together as a single transition,
+    ///   passing the message directly from the sender to the receiver.
     pub fn new_channel(&mut self, var_type: Type, capacity: Option<usize>) -> Channel {
         let channel = Channel(self.channels.len() as u16);
         self.channels.push((var_type, capacity));
         channel
     }
 
+    /// Adds a new broadcast channel of the given type to the CS.
+    ///
+    /// A broadcast channel has no queue of its own:
+    /// a message sent to it is delivered to all the channels subscribed to it (see [`Self::subscribe`])
+    /// in a single step, which can only happen if all of them have room for it.
+    /// PGs receive broadcast messages from the subscribed channels,
+    /// so that receiving from or probing the broadcast channel itself is not possible.
+    pub fn new_broadcast(&mut self, var_type: Type) -> Channel {
+        let channel = self.new_channel(var_type, None);
+        self.broadcasts.insert(channel, Vec::new());
+        channel
+    }
+
+    /// Subscribes the channel to the broadcast channel,
+    /// so that it gets a copy of every message sent to the broadcast,
+    /// while retaining its own queue and capacity.
+    ///
+    /// Fails if the first channel is not a broadcast, if the channels' types do not match,
+    /// or if the subscriber is a broadcast, handshake or unreliable channel.
+    ///
+    /// ```
+    /// # use scan_core::*;
+    /// # use scan_core::channel_system::*;
+    /// let mut cs_builder = ChannelSystemBuilder::new();
+    /// let broadcast = cs_builder.new_broadcast(Type::Integer);
+    /// let queue = cs_builder.new_channel(Type::Integer, Some(1));
+    /// cs_builder
+    ///     .subscribe(broadcast, queue)
+    ///     .expect("queue can subscribe to broadcast");
+    /// let handshake = cs_builder.new_channel(Type::Integer, Some(0));
+    /// assert!(cs_builder.subscribe(broadcast, handshake).is_err());
+    /// ```
+    pub fn subscribe(&mut self, broadcast: Channel, subscriber: Channel) -> Result<(), CsError> {
+        let (broadcast_type, _) = self
+            .channels
+            .get(broadcast.0 as usize)
+            .ok_or(CsError::MissingChannel(broadcast))?;
+        let (subscriber_type, capacity) = self
+            .channels
+            .get(subscriber.0 as usize)
+            .ok_or(CsError::MissingChannel(subscriber))?;
+        if !self.broadcasts.contains_key(&broadcast) {
+            Err(CsError::NotBroadcast(broadcast))
+        } else if *capacity == Some(0)
+            || self.broadcasts.contains_key(&subscriber)
+            || self
+                .faults
+                .get(&subscriber)
+                .is_some_and(|faults| !faults.is_reliable())
+        {
+            Err(CsError::InvalidSubscriber(subscriber))
+        } else if broadcast_type != subscriber_type {
+            Err(CsError::Type(TypeError::TypeMismatch {
+                expected: Expected::Type(broadcast_type.clone()),
+                found: subscriber_type.clone(),
+            }))
+        } else {
+            let subscribers = self
+                .broadcasts
+                .get_mut(&broadcast)
+                .expect("broadcast channel");
+            if !subscribers.contains(&subscriber) {
+                subscribers.push(subscriber);
+            }
+            Ok(())
+        }
+    }
+
+    /// Sets the model of the faults affecting the delivery of the messages sent through the channel,
+    /// which is reliable by default.
+    ///
+    /// Fails if the channel uses the handshake protocol, if it is a broadcast channel or subscribes to one,
+    /// or if the fault model's probabilities are not between 0 and 1.
+    ///
+    /// ```
+    /// # use scan_core::*;
+    /// # use scan_core::channel_system::*;
+    /// let mut cs_builder = ChannelSystemBuilder::new();
+    /// let lossy = cs_builder.new_channel(Type::Integer, None);
+    /// cs_builder
+    ///     .set_fault_model(lossy, FaultModel { drop: 0.1, ..Default::default() })
+    ///     .expect("valid fault model");
+    /// let handshake = cs_builder.new_channel(Type::Integer, Some(0));
+    /// assert!(cs_builder.set_fault_model(handshake, FaultModel::default()).is_err());
+    /// ```
+    pub fn set_fault_model(&mut self, channel: Channel, faults: FaultModel) -> Result<(), CsError> {
+        let (_, capacity) = self
+            .channels
+            .get(channel.0 as usize)
+            .ok_or(CsError::MissingChannel(channel))?;
+        if *capacity == Some(0) {
+            Err(CsError::FaultyHandshakeChannel(channel))
+        } else if let Some(broadcast) =
+            self.broadcasts.iter().find_map(|(broadcast, subscribers)| {
+                (*broadcast == channel || subscribers.contains(&channel)).then_some(*broadcast)
+            })
+        {
+            Err(CsError::UnreliableBroadcast(broadcast))
+        } else if !faults.is_valid() {
+            Err(CsError::InvalidFaultModel(channel))
+        } else {
+            self.faults.insert(channel, faults);
+            Ok(())
+        }
+    }
+
     /// Adds a new Send communication action to the given PG.
     ///
     /// Fails if the channel and message types do not match.
@@ -560,23 +1170,32 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
             .ok_or(CsError::MissingChannel(channel))?
             .0
             .to_owned();
-        let msg = PgExpression::try_from((pg_id, msg))?;
-        let message_type = msg.r#type().map_err(CsError::Type)?;
-        if channel_type != message_type {
-            Err(CsError::ProgramGraph(pg_id, PgError::TypeMismatch))
-        } else {
-            let action = self.program_graphs[pg_id.0 as usize]
-                .new_send(msg)
-                .map_err(|err| CsError::ProgramGraph(pg_id, err))?;
-            let action = Action(pg_id, action);
-            self.communications.insert(action, (channel, Message::Send));
-            Ok(action)
-        }
+        let msg = self.pg_expression(pg_id, msg)?;
+        let fmt_var = |var: &PgVar, f: &mut Formatter<'_>| match self.pg_symbols(pg_id) {
+            Some(symbols) => symbols.fmt_var(var, f),
+            None => write!(f, "{var:?}"),
+        };
+        msg.type_with(&fmt_var)
+            .map_err(CsError::Type)?
+            .expect(&channel_type)
+            .map_err(|err| {
+                CsError::ProgramGraph(
+                    self.symbols.named_pg(pg_id),
+                    PgError::Type(msg.in_expression_with(err, &fmt_var)),
+                )
+            })?;
+        let action = self.program_graphs[pg_id.0 as usize]
+            .new_send(msg)
+            .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))?;
+        let action = Action(pg_id, action);
+        self.communications.insert(action, (channel, Message::Send));
+        Ok(action)
     }
 
     /// Adds a new Receive communication action to the given PG.
     ///
-    /// Fails if the channel and message types do not match.
+    /// Fails if the channel and message types do not match,
+    /// or if the channel is a broadcast.
     pub fn new_receive(
         &mut self,
         pg_id: PgId,
@@ -584,7 +1203,12 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
         var: Var,
     ) -> Result<Action, CsError> {
         if pg_id != var.0 {
-            Err(CsError::VarNotInPg(var, pg_id))
+            Err(CsError::VarNotInPg(
+                self.named_var(var),
+                self.symbols.named_pg(pg_id),
+            ))
+        } else if self.broadcasts.contains_key(&channel) {
+            Err(CsError::BroadcastChannel(channel))
         } else {
             let channel_type = self
                 .channels
@@ -595,16 +1219,19 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
             let message_type = self
                 .program_graphs
                 .get(pg_id.0 as usize)
-                .ok_or(CsError::MissingPg(pg_id))?
+                .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
                 .var_type(var.1)
-                .map_err(|err| CsError::ProgramGraph(pg_id, err))?
+                .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))?
                 .to_owned();
             if channel_type != message_type {
-                Err(CsError::ProgramGraph(pg_id, PgError::TypeMismatch))
+                Err(CsError::ProgramGraph(
+                    self.symbols.named_pg(pg_id),
+                    PgError::TypeMismatch,
+                ))
             } else {
                 let action = self.program_graphs[pg_id.0 as usize]
                     .new_receive(var.1)
-                    .map_err(|err| CsError::ProgramGraph(pg_id, err))?;
+                    .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))?;
                 let action = Action(pg_id, action);
                 self.communications
                     .insert(action, (channel, Message::Receive));
@@ -615,7 +1242,7 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
 
     /// Adds a new ProbeEmptyQueue communication action to the given PG.
     ///
-    /// Fails if the queue uses the handshake protocol.
+    /// Fails if the queue uses the handshake protocol or it is a broadcast.
     pub fn new_probe_empty_queue(
         &mut self,
         pg_id: PgId,
@@ -628,11 +1255,13 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
         if matches!(cap, Some(0)) {
             // it makes no sense to probe an handshake channel
             Err(CsError::ProbingHandshakeChannel(channel))
+        } else if self.broadcasts.contains_key(&channel) {
+            Err(CsError::BroadcastChannel(channel))
         } else {
             let action = self
                 .program_graphs
                 .get_mut(pg_id.0 as usize)
-                .ok_or(CsError::MissingPg(pg_id))?
+                .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
                 .new_action();
             let action = Action(pg_id, action);
             self.communications
@@ -643,7 +1272,7 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
 
     /// Adds a new ProbeFullQueue communication action to the given PG.
     ///
-    /// Fails if the queue uses the handshake protocol or it has infinite capacity.
+    /// Fails if the queue uses the handshake protocol, it has infinite capacity or it is a broadcast.
     pub fn new_probe_full_queue(
         &mut self,
         pg_id: PgId,
@@ -656,6 +1285,8 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
         if matches!(cap, Some(0)) {
             // it makes no sense to probe an handshake channel
             Err(CsError::ProbingHandshakeChannel(channel))
+        } else if self.broadcasts.contains_key(&channel) {
+            Err(CsError::BroadcastChannel(channel))
         } else if cap.is_none() {
             // it makes no sense to probe for fullness an handshake channel
             Err(CsError::ProbingInfiniteQueue(channel))
@@ -663,7 +1294,7 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
             let action = self
                 .program_graphs
                 .get_mut(pg_id.0 as usize)
-                .ok_or(CsError::MissingPg(pg_id))?
+                .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
                 .new_action();
             let action = Action(pg_id, action);
             self.communications
@@ -672,6 +1303,92 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
         }
     }
 
+    /// Associates a [`Symbol`] to the PG,
+    /// so that it can be referred to by name, e.g., in errors and logs.
+    pub fn set_pg_symbol(&mut self, pg_id: PgId, symbol: impl Into<Symbol>) -> Result<(), CsError> {
+        if (pg_id.0 as usize) < self.program_graphs.len() {
+            self.symbols.pgs.insert(pg_id, symbol.into());
+            Ok(())
+        } else {
+            Err(CsError::MissingPg(self.symbols.named_pg(pg_id)))
+        }
+    }
+
+    /// Associates a [`Symbol`] to the channel,
+    /// so that it can be referred to by name, e.g., in errors and logs.
+    pub fn set_channel_symbol(
+        &mut self,
+        channel: Channel,
+        symbol: impl Into<Symbol>,
+    ) -> Result<(), CsError> {
+        if (channel.0 as usize) < self.channels.len() {
+            self.symbols.channels.insert(channel, symbol.into());
+            Ok(())
+        } else {
+            Err(CsError::MissingChannel(channel))
+        }
+    }
+
+    /// Associates a [`Symbol`] to the location.
+    ///
+    /// See [`ProgramGraphBuilder::set_location_symbol`].
+    pub fn set_location_symbol(
+        &mut self,
+        location: Location,
+        symbol: impl Into<Symbol>,
+    ) -> Result<(), CsError> {
+        let pg_id = location.0;
+        self.program_graphs
+            .get_mut(pg_id.0 as usize)
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
+            .set_location_symbol(location.1, symbol)
+            .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+    }
+
+    /// Associates a [`Symbol`] to the action.
+    ///
+    /// See [`ProgramGraphBuilder::set_action_symbol`].
+    pub fn set_action_symbol(
+        &mut self,
+        action: Action,
+        symbol: impl Into<Symbol>,
+    ) -> Result<(), CsError> {
+        let pg_id = action.0;
+        self.program_graphs
+            .get_mut(pg_id.0 as usize)
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
+            .set_action_symbol(action.1, symbol)
+            .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+    }
+
+    /// Associates a [`Symbol`] to the variable.
+    ///
+    /// See [`ProgramGraphBuilder::set_var_symbol`].
+    pub fn set_var_symbol(&mut self, var: Var, symbol: impl Into<Symbol>) -> Result<(), CsError> {
+        let pg_id = var.0;
+        self.program_graphs
+            .get_mut(pg_id.0 as usize)
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
+            .set_var_symbol(var.1, symbol)
+            .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+    }
+
+    /// Associates a [`Symbol`] to the clock.
+    ///
+    /// See [`ProgramGraphBuilder::set_clock_symbol`].
+    pub fn set_clock_symbol(
+        &mut self,
+        clock: Clock,
+        symbol: impl Into<Symbol>,
+    ) -> Result<(), CsError> {
+        let pg_id = clock.0;
+        self.program_graphs
+            .get_mut(pg_id.0 as usize)
+            .ok_or_else(|| CsError::MissingPg(self.symbols.named_pg(pg_id)))?
+            .set_clock_symbol(clock.1, symbol)
+            .map_err(|err| CsError::ProgramGraph(self.symbols.named_pg(pg_id), err))
+    }
+
     /// Produces a [`ChannelSystem`] defined by the [`ChannelSystemBuilder`]'s data and consuming it.
     pub fn build(mut self) -> ChannelSystem<R> {
         info!(
@@ -728,10 +1445,32 @@ impl<R: Rng + 'static> ChannelSystemBuilder<R> {
             })
             .collect();
 
+        let faults = (0..self.channels.len() as u16)
+            .map(|idx| {
+                self.faults
+                    .get(&Channel(idx))
+                    .filter(|faults| !faults.is_reliable())
+                    .copied()
+            })
+            .collect();
+
+        let broadcasts = (0..self.channels.len() as u16)
+            .map(|idx| self.broadcasts.remove(&Channel(idx)))
+            .collect();
+
+        let prioritized = program_graphs.iter().any(ProgramGraph::is_prioritized);
+        self.symbols.program_graphs = program_graphs
+            .iter()
+            .map(|pg| pg.symbols().clone())
+            .collect();
         let def = ChannelSystemDef {
             channels: self.channels,
+            faults,
+            broadcasts,
             communications,
             communications_pg_idxs,
+            prioritized,
+            symbols: self.symbols,
         };
 
         ChannelSystem {
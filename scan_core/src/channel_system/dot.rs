@@ -0,0 +1,130 @@
+// Export of channel systems in the Graphviz DOT language, to visualize their definition.
+//
+// Each PG is drawn as a cluster, with communications labelled by their channel,
+// and each channel is drawn as an edge from the PGs sending to it
+// (or to a broadcast channel it subscribes to) to the PGs receiving from it.
+
+use super::*;
+use crate::program_graph::{escape, symbol_name};
+use std::fmt::{self, Write};
+
+impl<R: Rng> ChannelSystem<R> {
+    /// Renders the CS's definition in the Graphviz DOT language.
+    ///
+    /// Every PG is rendered as a cluster (see [`ProgramGraph::to_dot`]),
+    /// with communicating transitions labelled by their channel,
+    /// and channels are rendered as dashed edges from the PGs sending to them
+    /// (or to the broadcast channels they subscribe to) to the PGs receiving from them.
+    /// Objects are referred to by their symbols, if any.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n    compound=true;\n");
+        self.write_dot(&mut dot)
+            .expect("writing to a string cannot fail");
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn channel_name(&self, channel: Channel) -> String {
+        symbol_name(self.def.symbols.channel(channel), channel)
+    }
+
+    fn write_dot(&self, out: &mut String) -> fmt::Result {
+        for (id, pg) in self.program_graphs.iter().enumerate() {
+            let pg_id = PgId(id as u16);
+            writeln!(out, "subgraph cluster_pg{id} {{")?;
+            let label = symbol_name(self.def.symbols.pg(pg_id), pg_id);
+            writeln!(out, "    label=\"{}\";", escape(&label))?;
+            pg.write_dot(out, &format!("pg{id}_"), &|action| {
+                self.def
+                    .communication(Action(pg_id, action))
+                    .map(|(channel, message)| {
+                        let channel = self.channel_name(channel);
+                        match message {
+                            Message::Send | Message::Receive => channel,
+                            Message::ProbeEmptyQueue => format!("{channel} is empty"),
+                            Message::ProbeFullQueue => format!("{channel} is full"),
+                        }
+                    })
+            })?;
+            writeln!(out, "}}")?;
+        }
+        for (idx, (t, capacity)) in self.def.channels.iter().enumerate() {
+            let channel = Channel(idx as u16);
+            let mut label = format!("{}: {t}", self.channel_name(channel));
+            if let Some(capacity) = capacity {
+                write!(label, " [{capacity}]")?;
+            }
+            let label = escape(&label);
+            let pgs = |message: Message, channels: &[Channel]| {
+                self.def
+                    .communications_pg_idxs
+                    .windows(2)
+                    .enumerate()
+                    .filter(|(_, idxs)| {
+                        self.def.communications[idxs[0] as usize..idxs[1] as usize]
+                            .iter()
+                            .any(|(_, c, m)| channels.contains(c) && *m == message)
+                    })
+                    .map(|(id, _)| id)
+                    .collect::<Vec<_>>()
+            };
+            let receivers = pgs(Message::Receive, &[channel]);
+            let senders = pgs(
+                Message::Send,
+                &(0..self.def.channels.len() as u16)
+                    .map(Channel)
+                    .filter(|broadcast| {
+                        *broadcast == channel || self.def.subscribers(*broadcast).contains(&channel)
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            for sender in senders {
+                for receiver in &receivers {
+                    // Edges between clusters have to connect some of their nodes
+                    write!(out, "pg{sender}_l0 -> pg{receiver}_l0 [label=\"{label}\", ")?;
+                    if sender != *receiver {
+                        write!(
+                            out,
+                            "ltail=cluster_pg{sender}, lhead=cluster_pg{receiver}, "
+                        )?;
+                    }
+                    writeln!(out, "style=dashed, color=blue];")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_dot() -> Result<(), CsError> {
+        let mut cs = ChannelSystemBuilder::new();
+        let channel = cs.new_channel(Type::Integer, Some(1));
+        cs.set_channel_symbol(channel, "numbers")?;
+        let sender = cs.new_program_graph();
+        cs.set_pg_symbol(sender, "sender")?;
+        let initial = cs.new_initial_location(sender)?;
+        let send = cs.new_send(sender, channel, CsExpression::from(1))?;
+        cs.add_transition(sender, initial, send, initial, None)?;
+        let receiver = cs.new_program_graph();
+        cs.set_pg_symbol(receiver, "receiver")?;
+        let initial = cs.new_initial_location(receiver)?;
+        let var = cs.new_var(receiver, CsExpression::from(0))?;
+        cs.set_var_symbol(var, "x")?;
+        let receive = cs.new_receive(receiver, channel, var)?;
+        cs.add_transition(receiver, initial, receive, initial, None)?;
+        let cs = cs.build();
+        let dot = cs.to_dot();
+        assert!(dot.contains("label=\"sender\";"));
+        assert!(dot.contains("pg0_l0 -> pg0_l0 [label=\"Action(0)\\nnumbers!1\"];"));
+        assert!(dot.contains("pg1_l0 -> pg1_l0 [label=\"Action(0)\\nnumbers?x\"];"));
+        assert!(dot.contains(
+            "pg0_l0 -> pg1_l0 [label=\"numbers: int [1]\", ltail=cluster_pg0, lhead=cluster_pg1, style=dashed, color=blue];"
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+// Names and source metadata of the objects of a CS,
+// used to make errors, logs and traces readable.
+
+use super::{Action, Channel, Clock, Location, PgId, Var};
+use crate::program_graph::{Named, Symbol, SymbolTable, describe};
+use std::collections::HashMap;
+
+/// The [`Symbol`]s of the PGs and channels of a CS,
+/// together with the [`SymbolTable`]s of its PGs.
+///
+/// Objects are not required to have a symbol.
+#[derive(Debug, Clone, Default)]
+pub struct CsSymbolTable {
+    pub(super) program_graphs: Vec<SymbolTable>,
+    pub(super) pgs: HashMap<PgId, Symbol>,
+    pub(super) channels: HashMap<Channel, Symbol>,
+}
+
+impl CsSymbolTable {
+    /// The symbol of the PG, if any.
+    pub fn pg(&self, pg_id: PgId) -> Option<&Symbol> {
+        self.pgs.get(&pg_id)
+    }
+
+    /// The symbol of the channel, if any.
+    pub fn channel(&self, channel: Channel) -> Option<&Symbol> {
+        self.channels.get(&channel)
+    }
+
+    /// The symbol table of the PG, if it exists.
+    pub fn pg_symbols(&self, pg_id: PgId) -> Option<&SymbolTable> {
+        self.program_graphs.get(pg_id.0 as usize)
+    }
+
+    /// The symbol of the location, if any.
+    pub fn location(&self, location: Location) -> Option<&Symbol> {
+        self.pg_symbols(location.0)
+            .and_then(|symbols| symbols.location(location.1))
+    }
+
+    /// The symbol of the action, if any.
+    pub fn action(&self, action: Action) -> Option<&Symbol> {
+        self.pg_symbols(action.0)
+            .and_then(|symbols| symbols.action(action.1))
+    }
+
+    /// The symbol of the variable, if any.
+    pub fn var(&self, var: Var) -> Option<&Symbol> {
+        self.pg_symbols(var.0)
+            .and_then(|symbols| symbols.var(var.1))
+    }
+
+    /// The symbol of the clock, if any.
+    pub fn clock(&self, clock: Clock) -> Option<&Symbol> {
+        self.pg_symbols(clock.0)
+            .and_then(|symbols| symbols.clock(clock.1))
+    }
+
+    pub(crate) fn named_pg(&self, pg_id: PgId) -> Named<PgId> {
+        Named::new(pg_id, self.pg(pg_id))
+    }
+
+    pub(crate) fn named_location(&self, location: Location) -> Named<Location> {
+        Named::new(location, self.location(location))
+    }
+
+    pub(crate) fn named_action(&self, action: Action) -> Named<Action> {
+        Named::new(action, self.action(action))
+    }
+
+    /// Describes the PG by its symbol, if any, or by its index otherwise.
+    pub fn describe_pg(&self, pg_id: PgId) -> String {
+        describe(self.pg(pg_id), pg_id)
+    }
+
+    /// Describes the channel by its symbol, if any, or by its index otherwise.
+    pub fn describe_channel(&self, channel: Channel) -> String {
+        describe(self.channel(channel), channel)
+    }
+
+    /// Describes the location by its symbol, if any, or by its index otherwise.
+    pub fn describe_location(&self, location: Location) -> String {
+        describe(self.location(location), location)
+    }
+
+    /// Describes the action by its symbol, if any, or by its index otherwise.
+    pub fn describe_action(&self, action: Action) -> String {
+        describe(self.action(action), action)
+    }
+
+    /// Describes the variable by its symbol, if any, or by its index otherwise.
+    pub fn describe_var(&self, var: Var) -> String {
+        describe(self.var(var), var)
+    }
+
+    /// Describes the clock by its symbol, if any, or by its index otherwise.
+    pub fn describe_clock(&self, clock: Clock) -> String {
+        describe(self.clock(clock), clock)
+    }
+}
@@ -2,23 +2,47 @@
 //!
 //! The type [`Expression<V>`] encodes the used language,
 //! where `V` is the type parameter of variables.
-//! The language features base types and product types,
-//! Boolean logic and basic arithmetic expressions.
+//! The language features base types (including strings, enumerations and sized integers) and product types,
+//! Boolean logic, arithmetic expressions and common mathematical functions.
+//!
+//! Arithmetic on [`Type::Integer`] fails with [`EvalError::Overflow`] on overflow.
+//! Arithmetic on sized integers ([`Type::Int`]) is exact (failing only if it exceeds 128 bits),
+//! and values are fitted into their type's range according to an [`Overflow`] semantics
+//! only when they are stored in variables or sent over channels.
 
 use rand::Rng;
-use std::hash::Hash;
+use rand::distr::{Distribution, weighted::WeightedIndex};
+use rand_distr::{Exp, Geometric, Normal, Poisson};
+use std::{fmt::Debug, hash::Hash, sync::Arc};
 use thiserror::Error;
 
+mod bounds;
+mod eval;
+mod syntax;
+
+pub use bounds::Bounds;
+pub(crate) use eval::FnExpression;
+pub use syntax::{ParseError, Resolver};
+pub(crate) use syntax::{Token, Tokens};
+
 /// The error type for operations with [`Type`].
-#[derive(Debug, Clone, Copy, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum TypeError {
     /// Types that should be matching are not,
     /// or are not compatible with each other.
-    #[error("type mismatch")]
-    TypeMismatch,
+    #[error("type mismatch: expected {expected}, found {found}")]
+    TypeMismatch {
+        /// The type, or class of types, that was expected.
+        expected: Expected,
+        /// The type that was found instead.
+        found: Type,
+    },
     /// The tuple has no component for such index.
     #[error("the tuple does not have the component")]
     MissingComponent,
+    /// The record has no such field.
+    #[error("the record does not have the field")]
+    MissingField,
     /// The variable's type is unknown.
     #[error("the type of variable is unknown")]
     UnknownVar,
@@ -31,6 +55,127 @@ pub enum TypeError {
     /// Probability violates some constraint.
     #[error("the probability violates some constraint")]
     BadProbability,
+    /// A random choice has no alternatives.
+    #[error("the random choice has no alternatives")]
+    EmptyChoice,
+    /// An operation is undefined on its operands, e.g., a division by zero.
+    #[error("the operation is undefined on its operands")]
+    UndefinedOperation,
+    /// The expression is random, so it has no constant value.
+    #[error("the expression is random")]
+    Random,
+    /// The error occurred in the given subexpression.
+    #[error("in expression `{expression}`")]
+    InExpression {
+        /// The offending subexpression.
+        expression: String,
+        /// The error occurring in the subexpression.
+        #[source]
+        source: Box<TypeError>,
+    },
+}
+
+impl TypeError {
+    fn mismatch(expected: Expected, found: Type) -> Self {
+        TypeError::TypeMismatch { expected, found }
+    }
+
+    /// The underlying error, stripped of the context in which it occurred.
+    pub fn kind(&self) -> &TypeError {
+        match self {
+            TypeError::InExpression { source, .. } => source.kind(),
+            err => err,
+        }
+    }
+
+    /// The offending subexpression, if known.
+    pub fn expression(&self) -> Option<&str> {
+        match self {
+            TypeError::InExpression { expression, .. } => Some(expression),
+            _ => None,
+        }
+    }
+}
+
+/// The error type for the evaluation of expressions at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum EvalError {
+    /// The operands do not have the type required by the operation.
+    #[error("type mismatch")]
+    TypeMismatch,
+    /// The index is out of bounds.
+    #[error("index out of bounds")]
+    IndexOutOfBounds,
+    /// The record has no such field.
+    #[error("missing field")]
+    MissingField,
+    /// The operation requires a non-empty list.
+    #[error("empty list")]
+    EmptyList,
+    /// An operation is undefined on its operands, e.g., a division by zero.
+    #[error("the operation is undefined on its operands")]
+    UndefinedOperation,
+    /// The parameters of a random distribution are invalid,
+    /// e.g., a negative standard deviation or weights that are all zero.
+    #[error("the parameters of the distribution are invalid")]
+    BadParameters,
+    /// Integer arithmetic overflows.
+    #[error("integer overflow")]
+    Overflow,
+}
+
+/// The type, or class of types, expected in a [`TypeError::TypeMismatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expected {
+    /// The given type.
+    Type(Type),
+    /// A numerical type.
+    Numeric,
+    /// An integer type.
+    Integer,
+    /// A tuple type.
+    Tuple,
+    /// A record type.
+    Record,
+    /// A list type.
+    List,
+    /// A list or array type.
+    ListOrArray,
+    /// A list, array or string type.
+    Sized,
+}
+
+impl std::fmt::Display for Expected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expected::Type(t) => write!(f, "{t}"),
+            Expected::Numeric => write!(f, "a numerical type"),
+            Expected::Integer => write!(f, "an integer type"),
+            Expected::Tuple => write!(f, "a tuple"),
+            Expected::Record => write!(f, "a record"),
+            Expected::List => write!(f, "a list"),
+            Expected::ListOrArray => write!(f, "a list or array"),
+            Expected::Sized => write!(f, "a list, array or string"),
+        }
+    }
+}
+
+/// A location in a source file, used by frontends to report errors,
+/// e.g., as context of a [`TypeError`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    /// The path of the source file.
+    pub file: std::path::PathBuf,
+    /// The line number, starting from 1.
+    pub line: usize,
+    /// The column number, starting from 1.
+    pub column: usize,
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file.display(), self.line, self.column)
+    }
 }
 
 /// The types supported by the language internally used by PGs and CSs.
@@ -40,12 +185,22 @@ pub enum Type {
     Boolean,
     /// Integer numerical type.
     Integer,
+    /// Sized integer numerical type.
+    Int(IntType),
     /// Floating-point numerical type.
     Float,
+    /// String type.
+    String,
+    /// Enumeration type.
+    Enum(Arc<Enum>),
+    /// Record type, with named fields.
+    Record(Arc<Record>),
     /// Product of a list of types (including other products).
     Product(Vec<Type>),
     /// List type
     List(Box<Type>),
+    /// Fixed-size array type, given by the type and the number of its elements.
+    Array(Box<Type>, usize),
 }
 
 impl Type {
@@ -55,18 +210,279 @@ impl Type {
         match self {
             Type::Boolean => Val::Boolean(false),
             Type::Integer => Val::Integer(0),
+            Type::Int(int_type) => Val::Int(*int_type, 0),
             Type::Float => Val::Float(0.0),
+            Type::String => Val::String(String::new()),
+            Type::Enum(r#enum) => Val::Enum(r#enum.clone(), 0),
+            Type::Record(record) => Val::Record(
+                record.clone(),
+                record
+                    .fields()
+                    .iter()
+                    .map(|(_, t)| t.default_value())
+                    .collect(),
+            ),
             Type::Product(tuple) => {
                 Val::Tuple(Vec::from_iter(tuple.iter().map(Self::default_value)))
             }
             Type::List(t) => Val::List((**t).clone(), Vec::new()),
+            Type::Array(t, len) => Val::Array((**t).clone(), vec![t.default_value(); *len]),
         }
     }
+
+    /// Whether the type is an integer type.
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Type::Integer | Type::Int(_))
+    }
+
+    /// Whether the type is numerical, i.e., an integer or floating-point type.
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Type::Integer | Type::Int(_) | Type::Float)
+    }
+
+    // The type resulting from arithmetic operations on values of the given types.
+    // Integers are converted to sized integers, and both to floating-point numbers, as needed,
+    // but sized integers of different types cannot be mixed.
+    fn numeric_join(lhs: &Type, rhs: &Type) -> Result<Type, TypeError> {
+        match (lhs, rhs) {
+            (Type::Integer, Type::Integer) => Ok(Type::Integer),
+            (Type::Int(lhs), Type::Int(rhs)) if lhs == rhs => Ok(Type::Int(*lhs)),
+            (Type::Int(t), Type::Integer) | (Type::Integer, Type::Int(t)) => Ok(Type::Int(*t)),
+            (Type::Int(_), Type::Int(_)) => Err(TypeError::mismatch(
+                Expected::Type(lhs.clone()),
+                rhs.clone(),
+            )),
+            (lhs, rhs) => {
+                lhs.clone().numeric()?;
+                rhs.clone().numeric()?;
+                Ok(Type::Float)
+            }
+        }
+    }
+
+    // Checks that the type is the expected one.
+    pub(crate) fn expect(self, expected: &Type) -> Result<Type, TypeError> {
+        if &self == expected {
+            Ok(self)
+        } else {
+            Err(TypeError::mismatch(Expected::Type(expected.clone()), self))
+        }
+    }
+
+    // Checks that the type is Boolean.
+    pub(crate) fn boolean(self) -> Result<Type, TypeError> {
+        self.expect(&Type::Boolean)
+    }
+
+    // Checks that the type is numerical.
+    fn numeric(self) -> Result<Type, TypeError> {
+        if self.is_numeric() {
+            Ok(self)
+        } else {
+            Err(TypeError::mismatch(Expected::Numeric, self))
+        }
+    }
+
+    // Checks that the type is an integer type.
+    fn integer(self) -> Result<Type, TypeError> {
+        if self.is_integer() {
+            Ok(self)
+        } else {
+            Err(TypeError::mismatch(Expected::Integer, self))
+        }
+    }
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Boolean => write!(f, "bool"),
+            Type::Integer => write!(f, "int"),
+            Type::Int(int_type) => write!(f, "{int_type}"),
+            Type::Float => write!(f, "float"),
+            Type::String => write!(f, "string"),
+            Type::Enum(r#enum) => write!(f, "{}", r#enum.name()),
+            Type::Record(record) => write!(f, "{}", record.name()),
+            Type::Product(types) => {
+                write!(f, "(")?;
+                for (i, t) in types.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{t}")?;
+                }
+                write!(f, ")")
+            }
+            Type::List(t) => write!(f, "[{t}]"),
+            Type::Array(t, len) => write!(f, "[{t}; {len}]"),
+        }
+    }
+}
+
+/// An enumeration, i.e., a named type with a finite list of labelled values.
+///
+/// Enumerations with different names are different types,
+/// even if they have the same labels.
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct Enum {
+    name: String,
+    labels: Vec<String>,
+}
+
+impl Enum {
+    /// Creates a new enumeration with the given name and labels.
+    pub fn new(name: String, labels: Vec<String>) -> Self {
+        Self { name, labels }
+    }
+
+    /// The name of the enumeration.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The labels of the enumeration, in order.
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// The label with the given index, if any.
+    pub fn label(&self, index: usize) -> Option<&str> {
+        self.labels.get(index).map(String::as_str)
+    }
+
+    /// The index of the given label, if any.
+    pub fn index(&self, label: &str) -> Option<usize> {
+        self.labels.iter().position(|l| l == label)
+    }
+}
+
+/// A record, i.e., a named type with a list of named and typed fields.
+///
+/// Records with different names are different types,
+/// even if they have the same fields.
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct Record {
+    name: String,
+    fields: Vec<(String, Type)>,
+}
+
+impl Record {
+    /// Creates a new record with the given name and fields.
+    pub fn new(name: String, fields: Vec<(String, Type)>) -> Self {
+        Self { name, fields }
+    }
+
+    /// The name of the record.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The fields of the record with their types, in order.
+    pub fn fields(&self) -> &[(String, Type)] {
+        &self.fields
+    }
+
+    /// The index of the given field, if any.
+    pub fn index(&self, field: &str) -> Option<usize> {
+        self.fields.iter().position(|(f, _)| f == field)
+    }
 }
 
 /// Integer values.
 pub type Integer = i32;
 
+/// Sized integer types, either signed or unsigned.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum IntType {
+    /// 8-bit signed integer.
+    I8,
+    /// 16-bit signed integer.
+    I16,
+    /// 32-bit signed integer.
+    I32,
+    /// 64-bit signed integer.
+    I64,
+    /// 8-bit unsigned integer.
+    U8,
+    /// 16-bit unsigned integer.
+    U16,
+    /// 32-bit unsigned integer.
+    U32,
+    /// 64-bit unsigned integer.
+    U64,
+}
+
+impl IntType {
+    /// The smallest value of the type.
+    pub fn min(self) -> i128 {
+        match self {
+            IntType::I8 => i8::MIN.into(),
+            IntType::I16 => i16::MIN.into(),
+            IntType::I32 => i32::MIN.into(),
+            IntType::I64 => i64::MIN.into(),
+            IntType::U8 | IntType::U16 | IntType::U32 | IntType::U64 => 0,
+        }
+    }
+
+    /// The largest value of the type.
+    pub fn max(self) -> i128 {
+        match self {
+            IntType::I8 => i8::MAX.into(),
+            IntType::I16 => i16::MAX.into(),
+            IntType::I32 => i32::MAX.into(),
+            IntType::I64 => i64::MAX.into(),
+            IntType::U8 => u8::MAX.into(),
+            IntType::U16 => u16::MAX.into(),
+            IntType::U32 => u32::MAX.into(),
+            IntType::U64 => u64::MAX.into(),
+        }
+    }
+
+    /// Fits a value into the range of the type according to the given [`Overflow`] semantics.
+    ///
+    /// Returns `None` if the value overflows and the semantics is [`Overflow::Error`].
+    pub fn fit(self, value: i128, overflow: Overflow) -> Option<i128> {
+        let (min, max) = (self.min(), self.max());
+        if (min..=max).contains(&value) {
+            Some(value)
+        } else {
+            match overflow {
+                Overflow::Wrap => Some(value.wrapping_sub(min).rem_euclid(max - min + 1) + min),
+                Overflow::Saturate => Some(value.clamp(min, max)),
+                Overflow::Error => None,
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for IntType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            IntType::I8 => "i8",
+            IntType::I16 => "i16",
+            IntType::I32 => "i32",
+            IntType::I64 => "i64",
+            IntType::U8 => "u8",
+            IntType::U16 => "u16",
+            IntType::U32 => "u32",
+            IntType::U64 => "u64",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Semantics of sized integer values overflowing the range of their type.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum Overflow {
+    /// Values wrap around the range of the type, as in two's complement arithmetic.
+    #[default]
+    Wrap,
+    /// Values are clamped to the bounds of the range of the type.
+    Saturate,
+    /// Overflowing is an error.
+    Error,
+}
+
 /// Floating-point values.
 pub type Float = f64;
 
@@ -77,12 +493,22 @@ pub enum Val {
     Boolean(bool),
     /// Integer values.
     Integer(Integer),
+    /// Sized integer values.
+    Int(IntType, i128),
     /// Floating-point values.
     Float(Float),
+    /// String values.
+    String(String),
+    /// Enumeration values, given by the index of their label.
+    Enum(Arc<Enum>, usize),
+    /// Record values, given by the values of their fields in order.
+    Record(Arc<Record>, Vec<Val>),
     /// Values for product types, i.e., tuples of suitable values.
     Tuple(Vec<Val>),
     /// Values for list types
     List(Type, Vec<Val>),
+    /// Values for array types
+    Array(Type, Vec<Val>),
 }
 
 impl Val {
@@ -91,9 +517,45 @@ impl Val {
         match self {
             Val::Boolean(_) => Type::Boolean,
             Val::Integer(_) => Type::Integer,
+            Val::Int(int_type, _) => Type::Int(*int_type),
             Val::Tuple(comps) => Type::Product(comps.iter().map(Val::r#type).collect()),
             Val::List(t, _) => Type::List(Box::new(t.clone())),
+            Val::Array(t, vals) => Type::Array(Box::new(t.clone()), vals.len()),
             Val::Float(_) => Type::Float,
+            Val::String(_) => Type::String,
+            Val::Enum(r#enum, _) => Type::Enum(r#enum.clone()),
+            Val::Record(record, _) => Type::Record(record.clone()),
+        }
+    }
+
+    /// Fits sized integer values (including those in tuples, records, lists and arrays)
+    /// into the range of their type according to the given [`Overflow`] semantics.
+    ///
+    /// Returns `None` if a value overflows and the semantics is [`Overflow::Error`].
+    pub fn fit(self, overflow: Overflow) -> Option<Val> {
+        match self {
+            Val::Int(int_type, i) => int_type.fit(i, overflow).map(|i| Val::Int(int_type, i)),
+            Val::Tuple(vals) => vals
+                .into_iter()
+                .map(|val| val.fit(overflow))
+                .collect::<Option<Vec<_>>>()
+                .map(Val::Tuple),
+            Val::List(t, vals) => vals
+                .into_iter()
+                .map(|val| val.fit(overflow))
+                .collect::<Option<Vec<_>>>()
+                .map(|vals| Val::List(t, vals)),
+            Val::Array(t, vals) => vals
+                .into_iter()
+                .map(|val| val.fit(overflow))
+                .collect::<Option<Vec<_>>>()
+                .map(|vals| Val::Array(t, vals)),
+            Val::Record(record, vals) => vals
+                .into_iter()
+                .map(|val| val.fit(overflow))
+                .collect::<Option<Vec<_>>>()
+                .map(|vals| Val::Record(record, vals)),
+            val => Some(val),
         }
     }
 }
@@ -104,12 +566,18 @@ impl From<Float> for Val {
     }
 }
 
+impl From<String> for Val {
+    fn from(value: String) -> Self {
+        Val::String(value)
+    }
+}
+
 /// Expressions for the language internally used by PGs and CSs.
 ///
 /// [`Expression<V>`] encodes the language in which `V` is the type of variables.
 ///
 /// Note that not all expressions that can be formed are well-typed.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expression<V>
 where
     V: Clone,
@@ -125,6 +593,12 @@ where
     Tuple(Vec<Expression<V>>),
     /// The component of a tuple.
     Component(usize, Box<Expression<V>>),
+    /// A record of the given type, with the values of its fields in order.
+    Record(Arc<Record>, Vec<Expression<V>>),
+    /// The field of a record, given by its index.
+    Field(usize, Box<Expression<V>>),
+    /// Replace the value of a field of a record (LHS), given by its index, with a new value (RHS).
+    SetField(usize, Box<(Expression<V>, Expression<V>)>),
     // -------------
     // Random values
     // -------------
@@ -134,6 +608,21 @@ where
     RandInt(Integer, Integer),
     /// A random float between a lower bound (included) and an upper bound (excluded).
     RandFloat(Float, Float),
+    /// A uniform distribution between the given lower bound (included) and upper bound (excluded),
+    /// which evaluates to the lower bound if the two coincide.
+    RandUniform(Box<(Expression<V>, Expression<V>)>),
+    /// A normal distribution with the given mean and standard deviation.
+    RandNormal(Box<(Expression<V>, Expression<V>)>),
+    /// An exponential distribution with the given rate.
+    RandExp(Box<Expression<V>>),
+    /// A Poisson distribution with the given rate.
+    RandPoisson(Box<Expression<V>>),
+    /// A geometric distribution with the given success probability,
+    /// counting the failures before the first success.
+    RandGeometric(Box<Expression<V>>),
+    /// A random choice among values (given by the second component)
+    /// with probability proportional to the respective weights (given by the first component).
+    RandChoice(Vec<(Expression<V>, Expression<V>)>),
     // -----------------
     // Logical operators
     // -----------------
@@ -158,6 +647,40 @@ where
     Mod(Box<(Expression<V>, Expression<V>)>),
     /// Div operation
     Div(Box<(Expression<V>, Expression<V>)>),
+    /// Conversion of a numerical expression to the given numerical type.
+    ///
+    /// Conversions to [`Type::Integer`] wrap around and truncate floating-point values,
+    /// while conversions to [`Type::Int`] preserve the (truncated) value.
+    Cast(Box<Expression<V>>, Type),
+    // ----------------------
+    // Mathematical functions
+    // ----------------------
+    /// Minimum of two numerical expressions.
+    Min(Box<(Expression<V>, Expression<V>)>),
+    /// Maximum of two numerical expressions.
+    Max(Box<(Expression<V>, Expression<V>)>),
+    /// Absolute value of a numerical expression.
+    Abs(Box<Expression<V>>),
+    /// Largest integer less than or equal to a numerical expression.
+    Floor(Box<Expression<V>>),
+    /// Smallest integer greater than or equal to a numerical expression.
+    Ceil(Box<Expression<V>>),
+    /// Nearest integer to a numerical expression, rounding half-way cases away from zero.
+    Round(Box<Expression<V>>),
+    /// Power operation: LHS to the power of RHS.
+    Pow(Box<(Expression<V>, Expression<V>)>),
+    /// Exponential function, i.e., e to the power of the expression.
+    Exp(Box<Expression<V>>),
+    /// Logarithm operation: logarithm of LHS in base RHS.
+    Log(Box<(Expression<V>, Expression<V>)>),
+    /// Square root of a numerical expression.
+    Sqrt(Box<Expression<V>>),
+    /// Sine of a numerical expression (in radians).
+    Sin(Box<Expression<V>>),
+    /// Cosine of a numerical expression (in radians).
+    Cos(Box<Expression<V>>),
+    /// Tangent of a numerical expression (in radians).
+    Tan(Box<Expression<V>>),
     // ------------
     // (In)Equality
     // ------------
@@ -178,8 +701,31 @@ where
     Append(Box<(Expression<V>, Expression<V>)>),
     /// Truncate last element from a list.
     Truncate(Box<Expression<V>>),
-    /// Take length of a list.
+    /// Take length of a list, an array or a string.
     Len(Box<Expression<V>>),
+    /// A list with elements of the given type.
+    List(Type, Vec<Expression<V>>),
+    /// A fixed-size array with elements of the given type.
+    Array(Type, Vec<Expression<V>>),
+    /// Element of a list or array (LHS) at the given index (RHS).
+    Index(Box<(Expression<V>, Expression<V>)>),
+    /// Replace the element of a list or array at the given index with a new element,
+    /// e.g., as the effect of an indexed write.
+    Update(Box<(Expression<V>, Expression<V>, Expression<V>)>),
+    /// Insert element at the front of a list.
+    PushFront(Box<(Expression<V>, Expression<V>)>),
+    /// Remove first element from a list.
+    PopFront(Box<Expression<V>>),
+    /// Whether a list or array (LHS) contains the element (RHS).
+    Contains(Box<(Expression<V>, Expression<V>)>),
+    /// The list of the elements of a list or array
+    /// from a start index (included) to an end index (excluded).
+    Slice(Box<(Expression<V>, Expression<V>, Expression<V>)>),
+    // -------
+    // Strings
+    // -------
+    /// n-ary concatenation of strings.
+    Concat(Vec<Expression<V>>),
     // /// The component of a tuple.
     // Entry(Box<(Expression<V>, Expression<V>)>),
     // -----
@@ -190,152 +736,233 @@ where
 
 impl<V> Expression<V>
 where
-    V: Clone,
+    V: Clone + Debug,
 {
     /// Computes the type of an expression.
     ///
     /// Fails if the expression is badly typed,
     /// e.g., if variables in it have type incompatible with the expression.
+    /// The error reports the innermost offending subexpression.
     pub fn r#type(&self) -> Result<Type, TypeError> {
+        self.type_with(&fmt_var_debug)
+    }
+
+    /// Computes the type of an expression, like [`Self::r#type`],
+    /// formatting the variables of the offending subexpression with the given function,
+    /// e.g., to report them by name.
+    pub fn type_with<F>(&self, fmt_var: &F) -> Result<Type, TypeError>
+    where
+        F: Fn(&V, &mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+    {
+        self.check_type(fmt_var)
+            .map_err(|err| self.in_expression_with(err, fmt_var))
+    }
+
+    // Attributes the error to the expression, unless it was already attributed to a subexpression.
+    pub(crate) fn in_expression(&self, err: TypeError) -> TypeError {
+        self.in_expression_with(err, &fmt_var_debug)
+    }
+
+    // Attributes the error to the expression, unless it was already attributed to a subexpression,
+    // formatting its variables with the given function.
+    pub(crate) fn in_expression_with<F>(&self, err: TypeError, fmt_var: &F) -> TypeError
+    where
+        F: Fn(&V, &mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+    {
+        match err {
+            TypeError::InExpression { .. } => err,
+            err => TypeError::InExpression {
+                expression: self.display_with(fmt_var).to_string(),
+                source: Box::new(err),
+            },
+        }
+    }
+
+    fn check_type<F>(&self, fmt_var: &F) -> Result<Type, TypeError>
+    where
+        F: Fn(&V, &mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+    {
         match self {
             Expression::Const(val) => Ok(val.r#type()),
             Expression::Tuple(tuple) => tuple
                 .iter()
-                .map(|e| e.r#type())
+                .map(|e| e.type_with(fmt_var))
                 .collect::<Result<Vec<Type>, TypeError>>()
                 .map(Type::Product),
             Expression::Var(_var, t) => Ok(t.clone()),
             Expression::And(props) | Expression::Or(props) => {
-                if props
-                    .iter()
-                    .map(|prop| prop.r#type())
-                    .collect::<Result<Vec<Type>, TypeError>>()?
-                    .iter()
-                    .all(|prop| matches!(prop, Type::Boolean))
-                {
-                    Ok(Type::Boolean)
-                } else {
-                    Err(TypeError::TypeMismatch)
-                }
+                props.iter().try_for_each(|prop| {
+                    prop.type_with(fmt_var)?
+                        .boolean()
+                        .map(|_| ())
+                        .map_err(|err| prop.in_expression_with(err, fmt_var))
+                })?;
+                Ok(Type::Boolean)
             }
             Expression::Implies(props) => {
-                if matches!(props.0.r#type()?, Type::Boolean)
-                    && matches!(props.1.r#type()?, Type::Boolean)
-                {
-                    Ok(Type::Boolean)
-                } else {
-                    Err(TypeError::TypeMismatch)
-                }
+                props.0.type_with(fmt_var)?.boolean()?;
+                props.1.type_with(fmt_var)?.boolean()
             }
-            Expression::Not(prop) => {
-                if matches!(prop.r#type()?, Type::Boolean) {
-                    Ok(Type::Boolean)
-                } else {
-                    Err(TypeError::TypeMismatch)
+            Expression::Not(prop) => prop
+                .type_with(fmt_var)?
+                .boolean()
+                .map_err(|err| prop.in_expression_with(err, fmt_var)),
+            Expression::Opposite(expr) | Expression::Abs(expr) => {
+                expr.type_with(fmt_var)?.numeric()
+            }
+            Expression::Floor(expr) | Expression::Ceil(expr) | Expression::Round(expr) => {
+                match expr.type_with(fmt_var)?.numeric()? {
+                    t @ Type::Int(_) => Ok(t),
+                    _ => Ok(Type::Integer),
                 }
             }
-            Expression::Opposite(expr) => match expr.r#type()? {
-                Type::Integer => Ok(Type::Integer),
-                Type::Float => Ok(Type::Float),
-                _ => Err(TypeError::TypeMismatch),
-            },
+            Expression::Exp(expr)
+            | Expression::Sqrt(expr)
+            | Expression::Sin(expr)
+            | Expression::Cos(expr)
+            | Expression::Tan(expr)
+            | Expression::RandExp(expr) => {
+                expr.type_with(fmt_var)?.numeric()?;
+                Ok(Type::Float)
+            }
+            Expression::Min(exprs) | Expression::Max(exprs) => {
+                Type::numeric_join(&exprs.0.type_with(fmt_var)?, &exprs.1.type_with(fmt_var)?)
+            }
             Expression::Sum(exprs) | Expression::Mult(exprs) => {
-                let types = exprs
-                    .iter()
-                    .map(|expr| expr.r#type())
-                    .collect::<Result<Vec<Type>, TypeError>>()?;
-
-                if types.iter().all(|expr| matches!(expr, Type::Integer)) {
-                    Ok(Type::Integer)
-                } else if types
-                    .iter()
-                    .all(|expr| matches!(expr, Type::Integer | Type::Float))
-                {
-                    Ok(Type::Float)
-                } else {
-                    Err(TypeError::TypeMismatch)
-                }
+                exprs.iter().try_fold(Type::Integer, |acc, expr| {
+                    Type::numeric_join(&acc, &expr.type_with(fmt_var)?)
+                })
+            }
+            Expression::Cast(expr, t) => {
+                expr.type_with(fmt_var)?.numeric()?;
+                t.clone().numeric()
             }
             Expression::Equal(exprs) => {
-                let type_0 = exprs.0.r#type()?;
-                let type_1 = exprs.1.r#type()?;
-                if (matches!(type_0, Type::Boolean) && matches!(type_1, Type::Boolean))
-                    || (matches!(type_0, Type::Integer | Type::Float)
-                        && matches!(type_1, Type::Integer | Type::Float))
-                {
-                    Ok(Type::Boolean)
+                let type_0 = exprs.0.type_with(fmt_var)?;
+                let type_1 = exprs.1.type_with(fmt_var)?;
+                if type_0.is_numeric() {
+                    type_1.numeric()?;
+                } else if matches!(type_0, Type::Boolean | Type::String | Type::Enum(_)) {
+                    type_1.expect(&type_0)?;
                 } else {
-                    Err(TypeError::TypeMismatch)
+                    return Err(TypeError::mismatch(Expected::Numeric, type_0));
                 }
+                Ok(Type::Boolean)
             }
             Expression::GreaterEq(exprs)
             | Expression::LessEq(exprs)
             | Expression::Greater(exprs)
             | Expression::Less(exprs) => {
-                if matches!(exprs.0.r#type()?, Type::Integer | Type::Float)
-                    && matches!(exprs.1.r#type()?, Type::Integer | Type::Float)
-                {
-                    Ok(Type::Boolean)
-                } else {
-                    Err(TypeError::TypeMismatch)
-                }
+                exprs.0.type_with(fmt_var)?.numeric()?;
+                exprs.1.type_with(fmt_var)?.numeric()?;
+                Ok(Type::Boolean)
             }
-            Expression::Component(index, expr) => {
-                if let Type::Product(components) = expr.r#type()? {
-                    components
-                        .get(*index)
-                        .cloned()
-                        .ok_or(TypeError::MissingComponent)
-                } else {
-                    Err(TypeError::TypeMismatch)
+            Expression::Component(index, expr) => match expr.type_with(fmt_var)? {
+                Type::Product(components) => components
+                    .get(*index)
+                    .cloned()
+                    .ok_or(TypeError::MissingComponent),
+                t => Err(TypeError::mismatch(Expected::Tuple, t)),
+            },
+            Expression::Record(record, fields) => {
+                if record.fields().len() != fields.len() {
+                    return Err(TypeError::MissingField);
                 }
+                record
+                    .fields()
+                    .iter()
+                    .zip(fields)
+                    .try_for_each(|((_, t), field)| {
+                        field.type_with(fmt_var)?.expect(t).map(|_| ())
+                    })?;
+                Ok(Type::Record(record.clone()))
             }
-            Expression::Append(exprs) => {
-                let list_type = exprs.0.r#type()?;
-                let element_type = exprs.1.r#type()?;
-                if let Type::List(ref elements_type) = list_type {
-                    if &element_type == elements_type.as_ref() {
-                        Ok(list_type)
-                    } else {
-                        Err(TypeError::TypeMismatch)
+            Expression::Field(index, expr) => match expr.type_with(fmt_var)? {
+                Type::Record(record) => record
+                    .fields()
+                    .get(*index)
+                    .map(|(_, t)| t.clone())
+                    .ok_or(TypeError::MissingField),
+                t => Err(TypeError::mismatch(Expected::Record, t)),
+            },
+            Expression::SetField(index, exprs) => match exprs.0.type_with(fmt_var)? {
+                Type::Record(record) => {
+                    let (_, t) = record.fields().get(*index).ok_or(TypeError::MissingField)?;
+                    exprs.1.type_with(fmt_var)?.expect(t)?;
+                    Ok(Type::Record(record))
+                }
+                t => Err(TypeError::mismatch(Expected::Record, t)),
+            },
+            Expression::Append(exprs) | Expression::PushFront(exprs) => {
+                match exprs.0.type_with(fmt_var)? {
+                    Type::List(elements_type) => {
+                        exprs.1.type_with(fmt_var)?.expect(&elements_type)?;
+                        Ok(Type::List(elements_type))
                     }
-                } else {
-                    Err(TypeError::TypeMismatch)
+                    t => Err(TypeError::mismatch(Expected::List, t)),
                 }
             }
-            Expression::Truncate(list) => {
-                let list_type = list.r#type()?;
-                if let Type::List(_) = list_type {
-                    Ok(list_type)
-                } else {
-                    Err(TypeError::TypeMismatch)
+            Expression::Truncate(list) | Expression::PopFront(list) => {
+                match list.type_with(fmt_var)? {
+                    list_type @ Type::List(_) => Ok(list_type),
+                    t => Err(TypeError::mismatch(Expected::List, t)),
                 }
             }
-            Expression::Len(list) => {
-                let list_type = list.r#type()?;
-                if let Type::List(_) = list_type {
-                    Ok(Type::Integer)
+            Expression::Len(list) => match list.type_with(fmt_var)? {
+                Type::List(_) | Type::Array(_, _) | Type::String => Ok(Type::Integer),
+                t => Err(TypeError::mismatch(Expected::Sized, t)),
+            },
+            Expression::List(t, elements) | Expression::Array(t, elements) => {
+                elements
+                    .iter()
+                    .try_for_each(|element| element.type_with(fmt_var)?.expect(t).map(|_| ()))?;
+                if let Expression::List(..) = self {
+                    Ok(Type::List(Box::new(t.clone())))
                 } else {
-                    Err(TypeError::TypeMismatch)
+                    Ok(Type::Array(Box::new(t.clone()), elements.len()))
                 }
             }
-            Expression::Mod(exprs) => {
-                if matches!(exprs.0.r#type()?, Type::Integer)
-                    && matches!(exprs.1.r#type()?, Type::Integer)
-                {
-                    Ok(Type::Integer)
-                } else {
-                    Err(TypeError::TypeMismatch)
-                }
+            Expression::Index(exprs) => {
+                Self::index_type(&exprs.0, &exprs.1, false, fmt_var).map(|(_, t)| t)
             }
-            Expression::Div(exprs) => {
-                if matches!(exprs.0.r#type()?, Type::Integer | Type::Float)
-                    && matches!(exprs.1.r#type()?, Type::Integer | Type::Float)
-                {
-                    Ok(Type::Float)
-                } else {
-                    Err(TypeError::TypeMismatch)
+            Expression::Update(exprs) => {
+                let (list_type, element_type) =
+                    Self::index_type(&exprs.0, &exprs.1, false, fmt_var)?;
+                exprs.2.type_with(fmt_var)?.expect(&element_type)?;
+                Ok(list_type)
+            }
+            Expression::Contains(exprs) => match exprs.0.type_with(fmt_var)? {
+                Type::List(t) | Type::Array(t, _) => {
+                    exprs.1.type_with(fmt_var)?.expect(&t)?;
+                    Ok(Type::Boolean)
                 }
+                t => Err(TypeError::mismatch(Expected::ListOrArray, t)),
+            },
+            Expression::Slice(exprs) => {
+                // Slices range over `start..end`, where both can be the length.
+                let (_, element_type) = Self::index_type(&exprs.0, &exprs.1, true, fmt_var)?;
+                Self::index_type(&exprs.0, &exprs.2, true, fmt_var)?;
+                Ok(Type::List(Box::new(element_type)))
+            }
+            Expression::Concat(exprs) => {
+                exprs.iter().try_for_each(|expr| {
+                    expr.type_with(fmt_var)?.expect(&Type::String).map(|_| ())
+                })?;
+                Ok(Type::String)
+            }
+            Expression::Mod(exprs) => {
+                let type_0 = exprs.0.type_with(fmt_var)?.integer()?;
+                let type_1 = exprs.1.type_with(fmt_var)?.integer()?;
+                Type::numeric_join(&type_0, &type_1)
+            }
+            Expression::Div(exprs)
+            | Expression::Pow(exprs)
+            | Expression::Log(exprs)
+            | Expression::RandUniform(exprs)
+            | Expression::RandNormal(exprs) => {
+                exprs.0.type_with(fmt_var)?.numeric()?;
+                exprs.1.type_with(fmt_var)?.numeric()?;
+                Ok(Type::Float)
             }
             Expression::RandBool(p) if 0f64 <= *p && *p <= 1f64 => Ok(Type::Boolean),
             Expression::RandBool(_) => Err(TypeError::BadProbability),
@@ -343,15 +970,23 @@ where
             Expression::RandInt(_, _) => Err(TypeError::BadBounds),
             Expression::RandFloat(l, u) if l < u => Ok(Type::Float),
             Expression::RandFloat(_, _) => Err(TypeError::BadBounds),
+            Expression::RandPoisson(expr) | Expression::RandGeometric(expr) => {
+                expr.type_with(fmt_var)?.numeric()?;
+                Ok(Type::Integer)
+            }
+            Expression::RandChoice(choices) => {
+                let mut types = choices.iter().map(|(weight, val)| {
+                    weight.type_with(fmt_var)?.numeric()?;
+                    val.type_with(fmt_var)
+                });
+                let t = types.next().ok_or(TypeError::EmptyChoice)??;
+                types.try_for_each(|other| other?.expect(&t).map(|_| ()))?;
+                Ok(t)
+            }
             Expression::Ite(exprs) => {
-                let r#if = exprs.0.r#type()?;
-                let then = exprs.1.r#type()?;
-                let r#else = exprs.2.r#type()?;
-                if matches!(r#if, Type::Boolean) && then == r#else {
-                    Ok(then)
-                } else {
-                    Err(TypeError::TypeMismatch)
-                }
+                exprs.0.type_with(fmt_var)?.boolean()?;
+                let then = exprs.1.type_with(fmt_var)?;
+                exprs.2.type_with(fmt_var)?.expect(&then)
             }
         }
     }
@@ -374,7 +1009,7 @@ where
                     if let Val::Boolean(b) = val {
                         Ok(acc && b)
                     } else {
-                        Err(TypeError::TypeMismatch)
+                        Err(self.eval_error(TypeError::UndefinedOperation))
                     }
                 })
                 .map(Val::Boolean),
@@ -385,7 +1020,7 @@ where
                     if let Val::Boolean(b) = val {
                         Ok(acc || b)
                     } else {
-                        Err(TypeError::TypeMismatch)
+                        Err(self.eval_error(TypeError::UndefinedOperation))
                     }
                 })
                 .map(Val::Boolean),
@@ -395,86 +1030,218 @@ where
                 {
                     Ok(Val::Boolean(rhs || !lhs))
                 } else {
-                    Err(TypeError::TypeMismatch)
+                    Err(self.eval_error(TypeError::UndefinedOperation))
                 }
             }
             Expression::Not(prop) => {
                 if let Val::Boolean(val) = prop.eval_constant()? {
                     Ok(Val::Boolean(!val))
                 } else {
-                    Err(TypeError::TypeMismatch)
+                    Err(self.eval_error(TypeError::UndefinedOperation))
                 }
             }
-            Expression::Opposite(expr) => match expr.eval_constant()? {
-                Val::Integer(i) => Ok(Val::Integer(-i)),
-                Val::Float(i) => Ok(Val::Float(-i)),
-                _ => Err(TypeError::TypeMismatch),
-            },
+            Expression::Opposite(expr) => opposite(&expr.eval_constant()?)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
             Expression::Sum(exprs) => exprs.iter().try_fold(Val::Integer(0), |acc, expr| {
-                let val = expr.eval_constant()?;
-                match (acc, val) {
-                    (Val::Integer(acc), Val::Integer(val)) => Ok(Val::Integer(acc + val)),
-                    (Val::Integer(acc), Val::Float(val)) => Ok(Val::Float(f64::from(acc) + val)),
-                    (Val::Float(acc), Val::Integer(val)) => Ok(Val::Float(acc + f64::from(val))),
-                    (Val::Float(acc), Val::Float(val)) => Ok(Val::Float(acc + val)),
-                    _ => Err(TypeError::TypeMismatch),
-                }
+                sum(&acc, &expr.eval_constant()?)
+                    .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation))
             }),
             Expression::Mult(exprs) => exprs.iter().try_fold(Val::Integer(1), |acc, expr| {
-                let val = expr.eval_constant()?;
-                match (acc, val) {
-                    (Val::Integer(acc), Val::Integer(val)) => Ok(Val::Integer(acc * val)),
-                    (Val::Integer(acc), Val::Float(val)) => Ok(Val::Float(f64::from(acc) * val)),
-                    (Val::Float(acc), Val::Integer(val)) => Ok(Val::Float(acc * f64::from(val))),
-                    (Val::Float(acc), Val::Float(val)) => Ok(Val::Float(acc * val)),
-                    _ => Err(TypeError::TypeMismatch),
-                }
+                mult(&acc, &expr.eval_constant()?)
+                    .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation))
             }),
-            Expression::Div(exprs) => {
-                let num = exprs.0.eval_constant()?;
-                let den = exprs.1.eval_constant()?;
-                match (num, den) {
-                    (Val::Integer(num), Val::Integer(den)) if den != 0 => {
-                        Ok(Val::Float(num as f64 / den as f64))
-                    }
-                    (Val::Integer(num), Val::Float(den)) if den != 0. => {
-                        Ok(Val::Float(num as f64 / den))
-                    }
-                    (Val::Float(num), Val::Integer(den)) if den != 0 => {
-                        Ok(Val::Float(num / den as f64))
+            Expression::Div(exprs) => div(&exprs.0.eval_constant()?, &exprs.1.eval_constant()?)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Cast(expr, t) => cast(&expr.eval_constant()?, t)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Min(exprs) => min(&exprs.0.eval_constant()?, &exprs.1.eval_constant()?)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Max(exprs) => max(&exprs.0.eval_constant()?, &exprs.1.eval_constant()?)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Abs(expr) => abs(&expr.eval_constant()?)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Floor(expr) => to_integer(&expr.eval_constant()?, Float::floor)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Ceil(expr) => to_integer(&expr.eval_constant()?, Float::ceil)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Round(expr) => to_integer(&expr.eval_constant()?, Float::round)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Pow(exprs) => float_fn2(
+                &exprs.0.eval_constant()?,
+                &exprs.1.eval_constant()?,
+                Float::powf,
+            )
+            .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Log(exprs) => float_fn2(
+                &exprs.0.eval_constant()?,
+                &exprs.1.eval_constant()?,
+                Float::log,
+            )
+            .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Exp(expr) => float_fn(&expr.eval_constant()?, Float::exp)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Sqrt(expr) => float_fn(&expr.eval_constant()?, Float::sqrt)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Sin(expr) => float_fn(&expr.eval_constant()?, Float::sin)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Cos(expr) => float_fn(&expr.eval_constant()?, Float::cos)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Tan(expr) => float_fn(&expr.eval_constant()?, Float::tan)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Concat(exprs) => exprs
+                .iter()
+                .try_fold(String::new(), |mut acc, expr| {
+                    if let Val::String(s) = expr.eval_constant()? {
+                        acc.push_str(&s);
+                        Ok(acc)
+                    } else {
+                        Err(self.eval_error(TypeError::UndefinedOperation))
                     }
-                    (Val::Float(num), Val::Float(den)) if den != 0. => Ok(Val::Float(num / den)),
-                    _ => Err(TypeError::TypeMismatch),
-                }
+                })
+                .map(Val::String),
+            Expression::Component(index, expr) => component(&expr.eval_constant()?, *index)
+                .ok_or_else(|| self.eval_error(TypeError::MissingComponent)),
+            Expression::Record(record, fields) => Ok(Val::Record(
+                record.clone(),
+                fields
+                    .iter()
+                    .map(Self::eval_constant)
+                    .collect::<Result<_, _>>()?,
+            )),
+            Expression::Field(index, expr) => field(&expr.eval_constant()?, *index)
+                .ok_or_else(|| self.eval_error(TypeError::MissingField)),
+            Expression::SetField(index, exprs) => {
+                set_field(exprs.0.eval_constant()?, *index, exprs.1.eval_constant()?)
+                    .ok_or_else(|| self.eval_error(TypeError::MissingField))
             }
-            Expression::Component(_, _expression) => todo!(),
-            Expression::RandBool(_) => todo!(),
-            Expression::RandInt(_, _) => todo!(),
-            Expression::RandFloat(_, _) => todo!(),
-            Expression::Mod(_) => todo!(),
-            Expression::Equal(_) => todo!(),
-            Expression::Greater(_) => todo!(),
-            Expression::GreaterEq(_) => todo!(),
-            Expression::Less(_) => todo!(),
-            Expression::LessEq(_) => todo!(),
-            Expression::Append(_) => todo!(),
-            Expression::Truncate(_expression) => todo!(),
-            Expression::Len(_expression) => todo!(),
+            Expression::RandBool(_)
+            | Expression::RandInt(_, _)
+            | Expression::RandFloat(_, _)
+            | Expression::RandUniform(_)
+            | Expression::RandNormal(_)
+            | Expression::RandExp(_)
+            | Expression::RandPoisson(_)
+            | Expression::RandGeometric(_)
+            | Expression::RandChoice(_) => Err(self.eval_error(TypeError::Random)),
+            Expression::Mod(exprs) => modulo(&exprs.0.eval_constant()?, &exprs.1.eval_constant()?)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Equal(exprs) => equal(&exprs.0.eval_constant()?, &exprs.1.eval_constant()?)
+                .map(Val::Boolean)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Greater(exprs) => self.eval_compare(exprs, i128::gt, Float::gt),
+            Expression::GreaterEq(exprs) => self.eval_compare(exprs, i128::ge, Float::ge),
+            Expression::Less(exprs) => self.eval_compare(exprs, i128::lt, Float::lt),
+            Expression::LessEq(exprs) => self.eval_compare(exprs, i128::le, Float::le),
+            Expression::Append(exprs) => append(exprs.0.eval_constant()?, exprs.1.eval_constant()?)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::Truncate(list) => truncate(list.eval_constant()?)
+                .ok_or_else(|| self.eval_error(TypeError::IndexOutOfBounds)),
+            Expression::Len(list) => len(&list.eval_constant()?)
+                .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation)),
+            Expression::List(t, elements) => Ok(Val::List(
+                t.clone(),
+                elements
+                    .iter()
+                    .map(Self::eval_constant)
+                    .collect::<Result<_, _>>()?,
+            )),
+            Expression::Array(t, elements) => Ok(Val::Array(
+                t.clone(),
+                elements
+                    .iter()
+                    .map(Self::eval_constant)
+                    .collect::<Result<_, _>>()?,
+            )),
+            Expression::Index(exprs) => index(&exprs.0.eval_constant()?, &exprs.1.eval_constant()?)
+                .ok_or_else(|| self.eval_error(TypeError::IndexOutOfBounds)),
+            Expression::Update(exprs) => update(
+                exprs.0.eval_constant()?,
+                &exprs.1.eval_constant()?,
+                exprs.2.eval_constant()?,
+            )
+            .ok_or_else(|| self.eval_error(TypeError::IndexOutOfBounds)),
+            Expression::PushFront(exprs) => {
+                push_front(exprs.0.eval_constant()?, exprs.1.eval_constant()?)
+                    .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation))
+            }
+            Expression::PopFront(list) => pop_front(list.eval_constant()?)
+                .ok_or_else(|| self.eval_error(TypeError::IndexOutOfBounds)),
+            Expression::Contains(exprs) => {
+                contains(&exprs.0.eval_constant()?, &exprs.1.eval_constant()?)
+                    .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation))
+            }
+            Expression::Slice(exprs) => slice(
+                &exprs.0.eval_constant()?,
+                &exprs.1.eval_constant()?,
+                &exprs.2.eval_constant()?,
+            )
+            .ok_or_else(|| self.eval_error(TypeError::IndexOutOfBounds)),
             Expression::Ite(_) => todo!(),
         }
     }
 
+    // The error of a constant expression that cannot be evaluated:
+    // a type error if the expression is badly typed, or else the given error.
+    fn eval_error(&self, error: TypeError) -> TypeError {
+        self.r#type().err().unwrap_or(error)
+    }
+
+    // Types of a list or array indexed by an integer, and of its elements.
+    // Constant indices of arrays are checked to be less than the length,
+    // or to be at most the length if `up_to_len`.
+    fn index_type<F>(
+        list: &Expression<V>,
+        idx: &Expression<V>,
+        up_to_len: bool,
+        fmt_var: &F,
+    ) -> Result<(Type, Type), TypeError>
+    where
+        F: Fn(&V, &mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+    {
+        let list_type = list.type_with(fmt_var)?;
+        idx.type_with(fmt_var)?.integer()?;
+        match list_type {
+            Type::List(ref t) => Ok((list_type.clone(), (**t).clone())),
+            Type::Array(ref t, len) => {
+                // Constant indices of arrays can be checked statically.
+                if let Expression::Const(idx) = idx
+                    && as_index(idx).is_none_or(|idx| idx > len || (idx == len && !up_to_len))
+                {
+                    Err(TypeError::IndexOutOfBounds)
+                } else {
+                    Ok((list_type.clone(), (**t).clone()))
+                }
+            }
+            t => Err(TypeError::mismatch(Expected::ListOrArray, t)),
+        }
+    }
+
+    fn eval_compare(
+        &self,
+        exprs: &(Expression<V>, Expression<V>),
+        int: fn(&i128, &i128) -> bool,
+        float: fn(&Float, &Float) -> bool,
+    ) -> Result<Val, TypeError> {
+        compare(
+            &exprs.0.eval_constant()?,
+            &exprs.1.eval_constant()?,
+            int,
+            float,
+        )
+        .map(Val::Boolean)
+        .ok_or_else(|| self.eval_error(TypeError::UndefinedOperation))
+    }
+
     pub(crate) fn context(&self, vars: &dyn Fn(V) -> Option<Type>) -> Result<(), TypeError> {
         match self {
             Expression::Var(var, t) => {
                 if let Some(var_t) = vars(var.clone()) {
-                    if &var_t == t {
-                        Ok(())
-                    } else {
-                        Err(TypeError::TypeMismatch)
-                    }
+                    t.clone()
+                        .expect(&var_t)
+                        .map(|_| ())
+                        .map_err(|err| self.in_expression(err))
                 } else {
-                    Err(TypeError::UnknownVar)
+                    Err(self.in_expression(TypeError::UnknownVar))
                 }
             }
             Expression::Const(_)
@@ -485,12 +1252,28 @@ where
             | Expression::And(tuple)
             | Expression::Or(tuple)
             | Expression::Sum(tuple)
-            | Expression::Mult(tuple) => tuple.iter().try_for_each(|expr| expr.context(vars)),
+            | Expression::Mult(tuple)
+            | Expression::Concat(tuple) => tuple.iter().try_for_each(|expr| expr.context(vars)),
             Expression::Component(_, expr)
+            | Expression::Field(_, expr)
             | Expression::Not(expr)
             | Expression::Opposite(expr)
             | Expression::Truncate(expr)
-            | Expression::Len(expr) => expr.context(vars),
+            | Expression::PopFront(expr)
+            | Expression::Len(expr)
+            | Expression::Cast(expr, _)
+            | Expression::RandExp(expr)
+            | Expression::RandPoisson(expr)
+            | Expression::RandGeometric(expr)
+            | Expression::Abs(expr)
+            | Expression::Floor(expr)
+            | Expression::Ceil(expr)
+            | Expression::Round(expr)
+            | Expression::Exp(expr)
+            | Expression::Sqrt(expr)
+            | Expression::Sin(expr)
+            | Expression::Cos(expr)
+            | Expression::Tan(expr) => expr.context(vars),
             Expression::Implies(exprs)
             | Expression::Equal(exprs)
             | Expression::Greater(exprs)
@@ -499,10 +1282,28 @@ where
             | Expression::LessEq(exprs)
             | Expression::Mod(exprs)
             | Expression::Div(exprs)
-            | Expression::Append(exprs) => {
+            | Expression::Min(exprs)
+            | Expression::Max(exprs)
+            | Expression::Pow(exprs)
+            | Expression::Log(exprs)
+            | Expression::RandUniform(exprs)
+            | Expression::RandNormal(exprs)
+            | Expression::Append(exprs)
+            | Expression::PushFront(exprs)
+            | Expression::Index(exprs)
+            | Expression::Contains(exprs)
+            | Expression::SetField(_, exprs) => {
                 exprs.0.context(vars).and_then(|_| exprs.1.context(vars))
             }
-            Expression::Ite(exprs) => exprs
+            Expression::RandChoice(choices) => choices
+                .iter()
+                .try_for_each(|(weight, val)| weight.context(vars).and_then(|_| val.context(vars))),
+            Expression::List(_, elements)
+            | Expression::Array(_, elements)
+            | Expression::Record(_, elements) => {
+                elements.iter().try_for_each(|expr| expr.context(vars))
+            }
+            Expression::Ite(exprs) | Expression::Update(exprs) | Expression::Slice(exprs) => exprs
                 .0
                 .context(vars)
                 .and_then(|_| exprs.1.context(vars))
@@ -510,14 +1311,86 @@ where
         }
     }
 
+    // Calls the given function on each occurrence of a variable in the expression.
+    pub(crate) fn for_each_var<F: FnMut(&V)>(&self, f: &mut F) {
+        match self {
+            Expression::Var(var, _) => f(var),
+            Expression::Const(_)
+            | Expression::RandBool(_)
+            | Expression::RandInt(_, _)
+            | Expression::RandFloat(_, _) => {}
+            Expression::Tuple(exprs)
+            | Expression::And(exprs)
+            | Expression::Or(exprs)
+            | Expression::Sum(exprs)
+            | Expression::Mult(exprs)
+            | Expression::Concat(exprs)
+            | Expression::List(_, exprs)
+            | Expression::Array(_, exprs)
+            | Expression::Record(_, exprs) => exprs.iter().for_each(|expr| expr.for_each_var(f)),
+            Expression::Component(_, expr)
+            | Expression::Field(_, expr)
+            | Expression::Not(expr)
+            | Expression::Opposite(expr)
+            | Expression::Truncate(expr)
+            | Expression::PopFront(expr)
+            | Expression::Len(expr)
+            | Expression::Cast(expr, _)
+            | Expression::RandExp(expr)
+            | Expression::RandPoisson(expr)
+            | Expression::RandGeometric(expr)
+            | Expression::Abs(expr)
+            | Expression::Floor(expr)
+            | Expression::Ceil(expr)
+            | Expression::Round(expr)
+            | Expression::Exp(expr)
+            | Expression::Sqrt(expr)
+            | Expression::Sin(expr)
+            | Expression::Cos(expr)
+            | Expression::Tan(expr) => expr.for_each_var(f),
+            Expression::Implies(exprs)
+            | Expression::Equal(exprs)
+            | Expression::Greater(exprs)
+            | Expression::GreaterEq(exprs)
+            | Expression::Less(exprs)
+            | Expression::LessEq(exprs)
+            | Expression::Mod(exprs)
+            | Expression::Div(exprs)
+            | Expression::Min(exprs)
+            | Expression::Max(exprs)
+            | Expression::Pow(exprs)
+            | Expression::Log(exprs)
+            | Expression::RandUniform(exprs)
+            | Expression::RandNormal(exprs)
+            | Expression::Append(exprs)
+            | Expression::PushFront(exprs)
+            | Expression::Index(exprs)
+            | Expression::Contains(exprs)
+            | Expression::SetField(_, exprs) => {
+                exprs.0.for_each_var(f);
+                exprs.1.for_each_var(f);
+            }
+            Expression::RandChoice(choices) => choices.iter().for_each(|(weight, val)| {
+                weight.for_each_var(f);
+                val.for_each_var(f);
+            }),
+            Expression::Ite(exprs) | Expression::Update(exprs) | Expression::Slice(exprs) => {
+                exprs.0.for_each_var(f);
+                exprs.1.for_each_var(f);
+                exprs.2.for_each_var(f);
+            }
+        }
+    }
+
     /// Creates the disjunction of a list of expressions.
     ///
     /// Optimizes automatically nested disjunctions through associativity.
     pub fn and(args: Vec<Self>) -> Result<Self, TypeError> {
         args.iter().try_for_each(|arg| {
-            matches!(arg.r#type()?, Type::Boolean)
-                .then_some(())
-                .ok_or(TypeError::TypeMismatch)
+            arg.r#type()?
+                .boolean()
+                .map(|_| ())
+                .map_err(|err| arg.in_expression(err))
         })?;
         match args.len() {
             0 => Ok(Expression::Const(Val::Boolean(true))),
@@ -541,9 +1414,10 @@ where
     /// Optimizes automatically nested conjunctions through associativity.
     pub fn or(args: Vec<Self>) -> Result<Self, TypeError> {
         args.iter().try_for_each(|arg| {
-            matches!(arg.r#type()?, Type::Boolean)
-                .then_some(())
-                .ok_or(TypeError::TypeMismatch)
+            arg.r#type()?
+                .boolean()
+                .map(|_| ())
+                .map_err(|err| arg.in_expression(err))
         })?;
         match args.len() {
             0 => Ok(Expression::Const(Val::Boolean(false))),
@@ -572,23 +1446,54 @@ where
             Expression::Component(index, Box::new(self))
         }
     }
+
+    /// Creates the access to a field of a record expression, given by its name.
+    ///
+    /// Optimizes automatically the field of a record construction.
+    pub fn field(self, name: &str) -> Result<Self, TypeError> {
+        let record = self.record_type()?;
+        let index = record
+            .index(name)
+            .ok_or_else(|| self.in_expression(TypeError::MissingField))?;
+        if let Expression::Record(_, mut fields) = self {
+            Ok(fields.swap_remove(index))
+        } else {
+            Ok(Expression::Field(index, Box::new(self)))
+        }
+    }
+
+    // The record type of a record expression.
+    fn record_type(&self) -> Result<Arc<Record>, TypeError> {
+        match self.r#type()? {
+            Type::Record(record) => Ok(record),
+            t => Err(self.in_expression(TypeError::mismatch(Expected::Record, t))),
+        }
+    }
+
+    /// Creates the update of a field of a record expression, given by its name.
+    pub fn set_field(self, name: &str, value: Self) -> Result<Self, TypeError> {
+        let record = self.record_type()?;
+        let index = record
+            .index(name)
+            .ok_or_else(|| self.in_expression(TypeError::MissingField))?;
+        Ok(Expression::SetField(index, Box::new((self, value))))
+    }
 }
 
 impl<V> std::ops::Not for Expression<V>
 where
-    V: Clone,
+    V: Clone + Debug,
 {
     type Output = Result<Self, TypeError>;
 
     fn not(self) -> Self::Output {
-        if let Type::Boolean = self.r#type()? {
-            if let Expression::Not(sub) = self {
-                Ok(*sub)
-            } else {
-                Ok(Expression::Not(Box::new(self)))
-            }
+        self.r#type()?
+            .boolean()
+            .map_err(|err| self.in_expression(err))?;
+        if let Expression::Not(sub) = self {
+            Ok(*sub)
         } else {
-            Err(TypeError::TypeMismatch)
+            Ok(Expression::Not(Box::new(self)))
         }
     }
 }
@@ -697,342 +1602,662 @@ where
     }
 }
 
-type DynFnExpr<V, R> = dyn Fn(&dyn Fn(V) -> Val, &mut R) -> Val + Send + Sync;
+// Formats variables of expressions by their debug representation.
+fn fmt_var_debug<V: Debug>(var: &V, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{var:?}")
+}
 
-pub(crate) struct FnExpression<V, R: Rng>(Box<DynFnExpr<V, R>>);
+// Mathematical functions over values,
+// shared by constant folding and evaluation.
+// Return `None` if values have the wrong type.
 
-impl<C, R: Rng> std::fmt::Debug for FnExpression<C, R> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Expression")
+fn as_float(val: &Val) -> Option<Float> {
+    match *val {
+        Val::Integer(i) => Some(Float::from(i)),
+        Val::Int(_, i) => Some(i as Float),
+        Val::Float(f) => Some(f),
+        _ => None,
     }
 }
 
-impl<V, R: Rng> FnExpression<V, R> {
-    #[inline(always)]
-    pub fn eval(&self, vars: &dyn Fn(V) -> Val, rng: &mut R) -> Val {
-        self.0(vars, rng)
+// Applies an arithmetic operation, with the appropriate version for each numerical type.
+// Operations on sized integers are performed with (wrapping) 128-bit precision.
+// Integer operations return `None` if undefined on their operands.
+fn arithmetic(
+    lhs: &Val,
+    rhs: &Val,
+    integer: fn(Integer, Integer) -> Option<Integer>,
+    int: fn(i128, i128) -> Option<i128>,
+    float: fn(Float, Float) -> Float,
+) -> Option<Val> {
+    match (lhs, rhs) {
+        (&Val::Integer(lhs), &Val::Integer(rhs)) => integer(lhs, rhs).map(Val::Integer),
+        (&Val::Int(t, lhs), &Val::Int(_, rhs)) => int(lhs, rhs).map(|i| Val::Int(t, i)),
+        (&Val::Int(t, lhs), &Val::Integer(rhs)) => int(lhs, rhs.into()).map(|i| Val::Int(t, i)),
+        (&Val::Integer(lhs), &Val::Int(t, rhs)) => int(lhs.into(), rhs).map(|i| Val::Int(t, i)),
+        (lhs, rhs) => Some(Val::Float(float(as_float(lhs)?, as_float(rhs)?))),
     }
 }
 
-impl<V: Clone + Send + Sync + 'static, R: Rng + 'static> From<Expression<V>>
-    for FnExpression<V, R>
-{
-    fn from(value: Expression<V>) -> Self {
-        FnExpression(match value {
-            Expression::Const(val) => Box::new(move |_, _| val.clone()),
-            Expression::Var(var, t) => Box::new(move |vars, _| {
-                // vars(var.clone())
-                let val = vars(var.clone());
-                if t == val.r#type() {
-                    val
-                } else {
-                    panic!("value and variable type mismatch");
-                }
-            }),
-            Expression::Tuple(exprs) => {
-                let exprs: Vec<FnExpression<_, _>> =
-                    exprs.into_iter().map(FnExpression::from).collect();
-                Box::new(move |vars, rng| {
-                    Val::Tuple(
-                        exprs
-                            .iter()
-                            .map(|expr| expr.eval(vars, rng))
-                            .collect::<Vec<_>>(),
-                    )
-                })
-            }
-            Expression::Component(index, expr) => {
-                let expr = Self::from(*expr);
-                Box::new(move |vars, rng| {
-                    if let Val::Tuple(vals) = expr.eval(vars, rng) {
-                        vals[index].clone()
-                    } else {
-                        panic!("index out of bounds");
-                    }
-                })
-            }
-            Expression::And(exprs) => {
-                let exprs: Vec<FnExpression<_, _>> = exprs.into_iter().map(Self::from).collect();
-                Box::new(move |vars, rng| {
-                    Val::Boolean(exprs.iter().all(|expr| {
-                        if let Val::Boolean(b) = expr.eval(vars, rng) {
-                            b
-                        } else {
-                            panic!("type mismatch");
-                        }
-                    }))
-                })
-            }
-            Expression::Or(exprs) => {
-                let exprs: Vec<FnExpression<_, _>> = exprs.into_iter().map(Self::from).collect();
-                Box::new(move |vars, rng| {
-                    Val::Boolean(exprs.iter().any(|expr| {
-                        if let Val::Boolean(b) = expr.eval(vars, rng) {
-                            b
-                        } else {
-                            panic!("type mismatch");
-                        }
-                    }))
-                })
-            }
-            Expression::Implies(exprs) => {
-                let (lhs, rhs) = *exprs;
-                let lhs = FnExpression::from(lhs);
-                let rhs = FnExpression::from(rhs);
-                Box::new(move |vars, rng| {
-                    if let (Val::Boolean(lhs), Val::Boolean(rhs)) =
-                        (lhs.eval(vars, rng), rhs.eval(vars, rng))
-                    {
-                        Val::Boolean(rhs || !lhs)
-                    } else {
-                        panic!("type mismatch");
-                    }
-                })
-            }
-            Expression::Not(expr) => {
-                let expr = FnExpression::from(*expr);
-                Box::new(move |vars, rng| {
-                    if let Val::Boolean(b) = expr.eval(vars, rng) {
-                        Val::Boolean(!b)
-                    } else {
-                        panic!("type mismatch");
-                    }
-                })
-            }
-            Expression::Opposite(expr) => {
-                let expr = FnExpression::from(*expr);
-                Box::new(move |vars, rng| match expr.eval(vars, rng) {
-                    Val::Integer(i) => Val::Integer(-i),
-                    Val::Float(f) => Val::Float(-f),
-                    _ => panic!("type mismatch"),
-                })
-            }
-            Expression::Sum(exprs) => {
-                let exprs: Vec<FnExpression<_, _>> = exprs.into_iter().map(Self::from).collect();
-                Box::new(move |vars, rng| {
-                    exprs.iter().fold(Val::Integer(0), |val, expr| match val {
-                        Val::Integer(acc) => match expr.eval(vars, rng) {
-                            Val::Integer(i) => Val::Integer(acc + i),
-                            Val::Float(f) => Val::Float(f64::from(acc) + f),
-                            _ => panic!("type mismatch"),
-                        },
-                        Val::Float(acc) => match expr.eval(vars, rng) {
-                            Val::Integer(i) => Val::Float(acc + f64::from(i)),
-                            Val::Float(f) => Val::Float(acc + f),
-                            _ => panic!("type mismatch"),
-                        },
-                        _ => panic!("type mismatch"),
-                    })
-                })
-            }
-            Expression::Mult(exprs) => {
-                let exprs: Vec<FnExpression<_, _>> = exprs.into_iter().map(Self::from).collect();
-                Box::new(move |vars, rng| {
-                    exprs.iter().fold(Val::Integer(1), |val, expr| match val {
-                        Val::Integer(acc) => match expr.eval(vars, rng) {
-                            Val::Integer(i) => Val::Integer(acc * i),
-                            Val::Float(f) => Val::Float(f64::from(acc) * f),
-                            _ => panic!("type mismatch"),
-                        },
-                        Val::Float(acc) => match expr.eval(vars, rng) {
-                            Val::Integer(i) => Val::Float(acc * f64::from(i)),
-                            Val::Float(f) => Val::Float(acc * f),
-                            _ => panic!("type mismatch"),
-                        },
-                        _ => panic!("type mismatch"),
-                    })
-                })
-            }
-            Expression::Equal(exprs) => {
-                let (lhs, rhs) = *exprs;
-                let lhs = FnExpression::from(lhs);
-                let rhs = FnExpression::from(rhs);
-                Box::new(
-                    move |vars, rng| match (lhs.eval(vars, rng), rhs.eval(vars, rng)) {
-                        (Val::Integer(lhs), Val::Integer(rhs)) => Val::Boolean(lhs == rhs),
-                        (Val::Integer(lhs), Val::Float(rhs)) => Val::Boolean(lhs as Float == rhs),
-                        (Val::Float(lhs), Val::Integer(rhs)) => Val::Boolean(lhs == rhs as Float),
-                        (Val::Float(lhs), Val::Float(rhs)) => Val::Boolean(lhs == rhs),
-                        (Val::Boolean(lhs), Val::Boolean(rhs)) => Val::Boolean(lhs == rhs),
-                        _ => panic!("type mismatch"),
-                    },
-                )
-            }
-            Expression::Greater(exprs) => {
-                let (lhs, rhs) = *exprs;
-                let lhs = FnExpression::from(lhs);
-                let rhs = FnExpression::from(rhs);
-                Box::new(move |vars, rng| match lhs.eval(vars, rng) {
-                    Val::Integer(lhs) => match rhs.eval(vars, rng) {
-                        Val::Integer(rhs) => Val::Boolean(lhs > rhs),
-                        Val::Float(rhs) => Val::Boolean(f64::from(lhs) > rhs),
-                        _ => panic!("type mismatch"),
-                    },
-                    Val::Float(lhs) => match rhs.eval(vars, rng) {
-                        Val::Integer(rhs) => Val::Boolean(lhs > f64::from(rhs)),
-                        Val::Float(rhs) => Val::Boolean(lhs > rhs),
-                        _ => panic!("type mismatch"),
-                    },
-                    _ => panic!("type mismatch"),
-                })
-            }
-            Expression::GreaterEq(exprs) => {
-                let (lhs, rhs) = *exprs;
-                let lhs = FnExpression::from(lhs);
-                let rhs = FnExpression::from(rhs);
-                Box::new(move |vars, rng| match lhs.eval(vars, rng) {
-                    Val::Integer(lhs) => match rhs.eval(vars, rng) {
-                        Val::Integer(rhs) => Val::Boolean(lhs >= rhs),
-                        Val::Float(rhs) => Val::Boolean(f64::from(lhs) >= rhs),
-                        _ => panic!("type mismatch"),
-                    },
-                    Val::Float(lhs) => match rhs.eval(vars, rng) {
-                        Val::Integer(rhs) => Val::Boolean(lhs >= f64::from(rhs)),
-                        Val::Float(rhs) => Val::Boolean(lhs >= rhs),
-                        _ => panic!("type mismatch"),
-                    },
-                    _ => panic!("type mismatch"),
-                })
-            }
-            Expression::Less(exprs) => {
-                let (lhs, rhs) = *exprs;
-                let lhs = FnExpression::from(lhs);
-                let rhs = FnExpression::from(rhs);
-                Box::new(move |vars, rng| match lhs.eval(vars, rng) {
-                    Val::Integer(lhs) => match rhs.eval(vars, rng) {
-                        Val::Integer(rhs) => Val::Boolean(lhs < rhs),
-                        Val::Float(rhs) => Val::Boolean(f64::from(lhs) < rhs),
-                        _ => panic!("type mismatch"),
-                    },
-                    Val::Float(lhs) => match rhs.eval(vars, rng) {
-                        Val::Integer(rhs) => Val::Boolean(lhs < f64::from(rhs)),
-                        Val::Float(rhs) => Val::Boolean(lhs < rhs),
-                        _ => panic!("type mismatch"),
-                    },
-                    _ => panic!("type mismatch"),
-                })
-            }
-            Expression::LessEq(exprs) => {
-                let (source_lhs, source_rhs) = *exprs;
-                let lhs = FnExpression::from(source_lhs);
-                let rhs = FnExpression::from(source_rhs);
-                Box::new(move |vars, rng| match lhs.eval(vars, rng) {
-                    Val::Integer(lhs) => match rhs.eval(vars, rng) {
-                        Val::Integer(rhs) => Val::Boolean(lhs <= rhs),
-                        Val::Float(rhs) => Val::Boolean(f64::from(lhs) <= rhs),
-                        _ => panic!("type mismatch"),
-                    },
-                    Val::Float(lhs) => match rhs.eval(vars, rng) {
-                        Val::Integer(rhs) => Val::Boolean(lhs <= f64::from(rhs)),
-                        Val::Float(rhs) => Val::Boolean(lhs <= rhs),
-                        _ => panic!("type mismatch"),
-                    },
-                    _ => panic!("type mismatch"),
-                })
-            }
-            Expression::Append(exprs) => {
-                let (list, element) = *exprs;
-                let list = FnExpression::from(list);
-                let element = FnExpression::from(element);
-                Box::new(move |vars, rng| {
-                    if let Val::List(t, mut l) = list.eval(vars, rng) {
-                        let element = element.eval(vars, rng);
-                        if element.r#type() == t {
-                            l.push(element);
-                            Val::List(t, l)
-                        } else {
-                            panic!("type mismatch");
-                        }
-                    } else {
-                        panic!("type mismatch");
-                    }
-                })
-            }
-            Expression::Truncate(list) => {
-                let list = FnExpression::from(*list);
-                Box::new(move |vars, rng| {
-                    if let Val::List(t, mut l) = list.eval(vars, rng) {
-                        if !l.is_empty() {
-                            let _ = l.pop();
-                            Val::List(t, l)
-                        } else {
-                            panic!("type mismatch");
-                        }
-                    } else {
-                        panic!("type mismatch");
-                    }
-                })
-            }
-            Expression::Len(list) => {
-                let list = FnExpression::from(*list);
-                Box::new(move |vars, rng| {
-                    if let Val::List(_t, l) = list.eval(vars, rng) {
-                        Val::Integer(l.len() as Integer)
-                    } else {
-                        panic!("type mismatch");
-                    }
-                })
-            }
-            Expression::Mod(exprs) => {
-                let (lhs, rhs) = *exprs;
-                let lhs = FnExpression::from(lhs);
-                let rhs = FnExpression::from(rhs);
-                Box::new(move |vars, rng| {
-                    if let (Val::Integer(lhs), Val::Integer(rhs)) =
-                        (lhs.eval(vars, rng), rhs.eval(vars, rng))
-                    {
-                        Val::Integer(lhs % rhs)
-                    } else {
-                        panic!("type mismatch");
-                    }
-                })
-            }
-            Expression::Div(exprs) => {
-                let (lhs, rhs) = *exprs;
-                let lhs = FnExpression::from(lhs);
-                let rhs = FnExpression::from(rhs);
-                Box::new(move |vars, rng| {
-                    let num = lhs.eval(vars, rng);
-                    let den = rhs.eval(vars, rng);
-                    match (num, den) {
-                        (Val::Integer(num), Val::Integer(den)) if den != 0 => {
-                            Val::Float(num as f64 / den as f64)
-                        }
-                        (Val::Integer(num), Val::Float(den)) if den != 0. => {
-                            Val::Float(num as f64 / den)
-                        }
-                        (Val::Float(num), Val::Integer(den)) if den != 0 => {
-                            Val::Float(num / den as f64)
-                        }
-                        (Val::Float(num), Val::Float(den)) if den != 0. => Val::Float(num / den),
-                        _ => panic!("type mismatch"),
-                    }
-                })
-            }
-            Expression::RandBool(p) => Box::new(move |_, rng| Val::Boolean(rng.random_bool(p))),
-            Expression::RandInt(l, u) => {
-                Box::new(move |_, rng| Val::Integer(rng.random_range(l..u)))
-            }
-            Expression::RandFloat(l, u) => {
-                Box::new(move |_, rng| Val::Float(rng.random_range(l..u)))
-            }
-            Expression::Ite(exprs) => {
-                let r#if = FnExpression::from(exprs.0);
-                let then = FnExpression::from(exprs.1);
-                let r#else = FnExpression::from(exprs.2);
-                Box::new(move |vars, rng| {
-                    let r#if = r#if.eval(vars, rng);
-                    if let Val::Boolean(r#if) = r#if {
-                        if r#if {
-                            then.eval(vars, rng)
-                        } else {
-                            r#else.eval(vars, rng)
-                        }
-                    } else {
-                        panic!("type mismatch");
-                    }
-                })
+// Compares numerical values, converting them to floating-point only if either is not an integer.
+fn compare(
+    lhs: &Val,
+    rhs: &Val,
+    int: fn(&i128, &i128) -> bool,
+    float: fn(&Float, &Float) -> bool,
+) -> Option<bool> {
+    match (lhs, rhs) {
+        (&Val::Integer(lhs), &Val::Integer(rhs)) => Some(int(&lhs.into(), &rhs.into())),
+        (&Val::Int(_, lhs), &Val::Int(_, rhs)) => Some(int(&lhs, &rhs)),
+        (&Val::Int(_, lhs), &Val::Integer(rhs)) => Some(int(&lhs, &rhs.into())),
+        (&Val::Integer(lhs), &Val::Int(_, rhs)) => Some(int(&lhs.into(), &rhs)),
+        (lhs, rhs) => Some(float(&as_float(lhs)?, &as_float(rhs)?)),
+    }
+}
+
+fn equal(lhs: &Val, rhs: &Val) -> Option<bool> {
+    match (lhs, rhs) {
+        (Val::Integer(lhs), Val::Integer(rhs)) => Some(lhs == rhs),
+        (Val::Boolean(lhs), Val::Boolean(rhs)) => Some(lhs == rhs),
+        (Val::String(lhs), Val::String(rhs)) => Some(lhs == rhs),
+        (Val::Enum(_, lhs), Val::Enum(_, rhs)) => Some(lhs == rhs),
+        (lhs, rhs) => compare(lhs, rhs, i128::eq, Float::eq),
+    }
+}
+
+fn sum(lhs: &Val, rhs: &Val) -> Option<Val> {
+    arithmetic(lhs, rhs, Integer::checked_add, i128::checked_add, |l, r| {
+        l + r
+    })
+}
+
+fn mult(lhs: &Val, rhs: &Val) -> Option<Val> {
+    arithmetic(lhs, rhs, Integer::checked_mul, i128::checked_mul, |l, r| {
+        l * r
+    })
+}
+
+fn modulo(lhs: &Val, rhs: &Val) -> Option<Val> {
+    match (lhs, rhs) {
+        (Val::Float(_), _) | (_, Val::Float(_)) => None,
+        (lhs, rhs) => arithmetic(lhs, rhs, Integer::checked_rem, i128::checked_rem, |l, r| {
+            l % r
+        }),
+    }
+}
+
+fn div(num: &Val, den: &Val) -> Option<Val> {
+    let (num, den) = (as_float(num)?, as_float(den)?);
+    (den != 0.).then(|| Val::Float(num / den))
+}
+
+fn opposite(val: &Val) -> Option<Val> {
+    match *val {
+        Val::Integer(i) => i.checked_neg().map(Val::Integer),
+        Val::Int(t, i) => i.checked_neg().map(|i| Val::Int(t, i)),
+        Val::Float(f) => Some(Val::Float(-f)),
+        _ => None,
+    }
+}
+
+fn cast(val: &Val, t: &Type) -> Option<Val> {
+    match (val, t) {
+        (&Val::Integer(i), Type::Integer) => Some(Val::Integer(i)),
+        (&Val::Int(_, i), Type::Integer) => Some(Val::Integer(i as Integer)),
+        (&Val::Float(f), Type::Integer) => Some(Val::Integer(f as Integer)),
+        (&Val::Integer(i), Type::Int(t)) => Some(Val::Int(*t, i.into())),
+        (&Val::Int(_, i), Type::Int(t)) => Some(Val::Int(*t, i)),
+        (&Val::Float(f), Type::Int(t)) => Some(Val::Int(*t, f as i128)),
+        (val, Type::Float) => as_float(val).map(Val::Float),
+        _ => None,
+    }
+}
+
+fn min(lhs: &Val, rhs: &Val) -> Option<Val> {
+    arithmetic(
+        lhs,
+        rhs,
+        |l, r| Some(l.min(r)),
+        |l, r| Some(l.min(r)),
+        Float::min,
+    )
+}
+
+fn max(lhs: &Val, rhs: &Val) -> Option<Val> {
+    arithmetic(
+        lhs,
+        rhs,
+        |l, r| Some(l.max(r)),
+        |l, r| Some(l.max(r)),
+        Float::max,
+    )
+}
+
+fn abs(val: &Val) -> Option<Val> {
+    match *val {
+        Val::Integer(i) => i.checked_abs().map(Val::Integer),
+        Val::Int(t, i) => i.checked_abs().map(|i| Val::Int(t, i)),
+        Val::Float(f) => Some(Val::Float(f.abs())),
+        _ => None,
+    }
+}
+
+fn to_integer(val: &Val, f: fn(Float) -> Float) -> Option<Val> {
+    match *val {
+        Val::Integer(i) => Some(Val::Integer(i)),
+        Val::Int(t, i) => Some(Val::Int(t, i)),
+        Val::Float(x) => Some(Val::Integer(f(x) as Integer)),
+        _ => None,
+    }
+}
+
+fn component(tuple: &Val, index: usize) -> Option<Val> {
+    match tuple {
+        Val::Tuple(vals) => vals.get(index).cloned(),
+        _ => None,
+    }
+}
+
+fn field(record: &Val, index: usize) -> Option<Val> {
+    match record {
+        Val::Record(_, vals) => vals.get(index).cloned(),
+        _ => None,
+    }
+}
+
+fn set_field(record: Val, index: usize, value: Val) -> Option<Val> {
+    match record {
+        Val::Record(record, mut vals) => {
+            *vals.get_mut(index)? = value;
+            Some(Val::Record(record, vals))
+        }
+        _ => None,
+    }
+}
+
+fn as_index(val: &Val) -> Option<usize> {
+    match *val {
+        Val::Integer(i) => usize::try_from(i).ok(),
+        Val::Int(_, i) => usize::try_from(i).ok(),
+        _ => None,
+    }
+}
+
+// Elements of a list or array.
+fn as_slice(val: &Val) -> Option<(&Type, &[Val])> {
+    match val {
+        Val::List(t, vals) | Val::Array(t, vals) => Some((t, vals)),
+        _ => None,
+    }
+}
+
+fn append(list: Val, element: Val) -> Option<Val> {
+    match list {
+        Val::List(t, mut vals) if element.r#type() == t => {
+            vals.push(element);
+            Some(Val::List(t, vals))
+        }
+        _ => None,
+    }
+}
+
+fn truncate(list: Val) -> Option<Val> {
+    match list {
+        Val::List(t, mut vals) => vals.pop().map(|_| Val::List(t, vals)),
+        _ => None,
+    }
+}
+
+fn push_front(list: Val, element: Val) -> Option<Val> {
+    match list {
+        Val::List(t, mut vals) if element.r#type() == t => {
+            vals.insert(0, element);
+            Some(Val::List(t, vals))
+        }
+        _ => None,
+    }
+}
+
+fn pop_front(list: Val) -> Option<Val> {
+    match list {
+        Val::List(t, mut vals) if !vals.is_empty() => {
+            vals.remove(0);
+            Some(Val::List(t, vals))
+        }
+        _ => None,
+    }
+}
+
+fn len(val: &Val) -> Option<Val> {
+    match val {
+        Val::String(s) => Some(Val::Integer(s.chars().count() as Integer)),
+        val => as_slice(val).map(|(_, vals)| Val::Integer(vals.len() as Integer)),
+    }
+}
+
+fn index(list: &Val, idx: &Val) -> Option<Val> {
+    as_slice(list)?.1.get(as_index(idx)?).cloned()
+}
+
+fn update(list: Val, idx: &Val, element: Val) -> Option<Val> {
+    let idx = as_index(idx)?;
+    match list {
+        Val::List(t, mut vals) if element.r#type() == t => {
+            *vals.get_mut(idx)? = element;
+            Some(Val::List(t, vals))
+        }
+        Val::Array(t, mut vals) if element.r#type() == t => {
+            *vals.get_mut(idx)? = element;
+            Some(Val::Array(t, vals))
+        }
+        _ => None,
+    }
+}
+
+fn contains(list: &Val, element: &Val) -> Option<Val> {
+    Some(Val::Boolean(as_slice(list)?.1.contains(element)))
+}
+
+fn slice(list: &Val, start: &Val, end: &Val) -> Option<Val> {
+    let (t, vals) = as_slice(list)?;
+    let vals = vals.get(as_index(start)?..as_index(end)?)?;
+    Some(Val::List(t.clone(), vals.to_vec()))
+}
+
+fn float_fn(val: &Val, f: fn(Float) -> Float) -> Option<Val> {
+    as_float(val).map(f).map(Val::Float)
+}
+
+fn float_fn2(lhs: &Val, rhs: &Val, f: fn(Float, Float) -> Float) -> Option<Val> {
+    Some(Val::Float(f(as_float(lhs)?, as_float(rhs)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Expr = Expression<usize>;
+
+    #[test]
+    fn math_types() -> Result<(), TypeError> {
+        let int = || Box::new(Expr::from(2));
+        let float = || Box::new(Expr::from(2.5));
+        assert_eq!(
+            Expr::Min(Box::new((*int(), *int()))).r#type()?,
+            Type::Integer
+        );
+        assert_eq!(
+            Expr::Max(Box::new((*int(), *float()))).r#type()?,
+            Type::Float
+        );
+        assert_eq!(Expr::Abs(int()).r#type()?, Type::Integer);
+        assert_eq!(Expr::Abs(float()).r#type()?, Type::Float);
+        assert_eq!(Expr::Floor(float()).r#type()?, Type::Integer);
+        assert_eq!(Expr::Pow(Box::new((*int(), *int()))).r#type()?, Type::Float);
+        assert_eq!(Expr::Sqrt(int()).r#type()?, Type::Float);
+        assert!(Expr::Sin(Box::new(Expr::from(true))).r#type().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn math_eval() -> Result<(), TypeError> {
+        let eval = |expr: Expr| expr.eval_constant();
+        assert_eq!(
+            eval(Expr::Min(Box::new((Expr::from(3), Expr::from(-1)))))?,
+            Val::Integer(-1)
+        );
+        assert_eq!(
+            eval(Expr::Max(Box::new((Expr::from(3), Expr::from(4.5)))))?,
+            Val::Float(4.5)
+        );
+        assert_eq!(eval(Expr::Abs(Box::new(Expr::from(-3))))?, Val::Integer(3));
+        assert_eq!(
+            eval(Expr::Floor(Box::new(Expr::from(-2.5))))?,
+            Val::Integer(-3)
+        );
+        assert_eq!(
+            eval(Expr::Ceil(Box::new(Expr::from(-2.5))))?,
+            Val::Integer(-2)
+        );
+        assert_eq!(
+            eval(Expr::Round(Box::new(Expr::from(2.5))))?,
+            Val::Integer(3)
+        );
+        assert_eq!(
+            eval(Expr::Pow(Box::new((Expr::from(2), Expr::from(10)))))?,
+            Val::Float(1024.)
+        );
+        assert_eq!(
+            eval(Expr::Log(Box::new((Expr::from(8), Expr::from(2)))))?,
+            Val::Float(3.)
+        );
+        assert_eq!(eval(Expr::Sqrt(Box::new(Expr::from(9))))?, Val::Float(3.));
+        assert_eq!(eval(Expr::Exp(Box::new(Expr::from(0))))?, Val::Float(1.));
+        assert_eq!(eval(Expr::Sin(Box::new(Expr::from(0))))?, Val::Float(0.));
+        assert_eq!(eval(Expr::Cos(Box::new(Expr::from(0))))?, Val::Float(1.));
+        assert_eq!(eval(Expr::Tan(Box::new(Expr::from(0))))?, Val::Float(0.));
+        Ok(())
+    }
+
+    #[test]
+    fn math_fn_eval() {
+        let expr = Expr::Max(Box::new((
+            Expr::Floor(Box::new(Expr::Var(0, Type::Float))),
+            Expr::Abs(Box::new(Expr::Var(1, Type::Integer))),
+        )));
+        assert_eq!(expr.r#type().expect("type"), Type::Integer);
+        let expr = FnExpression::<usize, rand::rngs::SmallRng>::from(expr);
+        let vals = [Val::Float(3.7), Val::Integer(-5)];
+        let vars = |var: usize| &vals[var];
+        let mut rng = rand::SeedableRng::from_seed([0; 32]);
+        assert_eq!(expr.eval(&vars, &mut rng), Ok(Val::Integer(5)));
+    }
+
+    #[test]
+    fn int_types() -> Result<(), TypeError> {
+        let byte = || Expr::Const(Val::Int(IntType::U8, 200));
+        let int = || Expr::from(100);
+        assert_eq!(
+            Expr::Sum(vec![byte(), int()]).r#type()?,
+            Type::Int(IntType::U8)
+        );
+        assert_eq!(
+            Expr::Sum(vec![byte(), Expr::from(1.5)]).r#type()?,
+            Type::Float
+        );
+        assert!(
+            Expr::Sum(vec![byte(), Expr::Const(Val::Int(IntType::I8, 1))])
+                .r#type()
+                .is_err()
+        );
+        assert_eq!(
+            Expr::Cast(Box::new(int()), Type::Int(IntType::I64)).r#type()?,
+            Type::Int(IntType::I64)
+        );
+        assert!(
+            Expr::Cast(Box::new(Expr::from(true)), Type::Integer)
+                .r#type()
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn int_overflow() -> Result<(), TypeError> {
+        let sum = Expr::Sum(vec![
+            Expr::Const(Val::Int(IntType::U8, 200)),
+            Expr::from(100),
+        ]);
+        let val = sum.eval_constant()?;
+        assert_eq!(val, Val::Int(IntType::U8, 300));
+        assert_eq!(
+            val.clone().fit(Overflow::Wrap),
+            Some(Val::Int(IntType::U8, 44))
+        );
+        assert_eq!(
+            val.clone().fit(Overflow::Saturate),
+            Some(Val::Int(IntType::U8, 255))
+        );
+        assert_eq!(val.fit(Overflow::Error), None);
+        assert_eq!(IntType::I8.fit(-129, Overflow::Wrap), Some(127));
+        assert_eq!(IntType::I8.fit(-129, Overflow::Saturate), Some(-128));
+        assert_eq!(IntType::U64.fit(-1, Overflow::Wrap), Some(u64::MAX.into()));
+        // Arithmetic on integers does not wrap around silently.
+        assert!(
+            Expr::Sum(vec![Expr::from(Integer::MAX), Expr::from(1)])
+                .eval_constant()
+                .is_err()
+        );
+        assert_eq!(
+            Expr::Cast(Box::new(Expr::from(-1)), Type::Int(IntType::U32)).eval_constant()?,
+            Val::Int(IntType::U32, -1)
+        );
+        assert_eq!(
+            Expr::Less(Box::new((
+                Expr::Const(Val::Int(IntType::U64, 3)),
+                Expr::from(3.5)
+            )))
+            .eval_constant()?,
+            Val::Boolean(true)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn type_errors() {
+        let err = Expr::And(vec![
+            Expr::from(true),
+            Expr::Less(Box::new((Expr::from(1), Expr::from(false)))),
+        ])
+        .r#type()
+        .expect_err("ill-typed expression");
+        assert!(matches!(
+            err.kind(),
+            TypeError::TypeMismatch {
+                expected: Expected::Numeric,
+                found: Type::Boolean,
             }
-        })
+        ));
+        // The innermost offending subexpression is reported.
+        let expression = err.expression().expect("offending expression");
+        assert_eq!(expression, "1 < false");
+        assert_eq!(
+            err.kind().to_string(),
+            "type mismatch: expected a numerical type, found bool"
+        );
+    }
+
+    #[test]
+    fn distributions() {
+        let rate = || Box::new(Expr::Var(0, Type::Float));
+        assert_eq!(Expr::RandExp(rate()).r#type().ok(), Some(Type::Float));
+        assert_eq!(Expr::RandPoisson(rate()).r#type().ok(), Some(Type::Integer));
+        assert!(matches!(
+            Expr::RandNormal(Box::new((Expr::from(0.), Expr::from(true)))).r#type(),
+            Err(err) if matches!(err.kind(), TypeError::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            Expr::RandChoice(Vec::new()).r#type(),
+            Err(err) if matches!(err.kind(), TypeError::EmptyChoice)
+        ));
+        assert!(matches!(
+            Expr::RandChoice(vec![
+                (Expr::from(1), Expr::from(1)),
+                (Expr::from(1), Expr::from(true))
+            ])
+            .r#type(),
+            Err(err) if matches!(err.kind(), TypeError::TypeMismatch { .. })
+        ));
+
+        let zero = Val::Float(0.);
+        let vars = |_| &zero;
+        let mut rng = rand::SeedableRng::from_seed([0; 32]);
+        let choice = FnExpression::<usize, rand::rngs::SmallRng>::from(Expr::RandChoice(vec![
+            (Expr::Var(0, Type::Float), Expr::from(1)),
+            (Expr::from(2), Expr::from(2)),
+        ]));
+        let normal = FnExpression::<usize, rand::rngs::SmallRng>::from(Expr::RandNormal(Box::new(
+            (Expr::from(5.), Expr::Var(0, Type::Float)),
+        )));
+        let geometric = FnExpression::<usize, rand::rngs::SmallRng>::from(Expr::RandGeometric(
+            Box::new(Expr::from(1.)),
+        ));
+        let point = FnExpression::<usize, rand::rngs::SmallRng>::from(Expr::RandUniform(Box::new(
+            (Expr::Var(0, Type::Float), Expr::from(0)),
+        )));
+        let uniform = FnExpression::<usize, rand::rngs::SmallRng>::from(Expr::RandUniform(
+            Box::new((Expr::Var(0, Type::Float), Expr::from(1))),
+        ));
+        for _ in 0..10 {
+            assert_eq!(choice.eval(&vars, &mut rng), Ok(Val::Integer(2)));
+            assert_eq!(normal.eval(&vars, &mut rng), Ok(Val::Float(5.)));
+            assert_eq!(geometric.eval(&vars, &mut rng), Ok(Val::Integer(0)));
+            assert_eq!(point.eval(&vars, &mut rng), Ok(Val::Float(0.)));
+            assert!(
+                matches!(uniform.eval(&vars, &mut rng), Ok(Val::Float(f)) if (0. ..1.).contains(&f))
+            );
+        }
+
+        // Bad parameters only known at runtime are errors.
+        let negative = Val::Float(-1.);
+        let vars = |_| &negative;
+        let normal = FnExpression::<usize, rand::rngs::SmallRng>::from(Expr::RandNormal(Box::new(
+            (Expr::from(0.), Expr::Var(0, Type::Float)),
+        )));
+        let exp = FnExpression::<usize, rand::rngs::SmallRng>::from(Expr::RandExp(Box::new(
+            Expr::Var(0, Type::Float),
+        )));
+        let choice = FnExpression::<usize, rand::rngs::SmallRng>::from(Expr::RandChoice(vec![
+            (Expr::Var(0, Type::Float), Expr::from(1)),
+            (Expr::from(0), Expr::from(2)),
+        ]));
+        let uniform = FnExpression::<usize, rand::rngs::SmallRng>::from(Expr::RandUniform(
+            Box::new((Expr::from(0), Expr::Var(0, Type::Float))),
+        ));
+        for expr in [normal, exp, choice, uniform] {
+            assert_eq!(expr.eval(&vars, &mut rng), Err(EvalError::BadParameters));
+        }
+        assert!(matches!(
+            Expr::RandExp(Box::new(Expr::from(1.))).eval_constant(),
+            Err(TypeError::Random)
+        ));
+    }
+
+    #[test]
+    fn lists() -> Result<(), TypeError> {
+        let list = || Expr::List(Type::Integer, vec![Expr::from(1), Expr::from(2)]);
+        let array = || Expr::Array(Type::Integer, vec![Expr::from(1), Expr::from(2)]);
+        assert_eq!(array().r#type()?, Type::Array(Box::new(Type::Integer), 2));
+        assert!(matches!(
+            Expr::List(Type::Integer, vec![Expr::from(true)]).r#type(),
+            Err(err) if matches!(err.kind(), TypeError::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            Expr::Index(Box::new((array(), Expr::from(2)))).r#type(),
+            Err(err) if matches!(err.kind(), TypeError::IndexOutOfBounds)
+        ));
+        assert!(matches!(
+            Expr::PushFront(Box::new((array(), Expr::from(0)))).r#type(),
+            Err(err) if matches!(err.kind(), TypeError::TypeMismatch { .. })
+        ));
+
+        let eval = |expr: Expr| expr.eval_constant();
+        let ints = |ints: &[Integer]| ints.iter().copied().map(Val::Integer).collect::<Vec<_>>();
+        assert_eq!(
+            eval(Expr::PushFront(Box::new((list(), Expr::from(0)))))?,
+            Val::List(Type::Integer, ints(&[0, 1, 2]))
+        );
+        assert_eq!(
+            eval(Expr::PopFront(Box::new(list())))?,
+            Val::List(Type::Integer, ints(&[2]))
+        );
+        assert_eq!(
+            eval(Expr::Append(Box::new((list(), Expr::from(3)))))?,
+            Val::List(Type::Integer, ints(&[1, 2, 3]))
+        );
+        assert_eq!(
+            eval(Expr::Truncate(Box::new(list())))?,
+            Val::List(Type::Integer, ints(&[1]))
+        );
+        assert_eq!(eval(Expr::Len(Box::new(array())))?, Val::Integer(2));
+        assert_eq!(
+            eval(Expr::Index(Box::new((list(), Expr::from(1)))))?,
+            Val::Integer(2)
+        );
+        assert!(matches!(
+            eval(Expr::Index(Box::new((list(), Expr::from(2))))),
+            Err(err) if matches!(err.kind(), TypeError::IndexOutOfBounds)
+        ));
+        assert_eq!(
+            eval(Expr::Update(Box::new((
+                array(),
+                Expr::from(0),
+                Expr::from(5)
+            ))))?,
+            Val::Array(Type::Integer, ints(&[5, 2]))
+        );
+        assert_eq!(
+            eval(Expr::Contains(Box::new((array(), Expr::from(2)))))?,
+            Val::Boolean(true)
+        );
+        assert_eq!(
+            eval(Expr::Slice(Box::new((
+                array(),
+                Expr::from(1),
+                Expr::from(2)
+            ))))?,
+            Val::List(Type::Integer, ints(&[2]))
+        );
+        // Slices can start and end at the length.
+        assert_eq!(
+            eval(Expr::Slice(Box::new((
+                array(),
+                Expr::from(2),
+                Expr::from(2)
+            ))))?,
+            Val::List(Type::Integer, Vec::new())
+        );
+        assert!(matches!(
+            Expr::Slice(Box::new((array(), Expr::from(0), Expr::from(3)))).r#type(),
+            Err(err) if matches!(err.kind(), TypeError::IndexOutOfBounds)
+        ));
+        assert!(matches!(
+            eval(Expr::Truncate(Box::new(Expr::List(
+                Type::Integer,
+                Vec::new()
+            )))),
+            Err(err) if matches!(err.kind(), TypeError::IndexOutOfBounds)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn records() -> Result<(), TypeError> {
+        let point = Arc::new(Record::new(
+            String::from("Point"),
+            vec![
+                (String::from("x"), Type::Integer),
+                (String::from("y"), Type::Float),
+            ],
+        ));
+        let origin = || Expr::Record(point.clone(), vec![Expr::from(0), Expr::from(0.)]);
+        assert_eq!(origin().r#type()?, Type::Record(point.clone()));
+        assert!(matches!(
+            Expr::Record(point.clone(), vec![Expr::from(0), Expr::from(true)]).r#type(),
+            Err(err) if matches!(err.kind(), TypeError::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            Expr::Record(point.clone(), vec![Expr::from(0)]).r#type(),
+            Err(err) if matches!(err.kind(), TypeError::MissingField)
+        ));
+        assert!(
+            matches!(origin().field("z"), Err(err) if matches!(err.kind(), TypeError::MissingField))
+        );
+        assert!(matches!(
+            origin()
+                .set_field("x", Expr::from(1.))
+                .and_then(|expr| expr.r#type()),
+            Err(err) if matches!(err.kind(), TypeError::TypeMismatch { .. })
+        ));
+
+        let var = Expr::Var(0, Type::Record(point.clone()));
+        assert_eq!(var.clone().field("y")?.r#type()?, Type::Float);
+        let moved = var.set_field("x", Expr::from(3))?;
+        let fn_expr = FnExpression::<usize, rand::rngs::SmallRng>::from(moved.field("x")?);
+        let origin_val = Type::Record(point.clone()).default_value();
+        let vars = |_| &origin_val;
+        let mut rng = rand::SeedableRng::from_seed([0; 32]);
+        assert_eq!(fn_expr.eval(&vars, &mut rng), Ok(Val::Integer(3)));
+
+        assert_eq!(origin().field("x")?.eval_constant()?, Val::Integer(0));
+        assert_eq!(
+            origin().set_field("y", Expr::from(1.))?.eval_constant()?,
+            Val::Record(point.clone(), vec![Val::Integer(0), Val::Float(1.)])
+        );
+        assert_eq!(
+            Type::Record(point.clone()).default_value(),
+            origin().eval_constant()?
+        );
+        Ok(())
     }
 }
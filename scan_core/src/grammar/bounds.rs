@@ -0,0 +1,353 @@
+// Bounds of bounded integer variables, and range analysis of integer expressions.
+//
+// The analysis computes an interval containing all possible values of an expression,
+// given intervals for its variables.
+// Since arithmetic on integers fails on overflow,
+// any operation that could overflow makes the result unbounded.
+
+use super::*;
+
+/// The range of a bounded integer variable, given by its (inclusive) lower and upper bounds.
+///
+/// A missing bound leaves the range unbounded on that side,
+/// so that the default [`Bounds`] contain every integer.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub struct Bounds {
+    /// The smallest value in the range, if any.
+    pub lower: Option<Integer>,
+    /// The largest value in the range, if any.
+    pub upper: Option<Integer>,
+}
+
+impl Bounds {
+    /// Creates the range of values between the given (inclusive) bounds.
+    pub fn new(lower: Option<Integer>, upper: Option<Integer>) -> Self {
+        Self { lower, upper }
+    }
+
+    /// Whether the range contains no value at all.
+    pub fn is_empty(&self) -> bool {
+        self.lower.zip(self.upper).is_some_and(|(l, u)| u < l)
+    }
+
+    /// Whether the range contains the given integer.
+    pub fn contains(&self, value: Integer) -> bool {
+        self.lower.is_none_or(|l| l <= value) && self.upper.is_none_or(|u| value <= u)
+    }
+
+    /// Whether the range contains the given value.
+    ///
+    /// Only integer values, either [`Val::Integer`] or sized [`Val::Int`], can be out of bounds.
+    pub fn contains_val(&self, val: &Val) -> bool {
+        match val {
+            Val::Integer(i) => self.contains(*i),
+            Val::Int(_, i) => {
+                self.lower.is_none_or(|l| i128::from(l) <= *i)
+                    && self.upper.is_none_or(|u| *i <= i128::from(u))
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether the range contains all values in the other range.
+    pub fn includes(&self, other: &Bounds) -> bool {
+        other.is_empty()
+            || (self
+                .lower
+                .is_none_or(|l| other.lower.is_some_and(|o| l <= o))
+                && self
+                    .upper
+                    .is_none_or(|u| other.upper.is_some_and(|o| o <= u)))
+    }
+
+    /// Whether the two ranges have some value in common.
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        !Bounds::new(
+            max_lower(self.lower, other.lower),
+            min_upper(self.upper, other.upper),
+        )
+        .is_empty()
+    }
+
+    /// The value in range closest to the given integer.
+    pub fn clamp(&self, value: Integer) -> Integer {
+        let value = self.lower.map_or(value, |l| value.max(l));
+        self.upper.map_or(value, |u| value.min(u))
+    }
+
+    /// The value in range closest to the given value, which is left unchanged if it is not an integer.
+    ///
+    /// Sized [`Val::Int`] values are kept within the range of their type,
+    /// so the result is out of bounds if the range does not intersect the type's range.
+    pub fn clamp_val(&self, val: Val) -> Val {
+        match val {
+            Val::Integer(i) => Val::Integer(self.clamp(i)),
+            Val::Int(int_type, i) => {
+                let i = self.lower.map_or(i, |l| i.max(l.into()));
+                let i = self.upper.map_or(i, |u| i.min(u.into()));
+                Val::Int(int_type, i.clamp(int_type.min(), int_type.max()))
+            }
+            val => val,
+        }
+    }
+
+    // The smallest range containing both ranges.
+    fn hull(self, other: Bounds) -> Bounds {
+        Bounds::new(
+            self.lower.zip(other.lower).map(|(l, o)| l.min(o)),
+            self.upper.zip(other.upper).map(|(u, o)| u.max(o)),
+        )
+    }
+}
+
+impl std::fmt::Display for Bounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(lower) = self.lower {
+            write!(f, "{lower}")?;
+        }
+        write!(f, "..")?;
+        if let Some(upper) = self.upper {
+            write!(f, "={upper}")?;
+        }
+        Ok(())
+    }
+}
+
+fn max_lower(lhs: Option<Integer>, rhs: Option<Integer>) -> Option<Integer> {
+    match (lhs, rhs) {
+        (Some(l), Some(r)) => Some(l.max(r)),
+        (l, r) => l.or(r),
+    }
+}
+
+fn min_upper(lhs: Option<Integer>, rhs: Option<Integer>) -> Option<Integer> {
+    match (lhs, rhs) {
+        (Some(l), Some(r)) => Some(l.min(r)),
+        (l, r) => l.or(r),
+    }
+}
+
+// Range from wide bounds, which is unbounded if it could overflow.
+fn fit(lower: Option<i64>, upper: Option<i64>) -> Bounds {
+    match (
+        lower.map(Integer::try_from).transpose(),
+        upper.map(Integer::try_from).transpose(),
+    ) {
+        (Ok(lower), Ok(upper)) => Bounds::new(lower, upper),
+        _ => Bounds::default(),
+    }
+}
+
+fn add(lhs: Bounds, rhs: Bounds) -> Bounds {
+    if let (Some(ll), Some(lu), Some(rl), Some(ru)) = (lhs.lower, lhs.upper, rhs.lower, rhs.upper) {
+        fit(
+            Some(i64::from(ll) + i64::from(rl)),
+            Some(i64::from(lu) + i64::from(ru)),
+        )
+    } else {
+        // An unbounded side could overflow into the other.
+        Bounds::default()
+    }
+}
+
+fn mult(lhs: Bounds, rhs: Bounds) -> Bounds {
+    let zero = Bounds::new(Some(0), Some(0));
+    if lhs == zero || rhs == zero {
+        zero
+    } else if let (Some(ll), Some(lu), Some(rl), Some(ru)) =
+        (lhs.lower, lhs.upper, rhs.lower, rhs.upper)
+    {
+        let products = [
+            i64::from(ll) * i64::from(rl),
+            i64::from(ll) * i64::from(ru),
+            i64::from(lu) * i64::from(rl),
+            i64::from(lu) * i64::from(ru),
+        ];
+        fit(
+            products.iter().min().copied(),
+            products.iter().max().copied(),
+        )
+    } else {
+        Bounds::default()
+    }
+}
+
+impl<V: Clone + Debug> Expression<V> {
+    /// Computes a range containing all the values an integer expression can evaluate to,
+    /// given the ranges of its variables.
+    ///
+    /// The result is sound but not necessarily tight:
+    /// expressions that are not integer, or whose range cannot be determined, are unbounded.
+    ///
+    /// ```
+    /// # use scan_core::*;
+    /// let x = Expression::Var("x", Type::Integer);
+    /// let expr = Expression::Sum(vec![x.clone(), Expression::from(1)]);
+    /// let bounds = expr.bounds(&|_| Bounds::new(Some(0), Some(9)));
+    /// assert_eq!(bounds, Bounds::new(Some(1), Some(10)));
+    /// ```
+    pub fn bounds(&self, var_bounds: &impl Fn(&V) -> Bounds) -> Bounds {
+        match self {
+            Expression::Const(Val::Integer(i)) => Bounds::new(Some(*i), Some(*i)),
+            Expression::Var(var, Type::Integer) => var_bounds(var),
+            Expression::RandInt(lower, upper) if lower < upper => {
+                Bounds::new(Some(*lower), Some(upper - 1))
+            }
+            Expression::Sum(exprs) => exprs
+                .iter()
+                .map(|expr| expr.bounds(var_bounds))
+                .reduce(add)
+                .unwrap_or(Bounds::new(Some(0), Some(0))),
+            Expression::Mult(exprs) => exprs
+                .iter()
+                .map(|expr| expr.bounds(var_bounds))
+                .reduce(mult)
+                .unwrap_or(Bounds::new(Some(1), Some(1))),
+            Expression::Opposite(expr) => {
+                // The opposite of the smallest integer overflows.
+                let bounds = expr.bounds(var_bounds);
+                if let Some(lower) = bounds.lower {
+                    fit(bounds.upper.map(|u| -i64::from(u)), Some(-i64::from(lower)))
+                } else {
+                    Bounds::default()
+                }
+            }
+            Expression::Abs(expr) => {
+                let bounds = expr.bounds(var_bounds);
+                match (bounds.lower, bounds.upper) {
+                    (Some(l), u) if l >= 0 => Bounds::new(Some(l), u),
+                    (Some(l), Some(u)) if u <= 0 => fit(Some(-i64::from(u)), Some(-i64::from(l))),
+                    (Some(l), Some(u)) => fit(Some(0), Some((-i64::from(l)).max(i64::from(u)))),
+                    _ => Bounds::default(),
+                }
+            }
+            Expression::Mod(exprs) => {
+                let (lhs, rhs) = (exprs.0.bounds(var_bounds), exprs.1.bounds(var_bounds));
+                // The remainder has the sign of the dividend and is smaller than the divisor.
+                let Some(max) = rhs
+                    .lower
+                    .zip(rhs.upper)
+                    .map(|(l, u)| i64::from(l).abs().max(i64::from(u).abs()) - 1)
+                    .filter(|max| *max >= 0)
+                else {
+                    return Bounds::default();
+                };
+                let lower = if lhs.lower.is_some_and(|l| l >= 0) {
+                    0
+                } else {
+                    lhs.lower.map_or(-max, |l| i64::from(l).max(-max))
+                };
+                let upper = if lhs.upper.is_some_and(|u| u <= 0) {
+                    0
+                } else {
+                    lhs.upper.map_or(max, |u| i64::from(u).min(max))
+                };
+                fit(Some(lower), Some(upper))
+            }
+            Expression::Min(exprs) => {
+                let (lhs, rhs) = (exprs.0.bounds(var_bounds), exprs.1.bounds(var_bounds));
+                Bounds::new(
+                    lhs.lower.zip(rhs.lower).map(|(l, r)| l.min(r)),
+                    min_upper(lhs.upper, rhs.upper),
+                )
+            }
+            Expression::Max(exprs) => {
+                let (lhs, rhs) = (exprs.0.bounds(var_bounds), exprs.1.bounds(var_bounds));
+                Bounds::new(
+                    max_lower(lhs.lower, rhs.lower),
+                    lhs.upper.zip(rhs.upper).map(|(l, r)| l.max(r)),
+                )
+            }
+            Expression::Ite(exprs) => exprs.1.bounds(var_bounds).hull(exprs.2.bounds(var_bounds)),
+            _ => Bounds::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Expr = Expression<usize>;
+
+    #[test]
+    fn bounds() {
+        let bounds = Bounds::new(Some(-2), Some(5));
+        assert!(bounds.contains(-2) && bounds.contains(5) && !bounds.contains(6));
+        assert!(bounds.contains_val(&Val::Boolean(true)));
+        assert!(!bounds.contains_val(&Val::Integer(-3)));
+        assert_eq!(bounds.clamp(10), 5);
+        assert!(bounds.contains_val(&Val::Int(IntType::U8, 5)));
+        assert!(!bounds.contains_val(&Val::Int(IntType::I64, i64::MAX.into())));
+        assert_eq!(
+            bounds.clamp_val(Val::Int(IntType::I8, -100)),
+            Val::Int(IntType::I8, -2)
+        );
+        assert_eq!(
+            Bounds::new(Some(300), None).clamp_val(Val::Int(IntType::U8, 0)),
+            Val::Int(IntType::U8, 255)
+        );
+        assert!(Bounds::default().includes(&bounds));
+        assert!(!bounds.includes(&Bounds::new(Some(0), None)));
+        assert!(bounds.intersects(&Bounds::new(Some(5), None)));
+        assert!(!bounds.intersects(&Bounds::new(None, Some(-3))));
+        assert!(Bounds::new(Some(1), Some(0)).is_empty());
+        assert_eq!(bounds.to_string(), "-2..=5");
+        assert_eq!(Bounds::new(Some(0), None).to_string(), "0..");
+    }
+
+    #[test]
+    fn range_analysis() {
+        let var_bounds = |var: &usize| match var {
+            0 => Bounds::new(Some(0), Some(9)),
+            1 => Bounds::new(Some(-3), Some(2)),
+            _ => Bounds::default(),
+        };
+        let x = || Expr::Var(0, Type::Integer);
+        let y = || Expr::Var(1, Type::Integer);
+        let z = || Expr::Var(2, Type::Integer);
+        let cases = [
+            (
+                Expr::Sum(vec![x(), y(), Expr::from(1)]),
+                Bounds::new(Some(-2), Some(12)),
+            ),
+            (Expr::Mult(vec![x(), y()]), Bounds::new(Some(-27), Some(18))),
+            (
+                Expr::Opposite(Box::new(y())),
+                Bounds::new(Some(-2), Some(3)),
+            ),
+            (Expr::Abs(Box::new(y())), Bounds::new(Some(0), Some(3))),
+            (
+                Expr::Mod(Box::new((z(), Expr::from(4)))),
+                Bounds::new(Some(-3), Some(3)),
+            ),
+            (
+                Expr::Mod(Box::new((x(), Expr::from(4)))),
+                Bounds::new(Some(0), Some(3)),
+            ),
+            (Expr::Min(Box::new((x(), z()))), Bounds::new(None, Some(9))),
+            (
+                Expr::Max(Box::new((y(), x()))),
+                Bounds::new(Some(0), Some(9)),
+            ),
+            (Expr::RandInt(0, 10), Bounds::new(Some(0), Some(9))),
+            (
+                Expr::Ite(Box::new((Expr::from(true), x(), y()))),
+                Bounds::new(Some(-3), Some(9)),
+            ),
+            (Expr::Sum(vec![x(), z()]), Bounds::default()),
+            (
+                Expr::Mult(vec![z(), Expr::from(0)]),
+                Bounds::new(Some(0), Some(0)),
+            ),
+            (
+                Expr::Mult(vec![Expr::from(Integer::MAX), Expr::from(2)]),
+                Bounds::default(),
+            ),
+            (Expr::Len(Box::new(z())), Bounds::default()),
+        ];
+        for (expr, bounds) in cases {
+            assert_eq!(expr.bounds(&var_bounds), bounds, "{expr:?}");
+        }
+    }
+}
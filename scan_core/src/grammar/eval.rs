@@ -0,0 +1,979 @@
+// Evaluation of expressions through a flat register machine.
+//
+// An expression is compiled into a sequence of instructions,
+// each of which stores its result into its own register.
+// Subexpressions are evaluated in order, with forward jumps implementing short-circuiting,
+// so that no instruction is ever executed more than once per evaluation.
+// Compilation folds constant subexpressions and shares common (non-random) subexpressions.
+// Operands are borrowed from variables and registers whenever possible,
+// and values are moved out of registers on their last use instead of being cloned.
+
+use super::*;
+use smallvec::SmallVec;
+use std::marker::PhantomData;
+
+// Operand of an instruction.
+#[derive(Debug, Clone, PartialEq)]
+enum Arg<V> {
+    // A constant value.
+    Const(Val),
+    // The value of a variable.
+    Var(V),
+    // The value of a register, to be cloned.
+    Reg(usize),
+    // The value of a register, to be moved out as this is its last use.
+    Move(usize),
+}
+
+// Value of an operand, to be consumed by the operation.
+#[inline(always)]
+fn arg<'a, V: Clone>(regs: &mut [Val], vars: &dyn Fn(V) -> &'a Val, arg: &Arg<V>) -> Val {
+    match arg {
+        Arg::Const(val) => val.clone(),
+        Arg::Var(var) => vars(var.clone()).clone(),
+        Arg::Reg(reg) => regs[*reg].clone(),
+        Arg::Move(reg) => std::mem::replace(&mut regs[*reg], Val::Boolean(false)),
+    }
+}
+
+// Value of an operand, to be only read by the operation.
+#[inline(always)]
+fn arg_ref<'a: 'b, 'b, V: Clone>(
+    regs: &'b [Val],
+    vars: &dyn Fn(V) -> &'a Val,
+    arg: &'b Arg<V>,
+) -> &'b Val {
+    match arg {
+        Arg::Const(val) => val,
+        Arg::Var(var) => vars(var.clone()),
+        Arg::Reg(reg) | Arg::Move(reg) => &regs[*reg],
+    }
+}
+
+// Value of a numerical operand, converted to floating-point.
+#[inline(always)]
+fn float_arg<'a, V: Clone>(
+    regs: &[Val],
+    vars: &dyn Fn(V) -> &'a Val,
+    arg: &Arg<V>,
+) -> Result<Float, EvalError> {
+    as_float(arg_ref(regs, vars, arg)).ok_or(EvalError::TypeMismatch)
+}
+
+// Deterministic operations only reading one operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unary {
+    Not,
+    Opposite,
+    Abs,
+    Floor,
+    Ceil,
+    Round,
+    Exp,
+    Sqrt,
+    Sin,
+    Cos,
+    Tan,
+    Len,
+}
+
+impl Unary {
+    fn apply(self, val: &Val) -> Option<Val> {
+        match self {
+            Unary::Not => match *val {
+                Val::Boolean(b) => Some(Val::Boolean(!b)),
+                _ => None,
+            },
+            Unary::Opposite => opposite(val),
+            Unary::Abs => abs(val),
+            Unary::Floor => to_integer(val, Float::floor),
+            Unary::Ceil => to_integer(val, Float::ceil),
+            Unary::Round => to_integer(val, Float::round),
+            Unary::Exp => float_fn(val, Float::exp),
+            Unary::Sqrt => float_fn(val, Float::sqrt),
+            Unary::Sin => float_fn(val, Float::sin),
+            Unary::Cos => float_fn(val, Float::cos),
+            Unary::Tan => float_fn(val, Float::tan),
+            Unary::Len => len(val),
+        }
+    }
+}
+
+// Deterministic operations only reading two operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Binary {
+    Implies,
+    Mod,
+    Div,
+    Min,
+    Max,
+    Pow,
+    Log,
+    Equal,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Index,
+    Contains,
+}
+
+impl Binary {
+    fn apply(self, lhs: &Val, rhs: &Val) -> Option<Val> {
+        match self {
+            Binary::Implies => match (lhs, rhs) {
+                (&Val::Boolean(lhs), &Val::Boolean(rhs)) => Some(Val::Boolean(rhs || !lhs)),
+                _ => None,
+            },
+            Binary::Mod => modulo(lhs, rhs),
+            Binary::Div => div(lhs, rhs),
+            Binary::Min => min(lhs, rhs),
+            Binary::Max => max(lhs, rhs),
+            Binary::Pow => float_fn2(lhs, rhs, Float::powf),
+            Binary::Log => float_fn2(lhs, rhs, Float::log),
+            Binary::Equal => equal(lhs, rhs).map(Val::Boolean),
+            Binary::Greater => compare(lhs, rhs, i128::gt, Float::gt).map(Val::Boolean),
+            Binary::GreaterEq => compare(lhs, rhs, i128::ge, Float::ge).map(Val::Boolean),
+            Binary::Less => compare(lhs, rhs, i128::lt, Float::lt).map(Val::Boolean),
+            Binary::LessEq => compare(lhs, rhs, i128::le, Float::le).map(Val::Boolean),
+            Binary::Index => index(lhs, rhs),
+            Binary::Contains => contains(lhs, rhs),
+        }
+    }
+}
+
+// Deterministic operations, whose result only depends on their operands and on the variables.
+#[derive(Debug, Clone, PartialEq)]
+enum Op<V> {
+    Tuple(Vec<Arg<V>>),
+    Component(usize, Arg<V>),
+    Record(Arc<Record>, Vec<Arg<V>>),
+    Field(usize, Arg<V>),
+    SetField(usize, Arg<V>, Arg<V>),
+    Sum(Vec<Arg<V>>),
+    Mult(Vec<Arg<V>>),
+    Cast(Arg<V>, Type),
+    List(Type, Vec<Arg<V>>),
+    Array(Type, Vec<Arg<V>>),
+    Update(Arg<V>, Arg<V>, Arg<V>),
+    Slice(Arg<V>, Arg<V>, Arg<V>),
+    Concat(Vec<Arg<V>>),
+    Append(Arg<V>, Arg<V>),
+    Truncate(Arg<V>),
+    PushFront(Arg<V>, Arg<V>),
+    PopFront(Arg<V>),
+    Unary(Unary, Arg<V>),
+    Binary(Binary, Arg<V>, Arg<V>),
+}
+
+impl<V: Clone> Op<V> {
+    // Operands in order of evaluation.
+    fn args_mut(&mut self) -> Vec<&mut Arg<V>> {
+        match self {
+            Op::Tuple(args)
+            | Op::Record(_, args)
+            | Op::Sum(args)
+            | Op::Mult(args)
+            | Op::List(_, args)
+            | Op::Array(_, args)
+            | Op::Concat(args) => args.iter_mut().collect(),
+            Op::Component(_, arg)
+            | Op::Field(_, arg)
+            | Op::Cast(arg, _)
+            | Op::Truncate(arg)
+            | Op::PopFront(arg)
+            | Op::Unary(_, arg) => vec![arg],
+            Op::SetField(_, lhs, rhs)
+            | Op::Append(lhs, rhs)
+            | Op::PushFront(lhs, rhs)
+            | Op::Binary(_, lhs, rhs) => vec![lhs, rhs],
+            Op::Update(arg_0, arg_1, arg_2) | Op::Slice(arg_0, arg_1, arg_2) => {
+                vec![arg_0, arg_1, arg_2]
+            }
+        }
+    }
+
+    // Error for failed evaluations.
+    fn error(&self) -> EvalError {
+        match self {
+            Op::Component(_, _)
+            | Op::Update(_, _, _)
+            | Op::Slice(_, _, _)
+            | Op::Binary(Binary::Index, _, _) => EvalError::IndexOutOfBounds,
+            Op::Field(_, _) | Op::SetField(_, _, _) => EvalError::MissingField,
+            Op::Truncate(_) | Op::PopFront(_) => EvalError::EmptyList,
+            Op::Binary(Binary::Mod | Binary::Div, _, _) => EvalError::UndefinedOperation,
+            Op::Sum(_) | Op::Mult(_) | Op::Unary(Unary::Opposite | Unary::Abs, _) => {
+                EvalError::Overflow
+            }
+            _ => EvalError::TypeMismatch,
+        }
+    }
+
+    // Evaluates the operation, returning `None` if the operands have the wrong type or value.
+    fn eval<'a>(&self, regs: &mut [Val], vars: &dyn Fn(V) -> &'a Val) -> Option<Val> {
+        match self {
+            Op::Tuple(args) => Some(Val::Tuple(
+                args.iter().map(|a| arg(regs, vars, a)).collect(),
+            )),
+            Op::Component(index, tuple) => component(arg_ref(regs, vars, tuple), *index),
+            Op::Record(record, args) => Some(Val::Record(
+                record.clone(),
+                args.iter().map(|a| arg(regs, vars, a)).collect(),
+            )),
+            Op::Field(index, record) => field(arg_ref(regs, vars, record), *index),
+            Op::SetField(index, record, value) => {
+                set_field(arg(regs, vars, record), *index, arg(regs, vars, value))
+            }
+            Op::Sum(args) => args
+                .iter()
+                .try_fold(Val::Integer(0), |acc, a| sum(&acc, arg_ref(regs, vars, a))),
+            Op::Mult(args) => args
+                .iter()
+                .try_fold(Val::Integer(1), |acc, a| mult(&acc, arg_ref(regs, vars, a))),
+            Op::Cast(arg_0, t) => cast(arg_ref(regs, vars, arg_0), t),
+            Op::List(t, args) => Some(Val::List(
+                t.clone(),
+                args.iter().map(|a| arg(regs, vars, a)).collect(),
+            )),
+            Op::Array(t, args) => Some(Val::Array(
+                t.clone(),
+                args.iter().map(|a| arg(regs, vars, a)).collect(),
+            )),
+            Op::Update(list, idx, element) => {
+                // The index is read before the element, which might be moved out of a register they share.
+                let list = arg(regs, vars, list);
+                let idx = arg(regs, vars, idx);
+                update(list, &idx, arg(regs, vars, element))
+            }
+            Op::Slice(list, start, end) => slice(
+                arg_ref(regs, vars, list),
+                arg_ref(regs, vars, start),
+                arg_ref(regs, vars, end),
+            ),
+            Op::Concat(args) => args
+                .iter()
+                .try_fold(String::new(), |mut acc, a| {
+                    if let Val::String(s) = arg_ref(regs, vars, a) {
+                        acc.push_str(s);
+                        Some(acc)
+                    } else {
+                        None
+                    }
+                })
+                .map(Val::String),
+            Op::Append(list, element) => {
+                let list = arg(regs, vars, list);
+                append(list, arg(regs, vars, element))
+            }
+            Op::Truncate(list) => truncate(arg(regs, vars, list)),
+            Op::PushFront(list, element) => {
+                let list = arg(regs, vars, list);
+                push_front(list, arg(regs, vars, element))
+            }
+            Op::PopFront(list) => pop_front(arg(regs, vars, list)),
+            Op::Unary(op, arg_0) => op.apply(arg_ref(regs, vars, arg_0)),
+            Op::Binary(op, lhs, rhs) => {
+                op.apply(arg_ref(regs, vars, lhs), arg_ref(regs, vars, rhs))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Instr<V> {
+    // Computes a deterministic operation.
+    Op(Op<V>),
+    // Samples a random value.
+    RandBool(f64),
+    RandInt(Integer, Integer),
+    RandFloat(Float, Float),
+    RandUniform(Arg<V>, Arg<V>),
+    RandNormal(Arg<V>, Arg<V>),
+    RandExp(Arg<V>),
+    RandPoisson(Arg<V>),
+    RandGeometric(Arg<V>),
+    // Samples an index with probability proportional to the given weights,
+    // and jumps to the corresponding target.
+    RandChoice(Vec<Arg<V>>, Vec<usize>),
+    // Register whose value is set by the `Assign` or `Test` instructions that follow.
+    Phi,
+    // Sets the value of a `Phi` register.
+    Assign(usize, Arg<V>),
+    // Sets the value of a `Phi` register to a Boolean,
+    // and jumps (forward) to the target if it has the given value.
+    Test(usize, Arg<V>, bool, usize),
+    // Jumps (forward) to the target.
+    Jump(usize),
+    // Jumps (forward) to the target if the condition has the given value.
+    Branch(Arg<V>, bool, usize),
+}
+
+impl<V: Clone> Instr<V> {
+    // Operands in order of evaluation.
+    fn args_mut(&mut self) -> Vec<&mut Arg<V>> {
+        match self {
+            Instr::Op(op) => op.args_mut(),
+            Instr::RandBool(_)
+            | Instr::RandInt(_, _)
+            | Instr::RandFloat(_, _)
+            | Instr::Phi
+            | Instr::Jump(_) => Vec::new(),
+            Instr::RandUniform(lower, upper) => vec![lower, upper],
+            Instr::RandNormal(mean, std_dev) => vec![mean, std_dev],
+            Instr::RandExp(arg)
+            | Instr::RandPoisson(arg)
+            | Instr::RandGeometric(arg)
+            | Instr::Assign(_, arg)
+            | Instr::Test(_, arg, _, _)
+            | Instr::Branch(arg, _, _) => vec![arg],
+            Instr::RandChoice(weights, _) => weights.iter_mut().collect(),
+        }
+    }
+}
+
+// Compiles expressions into a sequence of instructions.
+struct Compiler<V> {
+    instrs: Vec<Instr<V>>,
+    // Operations (with their register) that can be shared by the code being compiled,
+    // i.e., that have been computed unconditionally w.r.t. the current branch.
+    shared: Vec<(Op<V>, usize)>,
+}
+
+impl<V: Clone + PartialEq> Compiler<V> {
+    fn push(&mut self, instr: Instr<V>) -> usize {
+        self.instrs.push(instr);
+        self.instrs.len() - 1
+    }
+
+    // Folds constant operations, and shares operations already computed.
+    fn op(&mut self, mut op: Op<V>) -> Arg<V> {
+        if op.args_mut().iter().all(|arg| matches!(arg, Arg::Const(_))) {
+            // If evaluation fails, the error is left to happen at runtime,
+            // as the operation might never be evaluated.
+            if let Some(val) = op.eval(&mut [], &|_| -> &'static Val {
+                unreachable!("no variables")
+            }) {
+                return Arg::Const(val);
+            }
+        }
+        if let Some((_, reg)) = self.shared.iter().find(|(shared, _)| *shared == op) {
+            Arg::Reg(*reg)
+        } else {
+            let reg = self.push(Instr::Op(op.clone()));
+            self.shared.push((op, reg));
+            Arg::Reg(reg)
+        }
+    }
+
+    fn args(&mut self, exprs: Vec<Expression<V>>) -> Vec<Arg<V>> {
+        exprs.into_iter().map(|expr| self.compile(expr)).collect()
+    }
+
+    fn compile(&mut self, expr: Expression<V>) -> Arg<V> {
+        match expr {
+            Expression::Const(val) => Arg::Const(val),
+            Expression::Var(var, _) => Arg::Var(var),
+            Expression::Tuple(exprs) => {
+                let args = self.args(exprs);
+                self.op(Op::Tuple(args))
+            }
+            Expression::Component(index, expr) => {
+                let arg = self.compile(*expr);
+                self.op(Op::Component(index, arg))
+            }
+            Expression::Record(record, exprs) => {
+                let args = self.args(exprs);
+                self.op(Op::Record(record, args))
+            }
+            Expression::Field(index, expr) => {
+                let arg = self.compile(*expr);
+                self.op(Op::Field(index, arg))
+            }
+            Expression::SetField(index, exprs) => {
+                let (record, value) = self.compile2(*exprs);
+                self.op(Op::SetField(index, record, value))
+            }
+            Expression::RandBool(p) => Arg::Reg(self.push(Instr::RandBool(p))),
+            Expression::RandInt(l, u) => Arg::Reg(self.push(Instr::RandInt(l, u))),
+            Expression::RandFloat(l, u) => Arg::Reg(self.push(Instr::RandFloat(l, u))),
+            Expression::RandUniform(exprs) => {
+                let (lower, upper) = self.compile2(*exprs);
+                Arg::Reg(self.push(Instr::RandUniform(lower, upper)))
+            }
+            Expression::RandNormal(exprs) => {
+                let (mean, std_dev) = self.compile2(*exprs);
+                Arg::Reg(self.push(Instr::RandNormal(mean, std_dev)))
+            }
+            Expression::RandExp(expr) => {
+                let arg = self.compile(*expr);
+                Arg::Reg(self.push(Instr::RandExp(arg)))
+            }
+            Expression::RandPoisson(expr) => {
+                let arg = self.compile(*expr);
+                Arg::Reg(self.push(Instr::RandPoisson(arg)))
+            }
+            Expression::RandGeometric(expr) => {
+                let arg = self.compile(*expr);
+                Arg::Reg(self.push(Instr::RandGeometric(arg)))
+            }
+            Expression::RandChoice(choices) => self.rand_choice(choices),
+            Expression::And(exprs) => self.short_circuit(exprs, false),
+            Expression::Or(exprs) => self.short_circuit(exprs, true),
+            Expression::Implies(exprs) => self.binary(Binary::Implies, *exprs),
+            Expression::Not(expr) => self.unary(Unary::Not, *expr),
+            Expression::Opposite(expr) => self.unary(Unary::Opposite, *expr),
+            Expression::Sum(exprs) => {
+                let args = self.args(exprs);
+                self.op(Op::Sum(args))
+            }
+            Expression::Mult(exprs) => {
+                let args = self.args(exprs);
+                self.op(Op::Mult(args))
+            }
+            Expression::Mod(exprs) => self.binary(Binary::Mod, *exprs),
+            Expression::Div(exprs) => self.binary(Binary::Div, *exprs),
+            Expression::Cast(expr, t) => {
+                let arg = self.compile(*expr);
+                self.op(Op::Cast(arg, t))
+            }
+            Expression::Min(exprs) => self.binary(Binary::Min, *exprs),
+            Expression::Max(exprs) => self.binary(Binary::Max, *exprs),
+            Expression::Abs(expr) => self.unary(Unary::Abs, *expr),
+            Expression::Floor(expr) => self.unary(Unary::Floor, *expr),
+            Expression::Ceil(expr) => self.unary(Unary::Ceil, *expr),
+            Expression::Round(expr) => self.unary(Unary::Round, *expr),
+            Expression::Pow(exprs) => self.binary(Binary::Pow, *exprs),
+            Expression::Log(exprs) => self.binary(Binary::Log, *exprs),
+            Expression::Exp(expr) => self.unary(Unary::Exp, *expr),
+            Expression::Sqrt(expr) => self.unary(Unary::Sqrt, *expr),
+            Expression::Sin(expr) => self.unary(Unary::Sin, *expr),
+            Expression::Cos(expr) => self.unary(Unary::Cos, *expr),
+            Expression::Tan(expr) => self.unary(Unary::Tan, *expr),
+            Expression::Equal(exprs) => self.binary(Binary::Equal, *exprs),
+            Expression::Greater(exprs) => self.binary(Binary::Greater, *exprs),
+            Expression::GreaterEq(exprs) => self.binary(Binary::GreaterEq, *exprs),
+            Expression::Less(exprs) => self.binary(Binary::Less, *exprs),
+            Expression::LessEq(exprs) => self.binary(Binary::LessEq, *exprs),
+            Expression::Append(exprs) => {
+                let (list, element) = self.compile2(*exprs);
+                self.op(Op::Append(list, element))
+            }
+            Expression::Truncate(expr) => {
+                let arg = self.compile(*expr);
+                self.op(Op::Truncate(arg))
+            }
+            Expression::Len(expr) => self.unary(Unary::Len, *expr),
+            Expression::List(t, exprs) => {
+                let args = self.args(exprs);
+                self.op(Op::List(t, args))
+            }
+            Expression::Array(t, exprs) => {
+                let args = self.args(exprs);
+                self.op(Op::Array(t, args))
+            }
+            Expression::Index(exprs) => self.binary(Binary::Index, *exprs),
+            Expression::Update(exprs) => {
+                let (list, idx, element) = self.compile3(*exprs);
+                self.op(Op::Update(list, idx, element))
+            }
+            Expression::PushFront(exprs) => {
+                let (list, element) = self.compile2(*exprs);
+                self.op(Op::PushFront(list, element))
+            }
+            Expression::PopFront(expr) => {
+                let arg = self.compile(*expr);
+                self.op(Op::PopFront(arg))
+            }
+            Expression::Contains(exprs) => self.binary(Binary::Contains, *exprs),
+            Expression::Slice(exprs) => {
+                let (list, start, end) = self.compile3(*exprs);
+                self.op(Op::Slice(list, start, end))
+            }
+            Expression::Concat(exprs) => {
+                let args = self.args(exprs);
+                self.op(Op::Concat(args))
+            }
+            Expression::Ite(exprs) => self.ite(*exprs),
+        }
+    }
+
+    fn compile2(&mut self, exprs: (Expression<V>, Expression<V>)) -> (Arg<V>, Arg<V>) {
+        let lhs = self.compile(exprs.0);
+        let rhs = self.compile(exprs.1);
+        (lhs, rhs)
+    }
+
+    fn compile3(
+        &mut self,
+        exprs: (Expression<V>, Expression<V>, Expression<V>),
+    ) -> (Arg<V>, Arg<V>, Arg<V>) {
+        let (arg_0, arg_1) = self.compile2((exprs.0, exprs.1));
+        let arg_2 = self.compile(exprs.2);
+        (arg_0, arg_1, arg_2)
+    }
+
+    fn unary(&mut self, op: Unary, expr: Expression<V>) -> Arg<V> {
+        let arg = self.compile(expr);
+        self.op(Op::Unary(op, arg))
+    }
+
+    fn binary(&mut self, op: Binary, exprs: (Expression<V>, Expression<V>)) -> Arg<V> {
+        let (lhs, rhs) = self.compile2(exprs);
+        self.op(Op::Binary(op, lhs, rhs))
+    }
+
+    // Compiles conjunctions (`exit == false`) and disjunctions (`exit == true`),
+    // skipping the remaining operands as soon as one evaluates to `exit`.
+    fn short_circuit(&mut self, exprs: Vec<Expression<V>>, exit: bool) -> Arg<V> {
+        let scope = self.shared.len();
+        let mut phi = None;
+        let mut branches = Vec::new();
+        for expr in exprs {
+            let arg = self.compile(expr);
+            match arg {
+                Arg::Const(Val::Boolean(b)) if b != exit => continue,
+                Arg::Const(Val::Boolean(_)) if phi.is_none() => {
+                    self.shared.truncate(scope);
+                    return Arg::Const(Val::Boolean(exit));
+                }
+                arg => {
+                    let phi = *phi.get_or_insert_with(|| self.push(Instr::Phi));
+                    let is_const = matches!(arg, Arg::Const(_));
+                    branches.push(self.push(Instr::Test(phi, arg, exit, 0)));
+                    if is_const {
+                        // Remaining operands are unreachable.
+                        break;
+                    }
+                }
+            }
+        }
+        self.shared.truncate(scope);
+        let end = self.instrs.len();
+        for branch in branches {
+            if let Instr::Test(_, _, _, target) = &mut self.instrs[branch] {
+                *target = end;
+            }
+        }
+        phi.map_or(Arg::Const(Val::Boolean(!exit)), Arg::Reg)
+    }
+
+    fn ite(&mut self, exprs: (Expression<V>, Expression<V>, Expression<V>)) -> Arg<V> {
+        let (r#if, then, r#else) = exprs;
+        let cond = self.compile(r#if);
+        if let Arg::Const(Val::Boolean(cond)) = cond {
+            return self.compile(if cond { then } else { r#else });
+        }
+        let phi = self.push(Instr::Phi);
+        let branch = self.push(Instr::Branch(cond, false, 0));
+        let scope = self.shared.len();
+        let then = self.compile(then);
+        self.push(Instr::Assign(phi, then));
+        let jump = self.push(Instr::Jump(0));
+        self.shared.truncate(scope);
+        let else_target = self.instrs.len();
+        let r#else = self.compile(r#else);
+        self.push(Instr::Assign(phi, r#else));
+        self.shared.truncate(scope);
+        let end = self.instrs.len();
+        if let Instr::Branch(_, _, target) = &mut self.instrs[branch] {
+            *target = else_target;
+        }
+        if let Instr::Jump(target) = &mut self.instrs[jump] {
+            *target = end;
+        }
+        Arg::Reg(phi)
+    }
+
+    fn rand_choice(&mut self, choices: Vec<(Expression<V>, Expression<V>)>) -> Arg<V> {
+        let (weights, vals): (Vec<_>, Vec<_>) = choices.into_iter().unzip();
+        let weights = self.args(weights);
+        let choice = self.push(Instr::RandChoice(weights, Vec::new()));
+        let phi = self.push(Instr::Phi);
+        let scope = self.shared.len();
+        let mut targets = Vec::new();
+        let mut jumps = Vec::new();
+        for val in vals {
+            targets.push(self.instrs.len());
+            let val = self.compile(val);
+            self.push(Instr::Assign(phi, val));
+            jumps.push(self.push(Instr::Jump(0)));
+            self.shared.truncate(scope);
+        }
+        let end = self.instrs.len();
+        for jump in jumps {
+            if let Instr::Jump(target) = &mut self.instrs[jump] {
+                *target = end;
+            }
+        }
+        if let Instr::RandChoice(_, choice_targets) = &mut self.instrs[choice] {
+            *choice_targets = targets;
+        }
+        Arg::Reg(phi)
+    }
+}
+
+/// An expression compiled for fast evaluation.
+pub(crate) struct FnExpression<V, R: Rng> {
+    instrs: Vec<Instr<V>>,
+    result: Arg<V>,
+    rng: PhantomData<fn(&mut R)>,
+}
+
+impl<C, R: Rng> std::fmt::Debug for FnExpression<C, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Expression")
+    }
+}
+
+impl<V: Clone + PartialEq, R: Rng> From<Expression<V>> for FnExpression<V, R> {
+    fn from(value: Expression<V>) -> Self {
+        let mut compiler = Compiler {
+            instrs: Vec::new(),
+            shared: Vec::new(),
+        };
+        let result = compiler.compile(value);
+        let mut instrs = compiler.instrs;
+        // Since jumps only go forward, the last operand (in order) reading a register is its last use,
+        // and the register's value can be moved out instead of cloned.
+        // The result register is read last, after all instructions.
+        let mut used = vec![false; instrs.len()];
+        if let Arg::Reg(reg) = result {
+            used[reg] = true;
+        }
+        for instr in instrs.iter_mut().rev() {
+            for arg in instr.args_mut().into_iter().rev() {
+                if let Arg::Reg(reg) = *arg
+                    && !used[reg]
+                {
+                    used[reg] = true;
+                    *arg = Arg::Move(reg);
+                }
+            }
+        }
+        FnExpression {
+            instrs,
+            result,
+            rng: PhantomData,
+        }
+    }
+}
+
+impl<V: Clone, R: Rng> FnExpression<V, R> {
+    /// Evaluates the expression.
+    ///
+    /// Fails if an operation is undefined on the values of its operands,
+    /// e.g., an index is out of bounds.
+    pub fn eval<'a>(&self, vars: &dyn Fn(V) -> &'a Val, rng: &mut R) -> Result<Val, EvalError> {
+        match &self.result {
+            Arg::Const(val) => Ok(val.clone()),
+            Arg::Var(var) => Ok(vars(var.clone()).clone()),
+            // Single operations, e.g., simple guards, need no registers.
+            Arg::Reg(_) | Arg::Move(_) if self.instrs.len() == 1 => {
+                self.eval_instr(&mut [], vars, rng).map(|(_, val)| val)
+            }
+            Arg::Reg(reg) | Arg::Move(reg) => {
+                // Registers are filled in order,
+                // with placeholders for skipped instructions and control instructions.
+                // Jumps to the end need no placeholders,
+                // as they skip everything but the computation of the result.
+                let mut regs = SmallVec::<[Val; 16]>::new();
+                while regs.len() < self.instrs.len() {
+                    let (next, val) = self.eval_instr(&mut regs, vars, rng)?;
+                    regs.push(val);
+                    if next >= self.instrs.len() {
+                        break;
+                    } else if next > regs.len() {
+                        regs.resize(next, Val::Boolean(false));
+                    }
+                }
+                Ok(std::mem::replace(&mut regs[*reg], Val::Boolean(false)))
+            }
+        }
+    }
+
+    // Executes the instruction at the given position (the number of registers already filled),
+    // returning the position of the next instruction and the value of the instruction's register.
+    #[inline(always)]
+    fn eval_instr<'a>(
+        &self,
+        regs: &mut [Val],
+        vars: &dyn Fn(V) -> &'a Val,
+        rng: &mut R,
+    ) -> Result<(usize, Val), EvalError> {
+        let pc = regs.len();
+        let val = match &self.instrs[pc] {
+            Instr::Op(op) => op.eval(regs, vars).ok_or_else(|| op.error())?,
+            Instr::RandBool(p) => Val::Boolean(rng.random_bool(*p)),
+            Instr::RandInt(l, u) => Val::Integer(rng.random_range(*l..*u)),
+            Instr::RandFloat(l, u) => Val::Float(rng.random_range(*l..*u)),
+            Instr::RandUniform(lower, upper) => {
+                let lower = float_arg(regs, vars, lower)?;
+                let upper = float_arg(regs, vars, upper)?;
+                if lower < upper {
+                    Val::Float(rng.random_range(lower..upper))
+                } else if lower == upper {
+                    Val::Float(lower)
+                } else {
+                    return Err(EvalError::BadParameters);
+                }
+            }
+            Instr::RandNormal(mean, std_dev) => {
+                let mean = float_arg(regs, vars, mean)?;
+                let std_dev = float_arg(regs, vars, std_dev)?;
+                // The distribution would accept a negative standard deviation.
+                if std_dev < 0. {
+                    return Err(EvalError::BadParameters);
+                }
+                let normal = Normal::new(mean, std_dev).map_err(|_| EvalError::BadParameters)?;
+                Val::Float(normal.sample(rng))
+            }
+            Instr::RandExp(rate) => {
+                let rate = float_arg(regs, vars, rate)?;
+                let exp = Exp::new(rate).map_err(|_| EvalError::BadParameters)?;
+                Val::Float(exp.sample(rng))
+            }
+            Instr::RandPoisson(rate) => {
+                let rate = float_arg(regs, vars, rate)?;
+                let poisson = Poisson::new(rate).map_err(|_| EvalError::BadParameters)?;
+                Val::Integer(poisson.sample(rng) as Integer)
+            }
+            Instr::RandGeometric(p) => {
+                let p = float_arg(regs, vars, p)?;
+                let geometric = Geometric::new(p).map_err(|_| EvalError::BadParameters)?;
+                Val::Integer(geometric.sample(rng) as Integer)
+            }
+            Instr::RandChoice(weights, targets) => {
+                let weights = weights
+                    .iter()
+                    .map(|weight| float_arg(regs, vars, weight))
+                    .collect::<Result<SmallVec<[Float; 8]>, _>>()?;
+                let idx = WeightedIndex::new(weights)
+                    .map_err(|_| EvalError::BadParameters)?
+                    .sample(rng);
+                return Ok((targets[idx], Val::Boolean(false)));
+            }
+            Instr::Phi => Val::Boolean(false),
+            Instr::Assign(phi, val) => {
+                regs[*phi] = arg(regs, vars, val);
+                Val::Boolean(false)
+            }
+            Instr::Test(phi, val, exit, target) => {
+                if let Val::Boolean(val) = *arg_ref(regs, vars, val) {
+                    regs[*phi] = Val::Boolean(val);
+                    if val == *exit {
+                        return Ok((*target, Val::Boolean(false)));
+                    }
+                    Val::Boolean(false)
+                } else {
+                    return Err(EvalError::TypeMismatch);
+                }
+            }
+            Instr::Jump(target) => return Ok((*target, Val::Boolean(false))),
+            Instr::Branch(cond, on, target) => {
+                if let Val::Boolean(cond) = *arg_ref(regs, vars, cond) {
+                    if cond == *on {
+                        return Ok((*target, Val::Boolean(false)));
+                    }
+                    Val::Boolean(false)
+                } else {
+                    return Err(EvalError::TypeMismatch);
+                }
+            }
+        };
+        Ok((pc + 1, val))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::SmallRng};
+
+    type Expr = Expression<usize>;
+
+    fn compile(expr: Expr) -> FnExpression<usize, SmallRng> {
+        FnExpression::from(expr)
+    }
+
+    fn eval(expr: &FnExpression<usize, SmallRng>, vars: &[Val]) -> Result<Val, EvalError> {
+        let mut rng = SmallRng::from_seed([0; 32]);
+        expr.eval(&|var| &vars[var], &mut rng)
+    }
+
+    #[test]
+    fn constant_folding() {
+        let expr = compile(Expr::Implies(Box::new((
+            Expr::LessEq(Box::new((
+                Expr::Sum(vec![Expr::from(1), Expr::from(2), Expr::from(3)]),
+                Expr::from(100),
+            ))),
+            Expr::Greater(Box::new((Expr::from(5), Expr::from(6)))),
+        ))));
+        assert!(expr.instrs.is_empty());
+        assert_eq!(expr.result, Arg::Const(Val::Boolean(false)));
+
+        // Only the taken branch is compiled.
+        let expr = compile(Expr::Ite(Box::new((
+            Expr::from(true),
+            Expr::Var(0, Type::Integer),
+            Expr::Var(1, Type::Integer),
+        ))));
+        assert!(expr.instrs.is_empty());
+        assert_eq!(
+            eval(&expr, &[Val::Integer(1), Val::Integer(2)]),
+            Ok(Val::Integer(1))
+        );
+
+        // Failures are deferred to evaluation.
+        let expr = compile(Expr::Index(Box::new((
+            Expr::List(Type::Integer, Vec::new()),
+            Expr::from(0),
+        ))));
+        assert_eq!(expr.instrs.len(), 1);
+    }
+
+    #[test]
+    fn sharing() {
+        let var = || Expr::Var(0, Type::Integer);
+        let double = || Expr::Sum(vec![var(), var()]);
+        let expr = compile(Expr::Mult(vec![double(), double()]));
+        // One sum and one multiplication.
+        assert_eq!(expr.instrs.len(), 2);
+        assert_eq!(eval(&expr, &[Val::Integer(3)]), Ok(Val::Integer(36)));
+
+        // Random values are never shared.
+        let rand = || Expr::RandInt(0, 10);
+        let expr = compile(Expr::Sum(vec![rand(), rand()]));
+        assert_eq!(expr.instrs.len(), 3);
+    }
+
+    #[test]
+    fn short_circuit() {
+        let flag = Expr::Var(0, Type::Boolean);
+        // Would fail if evaluated.
+        let out_of_bounds = Expr::Equal(Box::new((
+            Expr::Index(Box::new((
+                Expr::Var(1, Type::List(Box::new(Type::Integer))),
+                Expr::from(0),
+            ))),
+            Expr::from(0),
+        )));
+        let vars = [Val::Boolean(false), Val::List(Type::Integer, Vec::new())];
+        let and = compile(Expr::And(vec![
+            flag.clone(),
+            Expr::from(true),
+            out_of_bounds.clone(),
+        ]));
+        assert_eq!(eval(&and, &vars), Ok(Val::Boolean(false)));
+        let or = compile(Expr::Or(vec![
+            Expr::Not(Box::new(flag.clone())),
+            out_of_bounds,
+        ]));
+        assert_eq!(eval(&or, &vars), Ok(Val::Boolean(true)));
+        let or = compile(Expr::Or(vec![flag.clone(), Expr::from(true)]));
+        assert_eq!(eval(&or, &vars), Ok(Val::Boolean(true)));
+        let and = compile(Expr::And(vec![Expr::from(false), flag]));
+        assert!(and.instrs.is_empty());
+    }
+
+    #[test]
+    fn branches() {
+        let list = || Expr::Var(0, Type::List(Box::new(Type::Integer)));
+        let len = || Expr::Len(Box::new(list()));
+        // The list is shared across branches, and moved on its last use.
+        let expr = compile(Expr::Ite(Box::new((
+            Expr::Greater(Box::new((len(), Expr::from(1)))),
+            Expr::Truncate(Box::new(list())),
+            Expr::Append(Box::new((list(), len()))),
+        ))));
+        let ints = |ints: &[Integer]| ints.iter().copied().map(Val::Integer).collect::<Vec<_>>();
+        assert_eq!(
+            eval(&expr, &[Val::List(Type::Integer, ints(&[1, 2, 3]))]),
+            Ok(Val::List(Type::Integer, ints(&[1, 2])))
+        );
+        assert_eq!(
+            eval(&expr, &[Val::List(Type::Integer, ints(&[1]))]),
+            Ok(Val::List(Type::Integer, ints(&[1, 1])))
+        );
+
+        let choice = compile(Expr::RandChoice(vec![
+            (Expr::from(0.), Expr::Var(0, Type::Integer)),
+            (Expr::from(1.), Expr::Var(1, Type::Integer)),
+        ]));
+        assert_eq!(
+            eval(&choice, &[Val::Integer(1), Val::Integer(2)]),
+            Ok(Val::Integer(2))
+        );
+    }
+
+    #[test]
+    fn shared_operands() {
+        let list = Expr::Var(0, Type::List(Box::new(Type::Integer)));
+        let next = || Expr::Sum(vec![Expr::Var(1, Type::Integer), Expr::from(1)]);
+        // The index and the element share the register moved out by the element.
+        let expr = compile(Expr::Update(Box::new((list, next(), next()))));
+        assert_eq!(expr.instrs.len(), 2);
+        let ints = |ints: &[Integer]| ints.iter().copied().map(Val::Integer).collect::<Vec<_>>();
+        assert_eq!(
+            eval(
+                &expr,
+                &[Val::List(Type::Integer, ints(&[0, 0, 0])), Val::Integer(1)]
+            ),
+            Ok(Val::List(Type::Integer, ints(&[0, 0, 2])))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let list = || Expr::Var(0, Type::List(Box::new(Type::Integer)));
+        let vars = [Val::List(Type::Integer, Vec::new()), Val::Integer(0)];
+        let index = compile(Expr::Index(Box::new((list(), Expr::from(0)))));
+        assert_eq!(eval(&index, &vars), Err(EvalError::IndexOutOfBounds));
+        let pop = compile(Expr::PopFront(Box::new(list())));
+        assert_eq!(eval(&pop, &vars), Err(EvalError::EmptyList));
+        let truncate = compile(Expr::Truncate(Box::new(list())));
+        assert_eq!(eval(&truncate, &vars), Err(EvalError::EmptyList));
+        let update = compile(Expr::Update(Box::new((
+            list(),
+            Expr::from(0),
+            Expr::from(1),
+        ))));
+        assert_eq!(eval(&update, &vars), Err(EvalError::IndexOutOfBounds));
+        let slice = compile(Expr::Slice(Box::new((
+            list(),
+            Expr::from(0),
+            Expr::from(1),
+        ))));
+        assert_eq!(eval(&slice, &vars), Err(EvalError::IndexOutOfBounds));
+        let modulo = compile(Expr::Mod(Box::new((
+            Expr::from(1),
+            Expr::Var(1, Type::Integer),
+        ))));
+        assert_eq!(eval(&modulo, &vars), Err(EvalError::UndefinedOperation));
+    }
+
+    #[test]
+    fn overflow() {
+        let vars = [
+            Val::Integer(Integer::MAX),
+            Val::Int(IntType::U64, u64::MAX.into()),
+        ];
+        let sum = compile(Expr::Sum(vec![Expr::Var(0, Type::Integer), Expr::from(1)]));
+        assert_eq!(eval(&sum, &vars), Err(EvalError::Overflow));
+        let opposite = compile(Expr::Opposite(Box::new(Expr::Sum(vec![
+            Expr::Opposite(Box::new(Expr::Var(0, Type::Integer))),
+            Expr::from(-1),
+        ]))));
+        assert_eq!(eval(&opposite, &vars), Err(EvalError::Overflow));
+        // Products of sized integers are exact as long as they fit 128 bits.
+        let u64 = || Expr::Var(1, Type::Int(IntType::U64));
+        let double = compile(Expr::Mult(vec![u64(), Expr::from(2)]));
+        assert_eq!(
+            eval(&double, &vars),
+            Ok(Val::Int(IntType::U64, i128::from(u64::MAX) * 2))
+        );
+        let cube = compile(Expr::Mult(vec![u64(), u64(), u64()]));
+        assert_eq!(eval(&cube, &vars), Err(EvalError::Overflow));
+    }
+}
@@ -0,0 +1,1329 @@
+// Textual syntax of expressions: a pretty-printer and a recursive-descent parser.
+//
+// Infix operators are used for logic, arithmetic and comparisons,
+// with the usual precedence (from lowest to highest):
+// `=>` (right-associative), `||`, `&&`, comparisons (non-associative),
+// `+`, then `*`, `/` and `%`, unary `!` and `-`, and postfix indexing and projections.
+// All other operations are written as function calls, e.g., `min(x, y)` or `len(l)`.
+// Compound constant values are written as the expression building them wrapped in `const(...)`.
+// The printer only omits parentheses where the parser would rebuild the same tree,
+// so that printing and then parsing an expression gives back the same expression.
+
+use super::*;
+use std::fmt::{self, Display, Formatter};
+
+/// The error type for parsing the textual syntax of [`Expression`]s and [`crate::Pmtl`] formulae.
+#[derive(Debug, Clone, Error)]
+pub enum ParseError {
+    /// The input contains a character that is not part of the syntax.
+    #[error("unexpected character `{0}` at position {1}")]
+    UnexpectedChar(char, usize),
+    /// The input contains a token where it is not allowed.
+    #[error("unexpected `{0}` at position {1}")]
+    UnexpectedToken(String, usize),
+    /// The input ended prematurely.
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+    /// A literal cannot be represented as a value.
+    #[error("invalid literal `{0}`")]
+    InvalidLiteral(String),
+    /// The resolver does not know the variable (or atom).
+    #[error("unknown variable `{0}`")]
+    UnknownVar(String),
+    /// The resolver does not know the type.
+    #[error("unknown type `{0}`")]
+    UnknownType(String),
+    /// There is no function with the given name.
+    #[error("unknown function `{0}`")]
+    UnknownFunction(String),
+    /// The enumeration has no such label.
+    #[error("unknown label `{0}`")]
+    UnknownLabel(String),
+    /// The record has no such field.
+    #[error("unknown field `{0}`")]
+    UnknownField(String),
+    /// A function is applied to the wrong number of arguments.
+    #[error("function `{function}` takes {expected} arguments, found {found}")]
+    Arity {
+        /// The name of the function.
+        function: String,
+        /// The number of arguments the function takes.
+        expected: usize,
+        /// The number of arguments the function has been applied to.
+        found: usize,
+    },
+    /// A subexpression that has to be typed (or constant) is not.
+    #[error("type error")]
+    Type(#[source] TypeError),
+}
+
+/// Resolves the names of variables and types occurring in the textual syntax of [`Expression`]s.
+///
+/// Closures mapping a name to a variable and its type are resolvers that know no named types.
+pub trait Resolver<V> {
+    /// The variable with the given name, together with its type, if any.
+    fn var(&self, name: &str) -> Option<(V, Type)>;
+
+    /// The enumeration or record type with the given name, if any.
+    fn named_type(&self, name: &str) -> Option<Type> {
+        let _ = name;
+        None
+    }
+}
+
+impl<V, F> Resolver<V> for F
+where
+    F: Fn(&str) -> Option<(V, Type)>,
+{
+    fn var(&self, name: &str) -> Option<(V, Type)> {
+        self(name)
+    }
+}
+
+// Operator precedences, from lowest to highest.
+const IMPLIES: u8 = 1;
+const OR: u8 = 2;
+const AND: u8 = 3;
+const COMPARE: u8 = 4;
+const ADD: u8 = 5;
+const MUL: u8 = 6;
+const UNARY: u8 = 7;
+const POSTFIX: u8 = 8;
+const ATOM: u8 = 9;
+
+impl<V: Clone + Debug> Expression<V> {
+    /// Displays the expression in textual syntax, formatting its variables with the given function.
+    ///
+    /// This is useful to display expressions whose variables do not implement [`Display`].
+    pub fn display_with<'a, F>(&'a self, fmt_var: F) -> impl Display + 'a
+    where
+        F: 'a + Fn(&V, &mut Formatter<'_>) -> fmt::Result,
+    {
+        Printer {
+            expr: self,
+            fmt_var,
+        }
+    }
+
+    /// Parses an expression from its textual syntax,
+    /// using the resolver to find variables and named types.
+    ///
+    /// ```
+    /// # use scan_core::*;
+    /// let resolver = |name: &str| (name == "x").then(|| (name.to_string(), Type::Integer));
+    /// let expr = Expression::parse("x + 1 < 2 * x", &resolver).expect("valid syntax");
+    /// assert_eq!(expr.to_string(), "x + 1 < 2 * x");
+    /// assert!(Expression::parse("y + 1", &resolver).is_err());
+    /// ```
+    pub fn parse(input: &str, resolver: &impl Resolver<V>) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            tokens: Tokens::new(input)?,
+            resolver,
+            _var: std::marker::PhantomData,
+        };
+        let expr = parser.expr()?;
+        parser.tokens.end()?;
+        Ok(expr)
+    }
+
+    // Precedence of the outermost operator of the expression as printed.
+    fn precedence(&self) -> u8 {
+        match self {
+            Expression::Implies(_) => IMPLIES,
+            Expression::Or(args) if args.len() > 1 => OR,
+            Expression::And(args) if args.len() > 1 => AND,
+            Expression::Equal(_)
+            | Expression::Greater(_)
+            | Expression::GreaterEq(_)
+            | Expression::Less(_)
+            | Expression::LessEq(_) => COMPARE,
+            Expression::Sum(args) if args.len() > 1 => ADD,
+            Expression::Mult(args) if args.len() > 1 => MUL,
+            Expression::Mod(_) | Expression::Div(_) => MUL,
+            Expression::Not(_) | Expression::Opposite(_) => UNARY,
+            Expression::Const(Val::Integer(i)) if *i < 0 => UNARY,
+            Expression::Const(Val::Int(_, i)) if *i < 0 => UNARY,
+            Expression::Const(Val::Float(f)) if f.is_sign_negative() => UNARY,
+            Expression::Index(_) | Expression::Component(_, _) | Expression::Field(_, _) => POSTFIX,
+            _ => ATOM,
+        }
+    }
+
+    // Whether the expression is printed starting with a digit,
+    // so that a preceding `-` would be parsed as the sign of a literal.
+    fn starts_with_digit(&self) -> bool {
+        match self {
+            Expression::Const(Val::Integer(_) | Val::Int(_, _) | Val::Float(_)) => {
+                self.precedence() == ATOM
+            }
+            Expression::Index(args) => args.0.precedence() >= POSTFIX && args.0.starts_with_digit(),
+            Expression::Component(_, expr) | Expression::Field(_, expr) => {
+                expr.precedence() >= POSTFIX && expr.starts_with_digit()
+            }
+            _ => false,
+        }
+    }
+
+    // The name of a field of the record the expression evaluates to, if known.
+    fn field_name(&self, index: usize) -> Option<String> {
+        self.record_type()
+            .ok()
+            .and_then(|record| record.fields().get(index).map(|(name, _)| name.clone()))
+    }
+}
+
+impl<V: Clone + Debug + Display> Display for Expression<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_with(|var, f| write!(f, "{var}")))
+    }
+}
+
+// Expression to build a compound value, to be printed as `const(...)`.
+fn constructor<V: Clone>(val: &Val) -> Option<Expression<V>> {
+    let consts = |vals: &[Val]| vals.iter().cloned().map(Expression::Const).collect();
+    match val {
+        Val::Tuple(vals) => Some(Expression::Tuple(consts(vals))),
+        Val::List(t, vals) => Some(Expression::List(t.clone(), consts(vals))),
+        Val::Array(t, vals) => Some(Expression::Array(t.clone(), consts(vals))),
+        Val::Record(record, vals) => Some(Expression::Record(record.clone(), consts(vals))),
+        _ => None,
+    }
+}
+
+struct Printer<'a, V: Clone, F> {
+    expr: &'a Expression<V>,
+    fmt_var: F,
+}
+
+impl<V, F> Display for Printer<'_, V, F>
+where
+    V: Clone + Debug,
+    F: Fn(&V, &mut Formatter<'_>) -> fmt::Result,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write(f, self.expr, 0)
+    }
+}
+
+impl<V, F> Printer<'_, V, F>
+where
+    V: Clone + Debug,
+    F: Fn(&V, &mut Formatter<'_>) -> fmt::Result,
+{
+    // Writes the expression, in parentheses if it binds less tightly than the given precedence.
+    fn write(&self, f: &mut Formatter<'_>, expr: &Expression<V>, precedence: u8) -> fmt::Result {
+        if expr.precedence() < precedence {
+            write!(f, "(")?;
+            self.write_expr(f, expr)?;
+            write!(f, ")")
+        } else {
+            self.write_expr(f, expr)
+        }
+    }
+
+    fn write_list(
+        &self,
+        f: &mut Formatter<'_>,
+        exprs: &[Expression<V>],
+        sep: &str,
+        precedence: u8,
+    ) -> fmt::Result {
+        for (i, expr) in exprs.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{sep}")?;
+            }
+            self.write(f, expr, precedence)?;
+        }
+        Ok(())
+    }
+
+    fn write_call(
+        &self,
+        f: &mut Formatter<'_>,
+        name: &str,
+        args: &[&Expression<V>],
+    ) -> fmt::Result {
+        write!(f, "{name}(")?;
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            self.write(f, arg, 0)?;
+        }
+        write!(f, ")")
+    }
+
+    fn write_infix(
+        &self,
+        f: &mut Formatter<'_>,
+        args: &(Expression<V>, Expression<V>),
+        op: &str,
+        precedence: u8,
+    ) -> fmt::Result {
+        self.write(f, &args.0, precedence + 1)?;
+        write!(f, " {op} ")?;
+        self.write(f, &args.1, precedence + 1)
+    }
+
+    fn write_nary(
+        &self,
+        f: &mut Formatter<'_>,
+        exprs: &[Expression<V>],
+        op: &str,
+        name: &str,
+        precedence: u8,
+    ) -> fmt::Result {
+        if exprs.len() > 1 {
+            self.write_list(f, exprs, &format!(" {op} "), precedence + 1)
+        } else {
+            write!(f, "{name}(")?;
+            self.write_list(f, exprs, ", ", 0)?;
+            write!(f, ")")
+        }
+    }
+
+    fn write_field(
+        &self,
+        f: &mut Formatter<'_>,
+        record: &Expression<V>,
+        index: usize,
+    ) -> fmt::Result {
+        if let Some(name) = record.field_name(index) {
+            write!(f, "{name}")
+        } else {
+            write!(f, "#{index}")
+        }
+    }
+
+    fn write_val(&self, f: &mut Formatter<'_>, val: &Val) -> fmt::Result {
+        match val {
+            Val::Boolean(b) => write!(f, "{b}"),
+            Val::Integer(i) => write!(f, "{i}"),
+            Val::Int(int_type, i) => write!(f, "{i}{int_type}"),
+            Val::Float(float) => write!(f, "{float:?}"),
+            Val::String(s) => write!(f, "{s:?}"),
+            Val::Enum(r#enum, index) => {
+                write!(
+                    f,
+                    "{}::{}",
+                    r#enum.name(),
+                    r#enum.label(*index).unwrap_or("?")
+                )
+            }
+            Val::Tuple(_) | Val::List(_, _) | Val::Array(_, _) | Val::Record(_, _) => {
+                let expr = constructor(val).expect("compound value");
+                write!(f, "const(")?;
+                self.write(f, &expr, 0)?;
+                write!(f, ")")
+            }
+        }
+    }
+
+    fn write_expr(&self, f: &mut Formatter<'_>, expr: &Expression<V>) -> fmt::Result {
+        match expr {
+            Expression::Const(val) => self.write_val(f, val),
+            Expression::Var(var, _) => (self.fmt_var)(var, f),
+            Expression::Tuple(exprs) => {
+                write!(f, "(")?;
+                self.write_list(f, exprs, ", ", 0)?;
+                if exprs.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            Expression::Component(index, tuple) => {
+                self.write(f, tuple, POSTFIX)?;
+                write!(f, ".{index}")
+            }
+            Expression::Record(record, exprs) => {
+                write!(f, "{} {{", record.name())?;
+                for (i, ((name, _), expr)) in record.fields().iter().zip(exprs).enumerate() {
+                    write!(f, "{}{name}: ", if i > 0 { ", " } else { " " })?;
+                    self.write(f, expr, 0)?;
+                }
+                write!(f, "{}}}", if exprs.is_empty() { "" } else { " " })
+            }
+            Expression::Field(index, record) => {
+                self.write(f, record, POSTFIX)?;
+                write!(f, ".")?;
+                self.write_field(f, record, *index)
+            }
+            Expression::SetField(index, args) => {
+                write!(f, "set_field(")?;
+                self.write(f, &args.0, 0)?;
+                write!(f, ", ")?;
+                self.write_field(f, &args.0, *index)?;
+                write!(f, ", ")?;
+                self.write(f, &args.1, 0)?;
+                write!(f, ")")
+            }
+            Expression::RandBool(p) => write!(f, "rand_bool({p:?})"),
+            Expression::RandInt(lower, upper) => write!(f, "rand_int({lower}, {upper})"),
+            Expression::RandFloat(lower, upper) => write!(f, "rand_float({lower:?}, {upper:?})"),
+            Expression::RandUniform(args) => {
+                self.write_call(f, "rand_uniform", &[&args.0, &args.1])
+            }
+            Expression::RandNormal(args) => self.write_call(f, "rand_normal", &[&args.0, &args.1]),
+            Expression::RandExp(expr) => self.write_call(f, "rand_exp", &[expr]),
+            Expression::RandPoisson(expr) => self.write_call(f, "rand_poisson", &[expr]),
+            Expression::RandGeometric(expr) => self.write_call(f, "rand_geometric", &[expr]),
+            Expression::RandChoice(choices) => {
+                write!(f, "rand_choice(")?;
+                for (i, (weight, val)) in choices.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    self.write(f, weight, 0)?;
+                    write!(f, ": ")?;
+                    self.write(f, val, 0)?;
+                }
+                write!(f, ")")
+            }
+            Expression::And(exprs) => self.write_nary(f, exprs, "&&", "and", AND),
+            Expression::Or(exprs) => self.write_nary(f, exprs, "||", "or", OR),
+            Expression::Implies(args) => {
+                self.write(f, &args.0, IMPLIES + 1)?;
+                write!(f, " => ")?;
+                self.write(f, &args.1, IMPLIES)
+            }
+            Expression::Not(expr) => {
+                write!(f, "!")?;
+                self.write(f, expr, UNARY)
+            }
+            Expression::Opposite(expr) => {
+                write!(f, "-")?;
+                if expr.starts_with_digit() {
+                    write!(f, "(")?;
+                    self.write_expr(f, expr)?;
+                    write!(f, ")")
+                } else {
+                    self.write(f, expr, UNARY)
+                }
+            }
+            Expression::Sum(exprs) => self.write_nary(f, exprs, "+", "sum", ADD),
+            Expression::Mult(exprs) => self.write_nary(f, exprs, "*", "mult", MUL),
+            Expression::Mod(args) => self.write_infix(f, args, "%", MUL),
+            Expression::Div(args) => self.write_infix(f, args, "/", MUL),
+            Expression::Cast(expr, t) => {
+                write!(f, "cast(")?;
+                self.write(f, expr, 0)?;
+                write!(f, ", {t})")
+            }
+            Expression::Min(args) => self.write_call(f, "min", &[&args.0, &args.1]),
+            Expression::Max(args) => self.write_call(f, "max", &[&args.0, &args.1]),
+            Expression::Abs(expr) => self.write_call(f, "abs", &[expr]),
+            Expression::Floor(expr) => self.write_call(f, "floor", &[expr]),
+            Expression::Ceil(expr) => self.write_call(f, "ceil", &[expr]),
+            Expression::Round(expr) => self.write_call(f, "round", &[expr]),
+            Expression::Pow(args) => self.write_call(f, "pow", &[&args.0, &args.1]),
+            Expression::Exp(expr) => self.write_call(f, "exp", &[expr]),
+            Expression::Log(args) => self.write_call(f, "log", &[&args.0, &args.1]),
+            Expression::Sqrt(expr) => self.write_call(f, "sqrt", &[expr]),
+            Expression::Sin(expr) => self.write_call(f, "sin", &[expr]),
+            Expression::Cos(expr) => self.write_call(f, "cos", &[expr]),
+            Expression::Tan(expr) => self.write_call(f, "tan", &[expr]),
+            Expression::Equal(args) => self.write_infix(f, args, "==", COMPARE),
+            Expression::Greater(args) => self.write_infix(f, args, ">", COMPARE),
+            Expression::GreaterEq(args) => self.write_infix(f, args, ">=", COMPARE),
+            Expression::Less(args) => self.write_infix(f, args, "<", COMPARE),
+            Expression::LessEq(args) => self.write_infix(f, args, "<=", COMPARE),
+            Expression::Append(args) => self.write_call(f, "append", &[&args.0, &args.1]),
+            Expression::Truncate(expr) => self.write_call(f, "truncate", &[expr]),
+            Expression::Len(expr) => self.write_call(f, "len", &[expr]),
+            Expression::List(t, exprs) => {
+                write!(f, "list<{t}>(")?;
+                self.write_list(f, exprs, ", ", 0)?;
+                write!(f, ")")
+            }
+            Expression::Array(t, exprs) => {
+                write!(f, "array<{t}>(")?;
+                self.write_list(f, exprs, ", ", 0)?;
+                write!(f, ")")
+            }
+            Expression::Index(args) => {
+                self.write(f, &args.0, POSTFIX)?;
+                write!(f, "[")?;
+                self.write(f, &args.1, 0)?;
+                write!(f, "]")
+            }
+            Expression::Update(args) => self.write_call(f, "update", &[&args.0, &args.1, &args.2]),
+            Expression::PushFront(args) => self.write_call(f, "push_front", &[&args.0, &args.1]),
+            Expression::PopFront(expr) => self.write_call(f, "pop_front", &[expr]),
+            Expression::Contains(args) => self.write_call(f, "contains", &[&args.0, &args.1]),
+            Expression::Slice(args) => self.write_call(f, "slice", &[&args.0, &args.1, &args.2]),
+            Expression::Concat(exprs) => {
+                write!(f, "concat(")?;
+                self.write_list(f, exprs, ", ", 0)?;
+                write!(f, ")")
+            }
+            Expression::Ite(args) => self.write_call(f, "ite", &[&args.0, &args.1, &args.2]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Ident(String),
+    // Digits of an integer literal, with the type suffix of sized integers.
+    Int(String, Option<IntType>),
+    Float(String),
+    Str(String),
+    Punct(&'static str),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(s) | Token::Float(s) => write!(f, "{s}"),
+            Token::Punct(s) => write!(f, "{s}"),
+            Token::Int(s, None) => write!(f, "{s}"),
+            Token::Int(s, Some(int_type)) => write!(f, "{s}{int_type}"),
+            Token::Str(s) => write!(f, "{s:?}"),
+        }
+    }
+}
+
+// Punctuation, longest first.
+const PUNCTS: [&str; 26] = [
+    "=>", "==", ">=", "<=", "&&", "||", "::", "(", ")", "[", "]", "{", "}", ",", ":", ";", ".",
+    "#", "!", ">", "<", "+", "-", "*", "/", "%",
+];
+
+const INT_TYPES: [IntType; 8] = [
+    IntType::I8,
+    IntType::I16,
+    IntType::I32,
+    IntType::I64,
+    IntType::U8,
+    IntType::U16,
+    IntType::U32,
+    IntType::U64,
+];
+
+// A stream of tokens, with their position in the input.
+pub(crate) struct Tokens {
+    tokens: Vec<(Token, usize)>,
+    next: usize,
+}
+
+impl Tokens {
+    pub(crate) fn new(input: &str) -> Result<Self, ParseError> {
+        let mut tokens: Vec<(Token, usize)> = Vec::new();
+        let mut chars = input.char_indices().peekable();
+        while let Some(&(pos, c)) = chars.peek() {
+            let rest = &input[pos..];
+            if c.is_whitespace() {
+                chars.next();
+                continue;
+            }
+            let (token, len) = if c.is_ascii_alphabetic() || c == '_' {
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                (Token::Ident(rest[..len].to_string()), len)
+            } else if c.is_ascii_digit() {
+                // Indices of components are plain integers, e.g., in `t.0.1`.
+                let after_dot = matches!(tokens.last(), Some((Token::Punct("."), _)));
+                Self::number(rest, after_dot)
+            } else if c == '"' {
+                Self::string(rest).ok_or(ParseError::UnexpectedEnd)?
+            } else if let Some(punct) = PUNCTS.iter().find(|punct| rest.starts_with(*punct)) {
+                (Token::Punct(punct), punct.len())
+            } else {
+                return Err(ParseError::UnexpectedChar(c, pos));
+            };
+            tokens.push((token, pos));
+            while chars.peek().is_some_and(|&(next, _)| next < pos + len) {
+                chars.next();
+            }
+        }
+        Ok(Self { tokens, next: 0 })
+    }
+
+    fn number(input: &str, integer_only: bool) -> (Token, usize) {
+        let digits = |from: usize| {
+            input[from..]
+                .find(|c: char| !c.is_ascii_digit())
+                .map_or(input.len(), |len| from + len)
+        };
+        let starts_digits = |from: usize| {
+            input[from..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_digit())
+        };
+        let mut len = digits(0);
+        let mut float = false;
+        if !integer_only {
+            if input[len..].starts_with('.') && starts_digits(len + 1) {
+                len = digits(len + 1);
+                float = true;
+            }
+            if input[len..].starts_with(['e', 'E']) {
+                let sign = usize::from(input[len + 1..].starts_with(['+', '-']));
+                if starts_digits(len + 1 + sign) {
+                    len = digits(len + 1 + sign);
+                    float = true;
+                }
+            }
+        }
+        if float {
+            return (Token::Float(input[..len].to_string()), len);
+        }
+        let suffix = INT_TYPES.into_iter().find(|int_type| {
+            let suffix = int_type.to_string();
+            input[len..].starts_with(&suffix)
+                && !input[len + suffix.len()..]
+                    .starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+        });
+        let token = Token::Int(input[..len].to_string(), suffix);
+        (
+            token,
+            len + suffix.map_or(0, |int_type| int_type.to_string().len()),
+        )
+    }
+
+    // Parses a string literal with Rust-style escapes, returning it and its length in the input.
+    fn string(input: &str) -> Option<(Token, usize)> {
+        let mut string = String::new();
+        let mut chars = input.char_indices().skip(1);
+        while let Some((pos, c)) = chars.next() {
+            match c {
+                '"' => return Some((Token::Str(string), pos + 1)),
+                '\\' => match chars.next()?.1 {
+                    'n' => string.push('\n'),
+                    'r' => string.push('\r'),
+                    't' => string.push('\t'),
+                    '0' => string.push('\0'),
+                    'u' => {
+                        let (start, _) = chars.next().filter(|(_, c)| *c == '{')?;
+                        let end = start + input[start..].find('}')?;
+                        let code = u32::from_str_radix(&input[start + 1..end], 16).ok()?;
+                        string.push(char::from_u32(code)?);
+                        while chars.next().is_some_and(|(pos, _)| pos < end) {}
+                    }
+                    c => string.push(c),
+                },
+                c => string.push(c),
+            }
+        }
+        None
+    }
+
+    pub(crate) fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(token, _)| token)
+    }
+
+    pub(crate) fn next(&mut self) -> Result<Token, ParseError> {
+        let (token, _) = self
+            .tokens
+            .get(self.next)
+            .ok_or(ParseError::UnexpectedEnd)?;
+        self.next += 1;
+        Ok(token.clone())
+    }
+
+    // Error for the last token taken from the stream.
+    pub(crate) fn unexpected(&self) -> ParseError {
+        self.tokens
+            .get(self.next.saturating_sub(1))
+            .map_or(ParseError::UnexpectedEnd, |(token, pos)| {
+                ParseError::UnexpectedToken(token.to_string(), *pos)
+            })
+    }
+
+    pub(crate) fn is(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    pub(crate) fn eat(&mut self, punct: &str) -> bool {
+        let is = self.is(punct);
+        if is {
+            self.next += 1;
+        }
+        is
+    }
+
+    pub(crate) fn expect(&mut self, punct: &str) -> Result<(), ParseError> {
+        if matches!(self.next()?, Token::Punct(p) if p == punct) {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    pub(crate) fn ident(&mut self) -> Result<String, ParseError> {
+        match self.next()? {
+            Token::Ident(ident) => Ok(ident),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    pub(crate) fn natural<N: std::str::FromStr>(&mut self) -> Result<N, ParseError> {
+        match self.next()? {
+            Token::Int(digits, None) => digits
+                .parse()
+                .map_err(|_| ParseError::InvalidLiteral(digits)),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    pub(crate) fn end(&mut self) -> Result<(), ParseError> {
+        if self.peek().is_some() {
+            self.next += 1;
+            Err(self.unexpected())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+struct Parser<'a, V, R> {
+    tokens: Tokens,
+    resolver: &'a R,
+    _var: std::marker::PhantomData<V>,
+}
+
+impl<V, R> Parser<'_, V, R>
+where
+    V: Clone + Debug,
+    R: Resolver<V>,
+{
+    fn expr(&mut self) -> Result<Expression<V>, ParseError> {
+        let lhs = self.or()?;
+        if self.tokens.eat("=>") {
+            let rhs = self.expr()?;
+            Ok(Expression::Implies(Box::new((lhs, rhs))))
+        } else {
+            Ok(lhs)
+        }
+    }
+
+    // Parses a chain of operands separated by the given operator.
+    fn chain(
+        &mut self,
+        op: &str,
+        operand: fn(&mut Self) -> Result<Expression<V>, ParseError>,
+        nary: fn(Vec<Expression<V>>) -> Expression<V>,
+    ) -> Result<Expression<V>, ParseError> {
+        let first = operand(self)?;
+        if self.tokens.is(op) {
+            let mut operands = vec![first];
+            while self.tokens.eat(op) {
+                operands.push(operand(self)?);
+            }
+            Ok(nary(operands))
+        } else {
+            Ok(first)
+        }
+    }
+
+    fn or(&mut self) -> Result<Expression<V>, ParseError> {
+        self.chain("||", Self::and, Expression::Or)
+    }
+
+    fn and(&mut self) -> Result<Expression<V>, ParseError> {
+        self.chain("&&", Self::comparison, Expression::And)
+    }
+
+    fn comparison(&mut self) -> Result<Expression<V>, ParseError> {
+        let lhs = self.sum()?;
+        let comparison: fn(_) -> _ = match self.tokens.peek() {
+            Some(Token::Punct("==")) => Expression::Equal,
+            Some(Token::Punct(">")) => Expression::Greater,
+            Some(Token::Punct(">=")) => Expression::GreaterEq,
+            Some(Token::Punct("<")) => Expression::Less,
+            Some(Token::Punct("<=")) => Expression::LessEq,
+            _ => return Ok(lhs),
+        };
+        self.tokens.next()?;
+        let rhs = self.sum()?;
+        Ok(comparison(Box::new((lhs, rhs))))
+    }
+
+    fn sum(&mut self) -> Result<Expression<V>, ParseError> {
+        self.chain("+", Self::mult, Expression::Sum)
+    }
+
+    fn mult(&mut self) -> Result<Expression<V>, ParseError> {
+        let mut lhs = self.unary()?;
+        // Whether `lhs` is a multiplication that can be extended by a further factor.
+        let mut extend = false;
+        loop {
+            if self.tokens.eat("*") {
+                let rhs = self.unary()?;
+                match &mut lhs {
+                    Expression::Mult(factors) if extend => factors.push(rhs),
+                    _ => lhs = Expression::Mult(vec![lhs, rhs]),
+                }
+                extend = true;
+            } else if self.tokens.eat("/") {
+                lhs = Expression::Div(Box::new((lhs, self.unary()?)));
+                extend = false;
+            } else if self.tokens.eat("%") {
+                lhs = Expression::Mod(Box::new((lhs, self.unary()?)));
+                extend = false;
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expression<V>, ParseError> {
+        if self.tokens.eat("!") {
+            Ok(Expression::Not(Box::new(self.unary()?)))
+        } else if self.tokens.eat("-") {
+            if matches!(self.tokens.peek(), Some(Token::Int(..) | Token::Float(_))) {
+                let token = self.tokens.next()?;
+                self.literal(token, true)
+            } else {
+                Ok(Expression::Opposite(Box::new(self.unary()?)))
+            }
+        } else {
+            self.postfix()
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Expression<V>, ParseError> {
+        let mut expr = self.atom()?;
+        loop {
+            if self.tokens.eat("[") {
+                let index = self.expr()?;
+                self.tokens.expect("]")?;
+                expr = Expression::Index(Box::new((expr, index)));
+            } else if self.tokens.eat(".") {
+                if matches!(self.tokens.peek(), Some(Token::Int(..))) {
+                    let index = self.tokens.natural()?;
+                    expr = Expression::Component(index, Box::new(expr));
+                } else {
+                    let index = self.field(&expr)?;
+                    expr = Expression::Field(index, Box::new(expr));
+                }
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    // Parses a field of the record the expression evaluates to, given by name or by `#index`.
+    fn field(&mut self, record: &Expression<V>) -> Result<usize, ParseError> {
+        if self.tokens.eat("#") {
+            self.tokens.natural()
+        } else {
+            let name = self.tokens.ident()?;
+            let record = record.record_type().map_err(ParseError::Type)?;
+            record.index(&name).ok_or(ParseError::UnknownField(name))
+        }
+    }
+
+    fn literal(&mut self, token: Token, negative: bool) -> Result<Expression<V>, ParseError> {
+        let sign = if negative { "-" } else { "" };
+        let invalid = || ParseError::InvalidLiteral(format!("{sign}{token}"));
+        let val = match &token {
+            Token::Int(digits, None) => format!("{sign}{digits}")
+                .parse()
+                .map(Val::Integer)
+                .map_err(|_| invalid())?,
+            Token::Int(digits, Some(int_type)) => format!("{sign}{digits}")
+                .parse::<i128>()
+                .ok()
+                .filter(|i| (int_type.min()..=int_type.max()).contains(i))
+                .map(|i| Val::Int(*int_type, i))
+                .ok_or_else(invalid)?,
+            Token::Float(digits) => format!("{sign}{digits}")
+                .parse()
+                .map(Val::Float)
+                .map_err(|_| invalid())?,
+            Token::Str(s) if !negative => Val::String(s.clone()),
+            _ => return Err(self.tokens.unexpected()),
+        };
+        Ok(Expression::Const(val))
+    }
+
+    fn atom(&mut self) -> Result<Expression<V>, ParseError> {
+        match self.tokens.next()? {
+            token @ (Token::Int(..) | Token::Float(_) | Token::Str(_)) => {
+                self.literal(token, false)
+            }
+            Token::Punct("(") => {
+                if self.tokens.eat(")") {
+                    return Ok(Expression::Tuple(Vec::new()));
+                }
+                let expr = self.expr()?;
+                if self.tokens.eat(")") {
+                    return Ok(expr);
+                }
+                let mut exprs = vec![expr];
+                while self.tokens.eat(",") && !self.tokens.is(")") {
+                    exprs.push(self.expr()?);
+                }
+                self.tokens.expect(")")?;
+                Ok(Expression::Tuple(exprs))
+            }
+            Token::Ident(ident) => match ident.as_str() {
+                "true" => Ok(Expression::Const(Val::Boolean(true))),
+                "false" => Ok(Expression::Const(Val::Boolean(false))),
+                "const" => {
+                    self.tokens.expect("(")?;
+                    let expr = self.expr()?;
+                    self.tokens.expect(")")?;
+                    expr.eval_constant()
+                        .map(Expression::Const)
+                        .map_err(ParseError::Type)
+                }
+                "list" | "array" => {
+                    self.tokens.expect("<")?;
+                    let t = self.r#type()?;
+                    self.tokens.expect(">")?;
+                    self.tokens.expect("(")?;
+                    let exprs = self.args()?;
+                    if ident == "list" {
+                        Ok(Expression::List(t, exprs))
+                    } else {
+                        Ok(Expression::Array(t, exprs))
+                    }
+                }
+                _ if self.tokens.eat("::") => {
+                    let Type::Enum(r#enum) = self.named_type(ident)? else {
+                        return Err(self.tokens.unexpected());
+                    };
+                    let label = self.tokens.ident()?;
+                    let index = r#enum
+                        .index(&label)
+                        .ok_or(ParseError::UnknownLabel(label))?;
+                    Ok(Expression::Const(Val::Enum(r#enum, index)))
+                }
+                _ if self.tokens.eat("{") => {
+                    let Type::Record(record) = self.named_type(ident)? else {
+                        return Err(self.tokens.unexpected());
+                    };
+                    let mut fields = vec![None; record.fields().len()];
+                    while !self.tokens.eat("}") {
+                        let name = self.tokens.ident()?;
+                        let index = record.index(&name).ok_or(ParseError::UnknownField(name))?;
+                        self.tokens.expect(":")?;
+                        fields[index] = Some(self.expr()?);
+                        if !self.tokens.eat(",") {
+                            self.tokens.expect("}")?;
+                            break;
+                        }
+                    }
+                    let fields = fields
+                        .into_iter()
+                        .zip(record.fields())
+                        .map(|(expr, (name, _))| expr.ok_or(ParseError::UnknownField(name.clone())))
+                        .collect::<Result<_, _>>()?;
+                    Ok(Expression::Record(record, fields))
+                }
+                _ if self.tokens.eat("(") => self.call(ident),
+                _ => self
+                    .resolver
+                    .var(&ident)
+                    .map(|(var, t)| Expression::Var(var, t))
+                    .ok_or(ParseError::UnknownVar(ident)),
+            },
+            _ => Err(self.tokens.unexpected()),
+        }
+    }
+
+    // Parses arguments up to the closing parenthesis.
+    fn args(&mut self) -> Result<Vec<Expression<V>>, ParseError> {
+        let mut args = Vec::new();
+        while !self.tokens.eat(")") {
+            args.push(self.expr()?);
+            if !self.tokens.eat(",") {
+                self.tokens.expect(")")?;
+                break;
+            }
+        }
+        Ok(args)
+    }
+
+    fn call(&mut self, function: String) -> Result<Expression<V>, ParseError> {
+        match function.as_str() {
+            "cast" => {
+                let expr = self.expr()?;
+                self.tokens.expect(",")?;
+                let t = self.r#type()?;
+                self.tokens.expect(")")?;
+                return Ok(Expression::Cast(Box::new(expr), t));
+            }
+            "set_field" => {
+                let record = self.expr()?;
+                self.tokens.expect(",")?;
+                let index = self.field(&record)?;
+                self.tokens.expect(",")?;
+                let expr = self.expr()?;
+                self.tokens.expect(")")?;
+                return Ok(Expression::SetField(index, Box::new((record, expr))));
+            }
+            "rand_choice" => {
+                let mut choices = Vec::new();
+                while !self.tokens.eat(")") {
+                    let weight = self.expr()?;
+                    self.tokens.expect(":")?;
+                    choices.push((weight, self.expr()?));
+                    if !self.tokens.eat(",") {
+                        self.tokens.expect(")")?;
+                        break;
+                    }
+                }
+                return Ok(Expression::RandChoice(choices));
+            }
+            _ => {}
+        }
+        let args = self.args()?;
+        let found = args.len();
+        let arity = |expected: usize| ParseError::Arity {
+            function: function.clone(),
+            expected,
+            found,
+        };
+        let expr = match function.as_str() {
+            "and" => Expression::And(args),
+            "or" => Expression::Or(args),
+            "sum" => Expression::Sum(args),
+            "mult" => Expression::Mult(args),
+            "concat" => Expression::Concat(args),
+            "rand_bool" => {
+                let [p] = args.try_into().map_err(|_| arity(1))?;
+                Expression::RandBool(constant_float(p)?)
+            }
+            "rand_int" => {
+                let [lower, upper] = args.try_into().map_err(|_| arity(2))?;
+                Expression::RandInt(constant_integer(lower)?, constant_integer(upper)?)
+            }
+            "rand_float" => {
+                let [lower, upper] = args.try_into().map_err(|_| arity(2))?;
+                Expression::RandFloat(constant_float(lower)?, constant_float(upper)?)
+            }
+            "rand_exp" | "rand_poisson" | "rand_geometric" | "abs" | "floor" | "ceil" | "round"
+            | "exp" | "sqrt" | "sin" | "cos" | "tan" | "truncate" | "len" | "pop_front" => {
+                let [arg] = args.try_into().map_err(|_| arity(1))?;
+                let unary = match function.as_str() {
+                    "rand_exp" => Expression::RandExp,
+                    "rand_poisson" => Expression::RandPoisson,
+                    "rand_geometric" => Expression::RandGeometric,
+                    "abs" => Expression::Abs,
+                    "floor" => Expression::Floor,
+                    "ceil" => Expression::Ceil,
+                    "round" => Expression::Round,
+                    "exp" => Expression::Exp,
+                    "sqrt" => Expression::Sqrt,
+                    "sin" => Expression::Sin,
+                    "cos" => Expression::Cos,
+                    "tan" => Expression::Tan,
+                    "truncate" => Expression::Truncate,
+                    "len" => Expression::Len,
+                    _ => Expression::PopFront,
+                };
+                unary(Box::new(arg))
+            }
+            "rand_uniform" | "rand_normal" | "min" | "max" | "pow" | "log" | "append"
+            | "push_front" | "contains" => {
+                let [lhs, rhs] = args.try_into().map_err(|_| arity(2))?;
+                let binary = match function.as_str() {
+                    "rand_uniform" => Expression::RandUniform,
+                    "rand_normal" => Expression::RandNormal,
+                    "min" => Expression::Min,
+                    "max" => Expression::Max,
+                    "pow" => Expression::Pow,
+                    "log" => Expression::Log,
+                    "append" => Expression::Append,
+                    "push_front" => Expression::PushFront,
+                    _ => Expression::Contains,
+                };
+                binary(Box::new((lhs, rhs)))
+            }
+            "update" | "slice" | "ite" => {
+                let [a, b, c] = args.try_into().map_err(|_| arity(3))?;
+                let ternary = match function.as_str() {
+                    "update" => Expression::Update,
+                    "slice" => Expression::Slice,
+                    _ => Expression::Ite,
+                };
+                ternary(Box::new((a, b, c)))
+            }
+            _ => return Err(ParseError::UnknownFunction(function)),
+        };
+        Ok(expr)
+    }
+
+    fn named_type(&self, name: String) -> Result<Type, ParseError> {
+        self.resolver
+            .named_type(&name)
+            .ok_or(ParseError::UnknownType(name))
+    }
+
+    fn r#type(&mut self) -> Result<Type, ParseError> {
+        match self.tokens.next()? {
+            Token::Punct("(") => {
+                let mut types = Vec::new();
+                while !self.tokens.eat(")") {
+                    types.push(self.r#type()?);
+                    if !self.tokens.eat(",") {
+                        self.tokens.expect(")")?;
+                        break;
+                    }
+                }
+                Ok(Type::Product(types))
+            }
+            Token::Punct("[") => {
+                let t = Box::new(self.r#type()?);
+                if self.tokens.eat(";") {
+                    let len = self.tokens.natural()?;
+                    self.tokens.expect("]")?;
+                    Ok(Type::Array(t, len))
+                } else {
+                    self.tokens.expect("]")?;
+                    Ok(Type::List(t))
+                }
+            }
+            Token::Ident(name) => match name.as_str() {
+                "bool" => Ok(Type::Boolean),
+                "int" => Ok(Type::Integer),
+                "float" => Ok(Type::Float),
+                "string" => Ok(Type::String),
+                _ => INT_TYPES
+                    .into_iter()
+                    .find(|int_type| int_type.to_string() == name)
+                    .map(Type::Int)
+                    .map_or_else(|| self.named_type(name), Ok),
+            },
+            _ => Err(self.tokens.unexpected()),
+        }
+    }
+}
+
+fn constant_float<V: Clone + Debug>(expr: Expression<V>) -> Result<Float, ParseError> {
+    match expr.eval_constant().map_err(ParseError::Type)? {
+        Val::Float(f) => Ok(f),
+        Val::Integer(i) => Ok(i as Float),
+        val => Err(ParseError::Type(TypeError::mismatch(
+            Expected::Type(Type::Float),
+            val.r#type(),
+        ))),
+    }
+}
+
+fn constant_integer<V: Clone + Debug>(expr: Expression<V>) -> Result<Integer, ParseError> {
+    match expr.eval_constant().map_err(ParseError::Type)? {
+        Val::Integer(i) => Ok(i),
+        val => Err(ParseError::Type(TypeError::mismatch(
+            Expected::Type(Type::Integer),
+            val.r#type(),
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Expr = Expression<String>;
+
+    fn point() -> Arc<Record> {
+        Arc::new(Record::new(
+            String::from("Point"),
+            vec![
+                (String::from("x"), Type::Integer),
+                (String::from("y"), Type::Float),
+            ],
+        ))
+    }
+
+    fn color() -> Arc<Enum> {
+        Arc::new(Enum::new(
+            String::from("Color"),
+            vec![String::from("red"), String::from("green")],
+        ))
+    }
+
+    struct Vars;
+
+    impl Resolver<String> for Vars {
+        fn var(&self, name: &str) -> Option<(String, Type)> {
+            let t = match name {
+                "b" => Type::Boolean,
+                "x" | "y" => Type::Integer,
+                "f" => Type::Float,
+                "p" => Type::Record(point()),
+                "t" => Type::Product(vec![Type::Integer, Type::Product(vec![Type::Float])]),
+                "l" => Type::List(Box::new(Type::Integer)),
+                _ => return None,
+            };
+            Some((name.to_string(), t))
+        }
+
+        fn named_type(&self, name: &str) -> Option<Type> {
+            match name {
+                "Point" => Some(Type::Record(point())),
+                "Color" => Some(Type::Enum(color())),
+                _ => None,
+            }
+        }
+    }
+
+    fn var(name: &str) -> Expr {
+        let (var, t) = Vars.var(name).expect("known variable");
+        Expr::Var(var, t)
+    }
+
+    fn round_trip(expr: Expr) {
+        let text = expr.to_string();
+        let parsed = Expr::parse(&text, &Vars).unwrap_or_else(|err| panic!("{text}: {err}"));
+        assert_eq!(parsed, expr, "{text}");
+    }
+
+    #[test]
+    fn round_trips() {
+        let x = || var("x");
+        let b = || var("b");
+        let exprs = vec![
+            Expr::from(-3),
+            Expr::Const(Val::Int(IntType::U8, 255)),
+            Expr::Const(Val::Int(IntType::I8, -128)),
+            Expr::from(1.5),
+            Expr::from(-0.25),
+            Expr::from(1e-7),
+            Expr::Const(Val::String(String::from("a \"quoted\"\n\u{1F600} string"))),
+            Expr::Const(Val::Enum(color(), 1)),
+            Expr::Const(Val::Tuple(vec![Val::Integer(1), Val::Boolean(true)])),
+            Expr::Const(Val::List(Type::Integer, Vec::new())),
+            Expr::Const(Val::Array(
+                Type::Float,
+                vec![Val::Float(1.), Val::Float(2.)],
+            )),
+            Expr::Const(Val::Record(point(), vec![Val::Integer(0), Val::Float(1.)])),
+            Expr::Tuple(vec![x()]),
+            Expr::Tuple(Vec::new()),
+            Expr::Component(1, Box::new(Expr::Component(0, Box::new(var("t"))))),
+            Expr::Field(1, Box::new(var("p"))),
+            Expr::Field(0, Box::new(Expr::Const(Val::Integer(0)))),
+            Expr::SetField(0, Box::new((var("p"), x()))),
+            Expr::Record(point(), vec![x(), var("f")]),
+            Expr::Implies(Box::new((
+                Expr::Implies(Box::new((b(), b()))),
+                Expr::Implies(Box::new((b(), b()))),
+            ))),
+            Expr::Or(vec![
+                Expr::And(vec![b(), b()]),
+                Expr::Or(vec![b(), b()]),
+                b(),
+            ]),
+            Expr::And(vec![b()]),
+            Expr::Or(Vec::new()),
+            Expr::Not(Box::new(Expr::Not(Box::new(Expr::And(vec![b(), b()]))))),
+            Expr::Equal(Box::new((Expr::Less(Box::new((x(), x()))), b()))),
+            Expr::Sum(vec![
+                Expr::Sum(vec![x(), x()]),
+                Expr::Opposite(Box::new(x())),
+                x(),
+            ]),
+            Expr::Mult(vec![
+                Expr::Sum(vec![x(), Expr::from(1)]),
+                Expr::Mult(vec![x(), x()]),
+            ]),
+            Expr::Mod(Box::new((
+                Expr::Mod(Box::new((x(), x()))),
+                Expr::Div(Box::new((x(), x()))),
+            ))),
+            Expr::Opposite(Box::new(Expr::from(1))),
+            Expr::Opposite(Box::new(Expr::from(-1))),
+            Expr::Opposite(Box::new(Expr::Opposite(Box::new(x())))),
+            Expr::Opposite(Box::new(Expr::Index(Box::new((
+                Expr::Const(Val::Integer(1)),
+                Expr::from(0),
+            ))))),
+            Expr::Index(Box::new((Expr::from(-1), Expr::Sum(vec![x(), x()])))),
+            Expr::Cast(Box::new(x()), Type::Int(IntType::I16)),
+            Expr::RandBool(0.5),
+            Expr::RandInt(-1, 3),
+            Expr::RandFloat(0., 1.),
+            Expr::RandUniform(Box::new((Expr::from(-1.), var("f")))),
+            Expr::RandNormal(Box::new((var("f"), Expr::from(1.)))),
+            Expr::RandChoice(vec![(Expr::from(1), x()), (Expr::from(2.), Expr::from(3))]),
+            Expr::Min(Box::new((x(), Expr::Max(Box::new((x(), x())))))),
+            Expr::Pow(Box::new((
+                var("f"),
+                Expr::Log(Box::new((var("f"), var("f")))),
+            ))),
+            Expr::Sqrt(Box::new(Expr::Abs(Box::new(Expr::Round(Box::new(var(
+                "f",
+            ))))))),
+            Expr::List(Type::List(Box::new(Type::Integer)), vec![var("l")]),
+            Expr::Array(Type::Integer, vec![x(), x()]),
+            Expr::Update(Box::new((var("l"), x(), Expr::from(0)))),
+            Expr::Slice(Box::new((
+                var("l"),
+                Expr::from(0),
+                Expr::Len(Box::new(var("l"))),
+            ))),
+            Expr::Contains(Box::new((Expr::PushFront(Box::new((var("l"), x()))), x()))),
+            Expr::Concat(vec![Expr::Const(Val::String(String::from("a")))]),
+            Expr::Ite(Box::new((b(), x(), Expr::Truncate(Box::new(var("l")))))),
+        ];
+        for expr in exprs {
+            round_trip(expr);
+        }
+    }
+
+    #[test]
+    fn display() {
+        let x = || var("x");
+        let expr = Expr::Implies(Box::new((
+            Expr::And(vec![
+                Expr::Less(Box::new((
+                    Expr::Sum(vec![x(), Expr::from(1)]),
+                    Expr::from(3),
+                ))),
+                Expr::Not(Box::new(var("b"))),
+            ]),
+            Expr::Equal(Box::new((Expr::Field(0, Box::new(var("p"))), x()))),
+        )));
+        assert_eq!(expr.to_string(), "x + 1 < 3 && !b => p.x == x");
+        assert_eq!(
+            Expr::Mult(vec![Expr::Sum(vec![x(), x()]), x()]).to_string(),
+            "(x + x) * x"
+        );
+        assert_eq!(
+            Expr::Const(Val::List(Type::Integer, vec![Val::Integer(1)])).to_string(),
+            "const(list<int>(1))"
+        );
+    }
+
+    #[test]
+    fn parse() -> Result<(), ParseError> {
+        let parse = |input: &str| Expr::parse(input, &Vars);
+        assert_eq!(
+            parse("1 + 2 * x")?,
+            Expr::Sum(vec![
+                Expr::from(1),
+                Expr::Mult(vec![Expr::from(2), var("x")])
+            ])
+        );
+        assert_eq!(parse("Color::green")?, Expr::Const(Val::Enum(color(), 1)));
+        assert_eq!(
+            parse("Point { y: f, x: 1 }")?,
+            Expr::Record(point(), vec![Expr::from(1), var("f")])
+        );
+        assert!(matches!(parse("z"), Err(ParseError::UnknownVar(_))));
+        assert!(matches!(parse("p.z"), Err(ParseError::UnknownField(_))));
+        assert!(matches!(
+            parse("foo(x)"),
+            Err(ParseError::UnknownFunction(_))
+        ));
+        assert!(matches!(parse("min(x)"), Err(ParseError::Arity { .. })));
+        assert!(matches!(parse("x +"), Err(ParseError::UnexpectedEnd)));
+        assert!(matches!(
+            parse("x x"),
+            Err(ParseError::UnexpectedToken(_, 2))
+        ));
+        assert!(matches!(parse("300u8"), Err(ParseError::InvalidLiteral(_))));
+        assert!(matches!(
+            parse("x $ y"),
+            Err(ParseError::UnexpectedChar('$', 2))
+        ));
+        Ok(())
+    }
+}
@@ -86,6 +86,31 @@ pub trait Oracle: Clone + Send + Sync {
     fn final_output_guarantees(&self) -> Option<usize> {
         self.output_guarantees()
     }
+
+    /// Checks a recorded trace, given as a sequence of state labels with their timestamps,
+    /// and returns the outcome of the run it represents.
+    ///
+    /// Timestamps have to be non-decreasing.
+    fn check_trace<I>(mut self, trace: I) -> RunOutcome
+    where
+        I: IntoIterator<Item = (Vec<bool>, Time)>,
+    {
+        for (labels, time) in trace {
+            self.update(&labels, time);
+            if self.output_assumes().is_some() {
+                return RunOutcome::Incomplete;
+            } else if let Some(i) = self.output_guarantees() {
+                return RunOutcome::Fail(i);
+            }
+        }
+        if self.final_output_assumes().is_some() {
+            RunOutcome::Incomplete
+        } else if let Some(i) = self.final_output_guarantees() {
+            RunOutcome::Fail(i)
+        } else {
+            RunOutcome::Success
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -101,6 +126,7 @@ where
     successes: Arc<AtomicU32>,
     failures: Arc<AtomicU32>,
     violations: Arc<Mutex<Vec<u32>>>,
+    error_property: Option<usize>,
     _event: PhantomData<Event>,
     _err: PhantomData<Err>,
 }
@@ -120,11 +146,19 @@ where
             successes: Arc::new(AtomicU32::new(0)),
             failures: Arc::new(AtomicU32::new(0)),
             violations: Arc::new(Mutex::new(Vec::new())),
+            error_property: None,
             _event: PhantomData,
             _err: PhantomData,
         }
     }
 
+    /// Makes runs that end with an error (e.g., by assigning an out-of-bounds value to a bounded variable)
+    /// count as violations of the property with the given index,
+    /// instead of being discarded.
+    pub fn set_error_property(&mut self, property: usize) {
+        self.error_property = Some(property);
+    }
+
     pub fn running(&self) -> bool {
         self.running.load(Ordering::Relaxed)
     }
@@ -165,6 +199,7 @@ where
         let successes = self.successes.clone();
         let failures = self.failures.clone();
         let violations = self.violations.clone();
+        let error_property = self.error_property;
 
         // WARN FIXME TODO: Implement algorithm for 2.4 Distributed sample generation in Budde et al.
         info!("verification starting");
@@ -178,40 +213,43 @@ where
                 let local_successes;
                 let local_failures;
 
-                match ts.as_ref().clone().experiment(
+                let result = match ts.as_ref().clone().experiment(
                     duration,
                     oracle.as_ref().clone(),
                     tracer.clone(),
                     running.clone(),
                 ) {
-                    Ok(result) => {
-                        if !running.load(Ordering::Relaxed) {
-                            return false;
-                        }
-                        match result {
-                            RunOutcome::Success => {
-                                local_successes = successes.fetch_add(1, Ordering::Relaxed);
-                                local_failures = failures.load(Ordering::Relaxed);
-                                // If all guarantees are satisfied, the execution is successful
-                                trace!("runs: {} successes", local_successes);
-                            }
-                            RunOutcome::Fail(guarantee) => {
-                                local_successes = successes.load(Ordering::Relaxed);
-                                local_failures = failures.fetch_add(1, Ordering::Relaxed);
-                                let violations = &mut *violations.lock().unwrap();
-                                violations.resize(violations.len().max(guarantee + 1), 0);
-                                violations[guarantee] += 1;
-                                // If guarantee is violated, we have found a counter-example!
-                                trace!("runs: {} failures", local_failures);
-                            }
-                            RunOutcome::Incomplete => return true,
-                        }
-                    }
+                    Ok(result) => result,
                     Err(err) => {
                         warn!("run returned error: {err}");
-                        return true;
+                        if let Some(property) = error_property {
+                            RunOutcome::Fail(property)
+                        } else {
+                            return true;
+                        }
                     }
                 };
+                if !running.load(Ordering::Relaxed) {
+                    return false;
+                }
+                match result {
+                    RunOutcome::Success => {
+                        local_successes = successes.fetch_add(1, Ordering::Relaxed);
+                        local_failures = failures.load(Ordering::Relaxed);
+                        // If all guarantees are satisfied, the execution is successful
+                        trace!("runs: {} successes", local_successes);
+                    }
+                    RunOutcome::Fail(guarantee) => {
+                        local_successes = successes.load(Ordering::Relaxed);
+                        local_failures = failures.fetch_add(1, Ordering::Relaxed);
+                        let violations = &mut *violations.lock().unwrap();
+                        violations.resize(violations.len().max(guarantee + 1), 0);
+                        violations[guarantee] += 1;
+                        // If guarantee is violated, we have found a counter-example!
+                        trace!("runs: {} failures", local_failures);
+                    }
+                    RunOutcome::Incomplete => return true,
+                }
                 let runs = local_successes + local_failures;
                 // Avoid division by 0
                 let avg = if runs == 0 {
@@ -1,18 +1,44 @@
 use crate::channel_system::{Channel, ChannelSystem, CsError, Event, EventType};
-use crate::{DummyRng, Expression, FnExpression, Time, TransitionSystem, Val};
+use crate::{DummyRng, Expected, Expression, FnExpression, Time, TransitionSystem, TypeError, Val};
 use rand::{Rng, SeedableRng};
 use std::collections::{BTreeMap, btree_map};
 use std::sync::Arc;
 
+// Values of event atoms.
+static TRUE: Val = Val::Boolean(true);
+static FALSE: Val = Val::Boolean(false);
+
 /// An atomic variable for [`Pmtl`] formulae.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Atom {
     /// A predicate.
     State(Channel),
     /// An event.
+    ///
+    /// Sending and receiving events also hold when the communication is affected by faults
+    /// (see [`crate::channel_system::FaultModel`]),
+    /// and sending events hold when the message is sent to a broadcast channel the channel subscribes to
+    /// (see [`crate::channel_system::ChannelSystemBuilder::subscribe`]).
     Event(Event),
 }
 
+// Whether the event is the atom's,
+// possibly affected by the faults of an unreliable channel
+// or sent to a broadcast channel with the given subscribers.
+fn is_atom_event(event: &Event, atom: &Event, subscribers: &[Channel]) -> bool {
+    event.pg_id == atom.pg_id
+        && (event.channel == atom.channel
+            || matches!(atom.event_type, EventType::Send(_)) && subscribers.contains(&atom.channel))
+        && match (&event.event_type, &atom.event_type) {
+            (
+                EventType::Dropped(val) | EventType::Duplicated(val) | EventType::Delayed(val, _),
+                EventType::Send(atom_val),
+            )
+            | (EventType::Reordered(val), EventType::Receive(atom_val)) => val == atom_val,
+            (event_type, atom_type) => event_type == atom_type,
+        }
+}
+
 /// A builder type for [`CsModel`].
 pub struct CsModelBuilder<R: Rng + SeedableRng> {
     cs: ChannelSystem<R>,
@@ -48,8 +74,8 @@ impl<R: Rng + SeedableRng> CsModelBuilder<R> {
         let predicate = FnExpression::<Atom, _>::from(predicate);
         let _ = predicate.eval(
             &|port| match port {
-                Atom::State(channel) => self.ports.get(&channel).unwrap().clone(),
-                Atom::Event(_event) => Val::Boolean(false),
+                Atom::State(channel) => self.ports.get(&channel).unwrap(),
+                Atom::Event(_event) => &FALSE,
             },
             &mut DummyRng,
         );
@@ -84,13 +110,57 @@ pub struct CsModel<R: Rng + SeedableRng> {
     last_event: Option<Event>,
 }
 
+impl<R: Rng + SeedableRng> CsModel<R> {
+    /// Overwrites the value of a port,
+    /// e.g., to replay a recorded trace.
+    ///
+    /// Fails if the channel is not a port of the model,
+    /// or if the value's type does not match the port's.
+    pub fn set_port(&mut self, channel: Channel, val: Val) -> Result<(), CsError> {
+        let port = self
+            .ports
+            .get_mut(&channel)
+            .ok_or(CsError::MissingChannel(channel))?;
+        if port.r#type() == val.r#type() {
+            *port = val;
+            Ok(())
+        } else {
+            Err(CsError::Type(TypeError::TypeMismatch {
+                expected: Expected::Type(port.r#type()),
+                found: val.r#type(),
+            }))
+        }
+    }
+
+    /// Overwrites the last event produced by the model,
+    /// e.g., to replay a recorded trace.
+    pub fn set_last_event(&mut self, event: Option<Event>) {
+        self.last_event = event;
+    }
+
+    /// The underlying [`ChannelSystem`].
+    pub fn channel_system(&self) -> &ChannelSystem<R> {
+        &self.cs
+    }
+}
+
 impl<R: Rng + Clone + Send + Sync + SeedableRng> TransitionSystem<Event, CsError> for CsModel<R> {
     fn transition(&mut self, duration: Time) -> Result<Option<Event>, CsError> {
-        let event = self.cs.montecarlo_execution(duration);
+        let event = self.cs.montecarlo_execution(duration)?;
         if let Some(ref event) = event {
-            if let btree_map::Entry::Occupied(mut e) = self.ports.entry(event.channel) {
-                if let EventType::Send(ref val) = event.event_type {
-                    e.insert(val.clone());
+            if let EventType::Send(ref val)
+            | EventType::Handshake(_, ref val)
+            | EventType::Dropped(ref val)
+            | EventType::Duplicated(ref val)
+            | EventType::Delayed(ref val, _) = event.event_type
+            {
+                // Messages sent to a broadcast channel reach the ports of its subscribers
+                for channel in
+                    std::iter::once(&event.channel).chain(self.cs.subscribers(event.channel))
+                {
+                    if let Some(port) = self.ports.get_mut(channel) {
+                        *port = val.clone();
+                    }
                 }
             }
         }
@@ -106,11 +176,17 @@ impl<R: Rng + Clone + Send + Sync + SeedableRng> TransitionSystem<Event, CsError
         self.predicates
             .iter()
             .map(|prop| {
-                if let Val::Boolean(b) = prop.eval(
+                if let Ok(Val::Boolean(b)) = prop.eval(
                     &|port| match port {
-                        Atom::State(channel) => self.ports.get(&channel).unwrap().clone(),
+                        Atom::State(channel) => self.ports.get(&channel).unwrap(),
                         Atom::Event(event) => {
-                            Val::Boolean(self.last_event.as_ref().is_some_and(|e| e == &event))
+                            if self.last_event.as_ref().is_some_and(|e| {
+                                is_atom_event(e, &event, self.cs.subscribers(e.channel))
+                            }) {
+                                &TRUE
+                            } else {
+                                &FALSE
+                            }
                         }
                     },
                     &mut DummyRng,
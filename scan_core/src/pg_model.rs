@@ -3,13 +3,14 @@ use std::sync::Arc;
 use rand::{SeedableRng, rngs::SmallRng};
 
 use crate::{
-    DummyRng, FnExpression, TransitionSystem, Val,
+    DummyRng, FnExpression, Time, TransitionSystem, Val,
     program_graph::{Action, PgError, PgExpression, ProgramGraph, Var},
 };
 
 pub struct PgModel {
     pg: ProgramGraph<SmallRng>,
     rng: SmallRng,
+    time: Time,
     global_vars: Arc<Vec<Var>>,
     predicates: Arc<Vec<FnExpression<Var, DummyRng>>>,
 }
@@ -30,10 +31,21 @@ impl PgModel {
         Self {
             pg,
             rng,
+            time: 0,
             global_vars,
             predicates,
         }
     }
+
+    /// The underlying [`ProgramGraph`].
+    pub fn program_graph(&self) -> &ProgramGraph<SmallRng> {
+        &self.pg
+    }
+
+    /// The variables observable through the model's state.
+    pub fn global_vars(&self) -> &[Var] {
+        &self.global_vars
+    }
 }
 
 impl Clone for PgModel {
@@ -41,6 +53,7 @@ impl Clone for PgModel {
         Self {
             pg: self.pg.clone(),
             rng: SmallRng::from_os_rng(),
+            time: self.time,
             global_vars: Arc::clone(&self.global_vars),
             predicates: Arc::clone(&self.predicates),
         }
@@ -48,19 +61,28 @@ impl Clone for PgModel {
 }
 
 impl TransitionSystem<Action, PgError> for PgModel {
-    fn transition(&mut self, _duration: crate::Time) -> Result<Option<Action>, PgError> {
-        Ok(self.pg.montecarlo(&mut self.rng))
+    fn transition(&mut self, duration: Time) -> Result<Option<Action>, PgError> {
+        loop {
+            if let Some(action) = self.pg.montecarlo(&mut self.rng)? {
+                return Ok(Some(action));
+            }
+            // Untimed PGs cannot do anything by waiting
+            if !self.pg.is_timed() || self.time >= duration || self.pg.wait(1).is_err() {
+                return Ok(None);
+            }
+            self.time += 1;
+        }
     }
 
-    fn time(&self) -> crate::Time {
-        0
+    fn time(&self) -> Time {
+        self.time
     }
 
     fn labels(&self) -> Vec<bool> {
         self.predicates
             .iter()
             .map(|p| {
-                if let Val::Boolean(b) = self.pg.eval(p) {
+                if let Ok(Val::Boolean(b)) = self.pg.eval(p) {
                     b
                 } else {
                     panic!("non-bool pred")
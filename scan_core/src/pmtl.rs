@@ -1,12 +1,41 @@
 mod numset;
+mod syntax;
 
 use crate::{Oracle, Time};
 use numset::NumSet;
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use std::{hash::Hash, sync::Arc};
 
 type DenseTime = (Time, Time);
 
+/// Comparison between the number of occurrences counted by [`Pmtl::Count`] and a constant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    /// Strictly less than the constant.
+    Less,
+    /// Less than or equal to the constant.
+    LessEq,
+    /// Equal to the constant.
+    Equal,
+    /// Greater than or equal to the constant.
+    GreaterEq,
+    /// Strictly greater than the constant.
+    Greater,
+}
+
+impl Comparison {
+    #[inline(always)]
+    fn compare(self, lhs: usize, rhs: usize) -> bool {
+        match self {
+            Comparison::Less => lhs < rhs,
+            Comparison::LessEq => lhs <= rhs,
+            Comparison::Equal => lhs == rhs,
+            Comparison::GreaterEq => lhs >= rhs,
+            Comparison::Greater => lhs > rhs,
+        }
+    }
+}
+
 /// A Past-time Metric Temporal Logic (PMTL) formula.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Pmtl<V>
@@ -33,6 +62,21 @@ where
     Once(Box<Pmtl<V>>, Time, Time),
     /// Temporal since predicate over a formula (with bounds).
     Since(Box<(Pmtl<V>, Pmtl<V>)>, Time, Time),
+    /// Temporal weak since predicate over a formula (with bounds):
+    /// like [`Pmtl::Since`], but it also holds if the first formula held since the start of the trace.
+    WeakSince(Box<(Pmtl<V>, Pmtl<V>)>, Time, Time),
+    /// Temporal trigger predicate over a formula (with bounds),
+    /// the dual of [`Pmtl::Since`].
+    Trigger(Box<(Pmtl<V>, Pmtl<V>)>, Time, Time),
+    /// Temporal previous predicate over a formula:
+    /// the formula held at the previous step (false at the first step).
+    Previous(Box<Pmtl<V>>),
+    /// Temporal weak previous predicate over a formula:
+    /// the formula held at the previous step (true at the first step).
+    WeakPrevious(Box<Pmtl<V>>),
+    /// Temporal counting predicate over a formula (with bounds):
+    /// compares the number of steps in which the formula held within the bounds against a constant.
+    Count(Box<Pmtl<V>>, Comparison, usize, Time, Time),
 }
 
 impl<V> Pmtl<V>
@@ -114,6 +158,58 @@ where
                     false
                 }
             }
+            ArcPmtl::WeakSince((lhs, _), (rhs, _), lower_bound, upper_bound) => {
+                if let Pmtl::WeakSince(self_sub, self_lower_bound, self_upper_bound) = self {
+                    self_sub.0.is_same(lhs.as_ref())
+                        && self_sub.1.is_same(rhs.as_ref())
+                        && lower_bound == self_lower_bound
+                        && upper_bound == self_upper_bound
+                } else {
+                    false
+                }
+            }
+            ArcPmtl::Trigger((lhs, _), (rhs, _), lower_bound, upper_bound) => {
+                if let Pmtl::Trigger(self_sub, self_lower_bound, self_upper_bound) = self {
+                    self_sub.0.is_same(lhs.as_ref())
+                        && self_sub.1.is_same(rhs.as_ref())
+                        && lower_bound == self_lower_bound
+                        && upper_bound == self_upper_bound
+                } else {
+                    false
+                }
+            }
+            ArcPmtl::Previous((sub, _)) => {
+                if let Pmtl::Previous(self_sub) = self {
+                    self_sub.is_same(sub.as_ref())
+                } else {
+                    false
+                }
+            }
+            ArcPmtl::WeakPrevious((sub, _)) => {
+                if let Pmtl::WeakPrevious(self_sub) = self {
+                    self_sub.is_same(sub.as_ref())
+                } else {
+                    false
+                }
+            }
+            ArcPmtl::Count((sub, _), comparison, count, lower_bound, upper_bound) => {
+                if let Pmtl::Count(
+                    self_sub,
+                    self_comparison,
+                    self_count,
+                    self_lower_bound,
+                    self_upper_bound,
+                ) = self
+                {
+                    self_sub.is_same(sub.as_ref())
+                        && comparison == self_comparison
+                        && count == self_count
+                        && lower_bound == self_lower_bound
+                        && upper_bound == self_upper_bound
+                } else {
+                    false
+                }
+            }
         }
     }
 }
@@ -133,6 +229,11 @@ where
     Historically(IdxPmtl<V>, Time, Time),
     Previously(IdxPmtl<V>, Time, Time),
     Since(IdxPmtl<V>, IdxPmtl<V>, Time, Time),
+    WeakSince(IdxPmtl<V>, IdxPmtl<V>, Time, Time),
+    Trigger(IdxPmtl<V>, IdxPmtl<V>, Time, Time),
+    Previous(IdxPmtl<V>),
+    WeakPrevious(IdxPmtl<V>),
+    Count(IdxPmtl<V>, Comparison, usize, Time, Time),
 }
 
 type IdxPmtl<V> = (Arc<ArcPmtl<V>>, usize);
@@ -147,6 +248,13 @@ pub struct PmtlOracle {
     valuations: Vec<NumSet>,
     outputs: Vec<NumSet>,
     buf_valuations: Vec<NumSet>,
+    // Steps in which the argument of a counting subformula held,
+    // as (start, end) times of the step, oldest first,
+    // that may still enter or leave the window.
+    occurrences: Vec<VecDeque<(Time, Time)>>,
+    // Number of steps in which the argument of a counting subformula held
+    // that have entered the window and can never leave it.
+    permanent_occurrences: Vec<usize>,
 }
 
 impl<V> Pmtl<V>
@@ -162,8 +270,14 @@ where
             ),
             Pmtl::Not(subformula)
             | Pmtl::Historically(subformula, _, _)
-            | Pmtl::Once(subformula, _, _) => subformula.as_ref().clone().set_subformulae(),
-            Pmtl::Implies(subs) | Pmtl::Since(subs, _, _) => {
+            | Pmtl::Once(subformula, _, _)
+            | Pmtl::Previous(subformula)
+            | Pmtl::WeakPrevious(subformula)
+            | Pmtl::Count(subformula, _, _, _, _) => subformula.as_ref().clone().set_subformulae(),
+            Pmtl::Implies(subs)
+            | Pmtl::Since(subs, _, _)
+            | Pmtl::WeakSince(subs, _, _)
+            | Pmtl::Trigger(subs, _, _) => {
                 let mut formulae = subs.0.clone().set_subformulae();
                 formulae.extend(subs.1.clone().set_subformulae());
                 formulae
@@ -250,6 +364,56 @@ fn subformulae<V: Clone + Eq + Hash>(set: HashSet<Pmtl<V>>) -> Vec<ArcPmtl<V>> {
                     upper_bound,
                 )
             }
+            Pmtl::WeakSince(subs, lower_bound, upper_bound) => {
+                let idx_0 = idx_vec
+                    .iter()
+                    .position(|f| subs.0.is_same(f))
+                    .expect("index");
+                let idx_1 = idx_vec
+                    .iter()
+                    .position(|f| subs.1.is_same(f))
+                    .expect("index");
+                ArcPmtl::WeakSince(
+                    (Arc::new(idx_vec[idx_0].clone()), idx_0),
+                    (Arc::new(idx_vec[idx_1].clone()), idx_1),
+                    lower_bound,
+                    upper_bound,
+                )
+            }
+            Pmtl::Trigger(subs, lower_bound, upper_bound) => {
+                let idx_0 = idx_vec
+                    .iter()
+                    .position(|f| subs.0.is_same(f))
+                    .expect("index");
+                let idx_1 = idx_vec
+                    .iter()
+                    .position(|f| subs.1.is_same(f))
+                    .expect("index");
+                ArcPmtl::Trigger(
+                    (Arc::new(idx_vec[idx_0].clone()), idx_0),
+                    (Arc::new(idx_vec[idx_1].clone()), idx_1),
+                    lower_bound,
+                    upper_bound,
+                )
+            }
+            Pmtl::Previous(sub) => {
+                let idx = idx_vec.iter().position(|f| sub.is_same(f)).expect("index");
+                ArcPmtl::Previous((Arc::new(idx_vec[idx].clone()), idx))
+            }
+            Pmtl::WeakPrevious(sub) => {
+                let idx = idx_vec.iter().position(|f| sub.is_same(f)).expect("index");
+                ArcPmtl::WeakPrevious((Arc::new(idx_vec[idx].clone()), idx))
+            }
+            Pmtl::Count(sub, comparison, count, lower_bound, upper_bound) => {
+                let idx = idx_vec.iter().position(|f| sub.is_same(f)).expect("index");
+                ArcPmtl::Count(
+                    (Arc::new(idx_vec[idx].clone()), idx),
+                    comparison,
+                    count,
+                    lower_bound,
+                    upper_bound,
+                )
+            }
         };
         idx_vec.push(arc_pmtl);
     }
@@ -264,10 +428,16 @@ where
         match self {
             Pmtl::True | Pmtl::False | Pmtl::Atom(_) => 0,
             Pmtl::And(subs) | Pmtl::Or(subs) => subs.iter().map(Pmtl::depth).max().unwrap_or(0) + 1,
-            Pmtl::Not(sub) | Pmtl::Historically(sub, _, _) | Pmtl::Once(sub, _, _) => {
-                sub.depth() + 1
-            }
-            Pmtl::Implies(subs) | Pmtl::Since(subs, _, _) => subs.0.depth().max(subs.1.depth()) + 1,
+            Pmtl::Not(sub)
+            | Pmtl::Historically(sub, _, _)
+            | Pmtl::Once(sub, _, _)
+            | Pmtl::Previous(sub)
+            | Pmtl::WeakPrevious(sub)
+            | Pmtl::Count(sub, _, _, _, _) => sub.depth() + 1,
+            Pmtl::Implies(subs)
+            | Pmtl::Since(subs, _, _)
+            | Pmtl::WeakSince(subs, _, _)
+            | Pmtl::Trigger(subs, _, _) => subs.0.depth().max(subs.1.depth()) + 1,
         }
     }
 }
@@ -306,9 +476,18 @@ impl PmtlOracle {
             time: (0, 1),
             assumes,
             guarantees,
-            valuations: vec![NumSet::new(); subformulae.len()],
+            // Weak operators behave as if their condition held before the start of the trace.
+            valuations: subformulae
+                .iter()
+                .map(|f| match f {
+                    ArcPmtl::WeakSince(..) | ArcPmtl::WeakPrevious(_) => NumSet::full(),
+                    _ => NumSet::new(),
+                })
+                .collect(),
             outputs: vec![NumSet::new(); subformulae.len()],
             buf_valuations: vec![NumSet::new(); subformulae.len()],
+            occurrences: vec![VecDeque::new(); subformulae.len()],
+            permanent_occurrences: vec![0; subformulae.len()],
             subformulae,
         }
     }
@@ -317,6 +496,81 @@ impl PmtlOracle {
     fn formula_output(&self, formula: usize) -> bool {
         self.outputs[formula].contains(self.time)
     }
+
+    // Computes valuation and output of a Since formula over the interval up to `new_time`,
+    // given the outputs of its arguments and its previous valuation.
+    fn since(
+        &self,
+        nset_lhs: &NumSet,
+        nset_rhs: &NumSet,
+        mut valuation: NumSet,
+        lower_bound: Time,
+        upper_bound: Time,
+        new_time: DenseTime,
+    ) -> (NumSet, NumSet) {
+        let nset_rhs_orig = nset_rhs;
+        let mut nset_lhs = nset_lhs.clone();
+        let mut nset_rhs = nset_rhs.clone();
+        nset_rhs.insert_bound(new_time);
+        nset_lhs.insert_bound(new_time);
+        nset_rhs.sync(&nset_lhs);
+        nset_lhs.sync(nset_rhs_orig);
+        let mut partial_lower_bound = self.time;
+        let mut nset_output = NumSet::new();
+        for (idx, (partial_upper_bound, out_lhs)) in nset_lhs
+            .bounds()
+            .iter()
+            .enumerate()
+            .filter(|(_, (ub, _))| self.time < *ub)
+        {
+            // since nset_0 and nset_1 are synched:
+            assert_eq!(*partial_upper_bound, nset_rhs.bounds()[idx].0);
+            assert!(partial_lower_bound < *partial_upper_bound);
+            assert!(self.time < *partial_upper_bound);
+            let out_rhs = nset_rhs.bounds()[idx].1;
+            valuation = match (out_lhs, out_rhs) {
+                (true, true) => {
+                    let lower_bound = if lower_bound > 0 {
+                        lower_bound
+                            .checked_add(partial_lower_bound.0)
+                            .map(|ub| (ub, 0))
+                            .unwrap_or((Time::MAX, Time::MAX))
+                    } else {
+                        partial_lower_bound
+                    };
+                    let upper_bound = upper_bound
+                        .checked_add(partial_upper_bound.0)
+                        .map(|ub| (ub, Time::MAX))
+                        .unwrap_or((Time::MAX, Time::MAX));
+                    valuation.add_interval(lower_bound, upper_bound);
+                    valuation
+                }
+                (true, false) => valuation,
+                (false, true) => {
+                    let lower_bound = if lower_bound > 0 {
+                        lower_bound
+                            .checked_add(partial_upper_bound.0)
+                            .map(|ub| (ub, 0))
+                            .unwrap_or((Time::MAX, Time::MAX))
+                    } else {
+                        *partial_upper_bound
+                    };
+                    let upper_bound = upper_bound
+                        .checked_add(partial_upper_bound.0)
+                        .map(|ub| (ub, Time::MAX))
+                        .unwrap_or((Time::MAX, Time::MAX));
+                    NumSet::from_range(lower_bound, upper_bound)
+                }
+                (false, false) => NumSet::new(),
+            };
+            let mut to_add = valuation.clone();
+            to_add.cut(partial_lower_bound, *partial_upper_bound);
+            nset_output.union(&to_add);
+            partial_lower_bound = *partial_upper_bound;
+        }
+        valuation.cut(self.time, (Time::MAX, Time::MAX));
+        (valuation.simplify(), nset_output.simplify())
+    }
 }
 
 impl Oracle for PmtlOracle {
@@ -473,71 +727,100 @@ impl Oracle for PmtlOracle {
                     self.buf_valuations.push(valuation.simplify());
                     self.outputs.push(nset_output.simplify());
                 }
-                ArcPmtl::Since(sub_0, sub_1, lower_bound, upper_bound) => {
+                ArcPmtl::Since(sub_0, sub_1, lower_bound, upper_bound)
+                | ArcPmtl::WeakSince(sub_0, sub_1, lower_bound, upper_bound) => {
+                    let nset_lhs = self.outputs.get(sub_0.1).expect("nset");
+                    let nset_rhs = self.outputs.get(sub_1.1).expect("nset");
+                    let valuation = self.valuations.get(idx).expect("formula").clone();
+                    let (valuation, nset_output) = self.since(
+                        nset_lhs,
+                        nset_rhs,
+                        valuation,
+                        *lower_bound,
+                        *upper_bound,
+                        new_time,
+                    );
+                    self.buf_valuations.push(valuation);
+                    self.outputs.push(nset_output);
+                }
+                ArcPmtl::Trigger(sub_0, sub_1, lower_bound, upper_bound) => {
+                    // lhs T rhs is equivalent to !(!lhs S !rhs)
                     let mut nset_lhs = self.outputs.get(sub_0.1).expect("nset").clone();
-                    let nset_rhs_orig = self.outputs.get(sub_1.1).expect("nset");
-                    let mut nset_rhs = nset_rhs_orig.clone();
-                    nset_rhs.insert_bound(new_time);
-                    nset_lhs.insert_bound(new_time);
-                    nset_rhs.sync(&nset_lhs);
-                    nset_lhs.sync(nset_rhs_orig);
-                    let mut valuation = self.valuations.get(idx).expect("formula").clone();
-                    let mut partial_lower_bound = self.time;
-                    let mut nset_output = NumSet::new();
-                    for (idx, (partial_upper_bound, out_lhs)) in nset_lhs
-                        .bounds()
-                        .iter()
-                        .enumerate()
-                        .filter(|(_, (ub, _))| self.time < *ub)
+                    nset_lhs.complement();
+                    nset_lhs.cut(self.time, new_time);
+                    let mut nset_rhs = self.outputs.get(sub_1.1).expect("nset").clone();
+                    nset_rhs.complement();
+                    nset_rhs.cut(self.time, new_time);
+                    let valuation = self.valuations.get(idx).expect("formula").clone();
+                    let (valuation, mut nset_output) = self.since(
+                        &nset_lhs.simplify(),
+                        &nset_rhs.simplify(),
+                        valuation,
+                        *lower_bound,
+                        *upper_bound,
+                        new_time,
+                    );
+                    nset_output.complement();
+                    nset_output.cut(self.time, new_time);
+                    self.buf_valuations.push(valuation);
+                    self.outputs.push(nset_output.simplify());
+                }
+                ArcPmtl::Previous(sub) | ArcPmtl::WeakPrevious(sub) => {
+                    // The valuation records whether the subformula held at the previous step.
+                    let nset = if self
+                        .valuations
+                        .get(idx)
+                        .expect("formula")
+                        .contains(self.time)
                     {
-                        // since nset_0 and nset_1 are synched:
-                        assert_eq!(*partial_upper_bound, nset_rhs.bounds()[idx].0);
-                        assert!(partial_lower_bound < *partial_upper_bound);
-                        assert!(self.time < *partial_upper_bound);
-                        let out_rhs = nset_rhs.bounds()[idx].1;
-                        valuation = match (out_lhs, out_rhs) {
-                            (true, true) => {
-                                let lower_bound = if *lower_bound > 0 {
-                                    lower_bound
-                                        .checked_add(partial_lower_bound.0)
-                                        .map(|ub| (ub, 0))
-                                        .unwrap_or((Time::MAX, Time::MAX))
-                                } else {
-                                    partial_lower_bound
-                                };
-                                let upper_bound = upper_bound
-                                    .checked_add(partial_upper_bound.0)
-                                    .map(|ub| (ub, Time::MAX))
-                                    .unwrap_or((Time::MAX, Time::MAX));
-                                valuation.add_interval(lower_bound, upper_bound);
-                                valuation
-                            }
-                            (true, false) => valuation,
-                            (false, true) => {
-                                let lower_bound = if *lower_bound > 0 {
-                                    lower_bound
-                                        .checked_add(partial_upper_bound.0)
-                                        .map(|ub| (ub, 0))
-                                        .unwrap_or((Time::MAX, Time::MAX))
-                                } else {
-                                    *partial_upper_bound
-                                };
-                                let upper_bound = upper_bound
-                                    .checked_add(partial_upper_bound.0)
-                                    .map(|ub| (ub, Time::MAX))
-                                    .unwrap_or((Time::MAX, Time::MAX));
-                                NumSet::from_range(lower_bound, upper_bound)
-                            }
-                            (false, false) => NumSet::new(),
-                        };
-                        let mut to_add = valuation.clone();
-                        to_add.cut(partial_lower_bound, *partial_upper_bound);
-                        nset_output.union(&to_add);
-                        partial_lower_bound = *partial_upper_bound;
+                        NumSet::from_range(self.time, new_time)
+                    } else {
+                        NumSet::new()
+                    };
+                    if self.outputs.get(sub.1).expect("nset").contains(new_time) {
+                        self.buf_valuations.push(NumSet::full());
+                    } else {
+                        self.buf_valuations.push(NumSet::new());
                     }
-                    valuation.cut(self.time, (Time::MAX, Time::MAX));
-                    self.buf_valuations.push(valuation.simplify());
-                    self.outputs.push(nset_output.simplify());
+                    self.outputs.push(nset);
+                }
+                ArcPmtl::Count(sub, comparison, count, lower_bound, upper_bound) => {
+                    let occurrences = self.occurrences.get_mut(idx).expect("occurrences");
+                    if self.outputs.get(sub.1).expect("nset").contains(new_time) {
+                        occurrences.push_back((self.time.0, time));
+                    }
+                    // Occurrences ending before the window can never be counted again.
+                    while occurrences.front().is_some_and(|(_, end)| {
+                        end.checked_add(*upper_bound).is_some_and(|ub| ub < time)
+                    }) {
+                        occurrences.pop_front();
+                    }
+                    // Occurrences that entered the window and never leave it are only counted,
+                    // so that they are not stored when the window is unbounded.
+                    let permanent = self
+                        .permanent_occurrences
+                        .get_mut(idx)
+                        .expect("permanent occurrences");
+                    while occurrences.front().is_some_and(|(start, end)| {
+                        start.checked_add(*lower_bound).is_some_and(|lb| lb <= time)
+                            && end.saturating_add(*upper_bound) == Time::MAX
+                    }) {
+                        occurrences.pop_front();
+                        *permanent += 1;
+                    }
+                    // Occurrences are sorted by start time,
+                    // so the ones that entered the window form a prefix.
+                    let counted = *permanent
+                        + occurrences.partition_point(|(start, _)| {
+                            start.checked_add(*lower_bound).is_some_and(|lb| lb <= time)
+                        });
+                    let nset = if comparison.compare(counted, *count) {
+                        NumSet::from_range(self.time, new_time)
+                    } else {
+                        NumSet::new()
+                    };
+                    self.buf_valuations.push(NumSet::new());
+                    self.outputs.push(nset);
                 }
             }
         }
@@ -549,6 +832,7 @@ impl Oracle for PmtlOracle {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::RunOutcome;
 
     #[test]
     fn subformulae_1() {
@@ -717,4 +1001,235 @@ mod tests {
         state.update(&[true], 4);
         assert!(state.output_guarantees().is_none());
     }
+
+    #[test]
+    fn previous() {
+        let formula = Pmtl::Previous(Box::new(Pmtl::Atom(0)));
+        let mut state = PmtlOracle::new(&[], &[formula]);
+        state.update(&[true], 0);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[false], 1);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[true], 2);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[true], 2);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[false], 5);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[false], 6);
+        assert!(state.output_guarantees().is_some());
+    }
+
+    #[test]
+    fn weak_previous() {
+        let formula = Pmtl::WeakPrevious(Box::new(Pmtl::Atom(0)));
+        let mut state = PmtlOracle::new(&[], &[formula]);
+        state.update(&[false], 0);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[true], 1);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[false], 2);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[false], 2);
+        assert!(state.output_guarantees().is_some());
+    }
+
+    #[test]
+    fn previous_previous() {
+        let formula = Pmtl::Previous(Box::new(Pmtl::Previous(Box::new(Pmtl::Atom(0)))));
+        let mut state = PmtlOracle::new(&[], &[formula]);
+        state.update(&[true], 0);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[false], 1);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[false], 2);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[false], 3);
+        assert!(state.output_guarantees().is_some());
+    }
+
+    #[test]
+    fn trigger() {
+        let formula = Pmtl::Trigger(Box::new((Pmtl::Atom(0), Pmtl::Atom(1))), 0, Time::MAX);
+        let mut state = PmtlOracle::new(&[], &[formula]);
+        state.update(&[false, true], 0);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[true, true], 1);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[false, false], 2);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[false, true], 3);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[true, true], 4);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[false, true], 5);
+        assert!(state.output_guarantees().is_none());
+    }
+
+    #[test]
+    fn trigger_duality() {
+        let trigger = Pmtl::Trigger(Box::new((Pmtl::Atom(0), Pmtl::Atom(1))), 1, 2);
+        let since = Pmtl::Not(Box::new(Pmtl::Since(
+            Box::new((
+                Pmtl::Not(Box::new(Pmtl::Atom(0))),
+                Pmtl::Not(Box::new(Pmtl::Atom(1))),
+            )),
+            1,
+            2,
+        )));
+        let mut state = PmtlOracle::new(&[], &[trigger, since]);
+        for (atoms, time) in [
+            ([false, true], 0),
+            ([true, false], 0),
+            ([false, false], 1),
+            ([false, true], 2),
+            ([true, true], 3),
+            ([false, false], 3),
+            ([false, true], 4),
+            ([true, true], 6),
+            ([false, true], 7),
+        ] {
+            state.update(&atoms, time);
+            assert_eq!(
+                state.formula_output(state.guarantees[0]),
+                state.formula_output(state.guarantees[1])
+            );
+        }
+    }
+
+    #[test]
+    fn weak_since() {
+        let formula = Pmtl::WeakSince(Box::new((Pmtl::Atom(0), Pmtl::Atom(1))), 0, Time::MAX);
+        let mut state = PmtlOracle::new(&[], &[formula]);
+        state.update(&[true, false], 0);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[true, false], 1);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[false, false], 2);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[false, true], 3);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[true, false], 4);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[false, false], 5);
+        assert!(state.output_guarantees().is_some());
+    }
+
+    #[test]
+    fn count_at_most() {
+        let formula = Pmtl::Count(Box::new(Pmtl::Atom(0)), Comparison::LessEq, 2, 0, 10);
+        let mut state = PmtlOracle::new(&[], &[formula]);
+        state.update(&[true], 0);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[false], 1);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[true], 2);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[true], 5);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[false], 10);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[false], 11);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[true], 12);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[false], 16);
+        assert!(state.output_guarantees().is_none());
+    }
+
+    #[test]
+    fn count_consecutive_steps() {
+        let formula = Pmtl::Count(Box::new(Pmtl::Atom(0)), Comparison::Equal, 3, 0, Time::MAX);
+        let mut state = PmtlOracle::new(&[], &[formula]);
+        state.update(&[true], 0);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[true], 0);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[true], 0);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[false], 100);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[true], 200);
+        assert!(state.output_guarantees().is_some());
+    }
+
+    #[test]
+    fn count_unbounded_window() {
+        let formula = Pmtl::Count(
+            Box::new(Pmtl::Atom(0)),
+            Comparison::GreaterEq,
+            100,
+            10,
+            Time::MAX,
+        );
+        let mut state = PmtlOracle::new(&[], &[formula]);
+        for time in 0..200 {
+            state.update(&[true], time);
+            // The 100th step starts at time 98 (the first one is instantaneous at time 0).
+            assert_eq!(state.output_guarantees().is_none(), time >= 108);
+            // Only the occurrences yet to enter the window are stored.
+            assert!(
+                state
+                    .occurrences
+                    .iter()
+                    .all(|occurrences| occurrences.len() <= 10)
+            );
+        }
+    }
+
+    #[test]
+    fn count_lower_bound() {
+        let formula = Pmtl::Count(Box::new(Pmtl::Atom(0)), Comparison::GreaterEq, 1, 2, 3);
+        let mut state = PmtlOracle::new(&[], &[formula]);
+        state.update(&[false], 0);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[true], 1);
+        assert!(state.output_guarantees().is_some());
+        state.update(&[false], 2);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[false], 4);
+        assert!(state.output_guarantees().is_none());
+        state.update(&[false], 5);
+        assert!(state.output_guarantees().is_some());
+    }
+
+    #[test]
+    fn count_matches_once() {
+        let once = Pmtl::Once(Box::new(Pmtl::Atom(0)), 1, 2);
+        let count = Pmtl::Count(Box::new(Pmtl::Atom(0)), Comparison::GreaterEq, 1, 1, 2);
+        let mut state = PmtlOracle::new(&[], &[once, count]);
+        for (atom, time) in [
+            (false, 0),
+            (false, 0),
+            (true, 1),
+            (false, 2),
+            (false, 3),
+            (false, 3),
+            (true, 4),
+            (false, 7),
+        ] {
+            state.update(&[atom], time);
+            assert_eq!(
+                state.formula_output(state.guarantees[0]),
+                state.formula_output(state.guarantees[1])
+            );
+        }
+    }
+
+    #[test]
+    fn check_trace() {
+        let assume = Pmtl::Atom(1);
+        let guarantee = Pmtl::Historically(Box::new(Pmtl::Atom(0)), 0, Time::MAX);
+        let oracle = PmtlOracle::new(&[assume], &[guarantee]);
+        let outcome = oracle
+            .clone()
+            .check_trace([(vec![true, true], 0), (vec![true, true], 1)]);
+        assert!(matches!(outcome, RunOutcome::Success));
+        let outcome = oracle
+            .clone()
+            .check_trace([(vec![true, true], 0), (vec![false, true], 1)]);
+        assert!(matches!(outcome, RunOutcome::Fail(0)));
+        let outcome = oracle.check_trace([(vec![true, true], 0), (vec![false, false], 1)]);
+        assert!(matches!(outcome, RunOutcome::Incomplete));
+    }
 }
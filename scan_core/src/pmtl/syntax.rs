@@ -0,0 +1,445 @@
+// Textual syntax of PMTL formulae: a pretty-printer and a recursive-descent parser.
+//
+// Boolean connectives are written as for expressions,
+// with `=>` (right-associative) binding less tightly than `||`, then `&&`.
+// Binary temporal operators are infix and non-associative, e.g., `p since[0, 10] q`,
+// and bind less tightly than negation and unary temporal operators, e.g., `once[0, inf] p`.
+// Counting formulae are written as `count[0, 10](p) >= 2`.
+
+use super::*;
+use crate::ParseError;
+use crate::grammar::{Token, Tokens};
+use std::fmt::{self, Display, Formatter};
+
+// Operator precedences, from lowest to highest.
+const IMPLIES: u8 = 1;
+const OR: u8 = 2;
+const AND: u8 = 3;
+const BINARY: u8 = 4;
+const UNARY: u8 = 5;
+const ATOM: u8 = 6;
+
+impl<V: Clone> Pmtl<V> {
+    /// Parses a formula from its textual syntax,
+    /// using the given function to find the atomic formulae by name.
+    ///
+    /// ```
+    /// # use scan_core::*;
+    /// let atoms = |name: &str| name.starts_with('p').then(|| name.to_string());
+    /// let formula = Pmtl::parse("p0 => p1 since[0, 10] !p2", atoms).expect("valid syntax");
+    /// assert_eq!(formula.to_string(), "p0 => p1 since[0, 10] !p2");
+    /// assert!(Pmtl::parse("q0 => p1", atoms).is_err());
+    /// ```
+    pub fn parse(input: &str, atoms: impl Fn(&str) -> Option<V>) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            tokens: Tokens::new(input)?,
+            atoms,
+        };
+        let formula = parser.implies()?;
+        parser.tokens.end()?;
+        Ok(formula)
+    }
+
+    // Precedence of the outermost operator of the formula as printed.
+    fn precedence(&self) -> u8 {
+        match self {
+            Pmtl::Implies(_) => IMPLIES,
+            Pmtl::Or(subs) if subs.len() > 1 => OR,
+            Pmtl::And(subs) if subs.len() > 1 => AND,
+            Pmtl::Since(_, _, _) | Pmtl::WeakSince(_, _, _) | Pmtl::Trigger(_, _, _) => BINARY,
+            Pmtl::Not(_)
+            | Pmtl::Historically(_, _, _)
+            | Pmtl::Once(_, _, _)
+            | Pmtl::Previous(_)
+            | Pmtl::WeakPrevious(_) => UNARY,
+            _ => ATOM,
+        }
+    }
+}
+
+impl<V: Clone + Display> Display for Pmtl<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Pmtl::True => write!(f, "true"),
+            Pmtl::False => write!(f, "false"),
+            Pmtl::Atom(v) => write!(f, "{v}"),
+            Pmtl::And(subs) => write_nary(f, subs, "&&", "and", AND),
+            Pmtl::Or(subs) => write_nary(f, subs, "||", "or", OR),
+            Pmtl::Not(sub) => {
+                write!(f, "!")?;
+                write_sub(f, sub, UNARY)
+            }
+            Pmtl::Implies(subs) => {
+                write_sub(f, &subs.0, IMPLIES + 1)?;
+                write!(f, " => ")?;
+                write_sub(f, &subs.1, IMPLIES)
+            }
+            Pmtl::Historically(sub, lower_bound, upper_bound) => {
+                write_unary(f, sub, "historically", *lower_bound, *upper_bound)
+            }
+            Pmtl::Once(sub, lower_bound, upper_bound) => {
+                write_unary(f, sub, "once", *lower_bound, *upper_bound)
+            }
+            Pmtl::Since(subs, lower_bound, upper_bound) => {
+                write_binary(f, subs, "since", *lower_bound, *upper_bound)
+            }
+            Pmtl::WeakSince(subs, lower_bound, upper_bound) => {
+                write_binary(f, subs, "weak_since", *lower_bound, *upper_bound)
+            }
+            Pmtl::Trigger(subs, lower_bound, upper_bound) => {
+                write_binary(f, subs, "trigger", *lower_bound, *upper_bound)
+            }
+            Pmtl::Previous(sub) => {
+                write!(f, "previous ")?;
+                write_sub(f, sub, UNARY)
+            }
+            Pmtl::WeakPrevious(sub) => {
+                write!(f, "weak_previous ")?;
+                write_sub(f, sub, UNARY)
+            }
+            Pmtl::Count(sub, comparison, count, lower_bound, upper_bound) => {
+                write!(f, "count")?;
+                write_bounds(f, *lower_bound, *upper_bound)?;
+                write!(f, "({sub}) {comparison} {count}")
+            }
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            Comparison::Less => "<",
+            Comparison::LessEq => "<=",
+            Comparison::Equal => "==",
+            Comparison::GreaterEq => ">=",
+            Comparison::Greater => ">",
+        };
+        write!(f, "{op}")
+    }
+}
+
+// Writes the formula, in parentheses if it binds less tightly than the given precedence.
+fn write_sub<V: Clone + Display>(
+    f: &mut Formatter<'_>,
+    formula: &Pmtl<V>,
+    precedence: u8,
+) -> fmt::Result {
+    if formula.precedence() < precedence {
+        write!(f, "({formula})")
+    } else {
+        write!(f, "{formula}")
+    }
+}
+
+fn write_nary<V: Clone + Display>(
+    f: &mut Formatter<'_>,
+    subs: &[Pmtl<V>],
+    op: &str,
+    name: &str,
+    precedence: u8,
+) -> fmt::Result {
+    if subs.len() < 2 {
+        write!(f, "{name}(")?;
+    }
+    for (i, sub) in subs.iter().enumerate() {
+        if i > 0 {
+            write!(f, " {op} ")?;
+        }
+        write_sub(f, sub, precedence + 1)?;
+    }
+    if subs.len() < 2 {
+        write!(f, ")")?;
+    }
+    Ok(())
+}
+
+// The unbounded upper bound is written as `inf`.
+fn write_bounds(f: &mut Formatter<'_>, lower_bound: Time, upper_bound: Time) -> fmt::Result {
+    if upper_bound == Time::MAX {
+        write!(f, "[{lower_bound}, inf]")
+    } else {
+        write!(f, "[{lower_bound}, {upper_bound}]")
+    }
+}
+
+fn write_unary<V: Clone + Display>(
+    f: &mut Formatter<'_>,
+    sub: &Pmtl<V>,
+    op: &str,
+    lower_bound: Time,
+    upper_bound: Time,
+) -> fmt::Result {
+    write!(f, "{op}")?;
+    write_bounds(f, lower_bound, upper_bound)?;
+    write!(f, " ")?;
+    write_sub(f, sub, UNARY)
+}
+
+fn write_binary<V: Clone + Display>(
+    f: &mut Formatter<'_>,
+    subs: &(Pmtl<V>, Pmtl<V>),
+    op: &str,
+    lower_bound: Time,
+    upper_bound: Time,
+) -> fmt::Result {
+    write_sub(f, &subs.0, BINARY + 1)?;
+    write!(f, " {op}")?;
+    write_bounds(f, lower_bound, upper_bound)?;
+    write!(f, " ")?;
+    write_sub(f, &subs.1, BINARY + 1)
+}
+
+struct Parser<F> {
+    tokens: Tokens,
+    atoms: F,
+}
+
+impl<V, F> Parser<F>
+where
+    V: Clone,
+    F: Fn(&str) -> Option<V>,
+{
+    fn implies(&mut self) -> Result<Pmtl<V>, ParseError> {
+        let lhs = self.or()?;
+        if self.tokens.eat("=>") {
+            let rhs = self.implies()?;
+            Ok(Pmtl::Implies(Box::new((lhs, rhs))))
+        } else {
+            Ok(lhs)
+        }
+    }
+
+    fn or(&mut self) -> Result<Pmtl<V>, ParseError> {
+        let first = self.and()?;
+        if self.tokens.is("||") {
+            let mut subs = vec![first];
+            while self.tokens.eat("||") {
+                subs.push(self.and()?);
+            }
+            Ok(Pmtl::Or(subs))
+        } else {
+            Ok(first)
+        }
+    }
+
+    fn and(&mut self) -> Result<Pmtl<V>, ParseError> {
+        let first = self.binary()?;
+        if self.tokens.is("&&") {
+            let mut subs = vec![first];
+            while self.tokens.eat("&&") {
+                subs.push(self.binary()?);
+            }
+            Ok(Pmtl::And(subs))
+        } else {
+            Ok(first)
+        }
+    }
+
+    fn binary(&mut self) -> Result<Pmtl<V>, ParseError> {
+        let lhs = self.unary()?;
+        let binary: fn(_, _, _) -> _ = match self.tokens.peek() {
+            Some(Token::Ident(op)) if op == "since" => Pmtl::Since,
+            Some(Token::Ident(op)) if op == "weak_since" => Pmtl::WeakSince,
+            Some(Token::Ident(op)) if op == "trigger" => Pmtl::Trigger,
+            _ => return Ok(lhs),
+        };
+        self.tokens.next()?;
+        let (lower_bound, upper_bound) = self.bounds()?;
+        let rhs = self.unary()?;
+        Ok(binary(Box::new((lhs, rhs)), lower_bound, upper_bound))
+    }
+
+    fn unary(&mut self) -> Result<Pmtl<V>, ParseError> {
+        if self.tokens.eat("!") {
+            return Ok(Pmtl::Not(Box::new(self.unary()?)));
+        }
+        let Some(Token::Ident(op)) = self.tokens.peek() else {
+            return self.atom();
+        };
+        match op.as_str() {
+            "historically" | "once" => {
+                let unary = if op == "once" {
+                    Pmtl::Once
+                } else {
+                    Pmtl::Historically
+                };
+                self.tokens.next()?;
+                let (lower_bound, upper_bound) = self.bounds()?;
+                Ok(unary(Box::new(self.unary()?), lower_bound, upper_bound))
+            }
+            "previous" => {
+                self.tokens.next()?;
+                Ok(Pmtl::Previous(Box::new(self.unary()?)))
+            }
+            "weak_previous" => {
+                self.tokens.next()?;
+                Ok(Pmtl::WeakPrevious(Box::new(self.unary()?)))
+            }
+            _ => self.atom(),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Pmtl<V>, ParseError> {
+        match self.tokens.next()? {
+            Token::Punct("(") => {
+                let formula = self.implies()?;
+                self.tokens.expect(")")?;
+                Ok(formula)
+            }
+            Token::Ident(ident) => match ident.as_str() {
+                "true" => Ok(Pmtl::True),
+                "false" => Ok(Pmtl::False),
+                "and" | "or" if self.tokens.eat("(") => {
+                    let mut subs = Vec::new();
+                    while !self.tokens.eat(")") {
+                        subs.push(self.implies()?);
+                        if !self.tokens.eat(",") {
+                            self.tokens.expect(")")?;
+                            break;
+                        }
+                    }
+                    if ident == "and" {
+                        Ok(Pmtl::And(subs))
+                    } else {
+                        Ok(Pmtl::Or(subs))
+                    }
+                }
+                "count" if self.tokens.is("[") => {
+                    let (lower_bound, upper_bound) = self.bounds()?;
+                    self.tokens.expect("(")?;
+                    let sub = self.implies()?;
+                    self.tokens.expect(")")?;
+                    let comparison = match self.tokens.next()? {
+                        Token::Punct("<") => Comparison::Less,
+                        Token::Punct("<=") => Comparison::LessEq,
+                        Token::Punct("==") => Comparison::Equal,
+                        Token::Punct(">=") => Comparison::GreaterEq,
+                        Token::Punct(">") => Comparison::Greater,
+                        _ => return Err(self.tokens.unexpected()),
+                    };
+                    let count = self.tokens.natural()?;
+                    Ok(Pmtl::Count(
+                        Box::new(sub),
+                        comparison,
+                        count,
+                        lower_bound,
+                        upper_bound,
+                    ))
+                }
+                _ => (self.atoms)(&ident)
+                    .map(Pmtl::Atom)
+                    .ok_or(ParseError::UnknownVar(ident)),
+            },
+            _ => Err(self.tokens.unexpected()),
+        }
+    }
+
+    fn bounds(&mut self) -> Result<(Time, Time), ParseError> {
+        self.tokens.expect("[")?;
+        let lower_bound = self.tokens.natural()?;
+        self.tokens.expect(",")?;
+        let upper_bound = if matches!(self.tokens.peek(), Some(Token::Ident(inf)) if inf == "inf") {
+            self.tokens.next()?;
+            Time::MAX
+        } else {
+            self.tokens.natural()?
+        };
+        self.tokens.expect("]")?;
+        Ok((lower_bound, upper_bound))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atoms(name: &str) -> Option<String> {
+        name.starts_with('p').then(|| name.to_string())
+    }
+
+    #[test]
+    fn round_trips() {
+        let p = |i: usize| Pmtl::Atom(format!("p{i}"));
+        let formulae = vec![
+            Pmtl::True,
+            Pmtl::And(vec![p(0)]),
+            Pmtl::Or(Vec::new()),
+            Pmtl::Implies(Box::new((
+                Pmtl::Implies(Box::new((p(0), p(1)))),
+                Pmtl::Implies(Box::new((p(2), Pmtl::False))),
+            ))),
+            Pmtl::Or(vec![
+                Pmtl::And(vec![p(0), p(1)]),
+                Pmtl::Or(vec![p(2), p(3)]),
+            ]),
+            Pmtl::Not(Box::new(Pmtl::Once(Box::new(p(0)), 0, Time::MAX))),
+            Pmtl::Historically(Box::new(Pmtl::And(vec![p(0), p(1)])), 2, 5),
+            Pmtl::Since(
+                Box::new((
+                    Pmtl::Since(Box::new((p(0), p(1))), 0, 3),
+                    Pmtl::Previous(Box::new(p(2))),
+                )),
+                1,
+                Time::MAX,
+            ),
+            Pmtl::Trigger(
+                Box::new((
+                    Pmtl::WeakPrevious(Box::new(p(0))),
+                    Pmtl::Not(Box::new(p(1))),
+                )),
+                0,
+                0,
+            ),
+            Pmtl::And(vec![
+                Pmtl::WeakSince(Box::new((p(0), p(1))), 0, 10),
+                Pmtl::Count(
+                    Box::new(Pmtl::Or(vec![p(0), p(1)])),
+                    Comparison::Less,
+                    3,
+                    0,
+                    10,
+                ),
+            ]),
+            Pmtl::Not(Box::new(Pmtl::Count(
+                Box::new(p(1)),
+                Comparison::GreaterEq,
+                1,
+                2,
+                Time::MAX,
+            ))),
+        ];
+        for formula in formulae {
+            let text = formula.to_string();
+            let parsed = Pmtl::parse(&text, atoms).unwrap_or_else(|err| panic!("{text}: {err}"));
+            assert_eq!(parsed, formula, "{text}");
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Pmtl::parse("historically[0, inf] (p0 => once[0, 5] p1)", atoms).expect("valid syntax"),
+            Pmtl::Historically(
+                Box::new(Pmtl::Implies(Box::new((
+                    Pmtl::Atom(String::from("p0")),
+                    Pmtl::Once(Box::new(Pmtl::Atom(String::from("p1"))), 0, 5)
+                )))),
+                0,
+                Time::MAX
+            )
+        );
+        assert!(matches!(
+            Pmtl::parse("p0 && q0", atoms),
+            Err(ParseError::UnknownVar(_))
+        ));
+        assert!(matches!(
+            Pmtl::parse("p0 since[0, 1] p1 since[0, 1] p2", atoms),
+            Err(ParseError::UnexpectedToken(_, _))
+        ));
+        assert!(matches!(
+            Pmtl::parse("once[0] p0", atoms),
+            Err(ParseError::UnexpectedToken(_, _))
+        ));
+    }
+}
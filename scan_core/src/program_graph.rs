@@ -15,6 +15,9 @@
 //! Finally, the effects of the transition's associated action are applied in order,
 //! by assigning each effect's variable the value of the effect's expression evaluation.
 //!
+//! A _probabilistic_ transition has instead several branches, each with its own post-location and effects,
+//! and one of them is chosen at random according to their weights (see [`ProgramGraphBuilder::add_probabilistic_transition`]).
+//!
 //! A PG is represented by a [`ProgramGraph`] and defined through a [`ProgramGraphBuilder`],
 //! by adding, one at a time, new locations, actions, effects, guards and transitions.
 //! Then, the [`ProgramGraph`] is built from the [`ProgramGraphBuilder`]
@@ -76,13 +79,22 @@
 //! pg.transition(action, &[post_loc], &mut rng).expect_err("The transition is not possible");
 //! ```
 
+mod analysis;
 mod builder;
+mod compression;
+mod dot;
+mod symbols;
 
 use crate::{DummyRng, Time, grammar::*};
+pub use analysis::PgDiagnostic;
 pub use builder::*;
+pub(crate) use dot::{escape, symbol_name};
+use log::{debug, trace};
 use rand::{Rng, SeedableRng, rngs::SmallRng, seq::IteratorRandom};
 use smallvec::SmallVec;
 use std::{collections::BTreeSet, sync::Arc};
+pub(crate) use symbols::describe;
+pub use symbols::{Named, Symbol, SymbolTable};
 use thiserror::Error;
 
 /// An indexing object for locations in a PG.
@@ -120,24 +132,47 @@ pub struct Clock(u16);
 /// A time constraint given by a clock and, optionally, a lower bound and/or an upper bound.
 pub type TimeConstraint = (Clock, Option<Time>, Option<Time>);
 
+/// The priority of a transition.
+///
+/// Only the enabled transitions with the highest priority can be chosen for execution,
+/// so that higher-priority transitions take precedence over lower-priority ones.
+/// By default, transitions have priority `0`, the lowest.
+pub type Priority = u16;
+
+/// The urgency of a location, restricting how the execution can proceed while in it.
+///
+/// Urgent and committed locations are meant to model atomic sequences of transitions,
+/// as, e.g., in UPPAAL timed automata.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum Urgency {
+    /// Time can pass and other PGs can move freely.
+    #[default]
+    Normal,
+    /// Time cannot pass while in the location.
+    Urgent,
+    /// Time cannot pass while in the location,
+    /// and the next transition of a system has to be taken by a PG in a committed location.
+    Committed,
+}
+
 /// An expression using PG's [`Var`] as variables.
 pub type PgExpression = Expression<Var>;
 
 /// The error type for operations with [`ProgramGraphBuilder`]s and [`ProgramGraph`]s.
-#[derive(Debug, Clone, Copy, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum PgError {
     /// There is no such action in the PG.
-    #[error("action {0:?} does not belong to this program graph")]
-    MissingAction(Action),
+    #[error("action {0} does not belong to this program graph")]
+    MissingAction(Named<Action>),
     /// There is no such clock in the PG.
-    #[error("clock {0:?} does not belong to this program graph")]
-    MissingClock(Clock),
+    #[error("clock {0} does not belong to this program graph")]
+    MissingClock(Named<Clock>),
     /// There is no such location in the PG.
-    #[error("location {0:?} does not belong to this program graph")]
-    MissingLocation(Location),
+    #[error("location {0} does not belong to this program graph")]
+    MissingLocation(Named<Location>),
     /// There is no such variable in the PG.
-    #[error("location {0:?} does not belong to this program graph")]
-    MissingVar(Var),
+    #[error("variable {0} does not belong to this program graph")]
+    MissingVar(Named<Var>),
     /// The PG does not allow this transition.
     #[error("there is no such transition")]
     MissingTransition,
@@ -158,17 +193,17 @@ pub enum PgError {
     #[error("cannot add effects to a Send action")]
     EffectOnSend,
     /// This action is a communication (either Send or Receive).
-    #[error("{0:?} is a communication (either Send or Receive)")]
-    Communication(Action),
+    #[error("action {0} is a communication (either Send or Receive)")]
+    Communication(Named<Action>),
     /// Mismatching (i.e., wrong number) post states of transition.
     #[error("Mismatching (i.e., wrong number) post states of transition")]
     MismatchingPostStates,
     /// The action is a not a Send communication.
-    #[error("{0:?} is a not a Send communication")]
-    NotSend(Action),
+    #[error("action {0} is a not a Send communication")]
+    NotSend(Named<Action>),
     /// The action is a not a Receive communication.
-    #[error("{0:?} is a not a Receive communication")]
-    NotReceive(Action),
+    #[error("action {0} is a not a Receive communication")]
+    NotReceive(Named<Action>),
     /// The epsilon action has no effects.
     #[error("The epsilon action has no effects")]
     NoEffects,
@@ -178,6 +213,33 @@ pub enum PgError {
     /// A type error
     #[error("type error")]
     Type(#[source] TypeError),
+    /// The evaluation of an expression failed.
+    #[error("evaluation error")]
+    Eval(#[source] EvalError),
+    /// A sized integer value overflows its type.
+    #[error("integer overflow")]
+    Overflow,
+    /// A value out of the bounds of a bounded integer variable is assigned to it.
+    #[error(
+        "value {val:?} is out of the bounds {bounds} of variable {}",
+        describe(.symbol.as_deref(), .var)
+    )]
+    OutOfBounds {
+        /// The bounded variable.
+        var: Var,
+        /// The symbol of the bounded variable, if any.
+        symbol: Option<Box<Symbol>>,
+        /// The value assigned to the variable.
+        val: Val,
+        /// The bounds of the variable.
+        bounds: Bounds,
+    },
+    /// The weights of the branches of a probabilistic transition are negative or all zero.
+    #[error("the weights of the probabilistic branches are negative or all zero")]
+    InvalidWeights,
+    /// Time cannot pass in an urgent or committed location.
+    #[error("time cannot pass in an urgent or committed location")]
+    Urgent,
 }
 
 #[derive(Debug)]
@@ -190,14 +252,68 @@ enum FnEffect<R: Rng> {
 
 type Guard = FnExpression<Var, DummyRng>;
 
-type Transition = (Action, Location, Option<Guard>, Vec<TimeConstraint>);
+type Transition = (
+    Action,
+    Location,
+    Option<Guard>,
+    Vec<TimeConstraint>,
+    Priority,
+);
+
+// Weight, post-location and effects of a branch of a probabilistic transition.
+type Branch<R> = (Guard, Location, Vec<(Var, FnExpression<Var, R>)>);
+
+// How time affects the state while in a location.
+#[derive(Default)]
+struct Flow {
+    // Rates of continuous variables, overriding the default ones
+    rates: Vec<(Var, Float)>,
+    // Clocks that do not advance
+    stopped: Vec<Clock>,
+    // Invariant restricting the passing of time
+    invariant: Option<Guard>,
+}
 
 struct ProgramGraphDef<R: Rng> {
+    overflow: Overflow,
+    bounds: Vec<Option<Bounds>>,
     effects: Vec<FnEffect<R>>,
     locations: Vec<(Vec<Transition>, Vec<TimeConstraint>, BTreeSet<Action>)>,
+    // Branches of the branching locations of probabilistic transitions, indexed by locations
+    // (empty for ordinary locations)
+    branches: Vec<Vec<Branch<R>>>,
+    // Whether any transition has non-default priority
+    prioritized: bool,
+    // Urgency of the locations
+    urgency: Vec<Urgency>,
+    // Whether any location has non-default urgency
+    urgent: bool,
+    // Continuous variables with their default rate
+    continuous: Vec<(Var, Float)>,
+    // Flows of the locations
+    // (empty if no location sets rates, stops clocks or has invariants)
+    flows: Vec<Flow>,
+    // Whether any location stops some clock
+    stopwatches: bool,
+    // The builder defining the PG
+    source: ProgramGraphBuilder,
 }
 
 impl<R: Rng> ProgramGraphDef<R> {
+    // Checks the value to be assigned to the variable is within its bounds, if any.
+    #[inline(always)]
+    fn check_bounds(&self, var: Var, val: Val) -> Result<Val, PgError> {
+        match self.bounds[var.0 as usize] {
+            Some(bounds) if !bounds.contains_val(&val) => Err(PgError::OutOfBounds {
+                var,
+                symbol: self.source.symbols.var(var).cloned().map(Box::new),
+                val,
+                bounds,
+            }),
+            _ => Ok(val),
+        }
+    }
+
     // Returns transition's guard.
     // Panics if the pre- or post-state do not exist.
     // Returns error if the transition does not exist.
@@ -213,7 +329,7 @@ impl<R: Rng> ProgramGraphDef<R> {
         transitions[part..]
             .iter()
             .take_while(move |(a, p, ..)| (*a, *p) == (action, post_state))
-            .map(|(_, _, g, c)| (g, c))
+            .map(|(_, _, g, c, _)| (g, c))
     }
 }
 
@@ -260,6 +376,11 @@ impl<R: Rng> ProgramGraph<R> {
         &self.current_states
     }
 
+    /// The [`Symbol`]s of the PG's objects, as defined by its builder.
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.def.source.symbols
+    }
+
     #[inline(always)]
     fn update_buf(&mut self) {
         if self.current_states.len() > 1 {
@@ -276,6 +397,10 @@ impl<R: Rng> ProgramGraph<R> {
     /// An admittable transition is characterized by the required action and the post-state
     /// (the pre-state being necessarily the current state of the machine).
     /// The guard (if any) is guaranteed to be satisfied.
+    ///
+    /// Only the admittable transitions with the highest [`Priority`] are returned.
+    /// The priority of a transition synchronising multiple processes
+    /// is the lowest priority among those of the processes' transitions.
     pub fn possible_transitions(
         &self,
     ) -> impl Iterator<
@@ -284,44 +409,110 @@ impl<R: Rng> ProgramGraph<R> {
             impl Iterator<Item = impl Iterator<Item = Location> + use<'_, R>> + use<'_, R>,
         ),
     > + use<'_, R> {
+        let level = if self.def.prioritized {
+            self.priority_level(|_| true).unwrap_or_default()
+        } else {
+            0
+        };
+        self.possible_transitions_at(level)
+    }
+
+    // Iterates over all admittable transitions with priority at least `level`.
+    pub(crate) fn possible_transitions_at(
+        &self,
+        level: Priority,
+    ) -> impl Iterator<
+        Item = (
+            Action,
+            impl Iterator<Item = impl Iterator<Item = Location> + use<'_, R>> + use<'_, R>,
+        ),
+    > + use<'_, R> {
+        self.actions()
+            .map(move |action| (action, self.possible_transitions_action(action, level)))
+    }
+
+    // Whether any transition has non-default priority.
+    #[inline(always)]
+    pub(crate) fn is_prioritized(&self) -> bool {
+        self.def.prioritized
+    }
+
+    // The highest priority among the admittable transitions whose action satisfies the predicate, if any.
+    pub(crate) fn priority_level(&self, predicate: impl Fn(Action) -> bool) -> Option<Priority> {
+        if self.current_states.is_empty() {
+            return None;
+        }
+        self.actions()
+            .filter(|action| predicate(*action))
+            .filter_map(|action| {
+                self.current_states
+                    .iter()
+                    .try_fold(Priority::MAX, |level, current_state| {
+                        self.admittable_transitions(action, *current_state)
+                            .map(|(_, priority)| priority)
+                            .max()
+                            .map(|priority| level.min(priority))
+                    })
+            })
+            .max()
+    }
+
+    #[inline(always)]
+    fn actions(&self) -> impl Iterator<Item = Action> + use<'_, R> {
         if self.current_states.len() == 1 {
             &self.def.locations[self.current_states[0].0 as usize].2
         } else {
             &self.buf
         }
         .iter()
-        .map(|action| (*action, self.possible_transitions_action(*action)))
+        .copied()
     }
 
     #[inline(always)]
     fn possible_transitions_action(
         &self,
         action: Action,
+        level: Priority,
     ) -> impl Iterator<Item = impl Iterator<Item = Location> + use<'_, R>> + use<'_, R> {
         self.current_states
             .iter()
-            .map(move |loc| self.possible_transitions_action_loc(action, *loc))
+            .map(move |loc| self.possible_transitions_action_loc(action, *loc, level))
     }
 
     fn possible_transitions_action_loc(
         &self,
         action: Action,
         current_state: Location,
+        level: Priority,
     ) -> impl Iterator<Item = Location> + use<'_, R> {
+        let mut last_post_state: Option<Location> = None;
+        self.admittable_transitions(action, current_state)
+            .filter_map(move |(post_state, priority)| {
+                // post_states could be duplicated waistfully
+                if priority < level || last_post_state.is_some_and(|s| s == post_state) {
+                    None
+                } else {
+                    last_post_state = Some(post_state);
+                    last_post_state
+                }
+            })
+    }
+
+    // Iterates over post-states and priorities of the admittable transitions with the given action.
+    fn admittable_transitions(
+        &self,
+        action: Action,
+        current_state: Location,
+    ) -> impl Iterator<Item = (Location, Priority)> + use<'_, R> {
         let ppoint = self.def.locations[current_state.0 as usize]
             .0
             .partition_point(|(a, ..)| *a < action);
-        let mut last_post_state: Option<Location> = None;
         self.def.locations[current_state.0 as usize].0[ppoint..]
             .iter()
             .take_while(move |(a, ..)| *a == action)
-            .filter_map(move |(_, post_state, guard, constraints)| {
-                // post_states could be duplicated waistfully
-                if last_post_state.is_some_and(|s| s == *post_state) {
-                    return None;
-                }
+            .filter_map(move |(_, post_state, guard, constraints, priority)| {
                 let (_, ref invariants, _) = self.def.locations[post_state.0 as usize];
-                if if action == EPSILON {
+                if action == EPSILON {
                     self.active_autonomous_transition(guard.as_ref(), constraints, invariants)
                 } else {
                     match self.def.effects[action.0 as usize] {
@@ -331,42 +522,48 @@ impl<R: Rng> ProgramGraph<R> {
                         FnEffect::Send(_) | FnEffect::Receive(_) => self
                             .active_autonomous_transition(guard.as_ref(), constraints, invariants),
                     }
-                } {
-                    last_post_state = Some(*post_state);
-                    last_post_state
-                } else {
-                    None
                 }
+                // A guard whose evaluation fails does not disable the transition,
+                // so that taking it reports the error.
+                .unwrap_or(true)
+                .then_some((*post_state, *priority))
             })
     }
 
+    // Evaluates the guard, if any.
+    fn check_guard(&self, guard: Option<&Guard>) -> Result<bool, PgError> {
+        guard.map_or(Ok(true), |guard| {
+            // TODO FIXME: is there a way to avoid creating a dummy RNG?
+            match guard
+                .eval(&|var| &self.vars[var.0 as usize], &mut DummyRng)
+                .map_err(PgError::Eval)?
+            {
+                Val::Boolean(pass) => Ok(pass),
+                _ => panic!("guard is not a boolean"),
+            }
+        })
+    }
+
     fn active_transition(
         &self,
         guard: Option<&Guard>,
         constraints: &[TimeConstraint],
         invariants: &[TimeConstraint],
         resets: &[Clock],
-    ) -> bool {
-        guard.is_none_or(|guard| {
-            // TODO FIXME: is there a way to avoid creating a dummy RNG?
-            if let Val::Boolean(pass) =
-                guard.eval(&|var| self.vars[var.0 as usize].clone(), &mut DummyRng)
-            {
-                pass
-            } else {
-                panic!("guard is not a boolean");
-            }
-        }) && constraints.iter().all(|(c, l, u)| {
-            let time = self.clocks[c.0 as usize];
-            l.is_none_or(|l| l <= time) && u.is_none_or(|u| time < u)
-        }) && invariants.iter().all(|(c, l, u)| {
-            let time = if resets.binary_search(c).is_ok() {
-                0
-            } else {
-                self.clocks[c.0 as usize]
-            };
-            l.is_none_or(|l| l <= time) && u.is_none_or(|u| time < u)
-        })
+    ) -> Result<bool, PgError> {
+        Ok(self.check_guard(guard)?
+            && constraints.iter().all(|(c, l, u)| {
+                let time = self.clocks[c.0 as usize];
+                l.is_none_or(|l| l <= time) && u.is_none_or(|u| time < u)
+            })
+            && invariants.iter().all(|(c, l, u)| {
+                let time = if resets.binary_search(c).is_ok() {
+                    0
+                } else {
+                    self.clocks[c.0 as usize]
+                };
+                l.is_none_or(|l| l <= time) && u.is_none_or(|u| time < u)
+            }))
     }
 
     fn active_autonomous_transition(
@@ -374,20 +571,12 @@ impl<R: Rng> ProgramGraph<R> {
         guard: Option<&Guard>,
         constraints: &[TimeConstraint],
         invariants: &[TimeConstraint],
-    ) -> bool {
-        guard.is_none_or(|guard| {
-            // TODO FIXME: is there a way to avoid creating a dummy RNG?
-            if let Val::Boolean(pass) =
-                guard.eval(&|var| self.vars[var.0 as usize].clone(), &mut DummyRng)
-            {
-                pass
-            } else {
-                panic!("guard is not a boolean");
-            }
-        }) && constraints.iter().chain(invariants).all(|(c, l, u)| {
-            let time = self.clocks[c.0 as usize];
-            l.is_none_or(|l| l <= time) && u.is_none_or(|u| time < u)
-        })
+    ) -> Result<bool, PgError> {
+        Ok(self.check_guard(guard)?
+            && constraints.iter().chain(invariants).all(|(c, l, u)| {
+                let time = self.clocks[c.0 as usize];
+                l.is_none_or(|l| l <= time) && u.is_none_or(|u| time < u)
+            }))
     }
 
     fn active_transitions(
@@ -395,43 +584,50 @@ impl<R: Rng> ProgramGraph<R> {
         action: Action,
         post_states: &[Location],
         resets: &[Clock],
-    ) -> bool {
-        self.current_states
-            .iter()
-            .zip(post_states)
-            .all(|(current_state, post_state)| {
-                self.def
-                    .guards(*current_state, action, *post_state)
-                    .any(|(guard, constraints)| {
-                        self.active_transition(
-                            guard.as_ref(),
-                            constraints,
-                            &self.def.locations[post_state.0 as usize].1,
-                            resets,
-                        )
-                    })
-            })
+    ) -> Result<bool, PgError> {
+        for (current_state, post_state) in self.current_states.iter().zip(post_states) {
+            let invariants = &self.def.locations[post_state.0 as usize].1;
+            if !self
+                .def
+                .guards(*current_state, action, *post_state)
+                .map(|(guard, constraints)| {
+                    self.active_transition(guard.as_ref(), constraints, invariants, resets)
+                })
+                .find(|active| !matches!(active, Ok(false)))
+                .transpose()?
+                .unwrap_or(false)
+            {
+                return Ok(false);
+            }
+        }
+        Ok(true)
     }
 
-    fn active_autonomous_transitions(&self, post_states: &[Location]) -> bool {
-        self.current_states
-            .iter()
-            .zip(post_states)
-            .all(|(current_state, post_state)| {
-                self.def
-                    .guards(*current_state, EPSILON, *post_state)
-                    .any(|(guard, constraints)| {
-                        self.active_autonomous_transition(
-                            guard.as_ref(),
-                            constraints,
-                            &self.def.locations[post_state.0 as usize].1,
-                        )
-                    })
-            })
+    fn active_autonomous_transitions(&self, post_states: &[Location]) -> Result<bool, PgError> {
+        for (current_state, post_state) in self.current_states.iter().zip(post_states) {
+            let invariants = &self.def.locations[post_state.0 as usize].1;
+            if !self
+                .def
+                .guards(*current_state, EPSILON, *post_state)
+                .map(|(guard, constraints)| {
+                    self.active_autonomous_transition(guard.as_ref(), constraints, invariants)
+                })
+                .find(|active| !matches!(active, Ok(false)))
+                .transpose()?
+                .unwrap_or(false)
+            {
+                return Ok(false);
+            }
+        }
+        Ok(true)
     }
 
     /// Executes a transition characterized by the argument action and post-state.
     ///
+    /// If a post-state is the branching location of a probabilistic transition,
+    /// one of its branches is chosen at random according to the branches' weights,
+    /// and the branch's post-location becomes the current state after applying the branch's effects.
+    ///
     /// Fails if the requested transition is not admissible,
     /// or if the post-location time invariants are violated.
     pub fn transition(
@@ -447,59 +643,261 @@ impl<R: Rng> ProgramGraph<R> {
             .iter()
             .find(|ps| ps.0 >= self.def.locations.len() as u16)
         {
-            return Err(PgError::MissingLocation(*ps));
+            return Err(PgError::MissingLocation(self.symbols().named_location(*ps)));
         }
         if action == EPSILON {
-            if !self.active_autonomous_transitions(post_states) {
+            if !self.active_autonomous_transitions(post_states)? {
                 return Err(PgError::UnsatisfiedGuard);
             }
+            let branches = self.choose_branches(post_states, rng)?;
+            self.enter(post_states, &branches, rng)?;
         } else if action.0 >= self.def.effects.len() as u16 {
-            return Err(PgError::MissingAction(action));
+            return Err(PgError::MissingAction(self.symbols().named_action(action)));
         } else if let FnEffect::Effects(ref effects, ref resets) =
             self.def.effects[action.0 as usize]
         {
-            if self.active_transitions(action, post_states, resets) {
-                effects.iter().for_each(|(var, effect)| {
-                    self.vars[var.0 as usize] =
-                        effect.eval(&|var| self.vars[var.0 as usize].clone(), rng)
-                });
+            if self.active_transitions(action, post_states, resets)? {
+                let branches = self.choose_branches(post_states, rng)?;
+                for (var, effect) in effects {
+                    let val = effect
+                        .eval(&|var| &self.vars[var.0 as usize], rng)
+                        .map_err(PgError::Eval)?
+                        .fit(self.def.overflow)
+                        .ok_or(PgError::Overflow)?;
+                    self.vars[var.0 as usize] = self.def.check_bounds(*var, val)?;
+                }
                 resets
                     .iter()
                     .for_each(|clock| self.clocks[clock.0 as usize] = 0);
+                self.enter(post_states, &branches, rng)?;
             } else {
                 return Err(PgError::UnsatisfiedGuard);
             }
         } else {
-            return Err(PgError::Communication(action));
+            return Err(PgError::Communication(self.symbols().named_action(action)));
         }
-        self.current_states.copy_from_slice(post_states);
         self.update_buf();
         Ok(())
     }
 
-    /// Checks if it is possible to wait a given amount of time-units without violating the time invariants.
+    // Chooses at random, according to their weights,
+    // a branch for each post-state that is the branching location of a probabilistic transition.
+    // Weights are evaluated in the pre-transition state, as guards are.
+    fn choose_branches(
+        &self,
+        post_states: &[Location],
+        rng: &mut R,
+    ) -> Result<SmallVec<[Option<usize>; 8]>, PgError> {
+        post_states
+            .iter()
+            .map(|post_state| {
+                let branches = &self.def.branches[post_state.0 as usize];
+                if branches.len() <= 1 {
+                    // No need to evaluate the weight of a single branch
+                    return Ok((!branches.is_empty()).then_some(0));
+                }
+                let weights = branches
+                    .iter()
+                    .map(|(weight, ..)| match self.eval(weight)? {
+                        Val::Integer(weight) => Ok(weight as f64),
+                        Val::Int(_, weight) => Ok(weight as f64),
+                        Val::Float(weight) => Ok(weight),
+                        _ => panic!("weight is not numeric"),
+                    })
+                    .collect::<Result<SmallVec<[f64; 4]>, PgError>>()?;
+                let total: f64 = weights.iter().sum();
+                if weights
+                    .iter()
+                    .any(|weight| weight.is_sign_negative() || weight.is_nan())
+                    || !(total > 0. && total.is_finite())
+                {
+                    return Err(PgError::InvalidWeights);
+                }
+                let mut pick = rng.random_range(0. ..total);
+                // Rounding errors could make the pick fall beyond the last branch
+                Ok(weights
+                    .iter()
+                    .position(|weight| {
+                        pick -= weight;
+                        pick < 0.
+                    })
+                    .or_else(|| weights.iter().rposition(|weight| *weight > 0.)))
+            })
+            .collect()
+    }
+
+    // Moves to the post-states,
+    // resolving branching locations into the post-location of the chosen branch and applying its effects.
+    fn enter(
+        &mut self,
+        post_states: &[Location],
+        branches: &[Option<usize>],
+        rng: &mut R,
+    ) -> Result<(), PgError> {
+        for (idx, (post_state, branch)) in post_states.iter().zip(branches).enumerate() {
+            if let Some(branch) = branch {
+                let (_, post_state, effects) = &self.def.branches[post_state.0 as usize][*branch];
+                for (var, effect) in effects {
+                    let val = effect
+                        .eval(&|var| &self.vars[var.0 as usize], rng)
+                        .map_err(PgError::Eval)?
+                        .fit(self.def.overflow)
+                        .ok_or(PgError::Overflow)?;
+                    self.vars[var.0 as usize] = self.def.check_bounds(*var, val)?;
+                }
+                self.current_states[idx] = *post_state;
+            } else {
+                self.current_states[idx] = *post_state;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks if it is possible to wait a given amount of time-units without violating the invariants.
+    ///
+    /// Time cannot pass at all if any process is in an urgent or committed location.
     pub fn can_wait(&self, delta: Time) -> bool {
-        self.current_states
+        self.check_wait(delta).is_ok()
+    }
+
+    // Checks if it is possible to wait a given amount of time-units,
+    // returning the reason why it is not otherwise.
+    pub(crate) fn check_wait(&self, delta: Time) -> Result<(), PgError> {
+        if delta > 0 && self.is_urgent() {
+            Err(PgError::Urgent)
+        } else if self
+            .current_states
             .iter()
             .flat_map(|current_state| self.def.locations[current_state.0 as usize].1.iter())
             .all(|(c, l, u)| {
                 // Invariants need to be satisfied during the whole wait.
                 let start_time = self.clocks[c.0 as usize];
-                let end_time = start_time + delta;
+                let end_time = if self.is_running(*c) {
+                    start_time + delta
+                } else {
+                    start_time
+                };
                 l.is_none_or(|l| l <= start_time) && u.is_none_or(|u| end_time < u)
             })
+            && self.check_invariants(delta)?
+        {
+            Ok(())
+        } else {
+            Err(PgError::Invariant)
+        }
+    }
+
+    // Checks that the invariants of the current locations hold at the end of the wait.
+    fn check_invariants(&self, delta: Time) -> Result<bool, PgError> {
+        if self.def.flows.is_empty()
+            || self
+                .current_states
+                .iter()
+                .all(|loc| self.def.flows[loc.0 as usize].invariant.is_none())
+        {
+            return Ok(true);
+        }
+        let mut vars = self.vars.clone();
+        for (var, val) in self.flow(delta) {
+            vars[var.0 as usize] = val;
+        }
+        for invariant in self
+            .current_states
+            .iter()
+            .filter_map(|loc| self.def.flows[loc.0 as usize].invariant.as_ref())
+        {
+            if invariant
+                .eval(&|var| &vars[var.0 as usize], &mut DummyRng)
+                .map_err(PgError::Eval)?
+                != Val::Boolean(true)
+            {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    // Whether the clock advances in the current locations, i.e., no current location stops it.
+    #[inline(always)]
+    fn is_running(&self, clock: Clock) -> bool {
+        !self.def.stopwatches
+            || self
+                .current_states
+                .iter()
+                .all(|loc| !self.def.flows[loc.0 as usize].stopped.contains(&clock))
+    }
+
+    // The values of the continuous variables after waiting the given amount of time-units.
+    fn flow(&self, delta: Time) -> SmallVec<[(Var, Val); 4]> {
+        self.def
+            .continuous
+            .iter()
+            .map(|(var, default)| {
+                let rate = if self.def.flows.is_empty() {
+                    *default
+                } else {
+                    self.current_states
+                        .iter()
+                        .flat_map(|loc| &self.def.flows[loc.0 as usize].rates)
+                        .filter(|(v, _)| v == var)
+                        .map(|(_, rate)| *rate)
+                        .reduce(|acc, rate| acc + rate)
+                        .unwrap_or(*default)
+                };
+                let Val::Float(val) = self.vars[var.0 as usize] else {
+                    panic!("continuous variables are floats");
+                };
+                (*var, Val::Float(val + rate * delta as Float))
+            })
+            .collect()
     }
 
     /// Waits a given amount of time-units.
     ///
-    /// Returns error if the waiting would violate the current location's time invariant (if any).
+    /// Clocks advance unless stopped by a current location,
+    /// and continuous variables change according to their rates.
+    ///
+    /// Returns error if the waiting would violate the current locations' invariants (if any),
+    /// or if the current location is urgent or committed.
     pub fn wait(&mut self, delta: Time) -> Result<(), PgError> {
-        if self.can_wait(delta) {
-            self.clocks.iter_mut().for_each(|t| *t += delta);
-            Ok(())
+        self.check_wait(delta)?;
+        for (var, val) in self.flow(delta) {
+            self.vars[var.0 as usize] = val;
+        }
+        if self.def.stopwatches {
+            for idx in 0..self.clocks.len() {
+                if self.is_running(Clock(idx as u16)) {
+                    self.clocks[idx] += delta;
+                }
+            }
         } else {
-            Err(PgError::Invariant)
+            self.clocks.iter_mut().for_each(|t| *t += delta);
         }
+        Ok(())
+    }
+
+    // Whether the passing of time can affect the PG,
+    // i.e., it has clocks or continuous variables.
+    pub(crate) fn is_timed(&self) -> bool {
+        !self.clocks.is_empty() || !self.def.continuous.is_empty()
+    }
+
+    /// Whether any process is currently in an urgent or committed location.
+    pub fn is_urgent(&self) -> bool {
+        self.def.urgent
+            && self
+                .current_states
+                .iter()
+                .any(|loc| self.def.urgency[loc.0 as usize] != Urgency::Normal)
+    }
+
+    /// Whether any process is currently in a committed location.
+    pub fn is_committed(&self) -> bool {
+        self.def.urgent
+            && self
+                .current_states
+                .iter()
+                .any(|loc| self.def.urgency[loc.0 as usize] == Urgency::Committed)
     }
 
     pub(crate) fn send<'a>(
@@ -509,16 +907,20 @@ impl<R: Rng> ProgramGraph<R> {
         rng: &'a mut R,
     ) -> Result<Val, PgError> {
         if action == EPSILON {
-            Err(PgError::NotSend(action))
-        } else if self.active_transitions(action, post_states, &[]) {
+            Err(PgError::NotSend(self.symbols().named_action(action)))
+        } else if self.active_transitions(action, post_states, &[])? {
             if let FnEffect::Send(effect) = &self.def.effects[action.0 as usize] {
-                let val = effect.eval(&|var| self.vars[var.0 as usize].clone(), rng);
+                let val = effect
+                    .eval(&|var| &self.vars[var.0 as usize], rng)
+                    .map_err(PgError::Eval)?
+                    .fit(self.def.overflow)
+                    .ok_or(PgError::Overflow)?;
                 self.current_states.copy_from_slice(post_states);
                 // self.current_states = post_states;
                 self.update_buf();
                 Ok(val)
             } else {
-                Err(PgError::NotSend(action))
+                Err(PgError::NotSend(self.symbols().named_action(action)))
             }
         } else {
             Err(PgError::UnsatisfiedGuard)
@@ -532,12 +934,12 @@ impl<R: Rng> ProgramGraph<R> {
         val: Val,
     ) -> Result<(), PgError> {
         if action == EPSILON {
-            Err(PgError::NotReceive(action))
-        } else if self.active_transitions(action, post_states, &[]) {
+            Err(PgError::NotReceive(self.symbols().named_action(action)))
+        } else if self.active_transitions(action, post_states, &[])? {
             if let FnEffect::Receive(var) = self.def.effects[action.0 as usize] {
                 let var_content = self.vars.get_mut(var.0 as usize).expect("variable exists");
                 if var_content.r#type() == val.r#type() {
-                    *var_content = val;
+                    *var_content = self.def.check_bounds(var, val)?;
                     self.current_states.copy_from_slice(post_states);
                     // self.current_states = post_states;
                     self.update_buf();
@@ -546,27 +948,28 @@ impl<R: Rng> ProgramGraph<R> {
                     Err(PgError::TypeMismatch)
                 }
             } else {
-                Err(PgError::NotReceive(action))
+                Err(PgError::NotReceive(self.symbols().named_action(action)))
             }
         } else {
             Err(PgError::UnsatisfiedGuard)
         }
     }
 
-    pub(crate) fn eval(&self, expr: &FnExpression<Var, DummyRng>) -> Val {
+    pub(crate) fn eval(&self, expr: &FnExpression<Var, DummyRng>) -> Result<Val, PgError> {
         expr.eval(
-            &|v: Var| self.vars.get(v.0 as usize).unwrap().clone(),
+            &|v: Var| self.vars.get(v.0 as usize).unwrap(),
             &mut DummyRng,
         )
+        .map_err(PgError::Eval)
     }
 
     pub(crate) fn val(&self, var: Var) -> Result<&Val, PgError> {
         self.vars
             .get(var.0 as usize)
-            .ok_or(PgError::MissingVar(var))
+            .ok_or_else(|| PgError::MissingVar(self.symbols().named_var(var)))
     }
 
-    pub(crate) fn montecarlo(&mut self, rng: &mut R) -> Option<Action> {
+    pub(crate) fn montecarlo(&mut self, rng: &mut R) -> Result<Option<Action>, PgError> {
         let mut rand = SmallRng::from_rng(rng);
         if let Some((action, post_states)) = self
             .possible_transitions()
@@ -578,11 +981,25 @@ impl<R: Rng> ProgramGraph<R> {
             })
             .choose(&mut rand)
         {
+            trace!(
+                "executing action {} towards {}",
+                self.symbols().describe_action(action),
+                post_states
+                    .iter()
+                    .map(|loc| self.symbols().describe_location(*loc))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
             self.transition(action, post_states.as_slice(), rng)
-                .expect("successful transition");
-            return Some(action);
+                .inspect_err(|err| {
+                    debug!(
+                        "failed to execute action {}: {err}",
+                        self.symbols().describe_action(action)
+                    )
+                })?;
+            return Ok(Some(action));
         }
-        None
+        Ok(None)
     }
 }
 
@@ -591,6 +1008,7 @@ mod tests {
     use rand::SeedableRng;
     use rand::rngs::SmallRng;
     use rand::rngs::mock::StepRng;
+    use std::sync::Arc;
 
     use super::*;
 
@@ -693,4 +1111,421 @@ mod tests {
             .expect_err("battery = 0");
         Ok(())
     }
+
+    #[test]
+    fn strings() -> Result<(), PgError> {
+        let mut builder = ProgramGraphBuilder::new();
+        let mut rng = SmallRng::from_seed([0; 32]);
+        let greeting = builder.new_var_with_rng(
+            Expression::Const(Val::from(String::from("hello"))),
+            &mut rng,
+        )?;
+        let initial = builder.new_initial_location();
+        let greeted = builder.new_location();
+        let done = builder.new_location();
+        let greet = builder.new_action();
+        builder.add_effect(
+            greet,
+            greeting,
+            PgExpression::Concat(vec![
+                PgExpression::Var(greeting, Type::String),
+                PgExpression::Const(Val::from(String::from(" world"))),
+            ]),
+        )?;
+        builder
+            .add_effect(greet, greeting, PgExpression::Const(Val::Integer(0)))
+            .expect_err("type mismatch");
+        let check = builder.new_action();
+        let is_greeting = PgExpression::Equal(Box::new((
+            PgExpression::Var(greeting, Type::String),
+            PgExpression::Const(Val::from(String::from("hello world"))),
+        )));
+        let has_len = PgExpression::Equal(Box::new((
+            PgExpression::Len(Box::new(PgExpression::Var(greeting, Type::String))),
+            PgExpression::Const(Val::Integer(11)),
+        )));
+        builder.add_transition(initial, greet, greeted, None)?;
+        builder.add_transition(
+            greeted,
+            check,
+            done,
+            Some(PgExpression::And(vec![is_greeting, has_len])),
+        )?;
+        let mut pg = builder.build();
+        pg.transition(check, &[done], &mut rng)
+            .expect_err("not greeted yet");
+        pg.transition(greet, &[greeted], &mut rng).expect("greet");
+        pg.transition(check, &[done], &mut rng)
+            .expect("greeting is 'hello world'");
+        Ok(())
+    }
+
+    #[test]
+    fn overflow() -> Result<(), PgError> {
+        for (overflow, expected) in [
+            (Overflow::Wrap, Some(Val::Int(IntType::I8, -128))),
+            (Overflow::Saturate, Some(Val::Int(IntType::I8, 127))),
+            (Overflow::Error, None),
+        ] {
+            let mut builder = ProgramGraphBuilder::new();
+            builder.set_overflow(overflow);
+            let mut rng = SmallRng::from_seed([0; 32]);
+            let counter = builder
+                .new_var_with_rng(Expression::Const(Val::Int(IntType::I8, 127)), &mut rng)?;
+            let initial = builder.new_initial_location();
+            let increment = builder.new_action();
+            builder.add_effect(
+                increment,
+                counter,
+                PgExpression::Sum(vec![
+                    PgExpression::Var(counter, Type::Int(IntType::I8)),
+                    PgExpression::from(1),
+                ]),
+            )?;
+            builder.add_transition(initial, increment, initial, None)?;
+            let mut pg = builder.build();
+            let result = pg.transition(increment, &[initial], &mut rng);
+            if let Some(val) = expected {
+                result?;
+                assert_eq!(pg.val(counter)?, &val);
+            } else {
+                assert!(matches!(result, Err(PgError::Overflow)));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn bounded() -> Result<(), PgError> {
+        let mut builder = ProgramGraphBuilder::new();
+        let counter = builder.new_var(PgExpression::from(0))?;
+        let flag = builder.new_var(PgExpression::from(false))?;
+        let sized = builder.new_var(PgExpression::Const(Val::Int(IntType::U8, 0)))?;
+        let bounds = Bounds::new(Some(0), Some(1));
+        builder.set_bounds(counter, bounds)?;
+        builder.set_bounds(sized, bounds)?;
+        assert!(matches!(
+            builder.set_bounds(flag, bounds),
+            Err(PgError::Type(_))
+        ));
+        assert_eq!(builder.var_bounds(counter)?, bounds);
+        assert_eq!(builder.var_bounds(flag)?, Bounds::default());
+        let initial = builder.new_initial_location();
+        let increment = builder.new_action();
+        builder.add_effect(
+            increment,
+            counter,
+            PgExpression::Sum(vec![
+                PgExpression::Var(counter, Type::Integer),
+                PgExpression::from(1),
+            ]),
+        )?;
+        builder.add_transition(initial, increment, initial, None)?;
+        let mut pg = builder.build();
+        let mut rng = SmallRng::from_seed([0; 32]);
+        pg.transition(increment, &[initial], &mut rng)?;
+        assert_eq!(pg.val(counter)?, &Val::Integer(1));
+        let result = pg.transition(increment, &[initial], &mut rng);
+        assert!(matches!(
+            result,
+            Err(PgError::OutOfBounds { var, val: Val::Integer(2), .. }) if var == counter
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn flows() -> Result<(), PgError> {
+        let mut builder = ProgramGraphBuilder::new();
+        let clock = builder.new_clock();
+        let battery = builder.new_var(PgExpression::from(0.))?;
+        let charging = builder.new_initial_location();
+        let idle = builder.new_location();
+        let done = builder.new_action();
+        builder.set_rate(battery, -1.)?;
+        builder.set_location_rate(charging, battery, 2.)?;
+        builder.stop_clock(charging, clock)?;
+        builder.set_invariant(
+            charging,
+            PgExpression::LessEq(Box::new((
+                PgExpression::Var(battery, Type::Float),
+                PgExpression::from(10.),
+            ))),
+        )?;
+        builder.add_timed_transition(charging, done, idle, None, vec![(clock, None, Some(1))])?;
+        assert!(matches!(
+            builder.set_rate(Var(1), 1.),
+            Err(PgError::MissingVar(_))
+        ));
+        let mut pg = builder.build::<SmallRng>();
+        let mut rng = SmallRng::from_seed([0; 32]);
+        pg.wait(5)?;
+        assert_eq!(pg.val(battery)?, &Val::Float(10.));
+        // Charging any further would violate the invariant
+        assert!(matches!(pg.wait(1), Err(PgError::Invariant)));
+        // The clock is stopped while charging
+        pg.transition(done, &[idle], &mut rng)?;
+        pg.wait(3)?;
+        assert_eq!(pg.val(battery)?, &Val::Float(7.));
+        Ok(())
+    }
+
+    #[test]
+    fn symbols() -> Result<(), PgError> {
+        let mut builder = ProgramGraphBuilder::new();
+        let counter = builder.new_var(PgExpression::from(0))?;
+        let clock = builder.new_clock();
+        let initial = builder.new_initial_location();
+        let increment = builder.new_action();
+        let source = SourceLocation {
+            file: "model.xml".into(),
+            line: 3,
+            column: 5,
+        };
+        builder.set_var_symbol(counter, Symbol::new("counter").with_source(source))?;
+        builder.set_clock_symbol(clock, "timer")?;
+        builder.set_location_symbol(initial, "idle")?;
+        builder.set_action_symbol(increment, "increment")?;
+        let err = builder
+            .set_var_symbol(Var(1), "missing")
+            .expect_err("missing variable");
+        assert!(matches!(err, PgError::MissingVar(_)));
+        assert_eq!(
+            err.to_string(),
+            "variable Var(1) does not belong to this program graph"
+        );
+        let badly_typed = PgExpression::And(vec![
+            PgExpression::Var(counter, Type::Integer),
+            PgExpression::from(true),
+        ]);
+        let Err(PgError::Type(err)) = builder.add_effect(increment, counter, badly_typed) else {
+            panic!("badly typed effect");
+        };
+        assert_eq!(err.expression(), Some("counter"));
+        builder.set_bounds(counter, Bounds::new(Some(0), Some(0)))?;
+        builder.add_effect(increment, counter, PgExpression::from(1))?;
+        builder.add_transition(initial, increment, initial, None)?;
+        let mut pg = builder.build();
+        assert_eq!(pg.symbols().describe_location(initial), "'idle'");
+        assert_eq!(pg.symbols().describe_action(increment), "'increment'");
+        assert_eq!(pg.symbols().describe_clock(clock), "'timer'");
+        assert_eq!(pg.symbols().describe_location(Location(1)), "Location(1)");
+        let mut rng = SmallRng::from_seed([0; 32]);
+        let err = pg
+            .transition(increment, &[initial], &mut rng)
+            .expect_err("out of bounds");
+        assert_eq!(
+            err.to_string(),
+            "value Integer(1) is out of the bounds 0..=0 of variable 'counter' (defined at model.xml:3:5)"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn priorities() -> Result<(), PgError> {
+        let mut builder = ProgramGraphBuilder::new();
+        let flag = builder.new_var(PgExpression::from(false))?;
+        let initial = builder.new_initial_location();
+        let other = builder.new_initial_location();
+        let low = builder.new_location();
+        let high = builder.new_location();
+        let action = builder.new_action();
+        let set = builder.new_action();
+        builder.add_effect(set, flag, PgExpression::from(true))?;
+        builder.add_transition(initial, action, low, None)?;
+        builder.add_transition(
+            initial,
+            action,
+            high,
+            Some(PgExpression::Var(flag, Type::Boolean)),
+        )?;
+        builder.add_transition(other, action, other, None)?;
+        builder.add_transition(initial, set, initial, None)?;
+        builder.add_transition(other, set, other, None)?;
+        builder.set_priority(initial, high, 2)?;
+        builder.set_priority(other, other, 1)?;
+        assert!(matches!(
+            builder.set_priority(high, low, 1),
+            Err(PgError::MissingTransition)
+        ));
+        let mut pg = builder.build();
+        let possible_transitions = |pg: &ProgramGraph<SmallRng>| {
+            pg.possible_transitions()
+                .map(|(action, post_states)| {
+                    (
+                        action,
+                        post_states
+                            .map(|locs| locs.collect::<Vec<_>>())
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        // High-priority transition is disabled by its guard,
+        // and synchronising on `action` has priority 0 (the lowest of the processes' transitions).
+        assert_eq!(
+            possible_transitions(&pg),
+            vec![
+                (action, vec![vec![low], vec![other]]),
+                (set, vec![vec![initial], vec![other]]),
+            ]
+        );
+        let mut rng = SmallRng::from_seed([0; 32]);
+        pg.transition(set, &[initial, other], &mut rng)?;
+        // Now synchronising on `action` has priority 1 and takes precedence
+        // (`set` has priority 1 too in the second process, as it shares pre- and post-location).
+        assert_eq!(
+            possible_transitions(&pg),
+            vec![
+                (action, vec![vec![high], vec![other]]),
+                (set, vec![vec![], vec![other]]),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn urgency() -> Result<(), PgError> {
+        let mut builder = ProgramGraphBuilder::new();
+        let initial = builder.new_initial_location();
+        let urgent = builder.new_location();
+        let committed = builder.new_location();
+        let action = builder.new_action();
+        builder.add_transition(initial, action, urgent, None)?;
+        builder.add_transition(urgent, action, committed, None)?;
+        builder.add_transition(committed, action, initial, None)?;
+        builder.set_urgency(urgent, Urgency::Urgent)?;
+        builder.set_urgency(committed, Urgency::Committed)?;
+        assert!(matches!(
+            builder.set_urgency(Location(3), Urgency::Urgent),
+            Err(PgError::MissingLocation(_))
+        ));
+        let mut pg = builder.build();
+        let mut rng = SmallRng::from_seed([0; 32]);
+        assert!(!pg.is_urgent() && pg.can_wait(1));
+        pg.wait(1)?;
+        pg.transition(action, &[urgent], &mut rng)?;
+        assert!(pg.is_urgent() && !pg.is_committed());
+        // Waiting no time at all is still allowed
+        assert!(pg.can_wait(0) && !pg.can_wait(1));
+        assert!(matches!(pg.wait(1), Err(PgError::Urgent)));
+        pg.transition(action, &[committed], &mut rng)?;
+        assert!(pg.is_urgent() && pg.is_committed());
+        assert!(matches!(pg.wait(1), Err(PgError::Urgent)));
+        pg.transition(action, &[initial], &mut rng)?;
+        pg.wait(1)?;
+        Ok(())
+    }
+
+    #[test]
+    fn probabilistic() -> Result<(), PgError> {
+        let mut builder = ProgramGraphBuilder::new();
+        let weight = builder.new_var(PgExpression::from(1))?;
+        let tosses = builder.new_var(PgExpression::from(0))?;
+        let initial = builder.new_initial_location();
+        let heads = builder.new_location();
+        let tails = builder.new_location();
+        let toss = builder.new_action();
+        builder.add_effect(
+            toss,
+            tosses,
+            PgExpression::Sum(vec![
+                PgExpression::Var(tosses, Type::Integer),
+                PgExpression::from(1),
+            ]),
+        )?;
+        assert!(matches!(
+            builder.add_probabilistic_transition(
+                initial,
+                toss,
+                None,
+                vec![(PgExpression::from(false), heads, Vec::new())],
+            ),
+            Err(PgError::Type(_))
+        ));
+        builder.add_probabilistic_transition(
+            initial,
+            toss,
+            None,
+            vec![
+                (
+                    PgExpression::Var(weight, Type::Integer),
+                    heads,
+                    vec![(weight, PgExpression::from(0))],
+                ),
+                (PgExpression::from(0.), tails, Vec::new()),
+            ],
+        )?;
+        let back = builder.new_action();
+        builder.add_transition(heads, back, initial, None)?;
+        let mut pg = builder.build();
+        let mut rng = SmallRng::from_seed([0; 32]);
+        let branching = {
+            let mut transitions = pg.possible_transitions();
+            let (action, mut post_states) = transitions.next().expect("toss");
+            assert_eq!(action, toss);
+            post_states.next().unwrap().next().unwrap()
+        };
+        // Only heads has positive weight
+        pg.transition(toss, &[branching], &mut rng)?;
+        assert_eq!(pg.current_states().as_slice(), &[heads]);
+        assert_eq!(pg.val(tosses)?, &Val::Integer(1));
+        assert_eq!(pg.val(weight)?, &Val::Integer(0));
+        pg.transition(back, &[initial], &mut rng)?;
+        // All weights are zero now
+        assert!(matches!(
+            pg.transition(toss, &[branching], &mut rng),
+            Err(PgError::InvalidWeights)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn enums() -> Result<(), PgError> {
+        let response = Arc::new(Enum::new(
+            String::from("Response"),
+            vec![String::from("SUCCESS"), String::from("FAILURE")],
+        ));
+        let success = Val::Enum(response.clone(), 0);
+        let failure = Val::Enum(response.clone(), 1);
+        let mut builder = ProgramGraphBuilder::new();
+        let mut rng = SmallRng::from_seed([0; 32]);
+        let state = builder.new_var_with_rng(Expression::Const(failure), &mut rng)?;
+        let initial = builder.new_initial_location();
+        let done = builder.new_location();
+        let succeed = builder.new_action();
+        builder
+            .add_effect(succeed, state, PgExpression::Const(Val::Integer(0)))
+            .expect_err("enums are not integers");
+        builder.add_effect(succeed, state, PgExpression::Const(success.clone()))?;
+        let finish = builder.new_action();
+        builder
+            .add_transition(
+                initial,
+                finish,
+                done,
+                Some(PgExpression::Equal(Box::new((
+                    PgExpression::Var(state, Type::Enum(response.clone())),
+                    PgExpression::Const(Val::Integer(0)),
+                )))),
+            )
+            .expect_err("enums cannot be compared with integers");
+        builder.add_transition(initial, succeed, initial, None)?;
+        builder.add_transition(
+            initial,
+            finish,
+            done,
+            Some(PgExpression::Equal(Box::new((
+                PgExpression::Var(state, Type::Enum(response)),
+                PgExpression::Const(success),
+            )))),
+        )?;
+        let mut pg = builder.build();
+        pg.transition(finish, &[done], &mut rng)
+            .expect_err("state is FAILURE");
+        pg.transition(succeed, &[initial], &mut rng)
+            .expect("succeed");
+        pg.transition(finish, &[done], &mut rng)
+            .expect("state is SUCCESS");
+        Ok(())
+    }
 }
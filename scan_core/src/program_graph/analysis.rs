@@ -0,0 +1,333 @@
+// Static analysis of program graphs, to catch modelling mistakes before simulation.
+//
+// The analysis is structural and conservative:
+// a location is deemed reachable if there is a path to it from an initial location
+// along transitions whose guard is not always false,
+// regardless of whether processes can actually synchronise on them.
+
+use super::{builder::Effect, *};
+use std::collections::VecDeque;
+
+/// A potential modelling mistake found by the static analysis of a [`ProgramGraph`].
+///
+/// See [`ProgramGraph::analyze`].
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum PgDiagnostic {
+    /// The location cannot be reached from the initial locations.
+    UnreachableLocation(Location),
+    /// The guard of the transition, given by its pre-location, action and post-location,
+    /// is always false.
+    FalseGuard(Location, Action, Location),
+    /// The variable is written but its value is never read.
+    UnreadVar(Var),
+}
+
+impl PgDiagnostic {
+    /// Describes the diagnostic, referring to the PG's objects by their symbols when possible.
+    ///
+    /// See also [`ProgramGraph::symbols`].
+    pub fn describe(&self, symbols: &SymbolTable) -> String {
+        match self {
+            PgDiagnostic::UnreachableLocation(loc) => {
+                format!(
+                    "location {} is unreachable",
+                    symbols.describe_location(*loc)
+                )
+            }
+            PgDiagnostic::FalseGuard(pre, action, post) => format!(
+                "transition from {} to {} with action {} has a guard that is always false",
+                symbols.describe_location(*pre),
+                symbols.describe_location(*post),
+                symbols.describe_action(*action),
+            ),
+            PgDiagnostic::UnreadVar(var) => {
+                format!(
+                    "variable {} is written but never read",
+                    symbols.describe_var(*var)
+                )
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for PgDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.describe(&SymbolTable::default()))
+    }
+}
+
+impl ProgramGraphBuilder {
+    // Whether each variable is assigned by some effect.
+    fn written_vars(&self) -> Vec<bool> {
+        let mut written = vec![false; self.vars.len()];
+        for effect in &self.effects {
+            match effect {
+                Effect::Effects(effects, _) => effects
+                    .iter()
+                    .for_each(|(var, _)| written[var.0 as usize] = true),
+                Effect::Receive(var) => written[var.0 as usize] = true,
+                Effect::Send(_) => {}
+            }
+        }
+        self.branches
+            .iter()
+            .flatten()
+            .flat_map(|(_, _, effects)| effects)
+            .for_each(|(var, _)| written[var.0 as usize] = true);
+        // Continuous variables change over time
+        self.rates
+            .iter()
+            .for_each(|(var, _)| written[var.0 as usize] = true);
+        written
+    }
+
+    // Whether each variable occurs in some guard or expression.
+    fn read_vars(&self) -> Vec<bool> {
+        let mut read = vec![false; self.vars.len()];
+        let mut visit = |var: &Var| read[var.0 as usize] = true;
+        for effect in &self.effects {
+            match effect {
+                Effect::Effects(effects, _) => effects
+                    .iter()
+                    .for_each(|(_, expr)| expr.for_each_var(&mut visit)),
+                Effect::Send(msg) => msg.for_each_var(&mut visit),
+                Effect::Receive(_) => {}
+            }
+        }
+        self.locations
+            .iter()
+            .flat_map(|(transitions, _)| transitions)
+            .filter_map(|(_, _, guard, ..)| guard.as_ref())
+            .for_each(|guard| guard.for_each_var(&mut visit));
+        for (weight, _, effects) in self.branches.iter().flatten() {
+            weight.for_each_var(&mut visit);
+            effects
+                .iter()
+                .for_each(|(_, expr)| expr.for_each_var(&mut visit));
+        }
+        self.invariants
+            .values()
+            .for_each(|invariant| invariant.for_each_var(&mut visit));
+        read
+    }
+
+    // Whether each transition (indexed by location and by position) has a guard that is always false,
+    // i.e., one that only depends on variables that are never written and is false on their initial values.
+    pub(super) fn false_guards(&self) -> Vec<Vec<bool>> {
+        let written = self.written_vars();
+        self.locations
+            .iter()
+            .map(|(transitions, _)| {
+                transitions
+                    .iter()
+                    .map(|(_, _, guard, ..)| {
+                        guard.as_ref().is_some_and(|guard| {
+                            let mut constant = true;
+                            guard.for_each_var(&mut |var| constant &= !written[var.0 as usize]);
+                            constant
+                                && matches!(
+                                    FnExpression::<Var, DummyRng>::from(guard.clone())
+                                        .eval(&|var| &self.vars[var.0 as usize], &mut DummyRng),
+                                    Ok(Val::Boolean(false))
+                                )
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Whether each location is reachable from the initial locations
+    // along transitions whose guard is not always false and whose action is enabled by the predicate.
+    pub(super) fn reachable_locations(
+        &self,
+        false_guards: &[Vec<bool>],
+        enabled: impl Fn(Action) -> bool,
+    ) -> Vec<bool> {
+        let mut reachable = vec![false; self.locations.len()];
+        let mut queue = VecDeque::new();
+        for loc in &self.initial_states {
+            reachable[loc.0 as usize] = true;
+            queue.push_back(*loc);
+        }
+        while let Some(pre) = queue.pop_front() {
+            let (transitions, _) = &self.locations[pre.0 as usize];
+            for (idx, (action, post, ..)) in transitions.iter().enumerate() {
+                if !false_guards[pre.0 as usize][idx] && enabled(*action) {
+                    // Branching locations are left immediately for one of their branches' post-locations
+                    let branches = &self.branches[post.0 as usize];
+                    for post in std::iter::once(post).chain(branches.iter().map(|(_, p, _)| p)) {
+                        if !reachable[post.0 as usize] {
+                            reachable[post.0 as usize] = true;
+                            queue.push_back(*post);
+                        }
+                    }
+                }
+            }
+        }
+        reachable
+    }
+
+    // Actions of the transitions from reachable locations whose guard is not always false.
+    fn live_actions<'a>(
+        &'a self,
+        false_guards: &'a [Vec<bool>],
+        reachable: &'a [bool],
+    ) -> impl Iterator<Item = Action> + 'a {
+        self.locations
+            .iter()
+            .enumerate()
+            .filter(|(pre, _)| reachable[*pre])
+            .flat_map(move |(pre, (transitions, _))| {
+                transitions
+                    .iter()
+                    .enumerate()
+                    .filter(move |(idx, _)| !false_guards[pre][*idx])
+                    .map(|(_, (action, ..))| *action)
+            })
+    }
+
+    // Diagnostics of the PG, given the reachable locations.
+    fn diagnostics(&self, false_guards: &[Vec<bool>], reachable: &[bool]) -> Vec<PgDiagnostic> {
+        let unreachable = reachable
+            .iter()
+            .enumerate()
+            // Branching locations are not part of the PG's structure as defined by the user
+            .filter(|(loc, reachable)| !**reachable && self.branches[*loc].is_empty())
+            .map(|(loc, _)| PgDiagnostic::UnreachableLocation(Location(loc as u16)));
+        let guards = self
+            .locations
+            .iter()
+            .enumerate()
+            .flat_map(|(pre, (transitions, _))| {
+                transitions
+                    .iter()
+                    .enumerate()
+                    .filter(move |(idx, _)| false_guards[pre][*idx])
+                    .map(move |(_, (action, post, ..))| {
+                        PgDiagnostic::FalseGuard(Location(pre as u16), *action, *post)
+                    })
+            });
+        let read = self.read_vars();
+        let unread = self
+            .written_vars()
+            .into_iter()
+            .zip(read)
+            .enumerate()
+            .filter(|(_, (written, read))| *written && !read)
+            .map(|(var, _)| PgDiagnostic::UnreadVar(Var(var as u16)));
+        unreachable.chain(guards).chain(unread).collect()
+    }
+}
+
+impl<R: Rng> ProgramGraph<R> {
+    /// Statically analyzes the PG's definition, looking for potential modelling mistakes:
+    ///
+    /// - locations that cannot be reached from the initial locations;
+    /// - transitions whose guard is always false;
+    /// - variables that are written but never read.
+    ///
+    /// ```
+    /// # use scan_core::program_graph::*;
+    /// let mut pg_builder = ProgramGraphBuilder::new();
+    /// let initial_loc = pg_builder.new_initial_location();
+    /// let unreachable_loc = pg_builder.new_location();
+    /// let action = pg_builder.new_action();
+    /// pg_builder
+    ///     .add_transition(initial_loc, action, unreachable_loc, Some(PgExpression::from(false)))
+    ///     .unwrap();
+    ///
+    /// # use rand::rngs::SmallRng;
+    /// let pg = pg_builder.build::<SmallRng>();
+    /// assert_eq!(
+    ///     pg.analyze(),
+    ///     vec![
+    ///         PgDiagnostic::UnreachableLocation(unreachable_loc),
+    ///         PgDiagnostic::FalseGuard(initial_loc, action, unreachable_loc),
+    ///     ]
+    /// );
+    /// ```
+    pub fn analyze(&self) -> Vec<PgDiagnostic> {
+        let source = &self.def.source;
+        let false_guards = source.false_guards();
+        let reachable = source.reachable_locations(&false_guards, |_| true);
+        source.diagnostics(&false_guards, &reachable)
+    }
+
+    // Transitions whose guard is always false, by location and by position.
+    pub(crate) fn false_guards(&self) -> Vec<Vec<bool>> {
+        self.def.source.false_guards()
+    }
+
+    // Locations reachable along transitions whose guard is not always false
+    // and whose action is enabled by the predicate.
+    pub(crate) fn reachable_locations(
+        &self,
+        false_guards: &[Vec<bool>],
+        enabled: impl Fn(Action) -> bool,
+    ) -> Vec<bool> {
+        self.def.source.reachable_locations(false_guards, enabled)
+    }
+
+    // Actions of the transitions from reachable locations whose guard is not always false.
+    pub(crate) fn live_actions<'a>(
+        &'a self,
+        false_guards: &'a [Vec<bool>],
+        reachable: &'a [bool],
+    ) -> impl Iterator<Item = Action> + 'a {
+        self.def.source.live_actions(false_guards, reachable)
+    }
+
+    // Diagnostics of the PG, given the reachable locations.
+    pub(crate) fn diagnostics(
+        &self,
+        false_guards: &[Vec<bool>],
+        reachable: &[bool],
+    ) -> Vec<PgDiagnostic> {
+        self.def.source.diagnostics(false_guards, reachable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analyze() -> Result<(), PgError> {
+        let mut builder = ProgramGraphBuilder::new();
+        let flag = builder.new_var(PgExpression::from(false))?;
+        let unread = builder.new_var(PgExpression::from(0))?;
+        let initial = builder.new_initial_location();
+        let reachable = builder.new_location();
+        let unreachable = builder.new_location();
+        let behind_false_guard = builder.new_location();
+        let action = builder.new_action();
+        builder.add_effect(action, unread, PgExpression::from(1))?;
+        builder.add_transition(initial, action, reachable, None)?;
+        builder.add_transition(unreachable, action, reachable, None)?;
+        // `flag` is never written, so this guard is always false
+        builder.add_transition(
+            reachable,
+            action,
+            behind_false_guard,
+            Some(PgExpression::Var(flag, Type::Boolean)),
+        )?;
+        builder.add_probabilistic_transition(
+            reachable,
+            action,
+            None,
+            vec![(PgExpression::from(1.), initial, Vec::new())],
+        )?;
+        let pg = builder.build::<SmallRng>();
+        assert_eq!(
+            pg.analyze(),
+            vec![
+                PgDiagnostic::UnreachableLocation(unreachable),
+                PgDiagnostic::UnreachableLocation(behind_false_guard),
+                PgDiagnostic::FalseGuard(reachable, action, behind_false_guard),
+                PgDiagnostic::UnreadVar(unread),
+            ]
+        );
+        Ok(())
+    }
+}
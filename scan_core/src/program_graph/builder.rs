@@ -1,14 +1,18 @@
 use super::{
-    Action, Clock, EPSILON, FnEffect, FnExpression, Location, PgError, PgExpression, ProgramGraph,
-    ProgramGraphDef, TimeConstraint, Var,
+    Action, Branch, Clock, EPSILON, Flow, FnEffect, FnExpression, Location, PgError, PgExpression,
+    Priority, ProgramGraph, ProgramGraphDef, Symbol, SymbolTable, TimeConstraint, Urgency, Var,
 };
-use crate::grammar::{Type, Val};
-use log::info;
+use crate::grammar::{Bounds, Expected, Float, Overflow, Type, TypeError, Val};
+use log::{info, warn};
 use rand::{Rng, SeedableRng, rngs::SmallRng};
-use std::{collections::BTreeSet, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Formatter,
+    sync::Arc,
+};
 
 #[derive(Debug, Clone)]
-enum Effect {
+pub(super) enum Effect {
     Effects(Vec<(Var, PgExpression)>, Vec<Clock>),
     Send(PgExpression),
     Receive(Var),
@@ -33,21 +37,56 @@ impl<R: Rng + 'static> From<Effect> for FnEffect<R> {
     }
 }
 
-type TransitionBuilder = (Action, Location, Option<PgExpression>, Vec<TimeConstraint>);
+pub(super) type TransitionBuilder = (
+    Action,
+    Location,
+    Option<PgExpression>,
+    Vec<TimeConstraint>,
+    Priority,
+);
+
+/// A branch of a probabilistic transition, given by:
+///
+/// - its weight, i.e., a numerical expression,
+/// - its post-location, and
+/// - its effects, i.e., assignments of expressions to variables.
+///
+/// See [`ProgramGraphBuilder::add_probabilistic_transition`].
+pub type BranchBuilder = (PgExpression, Location, Vec<(Var, PgExpression)>);
 
 /// Defines and builds a PG.
 #[derive(Clone)]
 pub struct ProgramGraphBuilder {
-    initial_states: Vec<Location>,
+    pub(super) initial_states: Vec<Location>,
     // Effects are indexed by actions
-    effects: Vec<Effect>,
+    pub(super) effects: Vec<Effect>,
     // Transitions are indexed by locations
     // We can assume there is at most one condition by logical disjunction
-    locations: Vec<(Vec<TransitionBuilder>, Vec<TimeConstraint>)>,
-    // Time invariants of each location
-    vars: Vec<Val>,
+    pub(super) locations: Vec<(Vec<TransitionBuilder>, Vec<TimeConstraint>)>,
+    // Branches of probabilistic transitions are indexed by their branching locations
+    pub(super) branches: Vec<Vec<BranchBuilder>>,
+    // Urgency of each location
+    pub(super) urgency: Vec<Urgency>,
+    // Initial values of variables
+    pub(super) vars: Vec<Val>,
+    // Bounds of bounded integer variables
+    bounds: Vec<Option<Bounds>>,
     // Number of clocks
     clocks: u16,
+    // Default rates of continuous variables
+    pub(super) rates: Vec<(Var, Float)>,
+    // Rates of continuous variables by location, overriding the default ones
+    pub(super) location_rates: HashMap<Location, Vec<(Var, Float)>>,
+    // Clocks stopped by location
+    pub(super) stopped_clocks: HashMap<Location, Vec<Clock>>,
+    // Invariants restricting the passing of time, by location
+    pub(super) invariants: HashMap<Location, PgExpression>,
+    // Semantics of sized integer overflow
+    overflow: Overflow,
+    // Whether chains of internal transitions are merged when building the PG
+    compression: bool,
+    // Names and source metadata
+    pub(super) symbols: SymbolTable,
 }
 
 impl Default for ProgramGraphBuilder {
@@ -65,17 +104,70 @@ impl ProgramGraphBuilder {
             initial_states: Vec::new(),
             effects: Vec::new(),
             vars: Vec::new(),
+            bounds: Vec::new(),
             locations: Vec::new(),
+            branches: Vec::new(),
+            urgency: Vec::new(),
             clocks: 0,
+            rates: Vec::new(),
+            location_rates: HashMap::new(),
+            stopped_clocks: HashMap::new(),
+            invariants: HashMap::new(),
+            overflow: Overflow::default(),
+            compression: false,
+            symbols: SymbolTable::default(),
         }
     }
 
+    /// Sets the semantics of sized integer values overflowing their type
+    /// when they are assigned to variables or sent over channels.
+    ///
+    /// With [`Overflow::Error`], an overflow makes the transition fail with [`PgError::Overflow`].
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.overflow = overflow;
+    }
+
+    /// Sets whether the PG is compressed when it is built,
+    /// merging chains of internal transitions into single transitions with the composed effects.
+    ///
+    /// A transition is merged with the one following it if both execute effects (or no action at all),
+    /// and its post-location can only be left by the latter, which has no guard nor time constraints.
+    /// This way, the PG takes fewer steps to execute,
+    /// but the values of its variables at the intermediate locations are not visible anymore.
+    /// Communications are never merged, so this is safe when the PG is part of a [`ChannelSystem`](crate::channel_system::ChannelSystem).
+    ///
+    /// Locations and actions keep their indices,
+    /// and the static analysis of the PG still refers to its uncompressed definition.
+    /// PGs with multiple processes are not compressed, as their actions are shared by all processes.
+    ///
+    /// ```
+    /// # use scan_core::program_graph::{PgExpression, ProgramGraphBuilder};
+    /// # use rand::{SeedableRng, rngs::SmallRng};
+    /// let mut pg_builder = ProgramGraphBuilder::new();
+    /// pg_builder.set_compression(true);
+    /// let initial = pg_builder.new_initial_location();
+    /// let intermediate = pg_builder.new_location();
+    /// let last = pg_builder.new_location();
+    /// let action = pg_builder.new_action();
+    /// pg_builder.add_transition(initial, action, intermediate, None).unwrap();
+    /// pg_builder.add_transition(intermediate, action, last, None).unwrap();
+    ///
+    /// // A single step reaches the last location
+    /// let mut pg = pg_builder.build();
+    /// pg.transition(action, &[last], &mut SmallRng::from_os_rng())
+    ///     .expect("transition is compressed");
+    /// assert_eq!(pg.current_states().as_slice(), &[last]);
+    /// ```
+    pub fn set_compression(&mut self, compression: bool) {
+        self.compression = compression;
+    }
+
     // Gets the type of a variable.
     pub(crate) fn var_type(&self, var: Var) -> Result<Type, PgError> {
         self.vars
             .get(var.0 as usize)
             .map(Val::r#type)
-            .ok_or(PgError::MissingVar(var))
+            .ok_or_else(|| PgError::MissingVar(self.symbols.named_var(var)))
     }
 
     /// Adds a new variable with the given initial value (and the inferred type) to the PG.
@@ -127,11 +219,69 @@ impl ProgramGraphBuilder {
         let _ = init.r#type().map_err(PgError::Type)?;
         init.context(&|var| self.vars.get(var.0 as usize).map(Val::r#type))
             .map_err(PgError::Type)?;
-        let val = FnExpression::from(init).eval(&|var| self.vars[var.0 as usize].clone(), rng);
+        let val = FnExpression::from(init)
+            .eval(&|var| &self.vars[var.0 as usize], rng)
+            .map_err(PgError::Eval)?
+            .fit(self.overflow)
+            .ok_or(PgError::Overflow)?;
         self.vars.push(val);
+        self.bounds.push(None);
         Ok(Var(idx as u16))
     }
 
+    /// Bounds the range of values of an integer variable, making it a bounded integer variable.
+    ///
+    /// Assigning the variable a value out of its bounds,
+    /// either by an effect or by receiving a message,
+    /// makes the transition fail with [`PgError::OutOfBounds`].
+    ///
+    /// It fails if the variable is not of integer type (either [`Type::Integer`] or a sized [`Type::Int`]),
+    /// or if its initial value is out of bounds.
+    ///
+    /// ```
+    /// # use scan_core::Bounds;
+    /// # use scan_core::program_graph::{PgExpression, ProgramGraphBuilder};
+    /// # let mut pg_builder = ProgramGraphBuilder::new();
+    /// let var = pg_builder.new_var(PgExpression::from(0)).expect("expression is well-typed");
+    /// pg_builder
+    ///     .set_bounds(var, Bounds::new(Some(0), Some(10)))
+    ///     .expect("initial value is within bounds");
+    /// pg_builder
+    ///     .set_bounds(var, Bounds::new(Some(1), None))
+    ///     .expect_err("initial value is out of bounds");
+    /// ```
+    pub fn set_bounds(&mut self, var: Var, bounds: Bounds) -> Result<(), PgError> {
+        match self.vars.get(var.0 as usize) {
+            None => Err(PgError::MissingVar(self.symbols.named_var(var))),
+            Some(val @ (Val::Integer(_) | Val::Int(..))) if !bounds.contains_val(val) => {
+                Err(PgError::OutOfBounds {
+                    var,
+                    symbol: self.symbols.var(var).cloned().map(Box::new),
+                    val: val.clone(),
+                    bounds,
+                })
+            }
+            Some(Val::Integer(_) | Val::Int(..)) => {
+                self.bounds[var.0 as usize] = Some(bounds);
+                Ok(())
+            }
+            Some(val) => Err(PgError::Type(TypeError::TypeMismatch {
+                expected: Expected::Type(Type::Integer),
+                found: val.r#type(),
+            })),
+        }
+    }
+
+    /// Gets the bounds of a variable, which are unbounded unless the variable is a bounded integer variable.
+    ///
+    /// See also [`Expression::bounds`](crate::Expression::bounds) for the range analysis of expressions.
+    pub fn var_bounds(&self, var: Var) -> Result<Bounds, PgError> {
+        self.bounds
+            .get(var.0 as usize)
+            .map(|bounds| bounds.unwrap_or_default())
+            .ok_or_else(|| PgError::MissingVar(self.symbols.named_var(var)))
+    }
+
     /// Adds a new clock and returns a [`Clock`] id object.
     ///
     /// See also [`crate::channel_system::ChannelSystemBuilder::new_clock`].
@@ -177,12 +327,12 @@ impl ProgramGraphBuilder {
             return Err(PgError::NoEffects);
         }
         if clock.0 >= self.clocks {
-            return Err(PgError::MissingClock(clock));
+            return Err(PgError::MissingClock(self.symbols.named_clock(clock)));
         }
         match self
             .effects
             .get_mut(action.0 as usize)
-            .ok_or(PgError::MissingAction(action))?
+            .ok_or_else(|| PgError::MissingAction(self.symbols.named_action(action)))?
         {
             Effect::Effects(_, resets) => {
                 resets.push(clock);
@@ -224,6 +374,23 @@ impl ProgramGraphBuilder {
         if action == EPSILON {
             return Err(PgError::NoEffects);
         }
+        self.check_effect(var, &effect)?;
+        match self
+            .effects
+            .get_mut(action.0 as usize)
+            .ok_or_else(|| PgError::MissingAction(self.symbols.named_action(action)))?
+        {
+            Effect::Effects(effects, _) => {
+                effects.push((var, effect));
+                Ok(())
+            }
+            Effect::Send(_) => Err(PgError::EffectOnSend),
+            Effect::Receive(_) => Err(PgError::EffectOnReceive),
+        }
+    }
+
+    // Checks that the effect is well-typed and can be assigned to the variable.
+    fn check_effect(&self, var: Var, effect: &PgExpression) -> Result<(), PgError> {
         effect
             .context(&|var| self.vars.get(var.0 as usize).map(Val::r#type))
             .map_err(PgError::Type)?;
@@ -231,23 +398,22 @@ impl ProgramGraphBuilder {
             .vars
             .get(var.0 as usize)
             .map(Val::r#type)
-            .ok_or_else(|| PgError::MissingVar(var.to_owned()))?;
-        if var_type == effect.r#type().map_err(PgError::Type)? {
-            match self
-                .effects
-                .get_mut(action.0 as usize)
-                .ok_or(PgError::MissingAction(action))?
-            {
-                Effect::Effects(effects, _) => {
-                    effects.push((var, effect));
-                    Ok(())
-                }
-                Effect::Send(_) => Err(PgError::EffectOnSend),
-                Effect::Receive(_) => Err(PgError::EffectOnReceive),
+            .ok_or_else(|| PgError::MissingVar(self.symbols.named_var(var)))?;
+        let fmt_var = |var: &Var, f: &mut Formatter<'_>| self.symbols.fmt_var(var, f);
+        effect
+            .type_with(&fmt_var)
+            .and_then(|effect_type| effect_type.expect(&var_type))
+            .map_err(|err| PgError::Type(effect.in_expression_with(err, &fmt_var)))?;
+        if let Some(bounds) = self.bounds[var.0 as usize] {
+            let range = effect.bounds(&|var| self.var_bounds(*var).unwrap_or_default());
+            if !range.intersects(&bounds) {
+                warn!(
+                    "effect assigns variable {} values in {range}, out of its bounds {bounds}",
+                    self.symbols.describe_var(var)
+                );
             }
-        } else {
-            Err(PgError::TypeMismatch)
         }
+        Ok(())
     }
 
     pub(crate) fn new_send(&mut self, msg: PgExpression) -> Result<Action, PgError> {
@@ -263,7 +429,7 @@ impl ProgramGraphBuilder {
 
     pub(crate) fn new_receive(&mut self, var: Var) -> Result<Action, PgError> {
         if self.vars.len() as u16 <= var.0 {
-            Err(PgError::MissingVar(var.to_owned()))
+            Err(PgError::MissingVar(self.symbols.named_var(var)))
         } else {
             // Actions are indexed progressively
             let idx = self.effects.len();
@@ -286,11 +452,13 @@ impl ProgramGraphBuilder {
         invariants: Vec<TimeConstraint>,
     ) -> Result<Location, PgError> {
         if let Some((clock, _, _)) = invariants.iter().find(|(c, _, _)| c.0 >= self.clocks) {
-            Err(PgError::MissingClock(*clock))
+            Err(PgError::MissingClock(self.symbols.named_clock(*clock)))
         } else {
             // Locations are indexed progressively
             let idx = self.locations.len();
             self.locations.push((Vec::new(), invariants));
+            self.branches.push(Vec::new());
+            self.urgency.push(Urgency::Normal);
             Ok(Location(idx as u16))
         }
     }
@@ -308,7 +476,9 @@ impl ProgramGraphBuilder {
                 Ok(())
             }
         } else {
-            Err(PgError::MissingLocation(location))
+            Err(PgError::MissingLocation(
+                self.symbols.named_location(location),
+            ))
         }
     }
 
@@ -404,20 +574,23 @@ impl ProgramGraphBuilder {
     ) -> Result<(), PgError> {
         // Check 'pre' and 'post' locations exists
         if self.locations.len() as u16 <= pre.0 {
-            Err(PgError::MissingLocation(pre))
+            Err(PgError::MissingLocation(self.symbols.named_location(pre)))
         } else if self.locations.len() as u16 <= post.0 {
-            Err(PgError::MissingLocation(post))
+            Err(PgError::MissingLocation(self.symbols.named_location(post)))
         } else if action != EPSILON && self.effects.len() as u16 <= action.0 {
             // Check 'action' exists
-            Err(PgError::MissingAction(action))
-        } else if guard
-            .as_ref()
-            .is_some_and(|guard| !matches!(guard.r#type(), Ok(Type::Boolean)))
-        {
-            Err(PgError::TypeMismatch)
+            Err(PgError::MissingAction(self.symbols.named_action(action)))
+        } else if let Some(Err(err)) = guard.as_ref().map(|guard| {
+            let fmt_var = |var: &Var, f: &mut Formatter<'_>| self.symbols.fmt_var(var, f);
+            guard
+                .type_with(&fmt_var)
+                .and_then(Type::boolean)
+                .map_err(|err| guard.in_expression_with(err, &fmt_var))
+        }) {
+            Err(PgError::Type(err))
         } else if let Some((clock, _, _)) = constraints.iter().find(|(c, _, _)| c.0 >= self.clocks)
         {
-            Err(PgError::MissingClock(*clock))
+            Err(PgError::MissingClock(self.symbols.named_clock(*clock)))
         } else {
             if let Some(ref guard) = guard {
                 guard
@@ -425,7 +598,7 @@ impl ProgramGraphBuilder {
                     .map_err(PgError::Type)?;
             }
             let (transitions, _) = &mut self.locations[pre.0 as usize];
-            transitions.push((action, post, guard, constraints));
+            transitions.push((action, post, guard, constraints, 0));
             Ok(())
         }
     }
@@ -497,11 +670,415 @@ impl ProgramGraphBuilder {
         self.add_timed_transition(pre, EPSILON, post, guard, constraints)
     }
 
+    /// Adds a probabilistic transition to the PG.
+    /// Requires specifying:
+    ///
+    /// - state pre-transition,
+    /// - action triggering the transition,
+    /// - (optionally) boolean expression guarding the transition, and
+    /// - the branches of the transition (see [`BranchBuilder`]).
+    ///
+    /// When the transition is executed, the action's effects are applied first,
+    /// then one of the branches is chosen at random with probability proportional to its weight,
+    /// its effects are applied and its post-location becomes the current state.
+    /// Weights are evaluated before the transition takes place,
+    /// and executing the transition fails with [`PgError::InvalidWeights`]
+    /// if any of them is negative or they are all zero.
+    ///
+    /// The transition leads to a (hidden) branching location, which is returned.
+    /// This is the post-state given by [`ProgramGraph::possible_transitions`],
+    /// and has to be passed to [`ProgramGraph::transition`] to execute the transition.
+    /// The transition is admissible only if the time invariants of all branches' post-locations are satisfied.
+    ///
+    /// Fails if the provided guard is not a boolean expression, if a weight is not a numerical expression,
+    /// if an effect cannot be assigned to its variable, or if the action is a communication.
+    ///
+    /// ```
+    /// # use scan_core::program_graph::{PgExpression, ProgramGraphBuilder};
+    /// # let mut pg_builder = ProgramGraphBuilder::new();
+    /// let initial_loc = pg_builder.new_initial_location();
+    /// let heads = pg_builder.new_location();
+    /// let tails = pg_builder.new_location();
+    /// let var = pg_builder.new_var(PgExpression::from(0)).unwrap();
+    /// let toss = pg_builder.new_action();
+    ///
+    /// // Add a transition to `heads` with probability 1/3, and to `tails` with probability 2/3
+    /// pg_builder
+    ///     .add_probabilistic_transition(
+    ///         initial_loc,
+    ///         toss,
+    ///         None,
+    ///         vec![
+    ///             (PgExpression::from(1), heads, vec![(var, PgExpression::from(1))]),
+    ///             (PgExpression::from(2.), tails, vec![(var, PgExpression::from(2))]),
+    ///         ],
+    ///     )
+    ///     .expect("this transition can be added");
+    /// pg_builder
+    ///     .add_probabilistic_transition(
+    ///         initial_loc,
+    ///         toss,
+    ///         None,
+    ///         vec![(PgExpression::from(true), heads, Vec::new())],
+    ///     )
+    ///     .expect_err("the weight is not numerical");
+    /// ```
+    pub fn add_probabilistic_transition(
+        &mut self,
+        pre: Location,
+        action: Action,
+        guard: Option<PgExpression>,
+        branches: Vec<BranchBuilder>,
+    ) -> Result<Location, PgError> {
+        if branches.is_empty() {
+            return Err(PgError::MissingTransition);
+        }
+        if let Some(Effect::Send(_) | Effect::Receive(_)) = self.effects.get(action.0 as usize) {
+            return Err(PgError::Communication(self.symbols.named_action(action)));
+        }
+        let mut invariants = Vec::new();
+        for (weight, post, effects) in &branches {
+            let (_, post_invariants) = self
+                .locations
+                .get(post.0 as usize)
+                .ok_or_else(|| PgError::MissingLocation(self.symbols.named_location(*post)))?;
+            invariants.extend_from_slice(post_invariants);
+            weight
+                .context(&|var| self.vars.get(var.0 as usize).map(Val::r#type))
+                .map_err(PgError::Type)?;
+            let fmt_var = |var: &Var, f: &mut Formatter<'_>| self.symbols.fmt_var(var, f);
+            weight
+                .type_with(&fmt_var)
+                .and_then(|weight_type| {
+                    if weight_type.is_numeric() {
+                        Ok(weight_type)
+                    } else {
+                        Err(TypeError::TypeMismatch {
+                            expected: Expected::Numeric,
+                            found: weight_type,
+                        })
+                    }
+                })
+                .map_err(|err| PgError::Type(weight.in_expression_with(err, &fmt_var)))?;
+            effects
+                .iter()
+                .try_for_each(|(var, effect)| self.check_effect(*var, effect))?;
+        }
+        invariants.sort_unstable();
+        invariants.dedup();
+        let branching = self.new_timed_location(invariants)?;
+        if let Err(err) = self.add_transition(pre, action, branching, guard) {
+            // Remove the unused branching location
+            self.locations.pop();
+            self.branches.pop();
+            self.urgency.pop();
+            return Err(err);
+        }
+        self.branches[branching.0 as usize] = branches;
+        Ok(branching)
+    }
+
+    /// Sets the [`Priority`] of all transitions from the `pre` location to the `post` location.
+    ///
+    /// Fails if there is no such transition.
+    ///
+    /// ```
+    /// # use scan_core::program_graph::{PgExpression, ProgramGraphBuilder};
+    /// # let mut pg_builder = ProgramGraphBuilder::new();
+    /// let initial_loc = pg_builder.new_initial_location();
+    /// let post_loc = pg_builder.new_location();
+    /// let action = pg_builder.new_action();
+    /// pg_builder.add_transition(initial_loc, action, initial_loc, None).unwrap();
+    /// pg_builder.add_transition(initial_loc, action, post_loc, None).unwrap();
+    ///
+    /// // The transition to `post_loc` takes precedence over the other one
+    /// pg_builder
+    ///     .set_priority(initial_loc, post_loc, 1)
+    ///     .expect("the transition exists");
+    /// pg_builder
+    ///     .set_priority(post_loc, initial_loc, 1)
+    ///     .expect_err("there is no such transition");
+    ///
+    /// # use rand::rngs::SmallRng;
+    /// let pg = pg_builder.build::<SmallRng>();
+    /// let (_, mut post_states) = pg.possible_transitions().next().unwrap();
+    /// let post_locs: Vec<_> = post_states.next().unwrap().collect();
+    /// assert_eq!(post_locs, vec![post_loc]);
+    /// ```
+    pub fn set_priority(
+        &mut self,
+        pre: Location,
+        post: Location,
+        priority: Priority,
+    ) -> Result<(), PgError> {
+        let (transitions, _) = self
+            .locations
+            .get_mut(pre.0 as usize)
+            .ok_or_else(|| PgError::MissingLocation(self.symbols.named_location(pre)))?;
+        let mut found = false;
+        transitions
+            .iter_mut()
+            .filter(|(_, p, ..)| *p == post)
+            .for_each(|(.., transition_priority)| {
+                *transition_priority = priority;
+                found = true;
+            });
+        if found {
+            Ok(())
+        } else {
+            Err(PgError::MissingTransition)
+        }
+    }
+
+    /// Sets the [`Urgency`] of the given location.
+    ///
+    /// Time cannot pass while any process is in an urgent or committed location.
+    ///
+    /// ```
+    /// # use scan_core::program_graph::{ProgramGraphBuilder, Urgency};
+    /// # let mut pg_builder = ProgramGraphBuilder::new();
+    /// let initial_loc = pg_builder.new_initial_location();
+    /// pg_builder
+    ///     .set_urgency(initial_loc, Urgency::Urgent)
+    ///     .expect("the location exists");
+    ///
+    /// # use rand::rngs::SmallRng;
+    /// let mut pg = pg_builder.build::<SmallRng>();
+    /// assert!(!pg.can_wait(1));
+    /// assert!(pg.wait(1).is_err());
+    /// ```
+    pub fn set_urgency(&mut self, location: Location, urgency: Urgency) -> Result<(), PgError> {
+        *self
+            .urgency
+            .get_mut(location.0 as usize)
+            .ok_or_else(|| PgError::MissingLocation(self.symbols.named_location(location)))? =
+            urgency;
+        Ok(())
+    }
+
+    // Checks that the variable is of float type, as continuous variables are.
+    fn check_continuous(&self, var: Var) -> Result<(), PgError> {
+        match self.vars.get(var.0 as usize) {
+            None => Err(PgError::MissingVar(self.symbols.named_var(var))),
+            Some(Val::Float(_)) => Ok(()),
+            Some(val) => Err(PgError::Type(TypeError::TypeMismatch {
+                expected: Expected::Type(Type::Float),
+                found: val.r#type(),
+            })),
+        }
+    }
+
+    /// Makes a float variable a continuous variable,
+    /// which changes linearly over time at the given rate
+    /// (unless the current location sets a different one, see [`Self::set_location_rate`]).
+    ///
+    /// As time is discrete, waiting `delta` time-units increases the variable by `rate * delta`.
+    ///
+    /// It fails if the variable is not of float type.
+    ///
+    /// ```
+    /// # use scan_core::program_graph::{PgExpression, ProgramGraphBuilder};
+    /// # let mut pg_builder = ProgramGraphBuilder::new();
+    /// let initial_loc = pg_builder.new_initial_location();
+    /// let battery = pg_builder.new_var(PgExpression::from(100.)).unwrap();
+    /// // The battery drains while idle
+    /// pg_builder.set_rate(battery, -0.5).expect("battery is a float variable");
+    ///
+    /// # use rand::rngs::SmallRng;
+    /// let mut pg = pg_builder.build::<SmallRng>();
+    /// pg.wait(4).unwrap();
+    /// ```
+    pub fn set_rate(&mut self, var: Var, rate: Float) -> Result<(), PgError> {
+        self.check_continuous(var)?;
+        if let Some((_, default)) = self.rates.iter_mut().find(|(v, _)| *v == var) {
+            *default = rate;
+        } else {
+            self.rates.push((var, rate));
+        }
+        Ok(())
+    }
+
+    /// Sets the rate at which a continuous variable changes over time while in the given location,
+    /// overriding the variable's default rate (which is `0` unless set by [`Self::set_rate`]).
+    ///
+    /// If multiple processes are in locations setting the rate of the same variable,
+    /// the variable changes at the sum of their rates.
+    ///
+    /// It fails if the variable is not of float type.
+    pub fn set_location_rate(
+        &mut self,
+        location: Location,
+        var: Var,
+        rate: Float,
+    ) -> Result<(), PgError> {
+        if location.0 as usize >= self.locations.len() {
+            return Err(PgError::MissingLocation(
+                self.symbols.named_location(location),
+            ));
+        }
+        self.check_continuous(var)?;
+        if !self.rates.iter().any(|(v, _)| *v == var) {
+            self.rates.push((var, 0.));
+        }
+        let rates = self.location_rates.entry(location).or_default();
+        if let Some((_, location_rate)) = rates.iter_mut().find(|(v, _)| *v == var) {
+            *location_rate = rate;
+        } else {
+            rates.push((var, rate));
+        }
+        Ok(())
+    }
+
+    /// Stops the clock while in the given location, making it a stopwatch:
+    /// the clock does not advance as long as any process is in a location stopping it.
+    ///
+    /// ```
+    /// # use scan_core::program_graph::ProgramGraphBuilder;
+    /// # let mut pg_builder = ProgramGraphBuilder::new();
+    /// let clock = pg_builder.new_clock();
+    /// let initial_loc = pg_builder
+    ///     .new_initial_timed_location(vec![(clock, None, Some(1))])
+    ///     .unwrap();
+    /// pg_builder.stop_clock(initial_loc, clock).expect("clock and location exist");
+    ///
+    /// # use rand::rngs::SmallRng;
+    /// let mut pg = pg_builder.build::<SmallRng>();
+    /// // The invariant is never violated, as the clock is stopped
+    /// assert!(pg.can_wait(10));
+    /// ```
+    pub fn stop_clock(&mut self, location: Location, clock: Clock) -> Result<(), PgError> {
+        if location.0 as usize >= self.locations.len() {
+            Err(PgError::MissingLocation(
+                self.symbols.named_location(location),
+            ))
+        } else if clock.0 >= self.clocks {
+            Err(PgError::MissingClock(self.symbols.named_clock(clock)))
+        } else {
+            let stopped = self.stopped_clocks.entry(location).or_default();
+            if !stopped.contains(&clock) {
+                stopped.push(clock);
+            }
+            Ok(())
+        }
+    }
+
+    /// Sets an invariant of the given location, i.e., a boolean expression restricting the passing of time:
+    /// it is not possible to wait while in the location
+    /// if the invariant would not hold at the end of the wait.
+    ///
+    /// Contrary to time invariants (see [`Self::new_timed_location`]),
+    /// invariants can refer to variables, and to continuous variables in particular,
+    /// but they are not checked when entering the location.
+    ///
+    /// It fails if the invariant is not a well-typed boolean expression.
+    pub fn set_invariant(
+        &mut self,
+        location: Location,
+        invariant: PgExpression,
+    ) -> Result<(), PgError> {
+        if location.0 as usize >= self.locations.len() {
+            return Err(PgError::MissingLocation(
+                self.symbols.named_location(location),
+            ));
+        }
+        invariant
+            .context(&|var| self.vars.get(var.0 as usize).map(Val::r#type))
+            .map_err(PgError::Type)?;
+        let fmt_var = |var: &Var, f: &mut Formatter<'_>| self.symbols.fmt_var(var, f);
+        invariant
+            .type_with(&fmt_var)
+            .and_then(Type::boolean)
+            .map_err(|err| PgError::Type(invariant.in_expression_with(err, &fmt_var)))?;
+        self.invariants.insert(location, invariant);
+        Ok(())
+    }
+
+    /// The [`Symbol`]s of the PG's objects defined so far.
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    /// Associates a [`Symbol`] to the location,
+    /// so that it can be referred to by name, e.g., in errors and logs.
+    ///
+    /// ```
+    /// # use scan_core::program_graph::{ProgramGraphBuilder, Symbol};
+    /// # let mut pg_builder = ProgramGraphBuilder::new();
+    /// let initial_loc = pg_builder.new_initial_location();
+    /// pg_builder
+    ///     .set_location_symbol(initial_loc, "start")
+    ///     .expect("the location exists");
+    ///
+    /// # use rand::rngs::SmallRng;
+    /// let pg = pg_builder.build::<SmallRng>();
+    /// assert_eq!(pg.symbols().location(initial_loc), Some(&Symbol::new("start")));
+    /// ```
+    pub fn set_location_symbol(
+        &mut self,
+        location: Location,
+        symbol: impl Into<Symbol>,
+    ) -> Result<(), PgError> {
+        if (location.0 as usize) < self.locations.len() {
+            self.symbols.locations.insert(location, symbol.into());
+            Ok(())
+        } else {
+            Err(PgError::MissingLocation(
+                self.symbols.named_location(location),
+            ))
+        }
+    }
+
+    /// Associates a [`Symbol`] to the action,
+    /// so that it can be referred to by name, e.g., in errors and logs.
+    pub fn set_action_symbol(
+        &mut self,
+        action: Action,
+        symbol: impl Into<Symbol>,
+    ) -> Result<(), PgError> {
+        if (action.0 as usize) < self.effects.len() {
+            self.symbols.actions.insert(action, symbol.into());
+            Ok(())
+        } else {
+            Err(PgError::MissingAction(self.symbols.named_action(action)))
+        }
+    }
+
+    /// Associates a [`Symbol`] to the variable,
+    /// so that it can be referred to by name, e.g., in errors and logs.
+    pub fn set_var_symbol(&mut self, var: Var, symbol: impl Into<Symbol>) -> Result<(), PgError> {
+        if (var.0 as usize) < self.vars.len() {
+            self.symbols.vars.insert(var, symbol.into());
+            Ok(())
+        } else {
+            Err(PgError::MissingVar(self.symbols.named_var(var)))
+        }
+    }
+
+    /// Associates a [`Symbol`] to the clock,
+    /// so that it can be referred to by name, e.g., in errors and logs.
+    pub fn set_clock_symbol(
+        &mut self,
+        clock: Clock,
+        symbol: impl Into<Symbol>,
+    ) -> Result<(), PgError> {
+        if clock.0 < self.clocks {
+            self.symbols.clocks.insert(clock, symbol.into());
+            Ok(())
+        } else {
+            Err(PgError::MissingClock(self.symbols.named_clock(clock)))
+        }
+    }
+
     /// Produces a [`ProgramGraph`] defined by the [`ProgramGraphBuilder`]'s data and consuming it.
     ///
     /// Since the construction of the builder is already checked ad every step,
     /// this method cannot fail.
     pub fn build<R: Rng + 'static>(mut self) -> ProgramGraph<R> {
+        // The definition is retained as it is to allow its inspection, e.g., by static analysis
+        let source = self.clone();
+        if self.compression {
+            self.compress();
+        }
         // Since vectors of effects and transitions will become unmutable,
         // they should be shrunk to take as little space as possible
         self.effects.iter_mut().for_each(|effect| {
@@ -513,15 +1090,35 @@ impl ProgramGraphBuilder {
         // Vars are not going to be unmutable,
         // but their number will be constant anyway
         self.vars.shrink_to_fit();
+        let prioritized = self
+            .locations
+            .iter()
+            .flat_map(|(transitions, _)| transitions)
+            .any(|(.., priority)| *priority > 0);
+        let stopwatches = !self.stopped_clocks.is_empty();
+        // Flows are only needed if some location defines one
+        let flows = if self.location_rates.is_empty() && !stopwatches && self.invariants.is_empty()
+        {
+            Vec::new()
+        } else {
+            (0..self.locations.len() as u16)
+                .map(Location)
+                .map(|loc| Flow {
+                    rates: self.location_rates.remove(&loc).unwrap_or_default(),
+                    stopped: self.stopped_clocks.remove(&loc).unwrap_or_default(),
+                    invariant: self.invariants.remove(&loc).map(FnExpression::from),
+                })
+                .collect()
+        };
         let mut locations = self
             .locations
             .into_iter()
             .map(|(transitions, mut invariants)| {
                 let mut transitions = transitions
                     .into_iter()
-                    .map(|(a, p, guard, mut c)| {
+                    .map(|(a, p, guard, mut c, priority)| {
                         c.sort_unstable();
-                        (a, p, guard.map(FnExpression::from), c)
+                        (a, p, guard.map(FnExpression::from), c, priority)
                     })
                     .collect::<Vec<_>>();
                 transitions.sort_unstable_by_key(|(a, p, ..)| (*a, *p));
@@ -540,8 +1137,39 @@ impl ProgramGraphBuilder {
             self.vars.len()
         );
         let def = ProgramGraphDef {
+            overflow: self.overflow,
+            bounds: self.bounds,
             effects: self.effects.into_iter().map(FnEffect::from).collect(),
             locations,
+            branches: self
+                .branches
+                .into_iter()
+                .map(|branches| {
+                    branches
+                        .into_iter()
+                        .map(|(weight, post, effects)| -> Branch<R> {
+                            (
+                                FnExpression::from(weight),
+                                post,
+                                effects
+                                    .into_iter()
+                                    .map(|(var, effect)| (var, FnExpression::from(effect)))
+                                    .collect(),
+                            )
+                        })
+                        .collect()
+                })
+                .collect(),
+            prioritized,
+            urgent: self
+                .urgency
+                .iter()
+                .any(|urgency| *urgency != Urgency::Normal),
+            urgency: self.urgency,
+            continuous: self.rates,
+            flows,
+            stopwatches,
+            source,
         };
         self.initial_states.sort_unstable();
         self.initial_states.shrink_to_fit();
@@ -0,0 +1,206 @@
+// Compression of chains of internal transitions, to reduce the number of steps of executions.
+//
+// A transition executing an internal action (i.e., effects or no action at all)
+// whose post-location can only be left by an unguarded internal transition
+// is redirected to the latter's post-location, executing the composed effects.
+// Intermediate locations are kept, as they might be reachable in other ways,
+// so that locations and actions preserve their indices.
+
+use super::{builder::Effect, *};
+use log::info;
+
+impl ProgramGraphBuilder {
+    // Whether the action does not communicate.
+    fn is_internal(&self, action: Action) -> bool {
+        action == EPSILON || matches!(self.effects[action.0 as usize], Effect::Effects(..))
+    }
+
+    // Whether the action has no effects.
+    fn is_trivial(&self, action: Action) -> bool {
+        action == EPSILON
+            || matches!(
+                &self.effects[action.0 as usize],
+                Effect::Effects(effects, resets) if effects.is_empty() && resets.is_empty()
+            )
+    }
+
+    // The internal transition leaving the location as soon as it is entered,
+    // if it is the only way to leave it.
+    fn forced_transition(&self, loc: Location) -> Option<(Action, Location)> {
+        let (transitions, invariants) = &self.locations[loc.0 as usize];
+        match transitions.as_slice() {
+            [(action, post, None, constraints, _)]
+                if *post != loc
+                    && constraints.is_empty()
+                    && invariants.is_empty()
+                    && self.branches[loc.0 as usize].is_empty()
+                    && !self.location_rates.contains_key(&loc)
+                    && !self.stopped_clocks.contains_key(&loc)
+                    && !self.invariants.contains_key(&loc)
+                    && self.is_internal(*action) =>
+            {
+                Some((*action, *post))
+            }
+            _ => None,
+        }
+    }
+
+    // An action executing the effects of the given internal actions in sequence.
+    // If at most one of them has effects, it is reused instead of creating a new action.
+    fn compose(&mut self, actions: &[Action]) -> Action {
+        let nontrivial = actions
+            .iter()
+            .copied()
+            .filter(|action| !self.is_trivial(*action))
+            .collect::<Vec<_>>();
+        match nontrivial.as_slice() {
+            [] => actions[0],
+            [action] => *action,
+            _ => {
+                let mut effects = Vec::new();
+                let mut resets = Vec::new();
+                for action in &nontrivial {
+                    if let Effect::Effects(e, r) = &self.effects[action.0 as usize] {
+                        effects.extend(e.iter().cloned());
+                        resets.extend(r.iter().copied());
+                    }
+                }
+                // Clocks are reset after all effects have been executed,
+                // and effects cannot depend on clocks
+                resets.sort_unstable();
+                resets.dedup();
+                let composed = Action(self.effects.len() as u16);
+                self.effects.push(Effect::Effects(effects, resets));
+                let names = nontrivial
+                    .iter()
+                    .map(|action| self.symbols.action(*action).map(|symbol| &symbol.name))
+                    .collect::<Option<Vec<_>>>();
+                if let Some(names) = names {
+                    let name = names
+                        .into_iter()
+                        .map(String::as_str)
+                        .collect::<Vec<_>>()
+                        .join("; ");
+                    self.symbols.actions.insert(composed, Symbol::new(name));
+                }
+                composed
+            }
+        }
+    }
+
+    // Merges chains of internal transitions, see `ProgramGraphBuilder::set_compression`.
+    pub(super) fn compress(&mut self) {
+        // Actions are shared by all processes, so they cannot be composed for one process only
+        if self.initial_states.len() > 1 {
+            return;
+        }
+        let reachable_before = self.reachable_count();
+        let mut merged = 0;
+        let mut skipped = 0;
+        for pre in 0..self.locations.len() {
+            for idx in 0..self.locations[pre].0.len() {
+                let (action, post, ..) = self.locations[pre].0[idx];
+                if !self.is_internal(action) {
+                    continue;
+                }
+                // Follow the chain of forced transitions, without going around cycles
+                let mut visited = vec![Location(pre as u16)];
+                let mut actions = vec![action];
+                let mut last = post;
+                while !visited.contains(&last)
+                    && let Some((next_action, next)) = self.forced_transition(last)
+                {
+                    visited.push(last);
+                    actions.push(next_action);
+                    last = next;
+                }
+                if actions.len() > 1 {
+                    let composed = self.compose(&actions);
+                    let transition = &mut self.locations[pre].0[idx];
+                    transition.0 = composed;
+                    transition.1 = last;
+                    merged += 1;
+                    skipped += actions.len() - 1;
+                }
+            }
+        }
+        if merged > 0 {
+            info!(
+                "compressed Program Graph: merged {merged} transitions skipping {skipped} steps, {} reachable locations out of {reachable_before}",
+                self.reachable_count(),
+            );
+        }
+    }
+
+    // Number of locations reachable from the initial locations.
+    fn reachable_count(&self) -> usize {
+        self.reachable_locations(&self.false_guards(), |_| true)
+            .into_iter()
+            .filter(|reachable| *reachable)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compress() -> Result<(), PgError> {
+        let mut builder = ProgramGraphBuilder::new();
+        builder.set_compression(true);
+        let var = builder.new_var(PgExpression::from(0))?;
+        let initial = builder.new_initial_location();
+        let first = builder.new_location();
+        let second = builder.new_location();
+        let third = builder.new_location();
+        let last = builder.new_location();
+        let increment = builder.new_action();
+        builder.add_effect(
+            increment,
+            var,
+            PgExpression::Sum(vec![
+                PgExpression::Var(var, Type::Integer),
+                PgExpression::from(1),
+            ]),
+        )?;
+        let double = builder.new_action();
+        builder.add_effect(
+            double,
+            var,
+            PgExpression::Mult(vec![
+                PgExpression::Var(var, Type::Integer),
+                PgExpression::from(2),
+            ]),
+        )?;
+        let send = builder.new_send(PgExpression::Var(var, Type::Integer))?;
+        builder.add_transition(initial, increment, first, None)?;
+        builder.add_autonomous_transition(first, second, None)?;
+        builder.add_transition(second, double, third, None)?;
+        // Communications are never merged
+        builder.add_transition(third, send, last, None)?;
+        // Guarded transitions are not merged
+        builder.add_transition(
+            last,
+            increment,
+            initial,
+            Some(PgExpression::Greater(Box::new((
+                PgExpression::Var(var, Type::Integer),
+                PgExpression::from(10),
+            )))),
+        )?;
+        let mut pg = builder.build::<SmallRng>();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let (action, posts) = pg
+            .possible_transitions()
+            .map(|(action, posts)| (action, posts.into_iter().flatten().collect::<Vec<_>>()))
+            .next()
+            .expect("initial transition");
+        assert_eq!(posts, vec![third]);
+        pg.transition(action, &[third], &mut rng)?;
+        assert_eq!(pg.val(var)?, &Val::Integer(2));
+        // The uncompressed definition is analyzed
+        assert!(pg.analyze().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+// Export of program graphs in the Graphviz DOT language, to visualize their definition.
+//
+// Locations are drawn as nodes and transitions as edges,
+// labelled with their action, guard, time constraints and effects.
+// Branching locations of probabilistic transitions are drawn as points,
+// with an edge for each branch labelled with its weight and effects.
+
+use super::{builder::Effect, *};
+use std::fmt::{self, Write};
+
+// The name of the object, if it has a symbol, or its index otherwise.
+pub(crate) fn symbol_name(symbol: Option<&Symbol>, object: impl std::fmt::Debug) -> String {
+    symbol.map_or_else(|| format!("{object:?}"), |symbol| symbol.name.clone())
+}
+
+// Escapes a string to be used as a quoted DOT identifier.
+pub(crate) fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl ProgramGraphBuilder {
+    fn var_name(&self, var: Var) -> String {
+        symbol_name(self.symbols.var(var), var)
+    }
+
+    fn clock_name(&self, clock: Clock) -> String {
+        symbol_name(self.symbols.clock(clock), clock)
+    }
+
+    fn expression(&self, expr: &PgExpression) -> String {
+        expr.display_with(|var, f| write!(f, "{}", self.var_name(*var)))
+            .to_string()
+    }
+
+    fn time_constraint(&self, (clock, lower, upper): &TimeConstraint) -> String {
+        let clock = self.clock_name(*clock);
+        match (lower, upper) {
+            (Some(lower), Some(upper)) => format!("{lower} <= {clock} < {upper}"),
+            (Some(lower), None) => format!("{lower} <= {clock}"),
+            (None, Some(upper)) => format!("{clock} < {upper}"),
+            (None, None) => clock,
+        }
+    }
+
+    fn assignments(&self, effects: &[(Var, PgExpression)]) -> impl Iterator<Item = String> {
+        effects
+            .iter()
+            .map(|(var, expr)| format!("{} := {}", self.var_name(*var), self.expression(expr)))
+    }
+
+    fn location_label(&self, loc: Location) -> String {
+        let (_, invariants) = &self.locations[loc.0 as usize];
+        let mut lines = vec![symbol_name(self.symbols.location(loc), loc)];
+        match self.urgency.get(loc.0 as usize) {
+            Some(Urgency::Urgent) => lines.push(String::from("urgent")),
+            Some(Urgency::Committed) => lines.push(String::from("committed")),
+            _ => {}
+        }
+        lines.extend(
+            invariants
+                .iter()
+                .map(|constraint| self.time_constraint(constraint)),
+        );
+        if let Some(invariant) = self.invariants.get(&loc) {
+            lines.push(self.expression(invariant));
+        }
+        if let Some(rates) = self.location_rates.get(&loc) {
+            lines.extend(
+                rates
+                    .iter()
+                    .map(|(var, rate)| format!("{}' = {rate}", self.var_name(*var))),
+            );
+        }
+        if let Some(clocks) = self.stopped_clocks.get(&loc) {
+            lines.extend(
+                clocks
+                    .iter()
+                    .map(|clock| format!("{} stopped", self.clock_name(*clock))),
+            );
+        }
+        lines.join("\n")
+    }
+
+    // The label of a transition, given by its action, guard, time constraints, effects and priority.
+    // Channels are not known to the PG, so they are given by the `channel` function, if any:
+    // sending and receiving actions are prefixed with the channel,
+    // while other communicating actions are described by it.
+    fn transition_label(
+        &self,
+        (action, _, guard, constraints, priority): &TransitionBuilder,
+        channel: &dyn Fn(Action) -> Option<String>,
+    ) -> String {
+        let mut lines = Vec::new();
+        if *action != EPSILON {
+            lines.push(symbol_name(self.symbols.action(*action), action));
+        }
+        if let Some(guard) = guard {
+            lines.push(format!("[{}]", self.expression(guard)));
+        }
+        lines.extend(
+            constraints
+                .iter()
+                .map(|constraint| self.time_constraint(constraint)),
+        );
+        if *action != EPSILON {
+            let channel = channel(*action);
+            match &self.effects[action.0 as usize] {
+                Effect::Effects(effects, resets) => {
+                    lines.extend(channel);
+                    lines.extend(self.assignments(effects));
+                    lines.extend(
+                        resets
+                            .iter()
+                            .map(|clock| format!("{} := 0", self.clock_name(*clock))),
+                    );
+                }
+                Effect::Send(msg) => lines.push(format!(
+                    "{}!{}",
+                    channel.unwrap_or_default(),
+                    self.expression(msg)
+                )),
+                Effect::Receive(var) => lines.push(format!(
+                    "{}?{}",
+                    channel.unwrap_or_default(),
+                    self.var_name(*var)
+                )),
+            }
+        }
+        if *priority > 0 {
+            lines.push(format!("priority {priority}"));
+        }
+        lines.join("\n")
+    }
+
+    fn write_dot(
+        &self,
+        out: &mut String,
+        prefix: &str,
+        channel: &dyn Fn(Action) -> Option<String>,
+    ) -> fmt::Result {
+        for (idx, (transitions, _)) in self.locations.iter().enumerate() {
+            let loc = Location(idx as u16);
+            if self.branches[idx].is_empty() {
+                let label = escape(&self.location_label(loc));
+                writeln!(out, "    {prefix}l{idx} [label=\"{label}\"];")?;
+            } else {
+                writeln!(out, "    {prefix}l{idx} [shape=point];")?;
+                for (weight, post, effects) in &self.branches[idx] {
+                    let label = std::iter::once(self.expression(weight))
+                        .chain(self.assignments(effects))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    writeln!(
+                        out,
+                        "    {prefix}l{idx} -> {prefix}l{} [label=\"{}\", style=dashed];",
+                        post.0,
+                        escape(&label)
+                    )?;
+                }
+            }
+            for transition in transitions {
+                writeln!(
+                    out,
+                    "    {prefix}l{idx} -> {prefix}l{} [label=\"{}\"];",
+                    transition.1.0,
+                    escape(&self.transition_label(transition, channel))
+                )?;
+            }
+        }
+        // Initial locations are pointed to by an arrow coming from nowhere
+        writeln!(out, "    {prefix}initial [shape=point, style=invis];")?;
+        for loc in &self.initial_states {
+            writeln!(out, "    {prefix}initial -> {prefix}l{};", loc.0)?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Rng> ProgramGraph<R> {
+    /// Renders the PG's definition in the Graphviz DOT language,
+    /// with locations as nodes and transitions as edges
+    /// labelled with their action, guard, time constraints and effects.
+    ///
+    /// Objects are referred to by their symbols, if any.
+    /// Compression does not apply, as the PG is rendered as it was defined
+    /// (see [`ProgramGraphBuilder::set_compression`]).
+    ///
+    /// ```
+    /// # use scan_core::program_graph::*;
+    /// # use rand::rngs::SmallRng;
+    /// let mut pg_builder = ProgramGraphBuilder::new();
+    /// let initial = pg_builder.new_initial_location();
+    /// let action = pg_builder.new_action();
+    /// pg_builder.set_action_symbol(action, "tick").unwrap();
+    /// pg_builder.add_transition(initial, action, initial, None).unwrap();
+    /// let pg = pg_builder.build::<SmallRng>();
+    /// assert!(pg.to_dot().contains("l0 -> l0 [label=\"tick\"];"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        self.write_dot(&mut dot, "", &|_| None)
+            .expect("writing to a string cannot fail");
+        dot.push_str("}\n");
+        dot
+    }
+
+    // Writes the PG's locations and transitions as DOT statements,
+    // prefixing the identifiers of the nodes with the given prefix.
+    pub(crate) fn write_dot(
+        &self,
+        out: &mut String,
+        prefix: &str,
+        channel: &dyn Fn(Action) -> Option<String>,
+    ) -> fmt::Result {
+        self.def.source.write_dot(out, prefix, channel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_dot() -> Result<(), PgError> {
+        let mut builder = ProgramGraphBuilder::new();
+        let var = builder.new_var(PgExpression::from(0))?;
+        builder.set_var_symbol(var, "x")?;
+        let clock = builder.new_clock();
+        let initial = builder.new_initial_location();
+        builder.set_location_symbol(initial, "idle")?;
+        let busy = builder.new_timed_location(vec![(clock, None, Some(5))])?;
+        let action = builder.new_action();
+        builder.add_effect(
+            action,
+            var,
+            PgExpression::Sum(vec![
+                PgExpression::Var(var, Type::Integer),
+                PgExpression::from(1),
+            ]),
+        )?;
+        builder.add_reset(action, clock)?;
+        builder.add_transition(
+            initial,
+            action,
+            busy,
+            Some(PgExpression::Less(Box::new((
+                PgExpression::Var(var, Type::Integer),
+                PgExpression::from(3),
+            )))),
+        )?;
+        builder.add_autonomous_timed_transition(
+            busy,
+            initial,
+            None,
+            vec![(clock, Some(2), None)],
+        )?;
+        let pg = builder.build::<SmallRng>();
+        assert_eq!(
+            pg.to_dot(),
+            "digraph {
+    l0 [label=\"idle\"];
+    l0 -> l1 [label=\"Action(0)\\n[x < 3]\\nx := x + 1\\nClock(0) := 0\"];
+    l1 [label=\"Location(1)\\nClock(0) < 5\"];
+    l1 -> l0 [label=\"2 <= Clock(0)\"];
+    initial [shape=point, style=invis];
+    initial -> l0;
+}
+"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+// Names and source metadata of the objects of a PG,
+// used to make errors, logs and traces readable.
+
+use super::{Action, Clock, Location, Var};
+use crate::grammar::SourceLocation;
+use std::collections::HashMap;
+
+/// The name of an object (e.g., a location or a variable) of a model,
+/// optionally with the position in the source files where it is defined.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Symbol {
+    /// The name of the object.
+    pub name: String,
+    /// The position of the object's definition in the source files, if known.
+    pub source: Option<SourceLocation>,
+}
+
+impl Symbol {
+    /// Creates a symbol with the given name and no source metadata.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            source: None,
+        }
+    }
+
+    /// Sets the position of the object's definition in the source files.
+    pub fn with_source(mut self, source: SourceLocation) -> Self {
+        self.source = Some(source);
+        self
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}'", self.name)?;
+        if let Some(source) = &self.source {
+            write!(f, " (defined at {source})")?;
+        }
+        Ok(())
+    }
+}
+
+/// The [`Symbol`]s of the locations, actions, variables and clocks of a PG.
+///
+/// Objects are not required to have a symbol.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    pub(super) locations: HashMap<Location, Symbol>,
+    pub(super) actions: HashMap<Action, Symbol>,
+    pub(super) vars: HashMap<Var, Symbol>,
+    pub(super) clocks: HashMap<Clock, Symbol>,
+}
+
+impl SymbolTable {
+    /// The symbol of the location, if any.
+    pub fn location(&self, location: Location) -> Option<&Symbol> {
+        self.locations.get(&location)
+    }
+
+    /// The symbol of the action, if any.
+    pub fn action(&self, action: Action) -> Option<&Symbol> {
+        self.actions.get(&action)
+    }
+
+    /// The symbol of the variable, if any.
+    pub fn var(&self, var: Var) -> Option<&Symbol> {
+        self.vars.get(&var)
+    }
+
+    /// The symbol of the clock, if any.
+    pub fn clock(&self, clock: Clock) -> Option<&Symbol> {
+        self.clocks.get(&clock)
+    }
+
+    pub(crate) fn named_location(&self, location: Location) -> Named<Location> {
+        Named::new(location, self.location(location))
+    }
+
+    pub(crate) fn named_action(&self, action: Action) -> Named<Action> {
+        Named::new(action, self.action(action))
+    }
+
+    pub(crate) fn named_var(&self, var: Var) -> Named<Var> {
+        Named::new(var, self.var(var))
+    }
+
+    pub(crate) fn named_clock(&self, clock: Clock) -> Named<Clock> {
+        Named::new(clock, self.clock(clock))
+    }
+
+    // Formats the variable by the name of its symbol, if any, e.g., within expressions.
+    pub(crate) fn fmt_var(&self, var: &Var, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.var(*var) {
+            Some(symbol) => f.write_str(&symbol.name),
+            None => write!(f, "{var:?}"),
+        }
+    }
+
+    /// Describes the location by its symbol, if any, or by its index otherwise.
+    pub fn describe_location(&self, location: Location) -> String {
+        describe(self.location(location), location)
+    }
+
+    /// Describes the action by its symbol, if any, or by its index otherwise.
+    pub fn describe_action(&self, action: Action) -> String {
+        describe(self.action(action), action)
+    }
+
+    /// Describes the variable by its symbol, if any, or by its index otherwise.
+    pub fn describe_var(&self, var: Var) -> String {
+        describe(self.var(var), var)
+    }
+
+    /// Describes the clock by its symbol, if any, or by its index otherwise.
+    pub fn describe_clock(&self, clock: Clock) -> String {
+        describe(self.clock(clock), clock)
+    }
+}
+
+/// An object of a model together with its [`Symbol`], if any,
+/// so that errors can refer to the object by name.
+///
+/// It is displayed as its symbol, if any, or as its index otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Named<T> {
+    /// The object.
+    pub object: T,
+    /// The symbol of the object, if any.
+    pub symbol: Option<Box<Symbol>>,
+}
+
+impl<T> Named<T> {
+    pub(crate) fn new(object: T, symbol: Option<&Symbol>) -> Self {
+        Self {
+            object,
+            symbol: symbol.cloned().map(Box::new),
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Display for Named<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&describe(self.symbol.as_deref(), &self.object))
+    }
+}
+
+pub(crate) fn describe(symbol: Option<&Symbol>, object: impl std::fmt::Debug) -> String {
+    symbol.map_or_else(|| format!("{object:?}"), Symbol::to_string)
+}
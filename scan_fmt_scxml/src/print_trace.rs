@@ -113,13 +113,33 @@ impl Tracer<Event> for TracePrinter {
         let mut param_name = String::new();
         let mut param_value = String::new();
         fields.push(time.as_str());
+        // Faults are reported next to the kind of event,
+        // which is otherwise treated as the communication they affect.
+        let (fault, event_type) = match event.event_type {
+            EventType::Dropped(ref val) => {
+                (Some(String::from("dropped")), EventType::Send(val.clone()))
+            }
+            EventType::Duplicated(ref val) => (
+                Some(String::from("duplicated")),
+                EventType::Send(val.clone()),
+            ),
+            EventType::Delayed(ref val, delay) => (
+                Some(format!("delayed {delay}")),
+                EventType::Send(val.clone()),
+            ),
+            EventType::Reordered(ref val) => (
+                Some(String::from("reordered")),
+                EventType::Receive(val.clone()),
+            ),
+            ref event_type => (None, event_type.clone()),
+        };
 
         if let Some((src, trg, event_idx, param)) = self.model.parameters.get(&event.channel) {
             origin_name = self.model.fsm_names.get(src).unwrap().to_owned();
             target_name = self.model.fsm_names.get(trg).unwrap().to_owned();
             event_name = self.model.events.get(*event_idx).unwrap().clone();
             param_name = param.to_owned();
-            match event.event_type {
+            match event_type {
                 EventType::Send(ref val) => {
                     action = "S".to_string();
                     param_value = format!("{val:?}");
@@ -128,11 +148,17 @@ impl Tracer<Event> for TracePrinter {
                     action = "R".to_string();
                     param_value = format!("{val:?}");
                 }
-                EventType::ProbeEmptyQueue | EventType::ProbeFullQueue => return,
+                EventType::Handshake(..)
+                | EventType::ProbeEmptyQueue
+                | EventType::ProbeFullQueue
+                | EventType::Dropped(_)
+                | EventType::Duplicated(_)
+                | EventType::Delayed(..)
+                | EventType::Reordered(_) => return,
             }
         } else if let Some(trg) = self.model.ext_queues.get(&event.channel) {
             target_name = self.model.fsm_names.get(trg).unwrap().to_owned();
-            match event.event_type {
+            match event_type {
                 EventType::Send(ref val) => {
                     action = "S".to_string();
                     if let Val::Tuple(e) = val {
@@ -171,12 +197,18 @@ impl Tracer<Event> for TracePrinter {
                         panic!("events should be pairs");
                     }
                 }
-                EventType::ProbeEmptyQueue | EventType::ProbeFullQueue => return,
+                EventType::Handshake(..)
+                | EventType::ProbeEmptyQueue
+                | EventType::ProbeFullQueue
+                | EventType::Dropped(_)
+                | EventType::Duplicated(_)
+                | EventType::Delayed(..)
+                | EventType::Reordered(_) => return,
             }
         } else if self.model.int_queues.contains(&event.channel) {
             origin_name = self.model.fsm_names.get(&event.pg_id).unwrap().to_owned();
             target_name = origin_name.clone();
-            match event.event_type {
+            match event_type {
                 EventType::Send(ref val) => {
                     action = "S".to_string();
                     if let Val::Integer(sent_event) = val {
@@ -193,12 +225,18 @@ impl Tracer<Event> for TracePrinter {
                         panic!("events should be indexed by integer");
                     }
                 }
-                EventType::ProbeEmptyQueue | EventType::ProbeFullQueue => return,
+                EventType::Handshake(..)
+                | EventType::ProbeEmptyQueue
+                | EventType::ProbeFullQueue
+                | EventType::Dropped(_)
+                | EventType::Duplicated(_)
+                | EventType::Delayed(..)
+                | EventType::Reordered(_) => return,
             }
         } else {
             event_name = String::new();
             param_name = String::new();
-            match event.event_type {
+            match event_type {
                 EventType::Send(ref val) => {
                     origin_name = self.model.fsm_names.get(&event.pg_id).unwrap().to_owned();
                     target_name = format!("{:?}", event.channel);
@@ -209,9 +247,22 @@ impl Tracer<Event> for TracePrinter {
                     target_name = self.model.fsm_names.get(&event.pg_id).unwrap().to_owned();
                     param_value = format!("{val:?}");
                 }
-                EventType::ProbeEmptyQueue | EventType::ProbeFullQueue => return,
+                EventType::Handshake(receiver, ref val) => {
+                    origin_name = self.model.fsm_names.get(&event.pg_id).unwrap().to_owned();
+                    target_name = self.model.fsm_names.get(&receiver).unwrap().to_owned();
+                    param_value = format!("{val:?}");
+                }
+                EventType::ProbeEmptyQueue
+                | EventType::ProbeFullQueue
+                | EventType::Dropped(_)
+                | EventType::Duplicated(_)
+                | EventType::Delayed(..)
+                | EventType::Reordered(_) => return,
             }
         }
+        if let Some(fault) = fault {
+            action = format!("{action} ({fault})").trim_start().to_owned();
+        }
 
         self.writer
             .as_mut()
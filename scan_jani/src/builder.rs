@@ -1,18 +1,18 @@
 use super::Model;
 use crate::parser::{
-    self, Automaton, BoolOp, ConstantDeclaration, Edge, Expression, Guard, Location, NumCompOp,
-    PropertyExpression, Sync, VariableDeclaration,
+    self, Automaton, BoolOp, ConstantDeclaration, Edge, Expression, Location, PropertyExpression,
+    Sync, VariableDeclaration,
 };
 use anyhow::{Context, anyhow, bail};
 use either::Either;
 use rand::{SeedableRng, rngs::SmallRng};
 use scan_core::{
-    Mtl, MtlOracle, PgModel, Type, TypeError, Val,
-    program_graph::{self, Action, PgExpression, ProgramGraphBuilder, Var},
+    Bounds, Mtl, MtlOracle, Overflow, PgModel, Type, TypeError, Val,
+    program_graph::{self, Action, PgExpression, Priority, ProgramGraphBuilder, Var},
 };
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
-    ops::Not,
+    collections::{BTreeMap, HashMap},
+    fmt::Formatter,
 };
 
 #[derive(Clone)]
@@ -22,9 +22,12 @@ pub struct JaniModelData {
     pub guarantees: Vec<String>,
 }
 
-pub(crate) fn build(jani_model: Model) -> anyhow::Result<(PgModel, MtlOracle, JaniModelData)> {
+pub(crate) fn build(
+    jani_model: Model,
+    overflow: Overflow,
+) -> anyhow::Result<(PgModel, MtlOracle, JaniModelData)> {
     let builder = JaniBuilder::default();
-    builder.build(jani_model)
+    builder.build(jani_model, overflow)
 }
 
 #[derive(Default)]
@@ -32,25 +35,30 @@ struct JaniBuilder {
     system_actions: HashMap<String, program_graph::Action>,
     global_vars: BTreeMap<String, (Var, Type)>,
     global_constants: HashMap<String, Val>,
+    // Distinct values of edge priorities, sorted
+    priorities: Vec<f64>,
 }
 
 impl JaniBuilder {
-    const RNG: &str = "__RNG__";
     const GEN: &str = "__GEN__";
 
     pub(crate) fn build(
         mut self,
         mut jani_model: Model,
+        overflow: Overflow,
     ) -> anyhow::Result<(PgModel, MtlOracle, JaniModelData)> {
         // WARN Necessary "normalization" process
         self.normalize(&mut jani_model);
 
         let mut pgb = ProgramGraphBuilder::new();
+        pgb.set_overflow(overflow);
 
         jani_model.system.syncs.iter().for_each(|sync| {
             let result = sync.result.as_ref().expect("no silent actions");
             if !self.system_actions.contains_key(result) {
                 let action = pgb.new_action();
+                pgb.set_action_symbol(action, result.as_str())
+                    .expect("action exists");
                 let prev = self.system_actions.insert(result.clone(), action);
                 assert!(prev.is_none(), "checked by above if condition");
             }
@@ -65,6 +73,16 @@ impl JaniBuilder {
             .iter()
             .try_for_each(|var| self.add_global_var(&mut pgb, var))?;
 
+        // Priorities are real numbers, so transitions are prioritized by their rank instead
+        self.priorities = jani_model
+            .automata
+            .iter()
+            .flat_map(|automaton| &automaton.edges)
+            .map(|edge| self.priority(edge))
+            .collect::<anyhow::Result<_>>()?;
+        self.priorities.sort_unstable_by(f64::total_cmp);
+        self.priorities.dedup();
+
         let init = pgb.new_action();
         jani_model
             .system
@@ -91,6 +109,7 @@ impl JaniBuilder {
             .map(|p| {
                 self.build_property(&p.expression)
                     .map(|p| p.right_or_else(Mtl::Atom))
+                    .with_context(|| format!("failed building property '{}'", p.name))
             })
             .collect::<Result<Vec<_>, _>>()?;
         fn extract_predicates(prop: &Mtl<PgExpression>) -> Vec<PgExpression> {
@@ -138,22 +157,20 @@ impl JaniBuilder {
         Ok((pg_model, oracle, data))
     }
 
-    // An action in JANI doesn not carry effects,
-    // so we need to duplicate actions until each one has unique effects.
+    // Actions in JANI are labels synchronising the automata,
+    // and the PG action of a transition is given by the result of the sync.
     // The modified model is such that:
     //
-    // - Every action has a unique set of assignments (duplicates actions).
-    // - Every edge has a unique destination (because destinations are tied to assignments).
-    // - Syncs are updated with new actions
-    //   (if action a is duplicated to a', all syncs containing a are duplicated with a' and a new result
-    //   so that result corresponds to a unique set of assignments, union of all the assignments of each of its actions).
-    // - Probability is encoded in guard
+    // - Every edge has an action (silent actions get a fresh name).
+    // - Every action of an automaton appears in some sync
+    //   (actions that do not synchronise between automata get a sync of their own).
+    //
+    // Destinations (with their probabilities and assignments) are the branches of probabilistic transitions,
+    // so they need no normalization.
     fn normalize(&mut self, jani_model: &mut Model) {
         // index is global so there is no risk of name-clash
         let mut idx = 0;
-        let rng = Expression::Identifier(String::from(Self::RNG));
         for automaton in &mut jani_model.automata {
-            let mut new_edges = Vec::new();
             for edge in &mut automaton.edges {
                 // Avoid silent actions
                 if edge.action.is_none() {
@@ -162,150 +179,39 @@ impl JaniBuilder {
                 }
                 let edge_action = edge.action.clone().expect("no silent action");
                 assert!(!edge_action.is_empty());
-                let mut prob: Option<Expression> = None;
-                for dest in &mut edge.destinations {
-                    // Add probability to guard
-                    let mut guard_exp = edge.guard.as_ref().map(|guard| guard.exp.clone());
-                    if let Some(ref p) = dest.probability {
-                        // First probability case has no lower bound
-                        if let Some(ref prob) = prob {
-                            let lower_bound = Expression::NumComp {
-                                op: NumCompOp::Leq,
-                                left: Box::new(prob.clone()),
-                                right: Box::new(rng.clone()),
-                            };
-                            guard_exp = guard_exp
-                                .map(|g| Expression::Bool {
-                                    op: BoolOp::And,
-                                    left: Box::new(lower_bound.clone()),
-                                    right: Box::new(g),
-                                })
-                                .or(Some(lower_bound));
-                        }
-                        let upper_prob = prob.map_or_else(
-                            || p.exp.clone(),
-                            |prob| Expression::IntOp {
-                                op: parser::IntOp::Plus,
-                                left: Box::new(prob),
-                                right: Box::new(p.exp.clone()),
-                            },
-                        );
-                        let upper_bound = Expression::NumComp {
-                            op: NumCompOp::Less,
-                            left: Box::new(rng.clone()),
-                            right: Box::new(upper_prob.clone()),
-                        };
-                        guard_exp = guard_exp
-                            .map(|g| Expression::Bool {
-                                op: BoolOp::And,
-                                left: Box::new(upper_bound.clone()),
-                                right: Box::new(g),
-                            })
-                            .or(Some(upper_bound));
-                        // Update accumulated probability
-                        prob = Some(upper_prob);
-                    } else if let Some(ref prob) = prob {
-                        // Last probability could be left implicit
-                        let lower_bound = Expression::NumComp {
-                            op: NumCompOp::Leq,
-                            left: Box::new(prob.clone()),
-                            right: Box::new(rng.clone()),
+                for e_idx in 0..jani_model.system.elements.len() {
+                    // If the action does not appear in syncs it means that it does not sync between automata.
+                    // We still want to keep track of it esplicitely.
+                    if jani_model.system.elements[e_idx].automaton == automaton.name
+                        && jani_model.system.syncs.iter().all(|sync| {
+                            sync.synchronise[e_idx]
+                                .as_ref()
+                                .is_none_or(|a| *a != edge_action)
+                        })
+                    {
+                        let mut synchronise = vec![None; jani_model.system.elements.len()];
+                        synchronise[e_idx] = Some(edge_action.clone());
+                        // ensure result is unique
+                        let result = if jani_model
+                            .system
+                            .syncs
+                            .iter()
+                            .any(|sync| sync.result.as_ref() == Some(&edge_action))
+                        {
+                            let result = edge_action.clone() + Self::GEN + &idx.to_string();
+                            idx += 1;
+                            result
+                        } else {
+                            edge_action.clone()
                         };
-                        guard_exp = guard_exp
-                            .map(|g| Expression::Bool {
-                                op: BoolOp::And,
-                                left: Box::new(lower_bound.clone()),
-                                right: Box::new(g),
-                            })
-                            .or(Some(lower_bound));
-                        // Need to remember this had a probability
-                        dest.probability = Some(parser::Probability {
-                            exp: Expression::IntOp {
-                                op: parser::IntOp::Minus,
-                                left: Box::new(Expression::ConstantValue(
-                                    parser::ConstantValue::NumberReal(1.),
-                                )),
-                                right: Box::new(prob.clone()),
-                            },
+                        jani_model.system.syncs.push(Sync {
+                            synchronise,
+                            result: Some(result),
                             _comment: String::new(),
                         });
                     }
-                    let action;
-                    if dest.assignments.is_empty() {
-                        // If there are no assignments we don't need a new name.
-                        action = edge_action.clone();
-                    } else {
-                        action = edge_action.clone() + Self::GEN + &idx.to_string();
-                        idx += 1;
-                    }
-
-                    new_edges.push(Edge {
-                        location: edge.location.clone(),
-                        action: Some(action.clone()),
-                        guard: guard_exp.map(|exp| Guard {
-                            exp,
-                            _comment: String::new(),
-                        }),
-                        destinations: vec![dest.clone()],
-                        _comment: String::new(),
-                    });
-
-                    // Update syncs with new action (has to synchronise like original one)
-                    for e_idx in 0..jani_model.system.elements.len() {
-                        if jani_model.system.elements[e_idx].automaton == automaton.name {
-                            // Only add new syncs if new action was generated
-                            if action != edge_action {
-                                let to_add = jani_model
-                                    .system
-                                    .syncs
-                                    .iter()
-                                    .filter(|sync| {
-                                        sync.synchronise[e_idx]
-                                            .as_ref()
-                                            .is_some_and(|a| *a == edge_action)
-                                    })
-                                    .map(|sync| {
-                                        let mut synchronise = sync.synchronise.clone();
-                                        let _ = synchronise[e_idx].insert(action.clone());
-                                        // Generate new unique result action
-                                        let result = sync.result.clone().unwrap_or_default()
-                                            + Self::GEN
-                                            + &idx.to_string();
-                                        idx += 1;
-                                        Sync {
-                                            synchronise,
-                                            result: Some(result),
-                                            _comment: String::new(),
-                                        }
-                                    })
-                                    .collect::<Vec<_>>();
-                                jani_model.system.syncs.extend(to_add);
-                            }
-
-                            // If original action did not appear in syncs it means that it does not sync between automata.
-                            // We still want to keep track of it esplicitely.
-                            if jani_model.system.syncs.iter().all(|sync| {
-                                sync.synchronise[e_idx]
-                                    .as_ref()
-                                    .is_none_or(|a| *a != edge_action)
-                            }) {
-                                let mut synchronise = vec![None; jani_model.system.elements.len()];
-                                synchronise[e_idx] = Some(action.clone());
-                                // ensure result is unique
-                                let result = action.clone() + Self::GEN + &idx.to_string();
-                                idx += 1;
-                                jani_model.system.syncs.push(Sync {
-                                    synchronise,
-                                    result: Some(result),
-                                    _comment: String::new(),
-                                });
-                            }
-                        }
-                    }
                 }
             }
-            // Replace edges with new ones
-            automaton.edges = new_edges;
         }
     }
 
@@ -314,11 +220,12 @@ impl JaniBuilder {
         pgb: &mut ProgramGraphBuilder,
         var: &VariableDeclaration,
     ) -> anyhow::Result<()> {
+        let bounds = self.bounds(&var.r#type)?;
         // TODO WARN FIXME: in JANI initial values are random?
         let init = var
             .initial_value
             .as_ref()
-            .and_then(|expr| self.build_expression(expr, &HashMap::new(), None).ok())
+            .and_then(|expr| self.build_expression(expr, &HashMap::new()).ok())
             .unwrap_or_else(|| {
                 PgExpression::Const(match &var.r#type {
                     parser::Type::Basic(basic_type) => match basic_type {
@@ -326,13 +233,23 @@ impl JaniBuilder {
                         parser::BasicType::Int => scan_core::Val::Integer(0),
                         parser::BasicType::Real => scan_core::Val::Float(0f64),
                     },
-                    parser::Type::Bounded(_bounded_type) => todo!(),
-                    parser::Type::Clock(_) => todo!(),
-                    parser::Type::Continuous(_) => todo!(),
+                    parser::Type::Bounded(_) => {
+                        scan_core::Val::Integer(bounds.unwrap_or_default().clamp(0))
+                    }
+                    parser::Type::Clock(_) | parser::Type::Continuous(_) => {
+                        scan_core::Val::Float(0f64)
+                    }
                 })
             });
+        let init = Self::cast_continuous(&var.r#type, init);
         let t = init.r#type()?;
         let var_id = pgb.new_var(init)?;
+        Self::set_default_rate(pgb, &var.r#type, var_id)?;
+        pgb.set_var_symbol(var_id, var.name.as_str())?;
+        if let Some(bounds) = bounds {
+            pgb.set_bounds(var_id, bounds)
+                .with_context(|| format!("failed to bound variable '{}'", var.name))?;
+        }
         self.global_vars.insert(var.name.clone(), (var_id, t));
         Ok(())
     }
@@ -343,7 +260,7 @@ impl JaniBuilder {
             .value
             .as_ref()
             .and_then(|expr| {
-                self.build_expression(expr, &HashMap::new(), None)
+                self.build_expression(expr, &HashMap::new())
                     .and_then(|e| e.eval_constant().map_err(|err| anyhow!(err)))
                     .ok()
             })
@@ -355,14 +272,16 @@ impl JaniBuilder {
     fn add_local_var(
         &self,
         pgb: &mut ProgramGraphBuilder,
+        automaton: &str,
         var: &VariableDeclaration,
         local_vars: &mut HashMap<String, (Var, Type)>,
     ) -> anyhow::Result<()> {
+        let bounds = self.bounds(&var.r#type)?;
         // TODO WARN FIXME: in JANI initial values are random?
         let init = var
             .initial_value
             .as_ref()
-            .and_then(|expr| self.build_expression(expr, local_vars, None).ok())
+            .and_then(|expr| self.build_expression(expr, local_vars).ok())
             // .ok_or_else(|| anyhow!("missing initial value"))?;
             .unwrap_or_else(|| {
                 PgExpression::Const(match &var.r#type {
@@ -371,17 +290,82 @@ impl JaniBuilder {
                         parser::BasicType::Int => scan_core::Val::Integer(0),
                         parser::BasicType::Real => scan_core::Val::Float(0f64),
                     },
-                    parser::Type::Bounded(_bounded_type) => todo!(),
-                    parser::Type::Clock(_) => todo!(),
-                    parser::Type::Continuous(_) => todo!(),
+                    parser::Type::Bounded(_) => {
+                        scan_core::Val::Integer(bounds.unwrap_or_default().clamp(0))
+                    }
+                    parser::Type::Clock(_) | parser::Type::Continuous(_) => {
+                        scan_core::Val::Float(0f64)
+                    }
                 })
             });
+        let init = Self::cast_continuous(&var.r#type, init);
         let t = init.r#type()?;
         let var_id = pgb.new_var(init)?;
+        Self::set_default_rate(pgb, &var.r#type, var_id)?;
+        // Local variables are only unique within their automaton
+        pgb.set_var_symbol(var_id, format!("{automaton}.{}", var.name))?;
+        if let Some(bounds) = bounds {
+            pgb.set_bounds(var_id, bounds)
+                .with_context(|| format!("failed to bound variable '{}'", var.name))?;
+        }
         local_vars.insert(var.name.clone(), (var_id, t));
         Ok(())
     }
 
+    // Clocks and continuous variables are real-valued, even if initialized by an integer.
+    fn cast_continuous(r#type: &parser::Type, init: PgExpression) -> PgExpression {
+        match r#type {
+            parser::Type::Clock(_) | parser::Type::Continuous(_)
+                if matches!(init.r#type(), Ok(Type::Integer)) =>
+            {
+                PgExpression::Cast(Box::new(init), Type::Float)
+            }
+            _ => init,
+        }
+    }
+
+    // Clocks advance at rate 1 and continuous variables are constant,
+    // unless the time progress condition of a location says otherwise.
+    fn set_default_rate(
+        pgb: &mut ProgramGraphBuilder,
+        r#type: &parser::Type,
+        var: Var,
+    ) -> anyhow::Result<()> {
+        match r#type {
+            parser::Type::Clock(_) => pgb.set_rate(var, 1.)?,
+            parser::Type::Continuous(_) => pgb.set_rate(var, 0.)?,
+            parser::Type::Basic(_) | parser::Type::Bounded(_) => {}
+        }
+        Ok(())
+    }
+
+    // Evaluates the bounds of bounded integer types.
+    fn bounds(&self, r#type: &parser::Type) -> anyhow::Result<Option<Bounds>> {
+        let parser::Type::Bounded(bounded_type) = r#type else {
+            return Ok(None);
+        };
+        if !matches!(bounded_type.base, parser::BasicType::Int) {
+            bail!("only bounded integer types are supported");
+        }
+        let bound = |expr: &Option<Expression>| {
+            expr.as_ref()
+                .map(|expr| {
+                    match self
+                        .build_expression(expr, &HashMap::new())?
+                        .eval_constant()?
+                    {
+                        Val::Integer(bound) => Ok(bound),
+                        val => Err(anyhow!("bound {val:?} is not an integer")),
+                    }
+                })
+                .transpose()
+        };
+        Ok(Some(Bounds::new(
+            bound(&bounded_type.lower_bound)?,
+            bound(&bounded_type.upper_bound)?,
+        )))
+    }
+
     fn data(self, jani_model: Model) -> JaniModelData {
         JaniModelData {
             actions: self
@@ -413,15 +397,32 @@ impl JaniBuilder {
         // Add local variables
         let mut local_vars: HashMap<String, (Var, Type)> = HashMap::new();
         let mut locations: HashMap<String, scan_core::program_graph::Location> = HashMap::new();
-        let mut rng_actions = HashSet::new();
+        automaton
+            .variables
+            .iter()
+            .try_for_each(|var| self.add_local_var(pgb, &automaton.name, var, &mut local_vars))
+            .context("failed adding local variables")?;
         let pg_initial = pgb.new_initial_location();
-        let rng = pgb.new_var(PgExpression::from(0.)).expect("new var");
-        pgb.add_effect(init, rng, PgExpression::RandFloat(0., 1.))
-            .expect("add effect");
         // Add locations
         for location in &automaton.locations {
-            self.build_location(jani_model, pgb, location, e_idx, &mut locations)
+            self.build_location(jani_model, pgb, automaton, location, e_idx, &mut locations)
                 .with_context(|| format!("failed building location: {}", &location.name))?;
+            if let Some(time_progress) = &location.time_progress {
+                let loc = locations[&location.name];
+                let mut invariants = Vec::new();
+                self.time_progress(pgb, loc, &time_progress.exp, &local_vars, &mut invariants)
+                    .with_context(|| {
+                        format!(
+                            "failed building time progress of location: {}",
+                            &location.name
+                        )
+                    })?;
+                if !invariants.is_empty() {
+                    let invariant =
+                        self.type_checked(PgExpression::And(invariants), &local_vars)?;
+                    pgb.set_invariant(loc, invariant)?;
+                }
+            }
         }
         // Connect initial location of PG with initial location(s) of the JANI model
         for initial in &automaton.initial_locations {
@@ -432,25 +433,16 @@ impl JaniBuilder {
                 .expect("add transition");
         }
 
-        automaton
-            .variables
-            .iter()
-            .try_for_each(|var| self.add_local_var(pgb, var, &mut local_vars))
-            .context("failed adding local variables")?;
-
         // Add edges
         for (n_edge, edge) in automaton.edges.iter().enumerate() {
-            self.build_edge(
-                jani_model,
-                pgb,
-                edge,
-                e_idx,
-                &local_vars,
-                &locations,
-                &mut rng_actions,
-                rng,
-            )
-            .with_context(|| format!("failed building {n_edge}-th edge for action"))?;
+            self.build_edge(jani_model, pgb, edge, e_idx, &local_vars, &locations)
+                .with_context(|| {
+                    format!(
+                        "failed building {n_edge}-th edge from location '{}' for action '{}'",
+                        edge.location,
+                        edge.action.as_deref().unwrap_or_default()
+                    )
+                })?;
         }
         Ok(())
     }
@@ -459,11 +451,13 @@ impl JaniBuilder {
         &mut self,
         jani_model: &Model,
         pgb: &mut ProgramGraphBuilder,
+        automaton: &Automaton,
         location: &Location,
         e_idx: usize,
         locations: &mut HashMap<String, scan_core::program_graph::Location>,
     ) -> anyhow::Result<()> {
         let loc = pgb.new_location();
+        pgb.set_location_symbol(loc, format!("{}.{}", automaton.name, location.name))?;
         assert!(locations.insert(location.name.clone(), loc).is_none());
         // For every action that is **NOT** synchronised on this automaton,
         // allow action with no change in state.
@@ -480,6 +474,73 @@ impl JaniBuilder {
         Ok(())
     }
 
+    // Splits the time progress condition into its conjuncts:
+    // those fixing the derivative of a variable set its rate in the location,
+    // while the others are collected as invariants.
+    fn time_progress(
+        &self,
+        pgb: &mut ProgramGraphBuilder,
+        loc: scan_core::program_graph::Location,
+        exp: &Expression,
+        local_vars: &HashMap<String, (Var, Type)>,
+        invariants: &mut Vec<PgExpression>,
+    ) -> anyhow::Result<()> {
+        match exp {
+            Expression::Bool {
+                op: BoolOp::And,
+                left,
+                right,
+            } => {
+                self.time_progress(pgb, loc, left, local_vars, invariants)?;
+                self.time_progress(pgb, loc, right, local_vars, invariants)
+            }
+            Expression::EqComp {
+                op: parser::EqCompOp::Eq,
+                left,
+                right,
+            } if matches!(**left, Expression::Derivative { .. })
+                || matches!(**right, Expression::Derivative { .. }) =>
+            {
+                let ((
+                    Expression::Derivative {
+                        op: parser::DerOp::Der,
+                        var,
+                    },
+                    rate,
+                )
+                | (
+                    rate,
+                    Expression::Derivative {
+                        op: parser::DerOp::Der,
+                        var,
+                    },
+                )) = (&**left, &**right)
+                else {
+                    unreachable!("one side is a derivative");
+                };
+                let (var_id, _) = local_vars
+                    .get(var)
+                    .or_else(|| self.global_vars.get(var))
+                    .ok_or_else(|| anyhow!("unknown id `{var}`"))?;
+                let rate = match self
+                    .build_expression(rate, local_vars)
+                    .with_context(|| format!("failed building the rate of '{var}'"))?
+                    .eval_constant()?
+                {
+                    Val::Float(rate) => rate,
+                    Val::Integer(rate) => f64::from(rate),
+                    val => bail!("rate {val:?} of '{var}' is not a number"),
+                };
+                pgb.set_location_rate(loc, *var_id, rate)
+                    .with_context(|| format!("failed setting the rate of '{var}'"))
+            }
+            _ => {
+                invariants.push(self.build_expression(exp, local_vars)?);
+                Ok(())
+            }
+        }
+    }
+
     fn build_edge(
         &mut self,
         jani_model: &Model,
@@ -488,9 +549,11 @@ impl JaniBuilder {
         e_idx: usize,
         local_vars: &HashMap<String, (Var, Type)>,
         locations: &HashMap<String, scan_core::program_graph::Location>,
-        rng_actions: &mut HashSet<Action>,
-        rng: Var,
     ) -> anyhow::Result<()> {
+        // An edge without destinations can never be taken
+        if edge.destinations.is_empty() {
+            return Ok(());
+        }
         let pre = locations.get(&edge.location).ok_or(anyhow!(
             "pre-transition location {} not found",
             edge.location
@@ -498,7 +561,7 @@ impl JaniBuilder {
         let guard = edge
             .guard
             .as_ref()
-            .map(|guard| self.build_expression(&guard.exp, local_vars, Some(rng)))
+            .map(|guard| self.build_expression(&guard.exp, local_vars))
             .transpose()
             .with_context(|| {
                 format!(
@@ -506,55 +569,121 @@ impl JaniBuilder {
                     edge.guard.as_ref().map(|g| &g.exp)
                 )
             })?;
-        // There must be only one destination per edge!
-        if let [dest] = edge.destinations.as_slice() {
-            let post = locations.get(&dest.location).ok_or(anyhow!(
-                "post-transition location {} not found",
-                dest.location
-            ))?;
-            jani_model
-                .system
-                .syncs
-                .iter()
-                .filter(|sync| {
-                    sync.synchronise[e_idx].as_ref().is_some_and(|sync_action| {
-                        *edge.action.as_ref().expect("no silent action") == *sync_action
-                    })
-                })
-                .try_for_each(|sync| {
-                    let result = sync.result.as_ref().expect("no silent actions generated");
-                    let action = self.system_actions.get(result).unwrap();
-                    // checks to do this only once per action
-                    if dest.probability.is_some() && !rng_actions.contains(action) {
-                        pgb.add_effect(*action, rng, PgExpression::RandFloat(0., 1.))
-                            .expect("effect");
-                        rng_actions.insert(*action);
-                    }
-                    for assignment in &dest.assignments {
-                        let (var, _) = local_vars
+        let priority = self.priority(edge)?;
+        let priority = self
+            .priorities
+            .binary_search_by(|p| p.total_cmp(&priority))
+            .expect("priorities have been collected") as Priority;
+        // Every destination is a branch of the transition, weighted by its probability
+        let branches = edge
+            .destinations
+            .iter()
+            .map(|dest| {
+                let post = locations.get(&dest.location).ok_or(anyhow!(
+                    "post-transition location {} not found",
+                    dest.location
+                ))?;
+                // If omitted, the probability is 1
+                let weight = dest
+                    .probability
+                    .as_ref()
+                    .map(|p| self.build_expression(&p.exp, local_vars))
+                    .transpose()
+                    .context("failed building probability")?
+                    .unwrap_or_else(|| PgExpression::from(1.));
+                let effects = dest
+                    .assignments
+                    .iter()
+                    .map(|assignment| {
+                        let (var, t) = local_vars
                             .get(&assignment.r#ref)
                             .or_else(|| self.global_vars.get(&assignment.r#ref))
                             .ok_or_else(|| anyhow!("unknown id `{}`", &assignment.r#ref))?;
-                        let expr = self
-                            .build_expression(&assignment.value, local_vars, Some(rng))
-                            .context("failed building expression")?;
-                        pgb.add_effect(*action, *var, expr)
-                            .context("failed adding effect to action")?;
-                    }
-                    pgb.add_transition(*pre, *action, *post, guard.clone())
-                        .context("failed adding transition")
-                })?;
-        } else {
-            panic!("edges should be normalized");
+                        let mut expr = self
+                            .build_expression(&assignment.value, local_vars)
+                            .with_context(|| {
+                                format!("failed building assignment to '{}'", assignment.r#ref)
+                            })?;
+                        // Clocks are reset by integer values
+                        if *t == Type::Float && self.type_of(&expr, local_vars)? == Type::Integer {
+                            expr = PgExpression::Cast(Box::new(expr), Type::Float);
+                        }
+                        Ok((*var, expr))
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                Ok((weight, *post, effects))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        jani_model
+            .system
+            .syncs
+            .iter()
+            .filter(|sync| {
+                sync.synchronise[e_idx].as_ref().is_some_and(|sync_action| {
+                    *edge.action.as_ref().expect("no silent action") == *sync_action
+                })
+            })
+            .try_for_each(|sync| {
+                let result = sync.result.as_ref().expect("no silent actions generated");
+                let action = self.system_actions.get(result).unwrap();
+                let branching = pgb
+                    .add_probabilistic_transition(*pre, *action, guard.clone(), branches.clone())
+                    .context("failed adding transition")?;
+                if priority > 0 {
+                    pgb.set_priority(*pre, branching, priority)
+                        .context("failed setting priority")?;
+                }
+                Ok(())
+            })
+    }
+
+    // Evaluates the priority of the edge.
+    fn priority(&self, edge: &Edge) -> anyhow::Result<f64> {
+        let Some(priority) = edge.priority.as_ref() else {
+            return Ok(0.);
+        };
+        match self
+            .build_expression(&priority.exp, &HashMap::new())
+            .context("failed building priority")?
+            .eval_constant()?
+        {
+            // Adding zero turns -0 into 0
+            Val::Float(priority) => Ok(priority + 0.),
+            Val::Integer(priority) => Ok(f64::from(priority)),
+            val => Err(anyhow!("priority {val:?} is not a number")),
         }
-        Ok(())
+    }
+
+    // Type-checks the expression, reporting the offending subexpression and the types involved.
+    fn type_checked(
+        &self,
+        expr: PgExpression,
+        local_vars: &HashMap<String, (Var, Type)>,
+    ) -> anyhow::Result<PgExpression> {
+        self.type_of(&expr, local_vars)?;
+        Ok(expr)
+    }
+
+    // Computes the type of the expression, naming its variables as in the model if it is badly typed.
+    fn type_of(
+        &self,
+        expr: &PgExpression,
+        local_vars: &HashMap<String, (Var, Type)>,
+    ) -> Result<Type, TypeError> {
+        expr.type_with(&|var: &Var, f: &mut Formatter<'_>| match local_vars
+            .iter()
+            .chain(self.global_vars.iter())
+            .find(|(_, (v, _))| v == var)
+        {
+            Some((name, _)) => write!(f, "{name}"),
+            None => write!(f, "{var:?}"),
+        })
     }
 
     fn build_expression(
         &self,
         expr: &Expression,
         local_vars: &HashMap<String, (Var, Type)>,
-        rng: Option<Var>,
     ) -> anyhow::Result<PgExpression> {
         match expr {
             Expression::ConstantValue(constant_value) => match constant_value {
@@ -566,9 +695,6 @@ impl JaniBuilder {
                 parser::ConstantValue::NumberReal(num) => Ok(PgExpression::from(*num)),
                 parser::ConstantValue::NumberInt(num) => Ok(PgExpression::from(*num)),
             },
-            Expression::Identifier(id) if id == Self::RNG => rng
-                .ok_or_else(|| anyhow!("rng not available"))
-                .map(|rng| PgExpression::Var(rng, Type::Float)),
             Expression::Identifier(id) => local_vars
                 .get(id)
                 .or_else(|| self.global_vars.get(id))
@@ -586,107 +712,140 @@ impl JaniBuilder {
                 then,
                 r#else,
             } => {
-                let r#if = self.build_expression(r#if, local_vars, rng)?;
-                let then = self.build_expression(then, local_vars, rng)?;
-                let r#else = self.build_expression(r#else, local_vars, rng)?;
+                let r#if = self.build_expression(r#if, local_vars)?;
+                let then = self.build_expression(then, local_vars)?;
+                let r#else = self.build_expression(r#else, local_vars)?;
                 match op {
                     parser::IteOp::Ite => Ok(PgExpression::Ite(Box::new((r#if, then, r#else)))),
                 }
             }
             Expression::Bool { op, left, right } => {
-                let left = self.build_expression(left, local_vars, rng)?;
-                let right = self.build_expression(right, local_vars, rng)?;
-                match op {
-                    BoolOp::And => PgExpression::and(vec![left, right]),
-                    BoolOp::Or => PgExpression::or(vec![left, right]),
-                }
-                .map_err(|err| err.into())
+                let left = self.build_expression(left, local_vars)?;
+                let right = self.build_expression(right, local_vars)?;
+                let expr = match op {
+                    BoolOp::And => PgExpression::And(vec![left, right]),
+                    BoolOp::Or => PgExpression::Or(vec![left, right]),
+                };
+                self.type_checked(expr, local_vars)
             }
             Expression::Neg { op, exp } => {
-                let exp = self.build_expression(exp, local_vars, rng)?;
-                match op {
-                    parser::NegOp::Neg => PgExpression::not(exp).map_err(|err| err.into()),
-                }
+                let exp = self.build_expression(exp, local_vars)?;
+                let expr = match op {
+                    parser::NegOp::Neg => PgExpression::Not(Box::new(exp)),
+                };
+                self.type_checked(expr, local_vars)
             }
             Expression::EqComp { op, left, right } => {
-                let left = self.build_expression(left, local_vars, rng)?;
-                let right = self.build_expression(right, local_vars, rng)?;
-                if left.r#type()? == right.r#type()?
-                    || (matches!(left.r#type()?, Type::Integer | Type::Float)
-                        && matches!(right.r#type()?, Type::Integer | Type::Float))
-                {
-                    match op {
-                        parser::EqCompOp::Eq => Ok(PgExpression::Equal(Box::new((left, right)))),
-                        parser::EqCompOp::Neq => PgExpression::Equal(Box::new((left, right)))
-                            .not()
-                            .map_err(|err| err.into()),
-                    }
-                } else {
-                    bail!(TypeError::TypeMismatch)
-                }
+                let left = self.build_expression(left, local_vars)?;
+                let right = self.build_expression(right, local_vars)?;
+                let equal = PgExpression::Equal(Box::new((left, right)));
+                let expr = match op {
+                    parser::EqCompOp::Eq => equal,
+                    parser::EqCompOp::Neq => PgExpression::Not(Box::new(equal)),
+                };
+                self.type_checked(expr, local_vars)
             }
             Expression::NumComp { op, left, right } => {
-                let left = self.build_expression(left, local_vars, rng)?;
-                let right = self.build_expression(right, local_vars, rng)?;
-                if matches!(left.r#type()?, Type::Integer | Type::Float)
-                    && matches!(right.r#type()?, Type::Integer | Type::Float)
-                {
-                    Ok(match op {
-                        parser::NumCompOp::Less => PgExpression::Less(Box::new((left, right))),
-                        parser::NumCompOp::Leq => PgExpression::LessEq(Box::new((left, right))),
-                        parser::NumCompOp::Greater => {
-                            PgExpression::Greater(Box::new((left, right)))
-                        }
-                        parser::NumCompOp::Geq => PgExpression::GreaterEq(Box::new((left, right))),
-                    })
-                } else {
-                    bail!(TypeError::TypeMismatch)
-                }
+                let left = self.build_expression(left, local_vars)?;
+                let right = self.build_expression(right, local_vars)?;
+                let expr = match op {
+                    parser::NumCompOp::Less => PgExpression::Less(Box::new((left, right))),
+                    parser::NumCompOp::Leq => PgExpression::LessEq(Box::new((left, right))),
+                    parser::NumCompOp::Greater => PgExpression::Greater(Box::new((left, right))),
+                    parser::NumCompOp::Geq => PgExpression::GreaterEq(Box::new((left, right))),
+                };
+                self.type_checked(expr, local_vars)
             }
             Expression::IntOp { op, left, right } => {
-                let left = self.build_expression(left, local_vars, rng)?;
-                let right = self.build_expression(right, local_vars, rng)?;
-                if matches!(left.r#type()?, Type::Integer | Type::Float)
-                    && matches!(right.r#type()?, Type::Integer | Type::Float)
-                {
-                    match op {
-                        parser::IntOp::Plus => Ok(PgExpression::Sum(vec![left, right])),
-                        parser::IntOp::Minus => Ok(PgExpression::Sum(vec![
-                            left,
-                            PgExpression::Opposite(Box::new(right)),
-                        ])),
-                        parser::IntOp::Mult => Ok(PgExpression::Mult(vec![left, right])),
-                        parser::IntOp::IntDiv => todo!(),
-                    }
-                } else {
-                    bail!(TypeError::TypeMismatch)
+                let left = self.build_expression(left, local_vars)?;
+                let right = self.build_expression(right, local_vars)?;
+                match op {
+                    parser::IntOp::Plus => Ok(PgExpression::Sum(vec![left, right])),
+                    parser::IntOp::Minus => Ok(PgExpression::Sum(vec![
+                        left,
+                        PgExpression::Opposite(Box::new(right)),
+                    ])),
+                    parser::IntOp::Mult => Ok(PgExpression::Mult(vec![left, right])),
+                    parser::IntOp::Mod => Ok(PgExpression::Mod(Box::new((left, right)))),
+                    parser::IntOp::Min => Ok(PgExpression::Min(Box::new((left, right)))),
+                    parser::IntOp::Max => Ok(PgExpression::Max(Box::new((left, right)))),
                 }
+                .and_then(|expr| self.type_checked(expr, local_vars))
             }
             Expression::RealOp { op, left, right } => {
-                let left = self.build_expression(left, local_vars, rng)?;
-                let right = self.build_expression(right, local_vars, rng)?;
-                if matches!(left.r#type()?, Type::Integer | Type::Float)
-                    && matches!(right.r#type()?, Type::Integer | Type::Float)
-                {
-                    match op {
-                        parser::RealOp::Div => Ok(PgExpression::Div(Box::new((left, right)))),
-                        parser::RealOp::Pow => todo!(),
-                        parser::RealOp::Log => todo!(),
-                    }
-                } else {
-                    bail!(TypeError::TypeMismatch)
+                let left = self.build_expression(left, local_vars)?;
+                let right = self.build_expression(right, local_vars)?;
+                match op {
+                    parser::RealOp::Div => Ok(PgExpression::Div(Box::new((left, right)))),
+                    parser::RealOp::Pow => Ok(PgExpression::Pow(Box::new((left, right)))),
+                    parser::RealOp::Log => Ok(PgExpression::Log(Box::new((left, right)))),
                 }
+                .and_then(|expr| self.type_checked(expr, local_vars))
             }
             Expression::Real2IntOp { op, exp } => {
-                let _exp = self.build_expression(exp, local_vars, rng)?;
-                if matches!(_exp.r#type()?, Type::Float) {
-                    match op {
-                        parser::Real2IntOp::Floor => todo!(),
-                        parser::Real2IntOp::Ceil => todo!(),
-                    }
-                } else {
-                    bail!(TypeError::TypeMismatch)
+                let exp = self.build_expression(exp, local_vars)?;
+                match op {
+                    parser::Real2IntOp::Floor => Ok(PgExpression::Floor(Box::new(exp))),
+                    parser::Real2IntOp::Ceil => Ok(PgExpression::Ceil(Box::new(exp))),
                 }
+                .and_then(|expr| self.type_checked(expr, local_vars))
+            }
+            Expression::NumOp { op, exp } => {
+                let exp = self.build_expression(exp, local_vars)?;
+                match op {
+                    parser::NumOp::Abs => Ok(PgExpression::Abs(Box::new(exp))),
+                    parser::NumOp::Sin => Ok(PgExpression::Sin(Box::new(exp))),
+                    parser::NumOp::Cos => Ok(PgExpression::Cos(Box::new(exp))),
+                    parser::NumOp::Tan => Ok(PgExpression::Tan(Box::new(exp))),
+                }
+                .and_then(|expr| self.type_checked(expr, local_vars))
+            }
+            Expression::Derivative {
+                op: parser::DerOp::Der,
+                var,
+            } => {
+                bail!("derivative of '{var}' is only allowed in time progress conditions")
+            }
+            Expression::Distribution { distribution, args } => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.build_expression(arg, local_vars))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let expr = match (distribution, <[_; 1]>::try_from(args)) {
+                    (parser::Distribution::Bernoulli, Ok([p])) => PgExpression::Ite(Box::new((
+                        PgExpression::Less(Box::new((PgExpression::RandFloat(0., 1.), p))),
+                        PgExpression::from(1),
+                        PgExpression::from(0),
+                    ))),
+                    (parser::Distribution::Geometric, Ok([p])) => {
+                        PgExpression::RandGeometric(Box::new(p))
+                    }
+                    (parser::Distribution::Poisson, Ok([rate])) => {
+                        PgExpression::RandPoisson(Box::new(rate))
+                    }
+                    (parser::Distribution::Exponential, Ok([rate])) => {
+                        PgExpression::RandExp(Box::new(rate))
+                    }
+                    (_, Ok(_)) => bail!("wrong number of parameters for {distribution:?}"),
+                    (_, Err(args)) => match (distribution, <[_; 2]>::try_from(args)) {
+                        // Uniform over the reals in [lower, upper + 1), rounded down to an integer.
+                        (parser::Distribution::DiscreteUniform, Ok([lower, upper])) => {
+                            PgExpression::Floor(Box::new(PgExpression::RandUniform(Box::new((
+                                lower,
+                                PgExpression::Sum(vec![upper, PgExpression::from(1)]),
+                            )))))
+                        }
+                        (parser::Distribution::Uniform, Ok([lower, upper])) => {
+                            PgExpression::RandUniform(Box::new((lower, upper)))
+                        }
+                        (parser::Distribution::Normal, Ok([mean, std_dev])) => {
+                            PgExpression::RandNormal(Box::new((mean, std_dev)))
+                        }
+                        _ => bail!("wrong number of parameters for {distribution:?}"),
+                    },
+                };
+                // Type-check parameters
+                self.type_checked(expr, local_vars)
             }
         }
     }
@@ -736,92 +895,77 @@ impl JaniBuilder {
             PropertyExpression::Bool { op, left, right } => {
                 let left = self.build_property(left)?.left().expect("expression");
                 let right = self.build_property(right)?.left().expect("expression");
-                match op {
-                    BoolOp::And => PgExpression::and(vec![left, right]).map_err(|err| err.into()),
-                    BoolOp::Or => PgExpression::or(vec![left, right]).map_err(|err| err.into()),
-                }
-                .map(Either::Left)
+                let expr = match op {
+                    BoolOp::And => PgExpression::And(vec![left, right]),
+                    BoolOp::Or => PgExpression::Or(vec![left, right]),
+                };
+                self.type_checked(expr, &HashMap::new()).map(Either::Left)
             }
             PropertyExpression::Neg { op, exp } => {
                 let exp = self.build_property(exp)?.left().expect("expression");
-                match op {
-                    parser::NegOp::Neg => PgExpression::not(exp).map_err(|err| err.into()),
-                }
-                .map(Either::Left)
+                let expr = match op {
+                    parser::NegOp::Neg => PgExpression::Not(Box::new(exp)),
+                };
+                self.type_checked(expr, &HashMap::new()).map(Either::Left)
             }
             PropertyExpression::EqComp { op, left, right } => {
                 let left = self.build_property(left)?.left().expect("expression");
                 let right = self.build_property(right)?.left().expect("expression");
-                if left.r#type()? == right.r#type()?
-                    || (matches!(left.r#type()?, Type::Integer | Type::Float)
-                        && matches!(right.r#type()?, Type::Integer | Type::Float))
-                {
-                    match op {
-                        parser::EqCompOp::Eq => Ok(PgExpression::Equal(Box::new((left, right)))),
-                        parser::EqCompOp::Neq => PgExpression::Equal(Box::new((left, right)))
-                            .not()
-                            .map_err(|err| err.into()),
-                    }
-                    .map(Either::Left)
-                } else {
-                    bail!(TypeError::TypeMismatch)
-                }
+                let equal = PgExpression::Equal(Box::new((left, right)));
+                let expr = match op {
+                    parser::EqCompOp::Eq => equal,
+                    parser::EqCompOp::Neq => PgExpression::Not(Box::new(equal)),
+                };
+                self.type_checked(expr, &HashMap::new()).map(Either::Left)
             }
             PropertyExpression::NumComp { op, left, right } => {
                 let left = self.build_property(left)?.left().expect("expression");
                 let right = self.build_property(right)?.left().expect("expression");
-                if matches!(left.r#type()?, Type::Integer | Type::Float)
-                    && matches!(right.r#type()?, Type::Integer | Type::Float)
-                {
-                    Ok(Either::Left(match op {
-                        parser::NumCompOp::Less => PgExpression::Less(Box::new((left, right))),
-                        parser::NumCompOp::Leq => PgExpression::LessEq(Box::new((left, right))),
-                        parser::NumCompOp::Greater => {
-                            PgExpression::Greater(Box::new((left, right)))
-                        }
-                        parser::NumCompOp::Geq => PgExpression::GreaterEq(Box::new((left, right))),
-                    }))
-                } else {
-                    bail!(TypeError::TypeMismatch)
-                }
+                let expr = match op {
+                    parser::NumCompOp::Less => PgExpression::Less(Box::new((left, right))),
+                    parser::NumCompOp::Leq => PgExpression::LessEq(Box::new((left, right))),
+                    parser::NumCompOp::Greater => PgExpression::Greater(Box::new((left, right))),
+                    parser::NumCompOp::Geq => PgExpression::GreaterEq(Box::new((left, right))),
+                };
+                self.type_checked(expr, &HashMap::new()).map(Either::Left)
             }
             PropertyExpression::IntOp { op, left, right } => {
                 let left = self.build_property(left)?.left().expect("expression");
                 let right = self.build_property(right)?.left().expect("expression");
-                if matches!(left.r#type()?, Type::Integer | Type::Float)
-                    && matches!(right.r#type()?, Type::Integer | Type::Float)
-                {
-                    match op {
-                        parser::IntOp::Plus => Ok(PgExpression::Sum(vec![left, right])),
-                        parser::IntOp::Minus => Ok(PgExpression::Sum(vec![
-                            left,
-                            PgExpression::Opposite(Box::new(right)),
-                        ])),
-                        parser::IntOp::Mult => Ok(PgExpression::Mult(vec![left, right])),
-                        parser::IntOp::IntDiv => todo!(),
-                    }
-                    .map(Either::Left)
-                } else {
-                    bail!(TypeError::TypeMismatch)
+                match op {
+                    parser::IntOp::Plus => Ok(PgExpression::Sum(vec![left, right])),
+                    parser::IntOp::Minus => Ok(PgExpression::Sum(vec![
+                        left,
+                        PgExpression::Opposite(Box::new(right)),
+                    ])),
+                    parser::IntOp::Mult => Ok(PgExpression::Mult(vec![left, right])),
+                    parser::IntOp::Mod => Ok(PgExpression::Mod(Box::new((left, right)))),
+                    parser::IntOp::Min => Ok(PgExpression::Min(Box::new((left, right)))),
+                    parser::IntOp::Max => Ok(PgExpression::Max(Box::new((left, right)))),
                 }
+                .and_then(|expr| self.type_checked(expr, &HashMap::new()))
+                .map(Either::Left)
             }
             PropertyExpression::RealOp { op, left, right } => {
                 let left = self.build_property(left)?.left().expect("expression");
                 let right = self.build_property(right)?.left().expect("expression");
-                if matches!(left.r#type()?, Type::Integer | Type::Float)
-                    && matches!(right.r#type()?, Type::Integer | Type::Float)
-                {
-                    match op {
-                        parser::RealOp::Div => Ok(PgExpression::Div(Box::new((left, right)))),
-                        parser::RealOp::Pow => todo!(),
-                        parser::RealOp::Log => todo!(),
-                    }
-                    .map(Either::Left)
-                } else {
-                    bail!(TypeError::TypeMismatch)
+                match op {
+                    parser::RealOp::Div => Ok(PgExpression::Div(Box::new((left, right)))),
+                    parser::RealOp::Pow => Ok(PgExpression::Pow(Box::new((left, right)))),
+                    parser::RealOp::Log => Ok(PgExpression::Log(Box::new((left, right)))),
                 }
+                .and_then(|expr| self.type_checked(expr, &HashMap::new()))
+                .map(Either::Left)
+            }
+            PropertyExpression::Real2IntOp { op, exp } => {
+                let exp = self.build_property(exp)?.left().expect("expression");
+                match op {
+                    parser::Real2IntOp::Floor => Ok(PgExpression::Floor(Box::new(exp))),
+                    parser::Real2IntOp::Ceil => Ok(PgExpression::Ceil(Box::new(exp))),
+                }
+                .and_then(|expr| self.type_checked(expr, &HashMap::new()))
+                .map(Either::Left)
             }
-            PropertyExpression::Real2IntOp { op: _, exp: _ } => todo!(),
             PropertyExpression::Until {
                 op,
                 left,
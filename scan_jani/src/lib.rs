@@ -9,26 +9,59 @@ pub use builder::JaniModelData;
 use builder::build;
 use log::info;
 use parser::Model;
-use scan_core::program_graph::{Action, PgError};
-use scan_core::{MtlOracle, PgModel, Scan};
+use scan_core::program_graph::{Action, PgDiagnostic, PgError};
+use scan_core::{MtlOracle, Overflow, PgModel, Scan};
 use std::{fs::File, path::Path};
 pub use tracer::TracePrinter;
 
 pub type JaniScan = Scan<Action, PgError, PgModel, MtlOracle>;
 
-pub fn load(path: &Path) -> anyhow::Result<(JaniScan, JaniModelData)> {
+/// Loads the model, with the given [`Overflow`] semantics for sized integers.
+pub fn load(path: &Path, overflow: Overflow) -> anyhow::Result<(JaniScan, JaniModelData)> {
+    let (pg_model, oracle, jani_info) = build(parse(path)?, overflow)?;
+    let scan = Scan::new(pg_model, oracle);
+
+    Ok((scan, jani_info))
+}
+
+/// Loads the model and statically analyzes it,
+/// returning a description of the potential modelling mistakes found.
+///
+/// Global variables are observable by properties,
+/// so they are not reported as never read.
+///
+/// See also [`ProgramGraph::analyze`](scan_core::program_graph::ProgramGraph::analyze).
+pub fn analyze(path: &Path) -> anyhow::Result<Vec<String>> {
+    let (pg_model, _, _) = build(parse(path)?, Overflow::default())?;
+    let global_vars = pg_model.global_vars();
+    let pg = pg_model.program_graph();
+    let diagnostics = pg
+        .analyze()
+        .into_iter()
+        .filter(|diagnostic| {
+            !matches!(diagnostic, PgDiagnostic::UnreadVar(var) if global_vars.contains(var))
+        })
+        .map(|diagnostic| diagnostic.describe(pg.symbols()))
+        .collect();
+    Ok(diagnostics)
+}
+
+/// Loads the model and renders its program graph in the Graphviz DOT language.
+///
+/// See also [`ProgramGraph::to_dot`](scan_core::program_graph::ProgramGraph::to_dot).
+pub fn dot(path: &Path) -> anyhow::Result<String> {
+    let (pg_model, _, _) = build(parse(path)?, Overflow::default())?;
+    Ok(pg_model.program_graph().to_dot())
+}
+
+fn parse(path: &Path) -> anyhow::Result<Model> {
     info!(target: "parser", "parsing JANI model file '{}'", path.display());
     let reader = File::open(path)
         .with_context(|| format!("failed to create reader from file '{}'", path.display()))?;
-    let jani_model: Model = serde_json::de::from_reader(reader).with_context(|| {
+    serde_json::de::from_reader(reader).with_context(|| {
         format!(
             "failed to parse model specification in '{}'",
             path.display(),
         )
-    })?;
-
-    let (pg_model, oracle, jani_info) = build(jani_model)?;
-    let scan = Scan::new(pg_model, oracle);
-
-    Ok((scan, jani_info))
+    })
 }
@@ -33,18 +33,27 @@ pub(crate) struct Automaton {
 pub(crate) struct Location {
     /// the name of the location, unique among all locations of this automaton
     pub(crate) name: Identifier,
+    /// the location's time progress condition, not allowed except TA, PTA, STA, HA, PHA and STA,
+    /// type bool; if omitted in TA, PTA, STA, HA, PHA or SHA, it is true
+    #[serde(default)]
+    pub(crate) time_progress: Option<TimeProgress>,
     /// values for transient variables in this location
     #[serde(default)]
     pub(crate) transient_values: Vec<TransientValue>,
     /// an optional comment
     #[serde(skip)]
     pub(crate) _comment: String,
-    // TODO
-    // "?time-progress": { // the location's time progress condition, not allowed except TA, PTA, STA, HA, PHA and STA,
-    //                     // type bool; if omitted in TA, PTA, STA, HA, PHA or SHA, it is true
-    //   "exp": Expression, // the invariant expression, type bool
-    //   "?comment": String // an optional comment
-    // },
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct TimeProgress {
+    /// the invariant expression, type bool
+    pub(crate) exp: Expression,
+    /// an optional comment
+    #[serde(skip)]
+    pub(crate) _comment: String,
 }
 
 #[derive(Deserialize)]
@@ -80,6 +89,20 @@ pub(crate) struct Edge {
     pub(crate) guard: Option<Guard>,
     /// the destinations of the edge, at least one, at most one for LTS, TA and HA
     pub(crate) destinations: Vec<Destination>,
+    /// the edge's priority, requires the `edge-priorities` feature; if omitted, it is 0
+    #[serde(default)]
+    pub(crate) priority: Option<EdgePriority>,
+    /// an optional comment
+    #[serde(skip)]
+    pub(crate) _comment: String,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct EdgePriority {
+    /// the priority expression, type real, constant;
+    /// only enabled edges with the highest priority can be taken
+    pub(crate) exp: Expression,
     /// an optional comment
     #[serde(skip)]
     pub(crate) _comment: String,
@@ -59,7 +59,7 @@ pub(crate) enum Expression {
         /// the right operand; numeric type
         right: Box<Expression>,
     },
-    /// addition / subtraction / multiplication / modulo:
+    /// addition / subtraction / multiplication / modulo / minimum / maximum:
     IntOp {
         /// result type is int (if left and right are both assignable to int) or real
         op: IntOp,
@@ -84,6 +84,27 @@ pub(crate) enum Expression {
         /// the single operand; numeric type
         exp: Box<Expression>,
     },
+    /// absolute value / sine / cosine / tangent: computes |exp| / sin(exp) / cos(exp) / tan(exp)
+    NumOp {
+        /// result type is int for the absolute value of an int, real otherwise
+        op: NumOp,
+        /// the single operand; numeric type
+        exp: Box<Expression>,
+    },
+    /// derivative of a continuous variable (hybrid automata), only allowed in time progress conditions
+    Derivative {
+        /// result type is real
+        op: DerOp,
+        /// the continuous variable
+        var: Identifier,
+    },
+    /// sampling from a probability distribution (distribution sampling extension)
+    Distribution {
+        /// result type is int for discrete distributions, real otherwise
+        distribution: Distribution,
+        /// the parameters of the distribution; numeric types
+        args: Vec<Expression>,
+    },
 }
 
 #[derive(Debug, Clone, Copy, Deserialize)]
@@ -133,6 +154,12 @@ pub(crate) enum NegOp {
     Neg,
 }
 
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum DerOp {
+    Der,
+}
+
 #[derive(Debug, Clone, Copy, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) enum EqCompOp {
@@ -155,7 +182,7 @@ pub(crate) enum NumCompOp {
     Geq,
 }
 
-/// computes left + right / left - right / left * right / left modulo right
+/// computes left + right / left - right / left * right / left modulo right / min(left, right) / max(left, right)
 #[derive(Debug, Clone, Copy, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) enum IntOp {
@@ -166,10 +193,12 @@ pub(crate) enum IntOp {
     #[serde(rename = "*")]
     Mult,
     #[serde(rename = "%")]
-    IntDiv,
+    Mod,
+    Min,
+    Max,
 }
 
-/// computes left + right / left - right / left * right / left modulo right
+/// computes left / right / left^right / log_right(left)
 #[derive(Debug, Clone, Copy, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) enum RealOp {
@@ -186,3 +215,32 @@ pub(crate) enum Real2IntOp {
     Floor,
     Ceil,
 }
+
+/// absolute value / sine / cosine / tangent: computes |exp| / sin(exp) / cos(exp) / tan(exp)
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum NumOp {
+    Abs,
+    Sin,
+    Cos,
+    Tan,
+}
+
+/// probability distributions with their parameters
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub(crate) enum Distribution {
+    /// uniform distribution over the integers between the bounds (both included)
+    DiscreteUniform,
+    /// probability of 1 (and 0 otherwise)
+    Bernoulli,
+    /// success probability; counts the failures before the first success
+    Geometric,
+    /// rate
+    Poisson,
+    /// uniform distribution over the reals between the bounds
+    Uniform,
+    /// mean and standard deviation
+    Normal,
+    /// rate
+    Exponential,
+}
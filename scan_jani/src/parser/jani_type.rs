@@ -31,13 +31,13 @@ pub(crate) enum BoundedTypeKind {
 pub(crate) struct BoundedType {
     #[serde(default)]
     kind: BoundedTypeKind,
-    base: BasicType,
+    pub(crate) base: BasicType,
     /// smallest value allowed by the type; constant expression of the base type
     #[serde(default)]
-    lower_bound: Option<Expression>,
+    pub(crate) lower_bound: Option<Expression>,
     /// largest value allowed by the type; constant expression of the base type
     #[serde(default)]
-    upper_bound: Option<Expression>,
+    pub(crate) upper_bound: Option<Expression>,
 }
 
 #[derive(Deserialize)]
@@ -47,8 +47,20 @@ pub(crate) enum Type {
     Basic(BasicType),
     Bounded(BoundedType),
     /// numeric; only allowed for TA, PTA, STA, HA, PHA and SHA; assignable from int and bounded int
-    Clock(u32),
+    Clock(ClockType),
     /// numeric; continuous variable that changes over time as allowed by the current location's
     /// invariant; only allowed for HA, PHA and SHA; assignable from all numeric types
-    Continuous(f64),
+    Continuous(ContinuousType),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ClockType {
+    Clock,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ContinuousType {
+    Continuous,
 }
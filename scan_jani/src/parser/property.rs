@@ -71,7 +71,7 @@ pub(crate) enum PropertyExpression {
         /// the right operand; numeric type
         right: Box<PropertyExpression>,
     },
-    /// addition / subtraction / multiplication / modulo:
+    /// addition / subtraction / multiplication / modulo / minimum / maximum:
     IntOp {
         /// result type is int (if left and right are both assignable to int) or real
         op: IntOp,
@@ -90,7 +90,6 @@ pub(crate) enum PropertyExpression {
         right: Box<PropertyExpression>,
     },
     /// floor / ceiling: computes ⌊exp⌋ / ⌈exp⌉
-    #[allow(dead_code)]
     Real2IntOp {
         /// result type is int
         op: Real2IntOp,
@@ -152,13 +152,28 @@ fn format_val(val: &Val) -> String {
         Val::Boolean(true) => "true".to_string(),
         Val::Boolean(false) => "false".to_string(),
         Val::Integer(i) => i.to_string(),
+        Val::Int(_, i) => i.to_string(),
         Val::Float(ordered_float) => ordered_float.to_string(),
+        Val::String(s) => s.clone(),
+        Val::Enum(r#enum, idx) => r#enum
+            .label(*idx)
+            .map_or_else(|| idx.to_string(), str::to_owned),
         Val::Tuple(vec) => {
             vec.iter()
                 .fold("(".to_string(), |acc, v| acc + format_val(v).as_str())
                 + ")"
         }
-        Val::List(_, vec) => {
+        Val::Record(record, vals) => {
+            let fields = record
+                .fields()
+                .iter()
+                .zip(vals)
+                .map(|((name, _), val)| format!("{name}: {}", format_val(val)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{{{fields}}}")
+        }
+        Val::List(_, vec) | Val::Array(_, vec) => {
             vec.iter()
                 .fold("[".to_string(), |acc, v| acc + format_val(v).as_str())
                 + "]"
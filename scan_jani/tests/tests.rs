@@ -1,3 +1,4 @@
+use scan_core::Overflow;
 use std::path::Path;
 
 #[test]
@@ -25,7 +26,54 @@ fn crowds() {
     test(Path::new("./tests/crowds.v1.jani"))
 }
 
+#[test]
+fn battery() {
+    // The model never deadlocks, so runs are bounded by their duration
+    let (scan, ..) =
+        scan_jani::load(Path::new("./tests/battery.jani"), Overflow::default()).expect("load");
+    scan.adaptive::<scan_jani::TracePrinter>(0.95, 0.01, 100, None);
+    // Discharging for 2 at rate 1 and charging for 1 at rate 2,
+    // the battery stays between 8 and 10 in every run.
+    assert!(scan.successes() > 0);
+    assert_eq!(scan.failures(), 0);
+}
+
+#[test]
+fn distributions() {
+    let (scan, ..) = scan_jani::load(Path::new("./tests/distributions.jani"), Overflow::default())
+        .expect("load");
+    scan.adaptive::<scan_jani::TracePrinter>(0.95, 0.01, 100, None);
+    // Samples are always within the support of their distribution
+    assert!(scan.successes() > 0);
+    assert_eq!(scan.failures(), 0);
+}
+
+#[test]
+fn type_error() {
+    let Err(err) = scan_jani::load(Path::new("./tests/type_error.jani"), Overflow::default())
+    else {
+        panic!("badly typed model must not load");
+    };
+    // Variables are named as in the model
+    let msg = format!("{err:#}");
+    assert!(msg.contains("in expression `count`"), "{msg}");
+}
+
+#[test]
+fn analyze() {
+    let diagnostics = scan_jani::analyze(Path::new("./tests/test.jani")).expect("analyze");
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+}
+
+#[test]
+fn dot() {
+    let dot = scan_jani::dot(Path::new("./tests/test.jani")).expect("dot");
+    assert!(dot.starts_with("digraph {"));
+    // Locations are named after their automaton
+    assert!(dot.contains("label=\"test.l\""), "{dot}");
+}
+
 fn test(path: &Path) {
-    let (scan, ..) = scan_jani::load(path).expect("load");
+    let (scan, ..) = scan_jani::load(path, Overflow::default()).expect("load");
     scan.adaptive::<scan_jani::TracePrinter>(0.95, 0.01, 10000, None);
 }
@@ -1,14 +1,20 @@
 //! Model builder for SCAN's XML specification format.
 
 use crate::parser::{Executable, If, OmgType, OmgTypes, Param, Parser, Scxml, Send, Target};
-use anyhow::anyhow;
+use anyhow::{Context, anyhow};
 use boa_interner::{Interner, ToInternedString};
 use log::{info, trace};
 use rand::rngs::SmallRng;
-use scan_core::{channel_system::*, *};
+use scan_core::{
+    channel_system::*,
+    program_graph::{Priority, Symbol, Urgency},
+    *,
+};
 use std::{
     collections::{HashMap, HashSet},
+    fmt::{Debug, Formatter},
     ops::Not,
+    sync::Arc,
 };
 
 // TODO:
@@ -22,8 +28,10 @@ pub struct ScxmlModel {
     pub parameters: HashMap<Channel, (PgId, PgId, usize, String)>,
     pub int_queues: HashSet<Channel>,
     pub ext_queues: HashMap<Channel, PgId>,
+    pub fault_queues: HashMap<Channel, (PgId, usize)>,
+    pub broadcasts: HashMap<Channel, Vec<Channel>>,
     pub events: Vec<String>,
-    pub ports: Vec<(String, Type)>,
+    pub ports: Vec<(String, Channel, Type)>,
     pub assumes: Vec<String>,
     pub guarantees: Vec<String>,
 }
@@ -43,6 +51,30 @@ struct EventBuilder {
     index: usize,
 }
 
+// Unreliable channel carrying an event subject to faults to its target,
+// together with the event's origin and parameters,
+// so that faults affect them as a whole.
+#[derive(Debug, Clone)]
+struct FaultQueue {
+    channel: Channel,
+    // The (event, origin, params...) tuple type of the messages.
+    r#type: Type,
+    // Name and type of the parameters, sorted by name.
+    params: Vec<(String, Type)>,
+}
+
+// The sending PG and event, and the scope of the executable content,
+// needed to pass the parameters of the event.
+// WARN: vars and params have the same type so they could be easily swapped by mistake.
+struct SendContext<'a> {
+    pg_id: PgId,
+    event_idx: usize,
+    vars: &'a HashMap<String, (Var, String)>,
+    origin: Option<Var>,
+    params: &'a HashMap<String, (Var, String)>,
+    interner: &'a Interner,
+}
+
 #[derive(Debug, Clone)]
 enum EcmaObj<V: Clone> {
     PrimitiveData(Expression<V>, String),
@@ -58,15 +90,10 @@ pub struct ModelBuilder {
     // NOTE: This is necessary because, at the moment, it is not possible to derive one from the other.
     // QUESTION: is there a better way?
     types: HashMap<String, (OmgType, Type)>,
-    // Associates an enum's label with a **globally unique** index.
+    // Associates an enum's label with the enums it belongs to.
     // The same label can belong to multiple enums,
-    // and given a label it is not possible to recover the originating enum.
-    // WARN FIXME TODO: simplistic implementation of enums
-    enums: HashMap<String, Integer>,
-    // Associates a struct's id and field id with the index it is assigned in the struct's representation as a product.
-    // NOTE: This is decided arbitrarily and not imposed by the OMG type definition.
-    // QUESTION: Is there a better way?
-    structs: HashMap<(String, String), usize>,
+    // in which case the originating enum has to be recovered from the expected type.
+    enums: HashMap<String, Vec<Arc<Enum>>>,
     // Each State Chart has an associated Program Graph,
     // and an arbitrary, progressive index
     fsm_names: HashMap<PgId, String>,
@@ -83,6 +110,20 @@ pub struct ModelBuilder {
     // - paramName
     // that is needed
     parameters: HashMap<(PgId, PgId, usize, String), Channel>,
+    // Events subject to faults have dedicated channels for them,
+    // one for each:
+    // - receiverStateChart
+    // - sentEvent (index)
+    fault_queues: HashMap<(PgId, usize), FaultQueue>,
+    // Events sent without a target are broadcast to their receivers,
+    // through a broadcast channel for each:
+    // - sentEvent (index)
+    broadcasts: HashMap<usize, Channel>,
+    // and their parameters through a broadcast channel for each:
+    // - senderStateChart
+    // - sentEvent (index)
+    // - paramName
+    param_broadcasts: HashMap<(PgId, usize, String), Channel>,
     // Properties
     guarantees: Vec<(String, Pmtl<usize>)>,
     assumes: Vec<(String, Pmtl<usize>)>,
@@ -98,12 +139,21 @@ impl ModelBuilder {
     /// Can fail if the model specification contains semantic errors
     /// (particularly type mismatches)
     /// or references to non-existing items.
+    ///
+    /// Executable content produces long chains of internal transitions,
+    /// which can be merged to reduce the number of steps of each execution
+    /// (see [`ChannelSystemBuilder::set_compression`](scan_core::channel_system::ChannelSystemBuilder::set_compression)).
     pub fn build(
         mut parser: Parser,
+        overflow: Overflow,
+        compression: bool,
     ) -> anyhow::Result<(CsModel<SmallRng>, PmtlOracle, ScxmlModel)> {
         let mut model_builder = ModelBuilder::default();
+        model_builder.cs.set_overflow(overflow);
+        model_builder.cs.set_compression(compression);
         model_builder.build_types(&parser.types)?;
         model_builder.prebuild_processes(&mut parser)?;
+        model_builder.build_faults(&parser)?;
 
         info!(target: "build", "Visit process list");
         for (_id, fsm) in parser.process_list.iter() {
@@ -124,36 +174,43 @@ impl ModelBuilder {
             let scan_type = match omg_type {
                 OmgType::Boolean => Type::Boolean,
                 OmgType::Int32 => Type::Integer,
+                OmgType::Int(int_type) => Type::Int(*int_type),
                 OmgType::F64 => Type::Float,
+                OmgType::String => Type::String,
                 OmgType::Uri => Type::Integer,
                 OmgType::Structure(fields) => {
-                    let mut fields_type: Vec<Type> = Vec::new();
-                    for (index, (field_id, field_type)) in fields.iter().enumerate() {
-                        self.structs
-                            .insert((name.to_owned(), field_id.to_owned()), index);
+                    // NOTE: fields are sorted by name so that the record is the same on every run.
+                    let mut fields = Vec::from_iter(fields.iter());
+                    fields.sort_unstable();
+                    let mut fields_type: Vec<(String, Type)> = Vec::new();
+                    for (field_id, field_type) in fields {
                         // NOTE: fields must have an already known type, to aviod recursion.
+                        self.register_type(field_type)?;
                         let (_, field_type) = self.types.get(field_type).ok_or(anyhow!(
                             "unknown type {} of field {} in struct {}",
                             field_type,
                             field_id,
                             name
                         ))?;
-                        // NOTE: fields have to be inserted in this order or they will not correspond to their index.
-                        fields_type.push(field_type.clone());
+                        fields_type.push((field_id.to_owned(), field_type.clone()));
                     }
-                    Type::Product(fields_type)
+                    Type::Record(Arc::new(Record::new(name.to_owned(), fields_type)))
                 }
                 OmgType::Enumeration(labels) => {
-                    // NOTE: enum labels are assigned a **globally unique** index,
-                    // and the same label can appear in different enums.
-                    // This makes it so that SUCCESS and FAILURE from ActionResponse are the same as those in ConditionResponse.
+                    // NOTE: the same label can appear in different enums,
+                    // e.g., SUCCESS and FAILURE from both ActionResponse and ConditionResponse.
+                    let r#enum = Arc::new(Enum::new(name.to_owned(), labels.to_owned()));
                     for label in labels.iter() {
-                        if !self.enums.contains_key(label) {
-                            let idx = self.enums.len();
-                            self.enums.insert(label.to_owned(), idx as Integer);
-                        }
+                        self.enums
+                            .entry(label.to_owned())
+                            .or_default()
+                            .push(r#enum.clone());
                     }
-                    Type::Integer
+                    Type::Enum(r#enum)
+                }
+                OmgType::Array(element_type, size) => {
+                    self.register_type(element_type)?;
+                    Self::array_type(&self.types[element_type].1, *size)
                 }
             };
             self.types
@@ -162,6 +219,51 @@ impl ModelBuilder {
         Ok(())
     }
 
+    // Registers array and sequence types, which are identified by the name of the type of their elements,
+    // as in `sequence<T>`, `sequence<T, N>` (with no fixed size) and `T[N]` (with fixed size N).
+    fn register_type(&mut self, name: &str) -> anyhow::Result<()> {
+        if self.types.contains_key(name) {
+            return Ok(());
+        }
+        let (element_type, size) = if let Some(sequence) = name
+            .strip_prefix("sequence<")
+            .and_then(|name| name.strip_suffix('>'))
+        {
+            // Bounded sequences are not fixed-size, so the bound is ignored.
+            let element_type = sequence
+                .rsplit_once(',')
+                .filter(|(_, bound)| bound.trim().parse::<usize>().is_ok())
+                .map_or(sequence, |(element_type, _)| element_type);
+            (element_type.trim(), None)
+        } else if let Some((element_type, size)) = name
+            .strip_suffix(']')
+            .and_then(|name| name.rsplit_once('['))
+        {
+            let size = size
+                .trim()
+                .parse::<usize>()
+                .with_context(|| format!("bad size of array type {name}"))?;
+            (element_type.trim(), Some(size))
+        } else {
+            return Err(anyhow!("unknown type {name}"));
+        };
+        self.register_type(element_type)?;
+        let scan_type = Self::array_type(&self.types[element_type].1, size);
+        self.types.insert(
+            name.to_owned(),
+            (OmgType::Array(element_type.to_owned(), size), scan_type),
+        );
+        Ok(())
+    }
+
+    fn array_type(element_type: &Type, size: Option<usize>) -> Type {
+        if let Some(size) = size {
+            Type::Array(Box::new(element_type.clone()), size)
+        } else {
+            Type::List(Box::new(element_type.clone()))
+        }
+    }
+
     fn event_index(&mut self, id: &str) -> usize {
         self.event_indexes.get(id).cloned().unwrap_or_else(|| {
             let index = self.events.len();
@@ -182,6 +284,12 @@ impl ModelBuilder {
             let ext_queue = self
                 .cs
                 .new_channel(Type::Product(vec![Type::Integer, Type::Integer]), None);
+            self.cs
+                .set_pg_symbol(pg_id, id)
+                .expect("program graph exists");
+            self.cs
+                .set_channel_symbol(ext_queue, format!("external queue of {id}"))
+                .expect("channel exists");
             let fsm = FsmBuilder { pg_id, ext_queue };
             self.fsm_builders.insert(id.to_string(), fsm);
             self.fsm_names.insert(pg_id, id.to_string());
@@ -197,6 +305,70 @@ impl ModelBuilder {
         Ok(())
     }
 
+    fn build_faults(&mut self, parser: &Parser) -> anyhow::Result<()> {
+        for fault in &parser.faults {
+            if let Some(event) = &fault.event
+                && !self.event_indexes.contains_key(event)
+            {
+                return Err(anyhow!("fault refers to unknown event {event}"));
+            }
+            if let Some(process) = &fault.process
+                && !self.fsm_builders.contains_key(process)
+            {
+                return Err(anyhow!("fault refers to unknown process {process}"));
+            }
+        }
+        // NOTE: events and targets are sorted so that channels are the same on every run.
+        let mut events = Vec::from_iter(self.event_indexes.iter());
+        events.sort_unstable_by_key(|(_, idx)| **idx);
+        for (event_name, &event_index) in events {
+            let event_builder = &self.events[event_index];
+            if event_builder.senders.is_empty() {
+                continue;
+            }
+            let mut targets = Vec::from_iter(event_builder.receivers.iter().copied());
+            targets.sort_unstable();
+            for target in targets {
+                let Some(fault) = parser
+                    .faults
+                    .iter()
+                    .filter(|fault| fault.applies(event_name, &self.fsm_names[&target]))
+                    .max_by_key(|fault| fault.specificity())
+                else {
+                    continue;
+                };
+                let mut params = Vec::with_capacity(event_builder.params.len());
+                for (param_name, param_type_name) in &event_builder.params {
+                    let param_type = self
+                        .types
+                        .get(param_type_name)
+                        .ok_or(anyhow!("type {} not found", param_type_name))?
+                        .1
+                        .to_owned();
+                    params.push((param_name.to_owned(), param_type));
+                }
+                params.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+                let r#type = Type::Product(
+                    [Type::Integer, Type::Integer]
+                        .into_iter()
+                        .chain(params.iter().map(|(_, t)| t.clone()))
+                        .collect(),
+                );
+                let channel = self.cs.new_channel(r#type.clone(), None);
+                self.cs.set_fault_model(channel, fault.model)?;
+                self.fault_queues.insert(
+                    (target, event_index),
+                    FaultQueue {
+                        channel,
+                        r#type,
+                        params,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
     fn prebuild_fsms(
         &mut self,
         pg_id: PgId,
@@ -205,6 +377,7 @@ impl ModelBuilder {
     ) -> anyhow::Result<()> {
         let mut types = HashMap::new();
         for data in &fmt.datamodel {
+            self.register_type(&data.omg_type)?;
             types.insert(data.id.to_owned(), data.omg_type.as_str().to_owned());
         }
         for (_, state) in fmt.states.iter_mut() {
@@ -237,16 +410,14 @@ impl ModelBuilder {
         interner: &Interner,
     ) -> anyhow::Result<()> {
         match executable {
-            Executable::Assign {
-                location: _,
-                expr: _,
-            } => Ok(()),
+            Executable::Assign { .. } => Ok(()),
             Executable::Raise { event: _ } => Ok(()),
             Executable::Send(Send {
                 event,
                 target: _,
                 delay: _,
                 params,
+                position: _,
             }) => {
                 let event_index = self.event_index(event);
                 let builder = self.events.get_mut(event_index).expect("index must exist");
@@ -255,7 +426,8 @@ impl ModelBuilder {
                     let param_type = param.omg_type.to_owned();
                     let param_type = param_type
                         .or_else(|| self.infer_type(&param.expr, types, interner).ok())
-                        .ok_or(anyhow!("missing type annotation for param {}", param.name))?;
+                        .ok_or(anyhow!("missing type annotation for param {}", param.name))
+                        .with_context(|| param.position.clone())?;
                     // Update omg_type value so that it contains its type for sure
                     param.omg_type = Some(param_type.to_owned());
                     let builder = self.events.get_mut(event_index).expect("index must exist");
@@ -265,7 +437,8 @@ impl ModelBuilder {
                     // Type parameters should not change type
                     if let Some(prev_type) = prev_type {
                         if prev_type != param_type {
-                            return Err(anyhow!("type parameter mismatch"));
+                            return Err(anyhow!("type parameter mismatch"))
+                                .with_context(|| param.position.clone());
                         }
                     }
                 }
@@ -303,18 +476,18 @@ impl ModelBuilder {
                     .get(&ident)
                     .cloned()
                     .or_else(|| {
-                        if self.enums.contains_key(&ident) {
-                            Some(String::from("int32"))
-                        } else {
-                            None
-                        }
+                        // Labels belonging to multiple enums have ambiguous type.
+                        self.enums
+                            .get(&ident)
+                            .filter(|enums| enums.len() == 1)
+                            .map(|enums| enums[0].name().to_owned())
                     })
                     .ok_or(anyhow!("type cannot be inferred"))
             }
             boa_ast::Expression::Literal(lit) => {
                 use boa_ast::expression::literal::Literal;
                 match lit {
-                    Literal::String(_) => todo!(),
+                    Literal::String(_) => Ok(String::from("string")),
                     Literal::Num(_) => Ok(String::from("f64")),
                     Literal::Int(_) => Ok(String::from("int32")),
                     Literal::BigInt(_) => todo!(),
@@ -360,6 +533,14 @@ impl ModelBuilder {
         }
     }
 
+    // Locations that are part of a macrostep are committed,
+    // so that no other FSM can interleave and time cannot pass during its execution.
+    fn new_committed_location(&mut self, pg_id: PgId) -> Result<Location, CsError> {
+        let location = self.cs.new_location(pg_id)?;
+        self.cs.set_urgency(pg_id, location, Urgency::Committed)?;
+        Ok(location)
+    }
+
     fn build_fsm(&mut self, scxml: &Scxml, interner: &mut Interner) -> anyhow::Result<()> {
         trace!(target: "build", "build fsm {}", scxml.name);
         // Initialize fsm.
@@ -375,6 +556,10 @@ impl ModelBuilder {
             // .initial_location(pg_id)
             .new_initial_location(pg_id)
             .expect("program graph must exist");
+        // Initializing the FSM and entering its initial state is part of the first macrostep.
+        self.cs
+            .set_urgency(pg_id, initial_loc, Urgency::Committed)
+            .expect("program graph must exist");
         let mut initialize = None;
         // Initialize variables from datamodel
         // NOTE vars cannot be initialized using previously defined vars because datamodel is an HashMap
@@ -386,28 +571,49 @@ impl ModelBuilder {
                 .ok_or(anyhow!("unknown type"))?
                 .1
                 .to_owned();
+            // Bounded variables start from the value in bounds closest to the default one.
+            let default = match data.bounds {
+                Some(bounds) => bounds.clamp_val(scan_type.default_value()),
+                None => scan_type.default_value(),
+            };
             let var = self
                 .cs
-                .new_var(pg_id, CsExpression::Const(scan_type.default_value()))
+                .new_var(pg_id, CsExpression::Const(default))
                 .expect("program graph exists!");
+            self.cs
+                .set_var_symbol(
+                    var,
+                    Symbol::new(data.id.as_str()).with_source(data.position.clone()),
+                )
+                .expect("variable exists");
+            if let Some(bounds) = data.bounds {
+                self.cs
+                    .set_bounds(var, bounds)
+                    .with_context(|| format!("failed to bound variable '{}'", data.id))
+                    .with_context(|| data.position.clone())?;
+            }
             vars.insert(data.id.to_owned(), (var, data.omg_type.to_owned()));
             // Initialize variable with `expr`, if any, by adding it as effect of `initialize` action.
             if let Some(ref expr) = data.expression {
-                let expr = self.expression(
-                    expr,
-                    interner,
-                    &vars,
-                    None,
-                    &HashMap::new(),
-                    Some(scan_type),
-                )?;
+                let expr = self
+                    .expression(
+                        expr,
+                        interner,
+                        &vars,
+                        None,
+                        &HashMap::new(),
+                        Some(scan_type),
+                    )
+                    .with_context(|| data.position.clone())?;
                 // Initialization has at least an effect, so we need to perform it.
                 // Create action if there was none.
                 let initialize = *initialize.get_or_insert_with(|| {
                     self.cs.new_action(pg_id).expect("program graph must exist")
                 });
                 // This might fail if `expr` does not typecheck.
-                self.cs.add_effect(pg_id, initialize, var, expr)?;
+                self.cs
+                    .add_effect(pg_id, initialize, var, expr)
+                    .with_context(|| data.position.clone())?;
             }
         }
         // Make vars immutable
@@ -416,7 +622,9 @@ impl ModelBuilder {
         // After initializing datamodel, transition to location representing point-of-entry of initial state of State Chart.
         let initial_state;
         if let Some(initialize) = initialize {
-            initial_state = self.cs.new_location(pg_id).expect("program graph exists!");
+            initial_state = self
+                .new_committed_location(pg_id)
+                .expect("program graph exists!");
             self.cs
                 .add_transition(pg_id, initial_loc, initialize, initial_state, None)
                 .expect("hand-coded args");
@@ -449,6 +657,9 @@ impl ModelBuilder {
             .expect("program graph exists!");
         // Implement internal queue
         let int_queue = self.cs.new_channel(Type::Integer, None);
+        self.cs
+            .set_channel_symbol(int_queue, format!("internal queue of {}", scxml.name))
+            .expect("channel exists");
         // This we only need for backtracking.
         let _ = self.int_queues.insert(int_queue);
         let dequeue_int = self
@@ -549,14 +760,56 @@ impl ModelBuilder {
         let param_vars = param_vars;
         let param_actions = param_actions;
 
+        // Events subject to faults are received together with their origin and parameters,
+        // which are then written onto the designated variables.
+        let mut dequeue_faulty = Vec::new();
+        for (&(_, event_index), queue) in self
+            .fault_queues
+            .iter()
+            .filter(|((target, _), _)| *target == pg_id)
+        {
+            let event_var = self
+                .cs
+                .new_var(pg_id, CsExpression::Const(queue.r#type.default_value()))
+                .expect("program graph exists!");
+            let dequeue = self
+                .cs
+                .new_receive(pg_id, queue.channel, event_var)
+                .expect("hand-coded args");
+            let process_event = self.cs.new_action(pg_id)?;
+            let component = |idx| {
+                CsExpression::Component(
+                    idx,
+                    Box::new(CsExpression::Var(event_var, queue.r#type.clone())),
+                )
+            };
+            self.cs
+                .add_effect(pg_id, process_event, current_event_var, component(0))
+                .expect("hand-coded args");
+            self.cs
+                .add_effect(pg_id, process_event, origin_var, component(1))
+                .expect("hand-coded args");
+            for (idx, (param_name, _)) in queue.params.iter().enumerate() {
+                let (param_var, _) = param_vars[&(event_index, param_name.to_owned())];
+                self.cs
+                    .add_effect(pg_id, process_event, param_var, component(idx + 2))
+                    .expect("hand-coded args");
+            }
+            dequeue_faulty.push((dequeue, process_event));
+        }
+
         // Consider each of the fsm's states
         for (state_id, state) in scxml.states.iter() {
             trace!(target: "build", "build state {}", state_id);
             // Each state is modeled by multiple locations connected by transitions
             // A starting location is used as a point-of-entry to the execution of the state.
-            let start_loc = *states
-                .entry(state_id.to_owned())
-                .or_insert_with(|| self.cs.new_location(pg_id).expect("program graph exists!"));
+            let start_loc = *states.entry(state_id.to_owned()).or_insert_with(|| {
+                self.new_committed_location(pg_id)
+                    .expect("program graph exists!")
+            });
+            self.cs
+                .set_location_symbol(start_loc, state_id.as_str())
+                .expect("location exists");
             // Execute the state's `onentry` executable content
             let mut onentry_loc = start_loc;
             for executable in state.on_entry.iter() {
@@ -577,17 +830,25 @@ impl ModelBuilder {
             let onentry_loc = onentry_loc;
 
             // Location where autonomous/eventless/NULL transitions activate
-            let mut null_trans = onentry_loc;
+            let null_trans = onentry_loc;
             // Location where internal events are dequeued
-            let int_queue_loc = self.cs.new_location(pg_id).expect("program graph exists!");
-            // Location where external events are dequeued
+            let int_queue_loc = self
+                .new_committed_location(pg_id)
+                .expect("program graph exists!");
+            // Location where external events are dequeued.
+            // This is the only location where the FSM waits for other FSMs (and time) to proceed,
+            // as all other locations are committed so that macrosteps are executed atomically.
             let ext_queue_loc = self.cs.new_location(pg_id).expect("program graph exists!");
             // Location where eventful transitions activate
-            let mut eventful_trans = self.cs.new_location(pg_id).expect("program graph exists!");
+            let eventful_trans = self
+                .new_committed_location(pg_id)
+                .expect("program graph exists!");
             // int_origin_loc will not be needed outside of this scope
             {
                 // Location where the origin of internal events is set as own.
-                let int_origin_loc = self.cs.new_location(pg_id).expect("program graph exists!");
+                let int_origin_loc = self
+                    .new_committed_location(pg_id)
+                    .expect("program graph exists!");
                 // Transition dequeueing a new internal event and searching for first active eventful transition
                 self.cs
                     .add_transition(pg_id, int_queue_loc, dequeue_int, int_origin_loc, None)
@@ -611,14 +872,16 @@ impl ModelBuilder {
                     .expect("hand-coded args");
             }
             // Location where parameters of events are read into suitable variables.
-            let ext_event_processing_param =
-                self.cs.new_location(pg_id).expect("program graph exists!");
+            let ext_event_processing_param = self
+                .new_committed_location(pg_id)
+                .expect("program graph exists!");
             // Process external events by reading the (event, origin) pair and writing the components to the designated variables.
             // ext_event_processing_loc will not be needed outside of this scope.
             {
                 // Location where the index/origin of external events are dequeued
-                let ext_event_processing_loc =
-                    self.cs.new_location(pg_id).expect("program graph exists!");
+                let ext_event_processing_loc = self
+                    .new_committed_location(pg_id)
+                    .expect("program graph exists!");
                 // Dequeue a new external event and search for first active named transition.
                 self.cs
                     .add_transition(
@@ -639,17 +902,31 @@ impl ModelBuilder {
                     )
                     .expect("hand-coded args");
             }
+            // Dequeue a new external event subject to faults and search for first active named transition.
+            for &(dequeue, process_event) in &dequeue_faulty {
+                let processing_loc = self
+                    .new_committed_location(pg_id)
+                    .expect("program graph exists!");
+                self.cs
+                    .add_transition(pg_id, ext_queue_loc, dequeue, processing_loc, None)
+                    .expect("hand-coded args");
+                self.cs
+                    .add_transition(pg_id, processing_loc, process_event, eventful_trans, None)
+                    .expect("hand-coded args");
+            }
             // Keep track of all known events.
             let mut known_events = Vec::new();
             // Retreive external event's parameters
             // We need to set up the parameter-passing channel for every possible event that could be sent,
             // from any possible other fsm,
             // and for any parameter of the event.
-            for event_builder in self
+            let event_builders = self
                 .events
                 .iter()
                 .filter(|eb| eb.receivers.contains(&pg_id) && !eb.senders.is_empty())
-            {
+                .cloned()
+                .collect::<Vec<_>>();
+            for event_builder in event_builders.iter() {
                 let event_index = event_builder.index;
                 for &sender_id in &event_builder.senders {
                     // Expression checking event and sender correspond to the given ones.
@@ -672,7 +949,9 @@ impl ModelBuilder {
                         let read_param = *param_actions
                             .get(&(sender_id, event_index, param_name.to_owned()))
                             .expect("has to be there");
-                        let next_loc = self.cs.new_location(pg_id).expect("program graph exists!");
+                        let next_loc = self
+                            .new_committed_location(pg_id)
+                            .expect("program graph exists!");
                         self.cs
                             .add_transition(
                                 pg_id,
@@ -712,7 +991,8 @@ impl ModelBuilder {
                 .expect("has to work");
 
             // Consider each of the state's transitions.
-            for transition in state.transitions.iter() {
+            // Transitions are prioritized in document order, so that the first active one is taken.
+            for (idx, transition) in state.transitions.iter().enumerate() {
                 trace!(
                     target: "build",
                     "build {} transition to {}",
@@ -725,7 +1005,10 @@ impl ModelBuilder {
                 // Get or create the location corresponding to the target state.
                 let target_loc = *states
                     .entry(transition.target.to_owned())
-                    .or_insert_with(|| self.cs.new_location(pg_id).expect("pg_id should exist"));
+                    .or_insert_with(|| {
+                        self.new_committed_location(pg_id)
+                            .expect("pg_id should exist")
+                    });
 
                 // Set up origin and parameters for conditional/executable content.
                 let exec_origin;
@@ -760,14 +1043,13 @@ impl ModelBuilder {
                             &exec_params,
                             Some(Type::Boolean),
                         )
+                        .with_context(|| transition.position.clone())
                     })
                     .transpose()?;
 
                 // Location corresponding to checking if the transition is active.
                 // Has to be defined depending on the type of transition.
                 let check_trans_loc;
-                // Location corresponding to verifying the transition is not active and moving to next one.
-                let next_trans_loc = self.cs.new_location(pg_id).expect("{pg_id:?} exists");
 
                 // Guard for transition.
                 // Has to be defined depending on the type of transition, etc...
@@ -788,29 +1070,33 @@ impl ModelBuilder {
                         .map(|cond| CsExpression::and(vec![event_match.clone(), cond]))
                         .transpose()?
                         .or(Some(event_match));
-                    // Check this transition after the other eventful transitions.
+                    // Check this transition together with the other eventful transitions.
                     check_trans_loc = eventful_trans;
-                    // Move location of next eventful transitions to a new location.
-                    eventful_trans = next_trans_loc;
                 } else {
                     // NULL (autonomous/eventless) transition
                     // No event needs to happen in order to trigger this transition.
                     guard = cond;
-                    // Check this transition after the other eventless transitions.
+                    // Check this transition together with the other eventless transitions.
                     check_trans_loc = null_trans;
-                    // Move location of next eventless transitions to a new location.
-                    null_trans = next_trans_loc;
                 }
 
                 // If transition is active, execute the relevant executable content and then the transition to the target.
                 // Could fail if 'cond' expression was not acceptable as guard.
-                let mut exec_trans_loc = self.cs.new_location(pg_id)?;
-                self.cs.add_autonomous_transition(
-                    pg_id,
-                    check_trans_loc,
-                    exec_trans_loc,
-                    guard.to_owned(),
-                )?;
+                let exec_trans_start = self.new_committed_location(pg_id)?;
+                self.cs
+                    .add_autonomous_transition(pg_id, check_trans_loc, exec_trans_start, guard)
+                    .with_context(|| transition.position.clone())?;
+                // Earlier transitions take precedence over later ones,
+                // and all of them over moving on to process the next event (with the default priority).
+                self.cs
+                    .set_priority(
+                        pg_id,
+                        check_trans_loc,
+                        exec_trans_start,
+                        (state.transitions.len() - idx) as Priority,
+                    )
+                    .expect("transition has just been added");
+                let mut exec_trans_loc = exec_trans_start;
                 // First execute the executable content of the state's `on_exit` tag,
                 // then that of the `transition` tag, following the specs.
                 for exec in state.on_exit.iter().chain(transition.effects.iter()) {
@@ -830,28 +1116,15 @@ impl ModelBuilder {
                 self.cs
                     .add_autonomous_transition(pg_id, exec_trans_loc, target_loc, None)
                     .expect("has to work");
-                // If the current transition is not active, move on to check the next one.
-                // NOTE: an autonomous transition without cond is always active so there is no point processing further transitions.
-                // This happens in State Charts already, so we model it faithfully without optimizations.
-                let not_guard = guard
-                    .map(CsExpression::not)
-                    .transpose()?
-                    .unwrap_or(CsExpression::from(false));
-                self.cs
-                    .add_autonomous_transition(
-                        pg_id,
-                        check_trans_loc,
-                        next_trans_loc,
-                        Some(not_guard),
-                    )
-                    .expect("cannot fail because guard was already checked");
             }
 
             // Connect NULL events with named events
-            // by transitioning from last "NUll" location to dequeuing event location.
+            // by transitioning from "NUll" location to dequeuing event location,
+            // if no eventless transition is active.
             self.cs
                 .add_autonomous_transition(pg_id, null_trans, int_queue_loc, None)?;
-            // Return to dequeue a new (internal or external) event.
+            // Return to dequeue a new (internal or external) event,
+            // if no eventful transition is active.
             self.cs
                 .add_autonomous_transition(pg_id, eventful_trans, int_queue_loc, None)?;
         }
@@ -877,7 +1150,7 @@ impl ModelBuilder {
                 let raise =
                     self.cs
                         .new_send(pg_id, int_queue, CsExpression::from(event_idx as Integer))?;
-                let next_loc = self.cs.new_location(pg_id)?;
+                let next_loc = self.new_committed_location(pg_id)?;
                 // queue the internal event
                 self.cs.add_transition(pg_id, loc, raise, next_loc, None)?;
                 Ok(next_loc)
@@ -887,11 +1160,13 @@ impl ModelBuilder {
                 target,
                 delay,
                 params: send_params,
+                position,
             }) => {
                 let event_idx = *self
                     .event_indexes
                     .get(event)
-                    .ok_or(anyhow!("event not found"))?;
+                    .ok_or(anyhow!("event not found"))
+                    .with_context(|| position.clone())?;
                 let mut loc = loc;
                 if let Some(delay) = delay {
                     // WARN NOTE FIXME: here we could reuse some other clock instead of creating a new one every time.
@@ -906,7 +1181,7 @@ impl ModelBuilder {
                         .add_transition(pg_id, loc, reset, next_loc, None)
                         .expect("params are right");
                     loc = next_loc;
-                    let next_loc = self.cs.new_location(pg_id).expect("PG exists");
+                    let next_loc = self.new_committed_location(pg_id).expect("PG exists");
                     self.cs
                         .add_autonomous_timed_transition(
                             pg_id,
@@ -919,7 +1194,7 @@ impl ModelBuilder {
                     loc = next_loc;
                 }
                 if let Some(target) = target {
-                    let done_loc = self.cs.new_location(pg_id)?;
+                    let done_loc = self.new_committed_location(pg_id)?;
                     let targets;
                     let target_expr;
                     match target {
@@ -927,21 +1202,25 @@ impl ModelBuilder {
                             let target_builder = self
                                 .fsm_builders
                                 .get(target)
-                                .ok_or(anyhow!(format!("target {target} not found")))?;
+                                .ok_or(anyhow!(format!("target {target} not found")))
+                                .with_context(|| position.clone())?;
                             targets = vec![target_builder.pg_id];
                             target_expr = Some(CsExpression::from(
                                 u16::from(target_builder.pg_id) as Integer
                             ));
                         }
                         Target::Expr(targetexpr) => {
-                            target_expr = Some(self.expression(
-                                targetexpr,
-                                interner,
-                                vars,
-                                origin.as_ref(),
-                                params,
-                                Some(Type::Integer),
-                            )?);
+                            target_expr = Some(
+                                self.expression(
+                                    targetexpr,
+                                    interner,
+                                    vars,
+                                    origin.as_ref(),
+                                    params,
+                                    Some(Type::Integer),
+                                )
+                                .with_context(|| position.clone())?,
+                            );
                             targets = self.events[event_idx].receivers.iter().cloned().collect();
                         }
                     }
@@ -950,20 +1229,43 @@ impl ModelBuilder {
                         let target_builder =
                             self.fsm_builders.get(target_name).expect("it must exist");
                         let target_ext_queue = target_builder.ext_queue;
-                        let send_event = self
-                            .cs
-                            .new_send(
-                                pg_id,
-                                target_ext_queue,
-                                CsExpression::Tuple(vec![
-                                    CsExpression::from(event_idx as Integer),
-                                    CsExpression::from(u16::from(pg_id) as Integer),
-                                ]),
-                            )
-                            .expect("params are hard-coded");
+                        let fault_queue = self.fault_queues.get(&(target_id, event_idx)).cloned();
+                        let send_event = if let Some(queue) = &fault_queue {
+                            // Events subject to faults carry their parameters along,
+                            // with default values for those the sender does not pass.
+                            let mut message = vec![
+                                CsExpression::from(event_idx as Integer),
+                                CsExpression::from(u16::from(pg_id) as Integer),
+                            ];
+                            for (param_name, param_type) in &queue.params {
+                                if let Some(param) =
+                                    send_params.iter().find(|param| &param.name == param_name)
+                                {
+                                    let (expr, _) = self
+                                        .param_expression(param, vars, origin, params, interner)?;
+                                    message.push(expr);
+                                } else {
+                                    message.push(CsExpression::Const(param_type.default_value()));
+                                }
+                            }
+                            self.cs
+                                .new_send(pg_id, queue.channel, CsExpression::Tuple(message))
+                                .with_context(|| position.clone())?
+                        } else {
+                            self.cs
+                                .new_send(
+                                    pg_id,
+                                    target_ext_queue,
+                                    CsExpression::Tuple(vec![
+                                        CsExpression::from(event_idx as Integer),
+                                        CsExpression::from(u16::from(pg_id) as Integer),
+                                    ]),
+                                )
+                                .expect("params are hard-coded")
+                        };
 
                         // Send event and event origin before moving on to next location.
-                        let mut next_loc = self.cs.new_location(pg_id).expect("PG exists");
+                        let mut next_loc = self.new_committed_location(pg_id).expect("PG exists");
                         self.cs
                             .add_transition(
                                 pg_id,
@@ -979,13 +1281,21 @@ impl ModelBuilder {
                             )
                             .expect("params are right");
 
-                        // Pass parameters. This could fail due to param content.
-                        for param in send_params {
-                            // Updates next location.
-                            next_loc = self.send_param(
-                                pg_id, target_id, param, event_idx, next_loc, vars, origin, params,
+                        // Pass parameters, unless they have been sent along with the event.
+                        // This could fail due to param content.
+                        if fault_queue.is_none() {
+                            let ctx = SendContext {
+                                pg_id,
+                                event_idx,
+                                vars,
+                                origin,
+                                params,
                                 interner,
-                            )?;
+                            };
+                            for param in send_params {
+                                // Updates next location.
+                                next_loc = self.send_param(&ctx, target_id, param, next_loc)?;
+                            }
                         }
                         // Once sending event and args done, get to exit-point
                         self.cs
@@ -996,21 +1306,75 @@ impl ModelBuilder {
                     Ok(done_loc)
                 } else {
                     // WARN: This behavior is non-compliant with the SCXML specification
-                    // An event sent without specifiying the target is sent to all FSMs that can process it
-                    let targets = self.events[event_idx]
+                    // An event sent without specifiying the target is sent to all FSMs that can process it,
+                    // at once through a broadcast channel, except those it is subject to faults for.
+                    // NOTE: targets are sorted so that channels are the same on every run.
+                    let mut targets = self.events[event_idx]
                         .receivers
                         .iter()
-                        .cloned()
+                        .copied()
                         .collect::<Vec<_>>();
+                    targets.sort_unstable();
+                    let (faulty_targets, targets): (Vec<_>, Vec<_>) = targets
+                        .into_iter()
+                        .partition(|target| self.fault_queues.contains_key(&(*target, event_idx)));
                     let mut next_loc = loc;
-                    for target in targets {
+                    if !targets.is_empty() {
+                        let broadcast = if let Some(broadcast) = self.broadcasts.get(&event_idx) {
+                            *broadcast
+                        } else {
+                            let broadcast = self
+                                .cs
+                                .new_broadcast(Type::Product(vec![Type::Integer, Type::Integer]));
+                            for target in &targets {
+                                let ext_queue =
+                                    self.fsm_builders[&self.fsm_names[target]].ext_queue;
+                                self.cs
+                                    .subscribe(broadcast, ext_queue)
+                                    .expect("external queues can subscribe");
+                            }
+                            self.broadcasts.insert(event_idx, broadcast);
+                            broadcast
+                        };
+                        let send_event = self
+                            .cs
+                            .new_send(
+                                pg_id,
+                                broadcast,
+                                CsExpression::Tuple(vec![
+                                    CsExpression::from(event_idx as Integer),
+                                    CsExpression::from(u16::from(pg_id) as Integer),
+                                ]),
+                            )
+                            .expect("params are hard-coded");
+                        let send_loc = self.new_committed_location(pg_id).expect("PG exists");
+                        self.cs
+                            .add_transition(pg_id, next_loc, send_event, send_loc, None)
+                            .expect("params are right");
+                        next_loc = send_loc;
+                        let ctx = SendContext {
+                            pg_id,
+                            event_idx,
+                            vars,
+                            origin,
+                            params,
+                            interner,
+                        };
+                        for param in send_params {
+                            // Updates next location.
+                            next_loc = self.broadcast_param(&ctx, &targets, param, next_loc)?;
+                        }
+                    }
+                    // The delay, if any, has already been waited for.
+                    for target in faulty_targets {
                         let target_name = self.fsm_names.get(&target).cloned();
                         next_loc = self.add_executable(
                             &Executable::Send(Send {
                                 event: event.to_owned(),
                                 target: target_name.map(Target::Id),
-                                delay: *delay,
+                                delay: None,
                                 params: send_params.to_owned(),
+                                position: position.clone(),
                             }),
                             pg_id,
                             int_queue,
@@ -1024,38 +1388,91 @@ impl ModelBuilder {
                     Ok(next_loc)
                 }
             }
-            Executable::Assign { location, expr } => {
+            Executable::Assign {
+                location,
+                index,
+                expr,
+                position,
+            } => {
                 // Add a transition that perform the assignment via the effect of the `assign` action.
-                let (var, scan_type) = vars.get(location).ok_or(anyhow!("undefined variable"))?;
+                let (var, scan_type) = vars
+                    .get(location)
+                    .ok_or(anyhow!("undefined variable {location}"))
+                    .with_context(|| position.clone())?;
                 let scan_type = self.types.get(scan_type).expect("type").1.clone();
-                let expr = self.expression(
-                    expr,
-                    interner,
-                    vars,
-                    origin.as_ref(),
-                    params,
-                    Some(scan_type),
-                )?;
+                let expr = if let Some(index) = index {
+                    // Indexed assignment updates a single element of the array.
+                    let (Type::List(element_type) | Type::Array(element_type, _)) = &scan_type
+                    else {
+                        return Err(anyhow!("indexed location {location} is not an array"))
+                            .with_context(|| position.clone());
+                    };
+                    let element_type = (**element_type).clone();
+                    let index = self
+                        .expression(
+                            index,
+                            interner,
+                            vars,
+                            origin.as_ref(),
+                            params,
+                            Some(Type::Integer),
+                        )
+                        .with_context(|| position.clone())?;
+                    let element = self
+                        .expression(
+                            expr,
+                            interner,
+                            vars,
+                            origin.as_ref(),
+                            params,
+                            Some(element_type),
+                        )
+                        .with_context(|| position.clone())?;
+                    CsExpression::Update(Box::new((
+                        CsExpression::Var(*var, scan_type),
+                        index,
+                        element,
+                    )))
+                } else {
+                    self.expression(
+                        expr,
+                        interner,
+                        vars,
+                        origin.as_ref(),
+                        params,
+                        Some(scan_type),
+                    )
+                    .with_context(|| position.clone())?
+                };
                 let assign = self.cs.new_action(pg_id).expect("PG exists");
-                self.cs.add_effect(pg_id, assign, *var, expr)?;
-                let next_loc = self.cs.new_location(pg_id).unwrap();
+                self.cs
+                    .add_effect(pg_id, assign, *var, expr)
+                    .with_context(|| position.clone())?;
+                let next_loc = self.new_committed_location(pg_id).unwrap();
                 self.cs.add_transition(pg_id, loc, assign, next_loc, None)?;
                 Ok(next_loc)
             }
-            Executable::If(If { r#elif, r#else, .. }) => {
+            Executable::If(If {
+                r#elif,
+                r#else,
+                position,
+                ..
+            }) => {
                 // We go to this location after the if/elif/else block
-                let end_loc = self.cs.new_location(pg_id).unwrap();
+                let end_loc = self.new_committed_location(pg_id).unwrap();
                 let mut curr_loc = loc;
                 for (cond, execs) in r#elif {
-                    let mut next_loc = self.cs.new_location(pg_id).unwrap();
-                    let cond = self.expression(
-                        cond,
-                        interner,
-                        vars,
-                        origin.as_ref(),
-                        params,
-                        Some(Type::Boolean),
-                    )?;
+                    let mut next_loc = self.new_committed_location(pg_id).unwrap();
+                    let cond = self
+                        .expression(
+                            cond,
+                            interner,
+                            vars,
+                            origin.as_ref(),
+                            params,
+                            Some(Type::Boolean),
+                        )
+                        .with_context(|| position.clone())?;
                     self.cs.add_autonomous_transition(
                         pg_id,
                         curr_loc,
@@ -1072,7 +1489,7 @@ impl ModelBuilder {
                         .add_autonomous_transition(pg_id, next_loc, end_loc, None)?;
                     // `elif/else` branch
                     let old_loc = curr_loc;
-                    curr_loc = self.cs.new_location(pg_id).unwrap();
+                    curr_loc = self.new_committed_location(pg_id).unwrap();
                     self.cs
                         .add_autonomous_transition(
                             pg_id,
@@ -1095,51 +1512,108 @@ impl ModelBuilder {
         }
     }
 
-    // WARN: vars and params have the same type so they could be easily swapped by mistake when calling the function.
     fn send_param(
         &mut self,
-        pg_id: PgId,
+        ctx: &SendContext,
         target_id: PgId,
         param: &Param,
-        event_idx: usize,
         param_loc: Location,
-        vars: &HashMap<String, (Var, String)>,
-        origin: Option<Var>,
-        params: &HashMap<String, (Var, String)>,
-        interner: &Interner,
     ) -> Result<Location, anyhow::Error> {
-        // Get param type.
-        let scan_type = self
-            .types
-            .get(param.omg_type.as_ref().expect("type name annotation"))
-            .cloned()
-            .ok_or(anyhow!("undefined type"))?
-            .1;
-        // Build expression from ECMAScript expression.
-        let expr = self.expression(
-            &param.expr,
-            interner,
-            vars,
-            origin.as_ref(),
-            params,
-            Some(scan_type.clone()),
-        )?;
+        let (pg_id, event_idx) = (ctx.pg_id, ctx.event_idx);
+        let (expr, scan_type) =
+            self.param_expression(param, ctx.vars, ctx.origin, ctx.params, ctx.interner)?;
         // Retreive or create channel for parameter passing.
         let param_chn = *self
             .parameters
             .entry((pg_id, target_id, event_idx, param.name.to_owned()))
             .or_insert(self.cs.new_channel(scan_type, None));
         // Can return error if expr is badly typed
-        let pass_param = self.cs.new_send(pg_id, param_chn, expr)?;
-        let next_loc = self.cs.new_location(pg_id).expect("PG exists");
+        let pass_param = self
+            .cs
+            .new_send(pg_id, param_chn, expr)
+            .with_context(|| param.position.clone())?;
+        let next_loc = self.new_committed_location(pg_id).expect("PG exists");
         self.cs
             .add_transition(pg_id, param_loc, pass_param, next_loc, None)
             .expect("hand-made params are correct");
         Ok(next_loc)
     }
 
+    fn broadcast_param(
+        &mut self,
+        ctx: &SendContext,
+        targets: &[PgId],
+        param: &Param,
+        param_loc: Location,
+    ) -> Result<Location, anyhow::Error> {
+        let (pg_id, event_idx) = (ctx.pg_id, ctx.event_idx);
+        let (expr, scan_type) =
+            self.param_expression(param, ctx.vars, ctx.origin, ctx.params, ctx.interner)?;
+        // Retreive or create broadcast channel for parameter passing,
+        // subscribing the parameter channels of all targets.
+        let key = (pg_id, event_idx, param.name.to_owned());
+        let param_broadcast = if let Some(param_broadcast) = self.param_broadcasts.get(&key) {
+            *param_broadcast
+        } else {
+            let param_broadcast = self.cs.new_broadcast(scan_type.clone());
+            for target_id in targets {
+                let param_chn = *self
+                    .parameters
+                    .entry((pg_id, *target_id, event_idx, param.name.to_owned()))
+                    .or_insert_with(|| self.cs.new_channel(scan_type.clone(), None));
+                self.cs
+                    .subscribe(param_broadcast, param_chn)
+                    .with_context(|| param.position.clone())?;
+            }
+            self.param_broadcasts.insert(key, param_broadcast);
+            param_broadcast
+        };
+        // Can return error if expr is badly typed
+        let pass_param = self
+            .cs
+            .new_send(pg_id, param_broadcast, expr)
+            .with_context(|| param.position.clone())?;
+        let next_loc = self.new_committed_location(pg_id).expect("PG exists");
+        self.cs
+            .add_transition(pg_id, param_loc, pass_param, next_loc, None)
+            .expect("hand-made params are correct");
+        Ok(next_loc)
+    }
+
+    // Builds the expression of the parameter's value, together with the parameter's type.
+    // WARN: vars and params have the same type so they could be easily swapped by mistake when calling the function.
+    fn param_expression(
+        &mut self,
+        param: &Param,
+        vars: &HashMap<String, (Var, String)>,
+        origin: Option<Var>,
+        params: &HashMap<String, (Var, String)>,
+        interner: &Interner,
+    ) -> anyhow::Result<(CsExpression, Type)> {
+        // Get param type.
+        let scan_type = self
+            .types
+            .get(param.omg_type.as_ref().expect("type name annotation"))
+            .cloned()
+            .ok_or(anyhow!("undefined type"))
+            .with_context(|| param.position.clone())?
+            .1;
+        // Build expression from ECMAScript expression.
+        let expr = self
+            .expression(
+                &param.expr,
+                interner,
+                vars,
+                origin.as_ref(),
+                params,
+                Some(scan_type.clone()),
+            )
+            .with_context(|| param.position.clone())?;
+        Ok((expr, scan_type))
+    }
+
     // WARN: vars and params have the same type so they could be easily swapped by mistake when calling the function.
-    fn expression<V: Clone>(
+    fn expression<V: Clone + Debug + PartialEq>(
         &mut self,
         expr: &boa_ast::Expression,
         interner: &Interner,
@@ -1148,45 +1622,132 @@ impl ModelBuilder {
         params: &HashMap<String, (V, String)>,
         expr_type: Option<Type>,
     ) -> anyhow::Result<Expression<V>> {
+        let int_type = if let Some(Type::Int(int_type)) = expr_type {
+            Some(int_type)
+        } else {
+            None
+        };
         let expr = match expr {
             boa_ast::Expression::This => todo!(),
             boa_ast::Expression::Identifier(ident) => {
                 let ident = ident.to_interned_string(interner);
-                self.enums
-                    .get(&ident)
-                    .map(|i| Expression::from(*i))
-                    .or_else(|| {
-                        vars.get(&ident).and_then(|(var, t)| {
+                if let Some(val) = self.enum_label(&ident, expr_type.as_ref())? {
+                    Expression::Const(val)
+                } else {
+                    vars.get(&ident)
+                        .and_then(|(var, t)| {
                             self.types
                                 .get(t)
                                 .map(|(_, t)| Expression::Var(var.clone(), t.to_owned()))
                             // .ok_or(anyhow!("missing type {t}"))
                         })
-                    })
-                    .ok_or(anyhow!("unknown identifier: {ident}"))?
+                        .ok_or(anyhow!("unknown identifier: {ident}"))?
+                }
             }
             boa_ast::Expression::Literal(lit) => {
                 use boa_ast::expression::literal::Literal;
                 match lit {
-                    Literal::String(_) => todo!(),
+                    Literal::String(sym) => Expression::Const(Val::String(
+                        interner
+                            .resolve(*sym)
+                            .ok_or(anyhow!("unknown symbol {:?}", sym))?
+                            .utf8()
+                            .ok_or(anyhow!("not utf8"))?
+                            .to_owned(),
+                    )),
                     Literal::Num(f) => Expression::from(*f),
                     Literal::Int(i) if expr_type.is_some_and(|t| matches!(t, Type::Float)) => {
                         Expression::from(*i as f64)
                     }
-                    Literal::Int(i) => Expression::from(*i),
+                    Literal::Int(i) => {
+                        if let Some(int_type) = int_type {
+                            Expression::Const(Val::Int(int_type, (*i).into()))
+                        } else {
+                            Expression::from(*i)
+                        }
+                    }
                     Literal::BigInt(_) => todo!(),
                     Literal::Bool(b) => Expression::from(*b),
                     Literal::Null => todo!(),
                     Literal::Undefined => todo!(),
                 }
             }
-            boa_ast::Expression::ArrayLiteral(_arr) => {
-                todo!()
-                // arr
-                //             .to_pattern(true)
-                //             .ok_or(anyhow!("array syntax error"))?
-                //             .into_iter()
-                //             .map(|element| match element {})
+            boa_ast::Expression::ArrayLiteral(arr) => {
+                // The type of the elements is given by the expected type or else by the first element.
+                let element_type = match &expr_type {
+                    Some(Type::List(t) | Type::Array(t, _)) => Some((**t).clone()),
+                    _ => None,
+                };
+                let mut elements = Vec::new();
+                for element in arr.as_ref() {
+                    let element = element
+                        .as_ref()
+                        .ok_or(anyhow!("array literals cannot have empty elements"))?;
+                    let element_type = element_type.clone().or_else(|| {
+                        elements
+                            .first()
+                            .and_then(|e: &Expression<V>| e.r#type().ok())
+                    });
+                    elements.push(self.expression(
+                        element,
+                        interner,
+                        vars,
+                        origin,
+                        params,
+                        element_type,
+                    )?);
+                }
+                let element_type = element_type
+                    .or_else(|| elements.first().and_then(|e| e.r#type().ok()))
+                    .ok_or(anyhow!("cannot infer type of empty array"))?;
+                // Literals are lists unless an array is expected.
+                if let Some(Type::Array(..)) = expr_type {
+                    Expression::Array(element_type, elements)
+                } else {
+                    Expression::List(element_type, elements)
+                }
+            }
+            boa_ast::Expression::ObjectLiteral(obj) => {
+                // Object literals are records, whose type has to be known in advance.
+                let Some(Type::Record(record)) = expr_type else {
+                    return Err(anyhow!("cannot infer record type of object literal"));
+                };
+                let mut fields: Vec<Option<Expression<V>>> = vec![None; record.fields().len()];
+                for property in obj.properties() {
+                    let boa_ast::expression::literal::PropertyDefinition::Property(
+                        boa_ast::property::PropertyName::Literal(sym),
+                        value,
+                    ) = property
+                    else {
+                        return Err(anyhow!("unsupported property in object literal"));
+                    };
+                    let name = interner
+                        .resolve(*sym)
+                        .ok_or(anyhow!("unknown symbol {:?}", sym))?
+                        .utf8()
+                        .ok_or(anyhow!("not utf8"))?;
+                    let index = record
+                        .index(name)
+                        .ok_or_else(|| anyhow!("record {} has no field {name}", record.name()))?;
+                    let field_type = record.fields()[index].1.clone();
+                    fields[index] = Some(self.expression(
+                        value,
+                        interner,
+                        vars,
+                        origin,
+                        params,
+                        Some(field_type),
+                    )?);
+                }
+                // Missing fields take the default value of their type.
+                let fields = fields
+                    .into_iter()
+                    .zip(record.fields())
+                    .map(|(field, (_, t))| {
+                        field.unwrap_or_else(|| Expression::Const(t.default_value()))
+                    })
+                    .collect();
+                Expression::Record(record, fields)
             }
             boa_ast::Expression::PropertyAccess(prop_acc) => {
                 let expr = &boa_ast::Expression::PropertyAccess(prop_acc.to_owned());
@@ -1205,7 +1766,7 @@ impl ModelBuilder {
                 match unary.op() {
                     UnaryOp::Minus => -expr,
                     UnaryOp::Plus => expr,
-                    UnaryOp::Not => Expression::not(expr)?,
+                    UnaryOp::Not => Expression::Not(Box::new(expr)),
                     _ => return Err(anyhow!("unimplemented operator")),
                 }
             }
@@ -1240,24 +1801,57 @@ impl ModelBuilder {
                         let rhs =
                             self.expression(bin.rhs(), interner, vars, origin, params, rhs_hint)?;
                         match ar_bin {
+                            ArithmeticOp::Add
+                                if matches!(lhs.r#type(), Ok(Type::String))
+                                    || matches!(rhs.r#type(), Ok(Type::String)) =>
+                            {
+                                Expression::Concat(vec![lhs, rhs])
+                            }
                             ArithmeticOp::Add => lhs + rhs,
                             ArithmeticOp::Sub => lhs + (-rhs),
                             ArithmeticOp::Div => Expression::Div(Box::new((lhs, rhs))),
                             ArithmeticOp::Mul => lhs * rhs,
-                            ArithmeticOp::Exp => todo!(),
+                            ArithmeticOp::Exp => Expression::Pow(Box::new((lhs, rhs))),
                             ArithmeticOp::Mod => Expression::Mod(Box::new((lhs, rhs))),
                         }
                     }
                     BinaryOp::Relational(rel_bin) => {
-                        // Type inference is not possible as muliple types are possible
-                        let lhs =
-                            self.expression(bin.lhs(), interner, vars, origin, params, None)?;
-                        let rhs =
-                            self.expression(bin.rhs(), interner, vars, origin, params, None)?;
+                        // Type inference is not possible as muliple types are possible,
+                        // but enum labels are resolved using the type of the other operand.
+                        let enum_hint = |expr: &Expression<V>| {
+                            expr.r#type().ok().filter(|t| matches!(t, Type::Enum(_)))
+                        };
+                        let (lhs, rhs) = if let Ok(lhs) =
+                            self.expression(bin.lhs(), interner, vars, origin, params, None)
+                        {
+                            let rhs_hint = enum_hint(&lhs);
+                            let rhs = self.expression(
+                                bin.rhs(),
+                                interner,
+                                vars,
+                                origin,
+                                params,
+                                rhs_hint,
+                            )?;
+                            (lhs, rhs)
+                        } else {
+                            let rhs =
+                                self.expression(bin.rhs(), interner, vars, origin, params, None)?;
+                            let lhs_hint = enum_hint(&rhs);
+                            let lhs = self.expression(
+                                bin.lhs(),
+                                interner,
+                                vars,
+                                origin,
+                                params,
+                                lhs_hint,
+                            )?;
+                            (lhs, rhs)
+                        };
                         match rel_bin {
                             RelationalOp::Equal => Expression::Equal(Box::new((lhs, rhs))),
                             RelationalOp::NotEqual => {
-                                Expression::Equal(Box::new((lhs, rhs))).not()?
+                                Expression::Not(Box::new(Expression::Equal(Box::new((lhs, rhs)))))
                             }
                             RelationalOp::GreaterThan => Expression::Greater(Box::new((lhs, rhs))),
                             RelationalOp::GreaterThanOrEqual => {
@@ -1288,8 +1882,8 @@ impl ModelBuilder {
                             Some(Type::Boolean),
                         )?;
                         match op {
-                            LogicalOp::And => Expression::and(vec![lhs, rhs])?,
-                            LogicalOp::Or => Expression::or(vec![lhs, rhs])?,
+                            LogicalOp::And => Expression::And(vec![lhs, rhs]),
+                            LogicalOp::Or => Expression::Or(vec![lhs, rhs]),
                             _ => return Err(anyhow!("unimplemented operator")),
                         }
                     }
@@ -1320,8 +1914,26 @@ impl ModelBuilder {
                                     .ok_or(anyhow!("unknown symbol {:?}", sym))?
                                     .utf8()
                                     .ok_or(anyhow!("not utf8"))?;
-                                if ident == "random" && target == "Math" {
-                                    return Ok(Expression::RandFloat(0., 1.));
+                                if target == "Math" {
+                                    return self.math_call(
+                                        ident,
+                                        call.args(),
+                                        interner,
+                                        vars,
+                                        origin,
+                                        params,
+                                    );
+                                } else if let Some((var, type_name)) = vars.get(target) {
+                                    let (_, t) = self.types.get(type_name).expect("var type");
+                                    let array = Expression::Var(var.clone(), t.clone());
+                                    return Self::array_call(
+                                        array,
+                                        ident,
+                                        call.args(),
+                                        |arg, t| {
+                                            self.expression(arg, interner, vars, origin, params, t)
+                                        },
+                                    );
                                 } else {
                                     return Err(anyhow!("unknown call"));
                                 }
@@ -1339,10 +1951,147 @@ impl ModelBuilder {
             }
             _ => return Err(anyhow!("unimplemented expression")),
         };
+        // Type errors report variables by their name in the source.
+        expr.type_with(&|var: &V, f: &mut Formatter<'_>| {
+            if let Some((name, _)) = vars.iter().find(|(_, (v, _))| v == var) {
+                write!(f, "{name}")
+            } else if let Some((name, _)) = params.iter().find(|(_, (v, _))| v == var) {
+                write!(f, "_event.data.{name}")
+            } else if origin.is_some_and(|origin| origin == var) {
+                write!(f, "_event.origin")
+            } else {
+                write!(f, "{var:?}")
+            }
+        })?;
+        // Numerical expressions are converted to the expected sized integer type, if any.
+        if let Some(int_type) = int_type
+            && expr
+                .r#type()
+                .is_ok_and(|t| t.is_numeric() && t != Type::Int(int_type))
+        {
+            Ok(Expression::Cast(Box::new(expr), Type::Int(int_type)))
+        } else {
+            Ok(expr)
+        }
+    }
+
+    // Builds a call to one of the functions of ECMAScript's `Math` object.
+    fn math_call<V: Clone + Debug + PartialEq>(
+        &mut self,
+        fun: &str,
+        args: &[boa_ast::Expression],
+        interner: &Interner,
+        vars: &HashMap<String, (V, String)>,
+        origin: Option<&V>,
+        params: &HashMap<String, (V, String)>,
+    ) -> anyhow::Result<Expression<V>> {
+        let args = args
+            .iter()
+            .map(|arg| self.expression(arg, interner, vars, origin, params, None))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let expr = match (fun, args.as_slice()) {
+            ("random", []) => Expression::RandFloat(0., 1.),
+            ("abs", [arg]) => Expression::Abs(Box::new(arg.clone())),
+            ("floor", [arg]) => Expression::Floor(Box::new(arg.clone())),
+            ("ceil", [arg]) => Expression::Ceil(Box::new(arg.clone())),
+            // NOTE: ECMAScript rounds half-way cases towards positive infinity.
+            ("round", [arg]) => Expression::Floor(Box::new(arg.clone() + Expression::from(0.5))),
+            ("sqrt", [arg]) => Expression::Sqrt(Box::new(arg.clone())),
+            ("exp", [arg]) => Expression::Exp(Box::new(arg.clone())),
+            ("log", [arg]) => Expression::Log(Box::new((
+                arg.clone(),
+                Expression::from(std::f64::consts::E),
+            ))),
+            ("log2", [arg]) => Expression::Log(Box::new((arg.clone(), Expression::from(2.)))),
+            ("log10", [arg]) => Expression::Log(Box::new((arg.clone(), Expression::from(10.)))),
+            ("sin", [arg]) => Expression::Sin(Box::new(arg.clone())),
+            ("cos", [arg]) => Expression::Cos(Box::new(arg.clone())),
+            ("tan", [arg]) => Expression::Tan(Box::new(arg.clone())),
+            ("pow", [base, exp]) => Expression::Pow(Box::new((base.clone(), exp.clone()))),
+            ("min", [arg, args @ ..]) => args.iter().fold(arg.clone(), |min, arg| {
+                Expression::Min(Box::new((min, arg.clone())))
+            }),
+            ("max", [arg, args @ ..]) => args.iter().fold(arg.clone(), |max, arg| {
+                Expression::Max(Box::new((max, arg.clone())))
+            }),
+            // Random distributions are not part of ECMAScript's `Math`,
+            // but follow the style of `Math.random`.
+            ("normal", [mean, std_dev]) => {
+                Expression::RandNormal(Box::new((mean.clone(), std_dev.clone())))
+            }
+            ("exponential", [rate]) => Expression::RandExp(Box::new(rate.clone())),
+            ("poisson", [rate]) => Expression::RandPoisson(Box::new(rate.clone())),
+            ("geometric", [p]) => Expression::RandGeometric(Box::new(p.clone())),
+            // Arguments alternate weights and the respective values.
+            ("choice", args) if !args.is_empty() && args.len() % 2 == 0 => Expression::RandChoice(
+                args.chunks_exact(2)
+                    .map(|pair| (pair[0].clone(), pair[1].clone()))
+                    .collect(),
+            ),
+            _ => {
+                return Err(anyhow!(
+                    "unknown function Math.{fun} with {} arguments",
+                    args.len()
+                ));
+            }
+        };
         Ok(expr)
     }
 
-    fn expression_prop_access<V: Clone>(
+    // Builds a call to one of the methods of ECMAScript's `Array` object,
+    // building the arguments (with their expected type, if known) with the given function.
+    // Mutating methods evaluate to the mutated array,
+    // as they can only be called by scripts assigning it back to its variable.
+    fn array_call<V: Clone + Debug>(
+        array: Expression<V>,
+        fun: &str,
+        args: &[boa_ast::Expression],
+        mut arg: impl FnMut(&boa_ast::Expression, Option<Type>) -> anyhow::Result<Expression<V>>,
+    ) -> anyhow::Result<Expression<V>> {
+        let (Type::List(element_type) | Type::Array(element_type, _)) = array.r#type()? else {
+            return Err(anyhow!("unknown method {fun} of non-array"));
+        };
+        let expr = match (fun, args) {
+            ("at", [index]) => {
+                Expression::Index(Box::new((array, arg(index, Some(Type::Integer))?)))
+            }
+            ("includes", [element]) => {
+                Expression::Contains(Box::new((array, arg(element, Some(*element_type))?)))
+            }
+            ("with", [index, element]) => Expression::Update(Box::new((
+                array,
+                arg(index, Some(Type::Integer))?,
+                arg(element, Some(*element_type))?,
+            ))),
+            ("slice", [start]) => Expression::Slice(Box::new((
+                array.clone(),
+                arg(start, Some(Type::Integer))?,
+                Expression::Len(Box::new(array)),
+            ))),
+            ("slice", [start, end]) => Expression::Slice(Box::new((
+                array,
+                arg(start, Some(Type::Integer))?,
+                arg(end, Some(Type::Integer))?,
+            ))),
+            ("push", [element]) => {
+                Expression::Append(Box::new((array, arg(element, Some(*element_type))?)))
+            }
+            ("unshift", [element]) => {
+                Expression::PushFront(Box::new((array, arg(element, Some(*element_type))?)))
+            }
+            ("shift", []) => Expression::PopFront(Box::new(array)),
+            ("pop", []) => Expression::Truncate(Box::new(array)),
+            _ => {
+                return Err(anyhow!(
+                    "unknown array method {fun} with {} arguments",
+                    args.len()
+                ));
+            }
+        };
+        Ok(expr)
+    }
+
+    fn expression_prop_access<V: Clone + Debug + PartialEq>(
         &mut self,
         expr: &boa_ast::Expression,
         interner: &Interner,
@@ -1410,7 +2159,39 @@ impl ModelBuilder {
                             )
                         })),
                     ))),
+                    "Math" => Ok(EcmaObj::Properties(HashMap::from_iter([
+                        (
+                            String::from("PI"),
+                            EcmaObj::PrimitiveData(
+                                Expression::from(std::f64::consts::PI),
+                                String::from("float64"),
+                            ),
+                        ),
+                        (
+                            String::from("E"),
+                            EcmaObj::PrimitiveData(
+                                Expression::from(std::f64::consts::E),
+                                String::from("float64"),
+                            ),
+                        ),
+                    ]))),
                     ident => {
+                        // Labels can be qualified by their enum, as in `<ENUM>.<LABEL>`.
+                        if let (None, Some((_, Type::Enum(r#enum)))) =
+                            (vars.get(ident), self.types.get(ident))
+                        {
+                            return Ok(EcmaObj::Properties(HashMap::from_iter(
+                                r#enum.labels().iter().enumerate().map(|(idx, label)| {
+                                    (
+                                        label.to_owned(),
+                                        EcmaObj::PrimitiveData(
+                                            Expression::Const(Val::Enum(r#enum.clone(), idx)),
+                                            ident.to_owned(),
+                                        ),
+                                    )
+                                }),
+                            )));
+                        }
                         let (var, type_name) = vars
                             .get(ident)
                             .ok_or(anyhow!("location {} not found", ident))?
@@ -1450,23 +2231,43 @@ impl ModelBuilder {
                                             .0
                                         {
                                             OmgType::Boolean => todo!(),
-                                            OmgType::Int32 => todo!(),
+                                            OmgType::Int32 | OmgType::Int(_) => todo!(),
                                             OmgType::F64 => todo!(),
+                                            OmgType::String if ident == "length" => {
+                                                Ok(EcmaObj::PrimitiveData(
+                                                    Expression::Len(Box::new(expr)),
+                                                    String::from("int32"),
+                                                ))
+                                            }
+                                            OmgType::String => {
+                                                Err(anyhow!("unknown string property {}", ident))
+                                            }
                                             OmgType::Uri => todo!(),
                                             OmgType::Structure(fields) => {
-                                                let index = *self
-                                                    .structs
-                                                    .get(&(type_name, ident.to_owned()))
-                                                    .ok_or(anyhow!("field {} not found", ident))?;
                                                 let field_type_name = fields
                                                     .get(ident)
                                                     .ok_or(anyhow!("field {} not found", ident))?;
                                                 Ok(EcmaObj::PrimitiveData(
-                                                    Expression::component(expr, index),
+                                                    expr.field(ident).with_context(|| {
+                                                        format!("field {ident} not found")
+                                                    })?,
                                                     field_type_name.to_owned(),
                                                 ))
                                             }
-                                            OmgType::Enumeration(_) => todo!(),
+                                            OmgType::Enumeration(_) => Err(anyhow!(
+                                                "enum {} has no property {}",
+                                                type_name,
+                                                ident
+                                            )),
+                                            OmgType::Array(_, _) if ident == "length" => {
+                                                Ok(EcmaObj::PrimitiveData(
+                                                    Expression::Len(Box::new(expr)),
+                                                    String::from("int32"),
+                                                ))
+                                            }
+                                            OmgType::Array(_, _) => {
+                                                Err(anyhow!("unknown array property {}", ident))
+                                            }
                                         }
                                     }
                                     EcmaObj::Properties(fields) => fields
@@ -1475,7 +2276,31 @@ impl ModelBuilder {
                                         .cloned(),
                                 }
                             }
-                            PropertyAccessField::Expr(_) => todo!(),
+                            PropertyAccessField::Expr(index) => match prop_target {
+                                EcmaObj::PrimitiveData(expr, type_name) => {
+                                    let Some((OmgType::Array(element_type, _), _)) =
+                                        self.types.get(&type_name)
+                                    else {
+                                        return Err(anyhow!("cannot index type {type_name}"));
+                                    };
+                                    let element_type = element_type.to_owned();
+                                    let index = self.expression(
+                                        index,
+                                        interner,
+                                        vars,
+                                        origin,
+                                        params,
+                                        Some(Type::Integer),
+                                    )?;
+                                    Ok(EcmaObj::PrimitiveData(
+                                        Expression::Index(Box::new((expr, index))),
+                                        element_type,
+                                    ))
+                                }
+                                EcmaObj::Properties(_) => {
+                                    Err(anyhow!("cannot index object with properties"))
+                                }
+                            },
                         }
                     }
                     PropertyAccess::Private(_) => todo!(),
@@ -1486,6 +2311,21 @@ impl ModelBuilder {
         }
     }
 
+    // Resolves an enum's label into the corresponding value, if it is a label at all.
+    // Labels belonging to multiple enums are disambiguated by the expected type.
+    fn enum_label(&self, label: &str, expr_type: Option<&Type>) -> anyhow::Result<Option<Val>> {
+        let Some(enums) = self.enums.get(label) else {
+            return Ok(None);
+        };
+        let r#enum = match expr_type {
+            Some(Type::Enum(r#enum)) if enums.contains(r#enum) => r#enum,
+            _ if enums.len() == 1 => &enums[0],
+            _ => return Err(anyhow!("label {label} belongs to multiple enums")),
+        };
+        let idx = r#enum.index(label).expect("label belongs to enum");
+        Ok(Some(Val::Enum(r#enum.clone(), idx)))
+    }
+
     fn build_ports(&mut self, parser: &Parser) -> anyhow::Result<()> {
         for (port_id, port) in parser.properties.ports.iter() {
             let origin_builder = self
@@ -1502,7 +2342,15 @@ impl ModelBuilder {
                 .event_indexes
                 .get(&port.event)
                 .ok_or(anyhow!("missing event {}", port.event))?;
+            let fault_queue = self.fault_queues.get(&(target, event_id));
             if let Some((param, init)) = &port.param {
+                if fault_queue.is_some() {
+                    return Err(anyhow!(
+                        "port {port_id} cannot observe parameter {param} of event {} subject to faults",
+                        port.event
+                    ));
+                }
+                let port_type = self.types.get(&port.r#type).map(|(_, t)| t.clone());
                 let init = self
                     .expression::<Var>(
                         init,
@@ -1510,7 +2358,7 @@ impl ModelBuilder {
                         &HashMap::new(),
                         None,
                         &HashMap::new(),
-                        None,
+                        port_type,
                     )?
                     .eval_constant()?;
                 let channel = *self
@@ -1520,7 +2368,18 @@ impl ModelBuilder {
                 self.ports
                     .insert(port_id.to_owned(), (Atom::State(channel), init));
             } else {
-                let channel = target_builder.ext_queue;
+                // Events subject to faults are observed on their dedicated channel,
+                // which is only possible if they carry no parameters.
+                let channel = match fault_queue {
+                    Some(queue) if queue.params.is_empty() => queue.channel,
+                    Some(_) => {
+                        return Err(anyhow!(
+                            "port {port_id} cannot observe event {} with parameters subject to faults",
+                            port.event
+                        ));
+                    }
+                    None => target_builder.ext_queue,
+                };
                 self.ports.insert(
                     port_id.to_owned(),
                     (
@@ -1569,36 +2428,87 @@ impl ModelBuilder {
         Ok(())
     }
 
-    fn build_model(self) -> (CsModel<SmallRng>, PmtlOracle, ScxmlModel) {
+    fn build_model(mut self) -> (CsModel<SmallRng>, PmtlOracle, ScxmlModel) {
+        let mut events = Vec::from_iter(self.event_indexes);
+        events.sort_unstable_by_key(|(_, idx)| *idx);
+        let events: Vec<String> = events
+            .into_iter()
+            .enumerate()
+            .map(|(enum_i, (name, idx))| {
+                assert_eq!(enum_i, idx);
+                name
+            })
+            .collect();
+        for ((sender, receiver, event, param), channel) in &self.parameters {
+            self.cs
+                .set_channel_symbol(
+                    *channel,
+                    format!(
+                        "parameter {param} of event {} from {} to {}",
+                        events[*event], self.fsm_names[sender], self.fsm_names[receiver]
+                    ),
+                )
+                .expect("channel exists");
+        }
+        for ((receiver, event), queue) in &self.fault_queues {
+            self.cs
+                .set_channel_symbol(
+                    queue.channel,
+                    format!(
+                        "unreliable queue of event {} to {}",
+                        events[*event], self.fsm_names[receiver]
+                    ),
+                )
+                .expect("channel exists");
+        }
+        for (event, broadcast) in &self.broadcasts {
+            self.cs
+                .set_channel_symbol(*broadcast, format!("broadcast of event {}", events[*event]))
+                .expect("channel exists");
+        }
+        for ((sender, event, param), broadcast) in &self.param_broadcasts {
+            self.cs
+                .set_channel_symbol(
+                    *broadcast,
+                    format!(
+                        "broadcast of parameter {param} of event {} from {}",
+                        events[*event], self.fsm_names[sender]
+                    ),
+                )
+                .expect("channel exists");
+        }
         let mut model = CsModelBuilder::new(self.cs.build());
         let mut ports = Vec::new();
         for (port_name, (atom, init)) in self.ports {
             // TODO FIXME handle error.
             if let Atom::State(channel) = atom {
                 model.add_port(channel, init.clone());
-                ports.push((port_name, init.r#type()));
+                ports.push((port_name, channel, init.r#type()));
             }
         }
+        // Ports' values are listed in the order of their channels,
+        // so port names have to follow the same order.
+        ports.sort_unstable_by_key(|(_, channel, _)| *channel);
         for pred_expr in self.predicates {
             // TODO FIXME handle error.
             let _id = model.add_predicate(pred_expr);
         }
+        let model = model.build();
+        let broadcasts = self
+            .broadcasts
+            .into_values()
+            .chain(self.param_broadcasts.into_values())
+            .map(|broadcast| {
+                let subscribers = model.channel_system().subscribers(broadcast).to_vec();
+                (broadcast, subscribers)
+            })
+            .collect();
         let (guarantee_names, guarantees): (Vec<_>, Vec<_>) = self.guarantees.into_iter().unzip();
         let (assume_names, assumes): (Vec<_>, Vec<_>) = self.assumes.into_iter().unzip();
         let oracle = PmtlOracle::new(assumes.as_slice(), guarantees.as_slice());
-        let mut events = Vec::from_iter(self.event_indexes);
-        events.sort_unstable_by_key(|(_, idx)| *idx);
-        let events = events
-            .into_iter()
-            .enumerate()
-            .map(|(enum_i, (name, idx))| {
-                assert_eq!(enum_i, idx);
-                name
-            })
-            .collect();
 
         (
-            model.build(),
+            model,
             oracle,
             ScxmlModel {
                 fsm_names: self.fsm_names,
@@ -1612,6 +2522,12 @@ impl ModelBuilder {
                     .values()
                     .map(|b| (b.ext_queue, b.pg_id))
                     .collect(),
+                fault_queues: self
+                    .fault_queues
+                    .into_iter()
+                    .map(|(target_event, queue)| (queue.channel, target_event))
+                    .collect(),
+                broadcasts,
                 int_queues: self.int_queues,
                 events,
                 fsm_indexes: self
@@ -0,0 +1,252 @@
+//! Offline checking of recorded execution traces against a model's properties.
+
+use super::ScxmlModel;
+use anyhow::{Context, anyhow, bail};
+use rand::rngs::SmallRng;
+use scan_core::channel_system::{Channel, Event, EventType, PgId};
+use scan_core::{
+    CsModel, Integer, Oracle, PmtlOracle, RunOutcome, Time, TransitionSystem, Type, Val,
+};
+use std::{collections::HashMap, fs::File, io::Read, path::Path};
+
+/// Associates the fields of a trace with the columns of a csv file.
+///
+/// The default mapping corresponds to the format of the traces produced by [`super::TracePrinter`].
+#[derive(Debug, Clone)]
+pub struct ColumnMapping {
+    /// Column of the timestamps.
+    pub time: String,
+    /// Column of the kind of event (`S` for send, `R` for receive),
+    /// possibly followed by the fault affecting it, as in `S (dropped)`.
+    /// If missing, every event is considered a send.
+    pub action: Option<String>,
+    /// Column of the state chart originating the event.
+    pub origin: Option<String>,
+    /// Column of the state chart targeted by the event.
+    pub target: Option<String>,
+    /// Column of the event's name.
+    pub event: Option<String>,
+    /// Associates a port's name with the column holding its values.
+    ///
+    /// Ports without an explicit mapping are looked up by name,
+    /// or by `<NAME>: <TYPE>` as in the traces produced by SCAN.
+    /// Every port must have a column in the trace.
+    pub ports: HashMap<String, String>,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        Self {
+            time: String::from("Time"),
+            action: Some(String::from("Send/Receive")),
+            origin: Some(String::from("Origin")),
+            target: Some(String::from("Target")),
+            event: Some(String::from("Event")),
+            ports: HashMap::new(),
+        }
+    }
+}
+
+impl ColumnMapping {
+    /// Maps a field of the trace to the given column.
+    ///
+    /// Fields are `time`, `action`, `origin`, `target` and `event`;
+    /// any other name is considered to be a port's.
+    pub fn map(&mut self, field: &str, column: String) {
+        match field {
+            "time" => self.time = column,
+            "action" => self.action = Some(column),
+            "origin" => self.origin = Some(column),
+            "target" => self.target = Some(column),
+            "event" => self.event = Some(column),
+            port => {
+                self.ports.insert(port.to_owned(), column);
+            }
+        }
+    }
+}
+
+/// Evaluates a model's properties over recorded execution traces in csv format.
+#[derive(Clone)]
+pub struct TraceChecker {
+    model: CsModel<SmallRng>,
+    oracle: PmtlOracle,
+    scxml_model: ScxmlModel,
+    mapping: ColumnMapping,
+    fsms: HashMap<String, PgId>,
+    ext_queues: HashMap<PgId, Channel>,
+    fault_queues: HashMap<(PgId, usize), Channel>,
+}
+
+impl TraceChecker {
+    pub(crate) fn new(
+        model: CsModel<SmallRng>,
+        oracle: PmtlOracle,
+        scxml_model: ScxmlModel,
+        mapping: ColumnMapping,
+    ) -> Self {
+        let fsms = scxml_model
+            .fsm_names
+            .iter()
+            .map(|(pg_id, name)| (name.clone(), *pg_id))
+            .collect();
+        let ext_queues = scxml_model
+            .ext_queues
+            .iter()
+            .map(|(channel, pg_id)| (*pg_id, *channel))
+            .collect();
+        let fault_queues = scxml_model
+            .fault_queues
+            .iter()
+            .map(|(channel, target_event)| (*target_event, *channel))
+            .collect();
+        Self {
+            model,
+            oracle,
+            scxml_model,
+            mapping,
+            fsms,
+            ext_queues,
+            fault_queues,
+        }
+    }
+
+    /// The model's data, e.g., to retrieve the names of the properties.
+    pub fn scxml_model(&self) -> &ScxmlModel {
+        &self.scxml_model
+    }
+
+    /// Checks the trace in the given csv file (optionally gz-compressed).
+    ///
+    /// Fails if the file cannot be read or does not conform to the column mapping,
+    /// including if the column of any port of the model is missing.
+    pub fn check(&self, path: &Path) -> anyhow::Result<RunOutcome> {
+        let file = File::open(path)
+            .with_context(|| format!("failed to open trace file '{}'", path.display()))?;
+        let reader: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "gz") {
+            Box::new(flate2::read::GzDecoder::new(file))
+        } else {
+            Box::new(file)
+        };
+        let mut reader = csv::Reader::from_reader(reader);
+        let header = reader
+            .headers()
+            .with_context(|| format!("failed to read header of '{}'", path.display()))?
+            .clone();
+        let column = |name: &str| header.iter().position(|h| h == name);
+        let time_col = column(&self.mapping.time)
+            .ok_or_else(|| anyhow!("missing time column '{}'", self.mapping.time))?;
+        let action_col = self.mapping.action.as_deref().and_then(column);
+        let origin_col = self.mapping.origin.as_deref().and_then(column);
+        let target_col = self.mapping.target.as_deref().and_then(column);
+        let event_col = self.mapping.event.as_deref().and_then(column);
+        let mut port_cols = Vec::new();
+        for (name, channel, r#type) in self.scxml_model.ports.iter() {
+            let col = if let Some(col_name) = self.mapping.ports.get(name) {
+                column(col_name).ok_or_else(|| anyhow!("missing column '{col_name}'"))?
+            } else {
+                let prefix = format!("{name}: ");
+                header
+                    .iter()
+                    .position(|h| h == name || h.starts_with(&prefix))
+                    .ok_or_else(|| {
+                        anyhow!("missing column of port '{name}' (map it with '{name}=COLUMN')")
+                    })?
+            };
+            port_cols.push((col, *channel, r#type));
+        }
+
+        let mut model = self.model.clone();
+        let mut trace = Vec::new();
+        let mut last_time = 0;
+        for (line, record) in reader.records().enumerate() {
+            let record = record.with_context(|| format!("failed to read record {}", line + 1))?;
+            let field = |col: usize| record.get(col).map(str::trim).unwrap_or_default();
+            let time = field(time_col)
+                .parse::<Time>()
+                .with_context(|| format!("failed to parse time of record {}", line + 1))?;
+            if time < last_time {
+                bail!("time of record {} is decreasing", line + 1);
+            }
+            last_time = time;
+            for (col, channel, r#type) in port_cols.iter() {
+                let val = parse_val(field(*col), r#type).with_context(|| {
+                    format!("failed to parse port value of record {}", line + 1)
+                })?;
+                model.set_port(*channel, val)?;
+            }
+            let is_send = action_col.is_none_or(|col| {
+                field(col)
+                    .split_whitespace()
+                    .next()
+                    .is_some_and(|action| action == "S")
+            });
+            let event = if is_send {
+                origin_col
+                    .zip(target_col)
+                    .zip(event_col)
+                    .and_then(|((origin, target), event)| {
+                        self.event(field(origin), field(target), field(event))
+                    })
+            } else {
+                None
+            };
+            model.set_last_event(event);
+            trace.push((model.labels(), time));
+        }
+
+        Ok(self.oracle.clone().check_trace(trace))
+    }
+
+    // Reconstructs the event sent from origin to target, if it exists in the model.
+    fn event(&self, origin: &str, target: &str, event: &str) -> Option<Event> {
+        let origin = *self.fsms.get(origin)?;
+        let target = *self.fsms.get(target)?;
+        let event_idx = self.scxml_model.events.iter().position(|e| e == event)?;
+        // Events subject to faults are sent through their dedicated channel
+        let channel = *self
+            .fault_queues
+            .get(&(target, event_idx))
+            .or_else(|| self.ext_queues.get(&target))?;
+        Some(Event {
+            pg_id: origin,
+            channel,
+            event_type: EventType::Send(Val::Tuple(vec![
+                Val::Integer(event_idx as Integer),
+                Val::Integer(u16::from(origin) as Integer),
+            ])),
+        })
+    }
+}
+
+fn parse_val(value: &str, r#type: &Type) -> anyhow::Result<Val> {
+    match r#type {
+        Type::Boolean => match value {
+            "true" | "True" | "1" => Ok(Val::Boolean(true)),
+            "false" | "False" | "0" => Ok(Val::Boolean(false)),
+            _ => bail!("'{value}' is not a boolean"),
+        },
+        Type::Integer => value
+            .parse()
+            .map(Val::Integer)
+            .with_context(|| format!("'{value}' is not an integer")),
+        Type::Int(int_type) => value
+            .parse::<i128>()
+            .ok()
+            .filter(|i| (int_type.min()..=int_type.max()).contains(i))
+            .map(|i| Val::Int(*int_type, i))
+            .ok_or_else(|| anyhow!("'{value}' is not a valid {int_type:?} integer")),
+        Type::Float => value
+            .parse()
+            .map(Val::Float)
+            .with_context(|| format!("'{value}' is not a float")),
+        Type::String => Ok(Val::String(value.to_owned())),
+        Type::Enum(r#enum) => r#enum
+            .index(value)
+            .map(|idx| Val::Enum(r#enum.clone(), idx))
+            .ok_or_else(|| anyhow!("'{value}' is not a label of {}", r#enum.name())),
+        Type::Product(_) | Type::List(_) | Type::Array(_, _) | Type::Record(_) => {
+            bail!("unsupported port type {:?}", r#type)
+        }
+    }
+}
@@ -1,24 +1,254 @@
 //! Parser and model builder for SCAN's CONVINCE-XML specification format.
 
 mod builder;
+mod check_trace;
 mod parser;
 mod print_trace;
 
 use std::path::Path;
 
 pub use builder::ScxmlModel;
+pub use check_trace::{ColumnMapping, TraceChecker};
 pub use print_trace::TracePrinter;
 use rand::rngs::SmallRng;
 pub use scan_core;
 use scan_core::{
-    CsModel, PmtlOracle, Scan,
-    channel_system::{CsError, Event},
+    CsModel, Overflow, PmtlOracle, Scan,
+    channel_system::{CsDiagnostic, CsError, Event},
 };
 
 pub type ScxmlScan = Scan<Event, CsError, CsModel<SmallRng>, PmtlOracle>;
-pub fn load(path: &Path) -> anyhow::Result<(ScxmlScan, ScxmlModel)> {
+
+/// Loads the model, with the given [`Overflow`] semantics for sized integers,
+/// optionally compressing the FSMs' internal steps
+/// (see [`ChannelSystemBuilder::set_compression`](scan_core::channel_system::ChannelSystemBuilder::set_compression)).
+pub fn load(
+    path: &Path,
+    overflow: Overflow,
+    compression: bool,
+) -> anyhow::Result<(ScxmlScan, ScxmlModel)> {
     let parser = parser::Parser::parse(path)?;
-    let (cs, oracle, model) = builder::ModelBuilder::build(parser)?;
+    let (cs, oracle, model) = builder::ModelBuilder::build(parser, overflow, compression)?;
     let scan = Scan::new(cs, oracle);
     Ok((scan, model))
 }
+
+/// Loads the model and prepares it to check recorded traces,
+/// whose columns are given by the [`ColumnMapping`],
+/// with the given [`Overflow`] semantics for sized integers.
+pub fn load_checker(
+    path: &Path,
+    mapping: ColumnMapping,
+    overflow: Overflow,
+) -> anyhow::Result<TraceChecker> {
+    let parser = parser::Parser::parse(path)?;
+    let (cs, oracle, model) = builder::ModelBuilder::build(parser, overflow, false)?;
+    Ok(TraceChecker::new(cs, oracle, model, mapping))
+}
+
+/// Loads the model and statically analyzes it,
+/// returning a description of the potential modelling mistakes found.
+///
+/// Channels observed by ports are not reported as never received from,
+/// and neither are FSMs' internal queues as never sent to.
+///
+/// See also [`ChannelSystem::analyze`](scan_core::channel_system::ChannelSystem::analyze).
+pub fn analyze(path: &Path) -> anyhow::Result<Vec<String>> {
+    let parser = parser::Parser::parse(path)?;
+    let (cs, _, model) = builder::ModelBuilder::build(parser, Overflow::default(), false)?;
+    let symbols = cs.channel_system().symbols();
+    let diagnostics = cs
+        .channel_system()
+        .analyze()
+        .into_iter()
+        .filter(|diagnostic| match diagnostic {
+            CsDiagnostic::UnreceivedChannel(channel) => {
+                !model.ports.iter().any(|(_, port, _)| port == channel)
+            }
+            CsDiagnostic::UnfedReceive(_, channel) => !model.int_queues.contains(channel),
+            _ => true,
+        })
+        .map(|diagnostic| match diagnostic {
+            CsDiagnostic::UnreceivedChannel(channel) => {
+                if let Some((sender, receiver, event, param)) = model.parameters.get(&channel) {
+                    format!(
+                        "parameter '{param}' of event '{}' sent by '{}' to '{}' is never received",
+                        model.events[*event], model.fsm_names[sender], model.fsm_names[receiver]
+                    )
+                } else if let Some(receiver) = model.ext_queues.get(&channel) {
+                    format!(
+                        "events sent to '{}' are never received",
+                        model.fsm_names[receiver]
+                    )
+                } else {
+                    diagnostic.describe(symbols)
+                }
+            }
+            _ => {
+                let pg_id = diagnostic.pg_id().expect("diagnostic of a PG");
+                format!(
+                    "{}: {}",
+                    model.fsm_names[&pg_id],
+                    diagnostic.describe(symbols)
+                )
+            }
+        })
+        .collect();
+    Ok(diagnostics)
+}
+
+/// Loads the model and renders its channel system in the Graphviz DOT language.
+///
+/// See also [`ChannelSystem::to_dot`](scan_core::channel_system::ChannelSystem::to_dot).
+pub fn dot(path: &Path) -> anyhow::Result<String> {
+    let parser = parser::Parser::parse(path)?;
+    let (cs, _, _) = builder::ModelBuilder::build(parser, Overflow::default(), false)?;
+    Ok(cs.channel_system().to_dot())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scan_core::{
+        EvalError, RunOutcome, TransitionSystem, Val, channel_system::EventType,
+        program_graph::PgError,
+    };
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        sync::{Arc, atomic::AtomicBool},
+    };
+
+    fn run(asset: &str, overflow: Overflow) -> Result<RunOutcome, CsError> {
+        let path = Path::new("./tests/assets").join(asset).join("model.xml");
+        let parser = parser::Parser::parse(&path).expect("parse");
+        let (cs, oracle, _) = builder::ModelBuilder::build(parser, overflow, false).expect("build");
+        cs.experiment::<TracePrinter, _>(100, oracle, None, Arc::new(AtomicBool::new(true)))
+    }
+
+    // Runs the model until it terminates, collecting the observable events of each FSM,
+    // together with the values of the ports after each of them.
+    // Events are described by name, since builds can number FSMs, channels and events differently.
+    fn observe(mut cs: CsModel<SmallRng>, model: &ScxmlModel) -> BTreeMap<String, Vec<String>> {
+        let mut trace = BTreeMap::<_, Vec<_>>::new();
+        while let Some(event) = cs.transition(100).expect("transition") {
+            let symbols = cs.channel_system().symbols();
+            let message = |val: &Val| match val {
+                Val::Tuple(fields) if !model.parameters.contains_key(&event.channel) => {
+                    match fields.as_slice() {
+                        [Val::Integer(event), Val::Integer(origin), params @ ..] => format!(
+                            "{} from {} {params:?}",
+                            model.events[*event as usize],
+                            model.fsm_indexes[&(*origin as usize)]
+                        ),
+                        _ => format!("{val:?}"),
+                    }
+                }
+                _ => format!("{val:?}"),
+            };
+            let event_type = match &event.event_type {
+                EventType::Send(val) => format!("send {}", message(val)),
+                EventType::Receive(val) => format!("receive {}", message(val)),
+                EventType::Dropped(val) => format!("drop {}", message(val)),
+                EventType::Duplicated(val) => format!("duplicate {}", message(val)),
+                EventType::Delayed(val, delay) => format!("delay {} by {delay}", message(val)),
+                EventType::Reordered(val) => format!("reorder {}", message(val)),
+                EventType::Handshake(pg_id, val) => {
+                    format!(
+                        "handshake {} with {}",
+                        message(val),
+                        symbols.describe_pg(*pg_id)
+                    )
+                }
+                event_type => format!("{event_type:?}"),
+            };
+            let mut ports = model
+                .ports
+                .iter()
+                .map(|(name, ..)| name)
+                .zip(cs.state())
+                .collect::<Vec<_>>();
+            ports.sort_unstable_by_key(|(name, _)| *name);
+            trace
+                .entry(symbols.describe_pg(event.pg_id))
+                .or_default()
+                .push(format!(
+                    "{event_type} on {} {ports:?}",
+                    symbols.describe_channel(event.channel)
+                ));
+        }
+        trace
+    }
+
+    #[test]
+    fn compression() {
+        const RUNS: u32 = 100;
+        for asset in [
+            "test_fsm",
+            "test_datamodel",
+            "test_enumdata",
+            "test_send",
+            "test_send_triangle",
+            "test_send_onentry",
+            "test_origin",
+            "test_origin_location",
+            "test_param",
+            "test_param_triangle",
+            "test_param_tennis",
+            "test_broadcast",
+            "test_if",
+            "test_elif",
+            "test_script",
+            "test_check_trace",
+        ] {
+            let path = Path::new("./tests/assets").join(asset).join("model.xml");
+            // Observable events and port values of each FSM are the same with or without compression.
+            let [uncompressed, compressed] = [false, true].map(|compression| {
+                let parser = parser::Parser::parse(&path).expect("parse");
+                let (cs, _, model) =
+                    builder::ModelBuilder::build(parser, Overflow::default(), compression)
+                        .expect("build");
+                (0..RUNS)
+                    .map(|_| observe(cs.clone(), &model))
+                    .collect::<BTreeSet<_>>()
+            });
+            assert_eq!(uncompressed, compressed, "{asset}");
+        }
+    }
+
+    #[test]
+    fn overflow() {
+        // The counter wraps around to a negative value, ending the run.
+        assert!(run("test_overflow", Overflow::Wrap).is_ok());
+        assert!(matches!(
+            run("test_overflow", Overflow::Error),
+            Err(CsError::ProgramGraph(_, PgError::Overflow))
+        ));
+    }
+
+    #[test]
+    fn array() {
+        // Elements of the fixed-size array are written up to index 2, and writing index 3 ends the run.
+        assert!(matches!(
+            run("test_array", Overflow::default()),
+            Err(CsError::ProgramGraph(
+                _,
+                PgError::Eval(EvalError::IndexOutOfBounds)
+            ))
+        ));
+    }
+
+    #[test]
+    fn bounds() {
+        // The sized level starts from its lower bound 1, and filling it past 5 ends the run.
+        assert!(matches!(
+            run("test_bounds", Overflow::default()),
+            Err(CsError::ProgramGraph(
+                _,
+                PgError::OutOfBounds {
+                    val: Val::Int(_, 6),
+                    ..
+                }
+            ))
+        ));
+    }
+}
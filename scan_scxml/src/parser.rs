@@ -19,6 +19,8 @@ use log::warn;
 use log::{error, info, trace};
 use quick_xml::Reader;
 use quick_xml::events::Event;
+use scan_core::channel_system::FaultModel;
+use scan_core::{SourceLocation, Time};
 use std::collections::HashMap;
 use std::io::BufRead;
 use std::io::Seek;
@@ -50,6 +52,7 @@ enum ConvinceTag {
     Specification,
     Model,
     ProcessList,
+    FaultList,
     DataTypeList,
     Enumeration(String),
     Structure(String),
@@ -61,6 +64,7 @@ impl From<ConvinceTag> for &'static str {
             ConvinceTag::Specification => TAG_SPECIFICATION,
             ConvinceTag::Model => TAG_MODEL,
             ConvinceTag::ProcessList => TAG_PROCESS_LIST,
+            ConvinceTag::FaultList => TAG_FAULT_LIST,
             ConvinceTag::DataTypeList => TAG_DATA_TYPE_LIST,
             ConvinceTag::Enumeration(_) => TAG_ENUMERATION,
             ConvinceTag::Structure(_) => TAG_STRUCT,
@@ -100,6 +104,44 @@ fn count_lines<R: BufRead + Seek>(mut reader: Reader<R>) -> usize {
     reader.into_inner().take(end_pos).lines().count()
 }
 
+/// Maps byte offsets in a source file to [`SourceLocation`]s.
+struct Locator {
+    file: PathBuf,
+    // Byte offsets at which each line begins.
+    lines: Vec<u64>,
+}
+
+impl Locator {
+    fn new(path: &Path) -> anyhow::Result<Self> {
+        let source = std::fs::read(path)
+            .with_context(|| format!("failed to read file '{}'", path.display()))?;
+        let lines = std::iter::once(0)
+            .chain(
+                source
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, byte)| **byte == b'\n')
+                    .map(|(pos, _)| pos as u64 + 1),
+            )
+            .collect();
+        Ok(Self {
+            file: path.to_owned(),
+            lines,
+        })
+    }
+
+    fn locate(&self, pos: u64) -> SourceLocation {
+        // There is always at least one line starting at offset 0.
+        let line = self.lines.partition_point(|&start| start <= pos);
+        let column = pos - self.lines[line - 1] + 1;
+        SourceLocation {
+            file: self.file.clone(),
+            line,
+            column: column as usize,
+        }
+    }
+}
+
 fn ecmascript(code: &str, scope: &Scope, interner: &mut Interner) -> anyhow::Result<Expression> {
     let script = boa_parser::Parser::new(boa_parser::Source::from_bytes(&code))
         .parse_script(scope, interner)
@@ -120,10 +162,69 @@ fn ecmascript(code: &str, scope: &Scope, interner: &mut Interner) -> anyhow::Res
     }
 }
 
+/// Faults affecting the delivery of events, as declared in the main file of a model specification.
+///
+/// A fault applies to the events with the given name sent to the given process,
+/// where a missing name or process stands for any.
+/// If more faults apply to an event, the one declared for both its name and target process prevails,
+/// followed by the one declared for its name only and then by the one declared for its target process only.
+#[derive(Debug, Clone)]
+pub struct Fault {
+    pub(crate) event: Option<String>,
+    pub(crate) process: Option<String>,
+    pub(crate) model: FaultModel,
+}
+
+impl Fault {
+    fn parse(attrs: &HashMap<String, String>) -> anyhow::Result<Self> {
+        let probability = |key: &str| -> anyhow::Result<f64> {
+            attrs.get(key).map_or(Ok(0f64), |val| {
+                val.parse::<f64>()
+                    .ok()
+                    .filter(|p| (0f64..=1f64).contains(p))
+                    .ok_or_else(|| anyhow!("'{key}' must be a probability, found '{val}'"))
+            })
+        };
+        let model = FaultModel {
+            drop: probability(ATTR_DROP)?,
+            duplicate: probability(ATTR_DUPLICATE)?,
+            reorder: attrs
+                .get(ATTR_REORDER)
+                .map(|val| val.parse::<bool>())
+                .transpose()
+                .with_context(|| format!("'{ATTR_REORDER}' must be a Boolean"))?
+                .unwrap_or_default(),
+            delay: attrs
+                .get(ATTR_DELAY)
+                .map(|val| val.parse::<Time>())
+                .transpose()
+                .with_context(|| format!("'{ATTR_DELAY}' must be a non-negative integer"))?
+                .unwrap_or_default(),
+        };
+        Ok(Fault {
+            event: attrs.get(ATTR_EVENT).cloned(),
+            process: attrs.get(ATTR_PROCESS).cloned(),
+            model,
+        })
+    }
+
+    // How specific the fault is, with faults specific to an event prevailing over those specific to a process.
+    pub(crate) fn specificity(&self) -> u8 {
+        2 * u8::from(self.event.is_some()) + u8::from(self.process.is_some())
+    }
+
+    // Whether the fault applies to the event sent to the process.
+    pub(crate) fn applies(&self, event: &str, process: &str) -> bool {
+        self.event.as_ref().is_none_or(|e| e == event)
+            && self.process.as_ref().is_none_or(|p| p == process)
+    }
+}
+
 /// Represents a model specified in the CONVINCE-XML format.
 #[derive(Debug)]
 pub struct Parser {
     pub(crate) process_list: HashMap<String, Scxml>,
+    pub(crate) faults: Vec<Fault>,
     pub(crate) types: OmgTypes,
     pub(crate) properties: Properties,
     pub(crate) interner: Interner,
@@ -138,6 +239,7 @@ impl Parser {
         info!(target: "parser", "creating parser");
         let mut parser = Parser {
             process_list: HashMap::new(),
+            faults: Vec::new(),
             types: OmgTypes::new(),
             properties: Properties::new(),
             interner: Interner::new(),
@@ -203,13 +305,14 @@ impl Parser {
                     let mut reader = Reader::from_file(path).with_context(|| {
                         format!("failed to create reader from file '{}'", path.display())
                     })?;
-                    let fsm = fsm::parse(&mut reader, &mut self.interner).with_context(|| {
-                        format!(
-                            "failed to parse fsm at line {} in '{}'",
-                            count_lines(reader),
-                            path.display(),
-                        )
-                    })?;
+                    let fsm =
+                        fsm::parse(&mut reader, path, &mut self.interner).with_context(|| {
+                            format!(
+                                "failed to parse fsm at line {} in '{}'",
+                                count_lines(reader),
+                                path.display(),
+                            )
+                        })?;
                     self.process_list.insert(fsm.name.to_owned(), fsm);
                 }
                 "xml" => {
@@ -260,6 +363,9 @@ impl Parser {
                         TAG_PROCESS_LIST if stack.last().is_some_and(|e| *e == ConvinceTag::Model) => {
                             ConvinceTag::ProcessList
                         }
+                        TAG_FAULT_LIST if stack.last().is_some_and(|e| *e == ConvinceTag::Model) => {
+                            ConvinceTag::FaultList
+                        }
                         _ => {
                             error!(target: "parser", "unknown or unexpected start tag '{tag_name}'");
                             bail!(ParserError::UnexpectedStartTag(tag_name.to_string()));
@@ -338,13 +444,27 @@ impl Parser {
                                 path.display()
                             );
                             let mut reader = Reader::from_file(path.clone())?;
-                            let fsm = fsm::parse(&mut reader, &mut self.interner)
+                            let fsm = fsm::parse(&mut reader, &path, &mut self.interner)
                                 .with_context(|| format!("failed to parse fsm at line {} in '{}'", count_lines(reader), path.display()))?;
                             // Add process to list and check that no process was already in the list under the same name
                             if self.process_list.insert(process_id.clone(), fsm).is_some() {
                                 panic!("process added to list multiple times");
                             }
                         }
+                        TAG_FAULT if stack.last().is_some_and(|e| *e == ConvinceTag::FaultList) => {
+                            let attrs = attrs(
+                                tag,
+                                &[],
+                                &[ATTR_EVENT, ATTR_PROCESS, ATTR_DROP, ATTR_DUPLICATE, ATTR_REORDER, ATTR_DELAY],
+                            )
+                            .context("failed to parse 'fault' tag attributes")?;
+                            let fault = Fault::parse(&attrs)
+                                .context("failed to parse 'fault' tag attributes")?;
+                            if self.faults.iter().any(|f| f.event == fault.event && f.process == fault.process) {
+                                bail!("fault for event {:?} and process {:?} declared multiple times", fault.event, fault.process);
+                            }
+                            self.faults.push(fault);
+                        }
                         _ => {
                             error!(target: "parser", "unknown or unexpected empty tag '{tag_name}'");
                             bail!(ParserError::UnexpectedTag(tag_name.to_string()));
@@ -1,4 +1,4 @@
-use super::{ecmascript, vocabulary::*};
+use super::{Locator, ecmascript, vocabulary::*};
 use crate::parser::{ParserError, attrs};
 use anyhow::{Context, anyhow, bail};
 use boa_ast::Expression as BoaExpression;
@@ -7,10 +7,11 @@ use boa_interner::Interner;
 use log::{error, info, trace};
 use quick_xml::events::Event;
 use quick_xml::{Reader, events};
-use scan_core::Time;
+use scan_core::{Bounds, Integer, SourceLocation, Time};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::io::{BufRead, Read};
+use std::path::Path;
 use std::str;
 
 #[derive(Debug)]
@@ -23,6 +24,8 @@ enum ScxmlTag {
     OnEntry(Vec<Executable>),
     OnExit(Vec<Executable>),
     Send(Send),
+    // Code of a script, with the script's position.
+    Script(String, SourceLocation),
 }
 
 impl From<&ScxmlTag> for &'static str {
@@ -36,6 +39,7 @@ impl From<&ScxmlTag> for &'static str {
             ScxmlTag::OnEntry(_) => TAG_ONENTRY,
             ScxmlTag::OnExit(_) => TAG_ONEXIT,
             ScxmlTag::Send(_) => TAG_SEND,
+            ScxmlTag::Script(..) => TAG_SCRIPT,
         }
     }
 }
@@ -54,6 +58,8 @@ pub struct Data {
     pub(crate) id: String,
     pub(crate) expression: Option<boa_ast::Expression>,
     pub(crate) omg_type: String,
+    pub(crate) bounds: Option<Bounds>,
+    pub(crate) position: SourceLocation,
 }
 
 impl Data {
@@ -61,9 +67,14 @@ impl Data {
         tag: events::BytesStart<'_>,
         // ident: Option<String>,
         omg_type: Option<String>,
+        position: SourceLocation,
         interner: &mut Interner,
     ) -> anyhow::Result<Data> {
-        let attrs = attrs(tag, &[ATTR_ID], &[ATTR_EXPR, ATTR_TYPE])?;
+        let attrs = attrs(
+            tag,
+            &[ATTR_ID],
+            &[ATTR_EXPR, ATTR_TYPE, ATTR_LOWER_BOUND, ATTR_UPPER_BOUND],
+        )?;
         let id = attrs[ATTR_ID].to_string();
         // Check id is matching
         // if id != ident {
@@ -78,10 +89,27 @@ impl Data {
             .get(ATTR_EXPR)
             .map(|expression| ecmascript(expression, &Scope::new_global(), interner))
             .transpose()?;
+        let bound = |attr: &str| {
+            attrs
+                .get(attr)
+                .map(|bound| {
+                    bound
+                        .trim()
+                        .parse::<Integer>()
+                        .with_context(|| format!("{attr} '{bound}' is not an integer"))
+                })
+                .transpose()
+        };
+        let lower_bound = bound(ATTR_LOWER_BOUND)?;
+        let upper_bound = bound(ATTR_UPPER_BOUND)?;
+        let bounds = (lower_bound.is_some() || upper_bound.is_some())
+            .then(|| Bounds::new(lower_bound, upper_bound));
         Ok(Data {
             id,
             expression,
             omg_type,
+            bounds,
+            position,
         })
     }
 
@@ -130,10 +158,15 @@ pub struct Transition {
     pub(crate) target: String,
     pub(crate) cond: Option<boa_ast::Expression>,
     pub(crate) effects: Vec<Executable>,
+    pub(crate) position: SourceLocation,
 }
 
 impl Transition {
-    fn parse(tag: events::BytesStart<'_>, interner: &mut Interner) -> anyhow::Result<Transition> {
+    fn parse(
+        tag: events::BytesStart<'_>,
+        position: SourceLocation,
+        interner: &mut Interner,
+    ) -> anyhow::Result<Transition> {
         let attrs = attrs(tag, &[ATTR_TARGET], &[ATTR_EVENT, ATTR_COND])?;
         let cond = attrs
             .get(ATTR_COND)
@@ -144,6 +177,7 @@ impl Transition {
             target: attrs[ATTR_TARGET].clone(),
             cond,
             effects: Vec::new(),
+            position,
         })
     }
 
@@ -167,7 +201,10 @@ pub enum Target {
 pub enum Executable {
     Assign {
         location: String,
+        // Index of the element to assign if the location is an array, as in `<ARRAY>[<INDEX>]`.
+        index: Option<boa_ast::Expression>,
         expr: boa_ast::Expression,
+        position: SourceLocation,
     },
     Raise {
         event: String,
@@ -185,12 +222,63 @@ impl Executable {
 
     fn parse_assign(
         tag: events::BytesStart<'_>,
+        position: SourceLocation,
         interner: &mut Interner,
     ) -> anyhow::Result<Executable> {
         let attrs = attrs(tag, &[ATTR_LOCATION, ATTR_EXPR], &[])?;
-        let location = attrs[ATTR_LOCATION].clone();
+        let location = attrs[ATTR_LOCATION].trim();
+        let (location, index) = if let Some((location, index)) = location
+            .strip_suffix(']')
+            .and_then(|location| location.split_once('['))
+        {
+            let index = ecmascript(index, &Scope::new_global(), interner)?;
+            (location.trim().to_owned(), Some(index))
+        } else {
+            (location.to_owned(), None)
+        };
         let expr = ecmascript(attrs[ATTR_EXPR].as_str(), &Scope::new_global(), interner)?;
-        Ok(Executable::Assign { location, expr })
+        Ok(Executable::Assign {
+            location,
+            index,
+            expr,
+            position,
+        })
+    }
+
+    // Only scripts made of a single call to one of the mutating methods of an array variable
+    // (`push`, `unshift`, `shift` and `pop`) are supported,
+    // and they are executed as an assignment of the mutated array to the variable.
+    fn parse_script(
+        code: &str,
+        position: SourceLocation,
+        interner: &mut Interner,
+    ) -> anyhow::Result<Executable> {
+        let expr = ecmascript(code, &Scope::new_global(), interner)?;
+        if let BoaExpression::Call(call) = &expr
+            && let BoaExpression::PropertyAccess(
+                boa_ast::expression::access::PropertyAccess::Simple(access),
+            ) = call.function()
+            && let BoaExpression::Identifier(id) = access.target()
+            && let boa_ast::expression::access::PropertyAccessField::Const(method) = access.field()
+            && interner
+                .resolve_expect(*method)
+                .utf8()
+                .is_some_and(|method| ["push", "unshift", "shift", "pop"].contains(&method))
+        {
+            let location = interner
+                .resolve_expect(id.sym())
+                .utf8()
+                .ok_or_else(|| anyhow!("not utf8"))?
+                .to_owned();
+            Ok(Executable::Assign {
+                location,
+                index: None,
+                expr,
+                position,
+            })
+        } else {
+            bail!("unsupported script '{}'", code.trim())
+        }
     }
 
     fn push(self, stack: &mut [ScxmlTag]) -> anyhow::Result<()> {
@@ -224,10 +312,15 @@ pub struct Send {
     pub(crate) target: Option<Target>,
     pub(crate) delay: Option<Time>,
     pub(crate) params: Vec<Param>,
+    pub(crate) position: SourceLocation,
 }
 
 impl Send {
-    fn parse(tag: events::BytesStart<'_>, interner: &mut Interner) -> anyhow::Result<Send> {
+    fn parse(
+        tag: events::BytesStart<'_>,
+        position: SourceLocation,
+        interner: &mut Interner,
+    ) -> anyhow::Result<Send> {
         let attrs = attrs(
             tag,
             &[ATTR_EVENT],
@@ -254,6 +347,7 @@ impl Send {
             target,
             delay,
             params: Vec::new(),
+            position,
         })
     }
 }
@@ -263,6 +357,7 @@ pub struct If {
     pub(crate) r#elif: Vec<(boa_ast::Expression, Vec<Executable>)>,
     pub(crate) r#else: Vec<Executable>,
     else_flag: bool,
+    pub(crate) position: SourceLocation,
 }
 
 impl If {
@@ -280,12 +375,14 @@ pub struct Param {
     pub(crate) name: String,
     pub(crate) omg_type: Option<String>,
     pub(crate) expr: BoaExpression,
+    pub(crate) position: SourceLocation,
 }
 
 impl Param {
     fn parse(
         tag: events::BytesStart<'_>,
         omg_type: Option<String>,
+        position: SourceLocation,
         interner: &mut Interner,
     ) -> anyhow::Result<Param> {
         let attrs = attrs(tag, &[ATTR_NAME], &[ATTR_TYPE, ATTR_LOCATION, ATTR_EXPR])?;
@@ -306,6 +403,7 @@ impl Param {
             name,
             omg_type,
             expr,
+            position,
         })
     }
 }
@@ -337,14 +435,18 @@ impl Scxml {
 
 pub(super) fn parse<R: BufRead>(
     reader: &mut Reader<R>,
+    path: &Path,
     interner: &mut Interner,
 ) -> anyhow::Result<Scxml> {
+    let locator = Locator::new(path)?;
     let mut buf = Vec::new();
     let mut stack: Vec<ScxmlTag> = Vec::new();
     // let mut type_annotation: Option<(String, String)> = None;
     let mut type_annotation: Option<String> = None;
     info!(target: "parser", "parsing fsm");
     loop {
+        // Position of the beginning of the next event, used to locate the parsed elements.
+        let pos = reader.buffer_position();
         match reader
             .read_event_into(&mut buf)
             .context("failed reading event")?
@@ -355,7 +457,8 @@ pub(super) fn parse<R: BufRead>(
                     .decode(tag.name().into_inner())?
                     .into_owned();
                 trace!(target: "parser", "start tag '{tag_name}'");
-                let tag_obj = parse_start_tag(tag_name, &stack, tag, interner)?;
+                let position = locator.locate(pos);
+                let tag_obj = parse_start_tag(tag_name, &stack, tag, position, interner)?;
                 stack.push(tag_obj);
             }
             Event::End(tag) => {
@@ -408,6 +511,13 @@ pub(super) fn parse<R: BufRead>(
                             {
                                 Executable::If(r#if).push(&mut stack)?;
                             }
+                            ScxmlTag::Script(code, position)
+                                if stack.last().is_some_and(|tag| tag.is_executable()) =>
+                            {
+                                Executable::parse_script(&code, position, interner)
+                                    .with_context(|| ParserError::Tag(tag_name.to_string()))?
+                                    .push(&mut stack)?;
+                            }
                             ScxmlTag::OnEntry(execs)
                                 if stack
                                     .last()
@@ -443,10 +553,22 @@ pub(super) fn parse<R: BufRead>(
                     .decoder()
                     .decode(tag.name().into_inner())?
                     .into_owned();
-                parse_empty_tag(tag_name, &mut stack, tag, &mut type_annotation, interner)?;
+                let position = locator.locate(pos);
+                parse_empty_tag(
+                    tag_name,
+                    &mut stack,
+                    tag,
+                    &mut type_annotation,
+                    position,
+                    interner,
+                )?;
+            }
+            Event::Text(text) => {
+                // Ignore text between tags, except for the code of scripts
+                if let Some(ScxmlTag::Script(code, _)) = stack.last_mut() {
+                    code.push_str(&text.unescape()?);
+                }
             }
-            // Ignore text between tags
-            Event::Text(_) => continue,
             Event::Comment(comment) => {
                 // Convert comment into string (is there no easier way?)
                 let comment = comment
@@ -489,6 +611,7 @@ fn parse_empty_tag(
     stack: &mut [ScxmlTag],
     tag: events::BytesStart<'_>,
     type_annotation: &mut Option<String>,
+    position: SourceLocation,
     interner: &mut Interner,
 ) -> Result<(), anyhow::Error> {
     trace!(target: "parser", "'{tag_name}' empty tag");
@@ -498,7 +621,7 @@ fn parse_empty_tag(
                 .last()
                 .is_some_and(|tag| matches!(*tag, ScxmlTag::Datamodel(_))) =>
         {
-            let data = Data::parse(tag, type_annotation.take(), interner)
+            let data = Data::parse(tag, type_annotation.take(), position, interner)
                 .with_context(|| ParserError::Tag(tag_name))?;
             Data::push(data, stack)?;
         }
@@ -515,8 +638,8 @@ fn parse_empty_tag(
                 .last()
                 .is_some_and(|tag| matches!(*tag, ScxmlTag::State(_))) =>
         {
-            let transition =
-                Transition::parse(tag, interner).with_context(|| ParserError::Tag(tag_name))?;
+            let transition = Transition::parse(tag, position, interner)
+                .with_context(|| ParserError::Tag(tag_name))?;
             transition.push(stack)?;
         }
         // we `rev()` the iterator only because we expect the relevant tag to be towards the end of the stack
@@ -525,11 +648,12 @@ fn parse_empty_tag(
             raise.push(stack)?;
         }
         TAG_SEND if stack.last().is_some_and(|tag| tag.is_executable()) => {
-            let send = Send::parse(tag, interner).with_context(|| ParserError::Tag(tag_name))?;
+            let send =
+                Send::parse(tag, position, interner).with_context(|| ParserError::Tag(tag_name))?;
             Executable::Send(send).push(stack)?;
         }
         TAG_ASSIGN if stack.last().is_some_and(|tag| tag.is_executable()) => {
-            let assign = Executable::parse_assign(tag, interner)
+            let assign = Executable::parse_assign(tag, position, interner)
                 .with_context(|| ParserError::Tag(tag_name))?;
             assign.push(stack)?;
         }
@@ -542,7 +666,7 @@ fn parse_empty_tag(
             //     .take()
             //     .ok_or(anyhow::Error::from(ParserError::NoTypeAnnotation))
             //     .with_context(|| reader.buffer_position())?;
-            let param = Param::parse(tag, type_annotation.take(), interner)
+            let param = Param::parse(tag, type_annotation.take(), position, interner)
                 .with_context(|| ParserError::Tag(tag_name))?;
             if let ScxmlTag::Send(send) = stack.last_mut().expect("param must be inside other tag")
             {
@@ -590,6 +714,7 @@ fn parse_start_tag(
     tag_name: String,
     stack: &[ScxmlTag],
     tag: events::BytesStart<'_>,
+    position: SourceLocation,
     interner: &mut Interner,
 ) -> Result<ScxmlTag, anyhow::Error> {
     match tag_name.as_str() {
@@ -613,16 +738,17 @@ fn parse_start_tag(
                 .last()
                 .is_some_and(|tag| matches!(*tag, ScxmlTag::State(_))) =>
         {
-            Transition::parse(tag, interner).map(ScxmlTag::Transition)
+            Transition::parse(tag, position, interner).map(ScxmlTag::Transition)
         }
         TAG_SEND if stack.iter().rev().any(|tag| tag.is_executable()) => {
-            Send::parse(tag, interner).map(ScxmlTag::Send)
+            Send::parse(tag, position, interner).map(ScxmlTag::Send)
         }
         TAG_IF if stack.iter().rev().any(|tag| tag.is_executable()) => If::parse(tag, interner)
             .map(|cond| If {
                 elif: vec![(cond, Vec::new())],
                 r#else: Vec::new(),
                 else_flag: false,
+                position,
             })
             .map(ScxmlTag::If),
         TAG_ONENTRY
@@ -632,6 +758,9 @@ fn parse_start_tag(
         {
             Ok(ScxmlTag::OnEntry(Vec::new()))
         }
+        TAG_SCRIPT if stack.last().is_some_and(|tag| tag.is_executable()) => {
+            Ok(ScxmlTag::Script(String::new(), position))
+        }
         TAG_ONEXIT
             if stack
                 .last()
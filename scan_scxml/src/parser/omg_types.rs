@@ -9,6 +9,7 @@ use quick_xml::{
         attributes::{AttrError, Attribute},
     },
 };
+use scan_core::IntType;
 use std::str;
 
 use crate::parser::{ATTR_ID, TAG_DATA_TYPE_LIST, TAG_ENUMERATION, TAG_LABEL, TAG_STRUCT};
@@ -18,10 +19,16 @@ use crate::parser::{ATTR_TYPE, ConvinceTag, ParserError, TAG_FIELD};
 pub enum OmgType {
     Boolean,
     Int32,
+    Int(IntType),
     F64,
+    String,
     Uri,
     Structure(HashMap<String, String>),
     Enumeration(Vec<String>),
+    // Type of the elements and, for arrays, fixed size.
+    // Sequences (`sequence<T>` or `sequence<T, N>`) have no fixed size,
+    // while arrays (`T[N]`) do.
+    Array(String, Option<usize>),
 }
 
 #[derive(Debug, Clone)]
@@ -30,15 +37,20 @@ pub struct OmgTypes {
 }
 
 impl OmgTypes {
-    pub const BASE_TYPES: [(&'static str, OmgType); 9] = [
+    pub const BASE_TYPES: [(&'static str, OmgType); 14] = [
         ("boolean", OmgType::Boolean),
         ("bool", OmgType::Boolean),
-        ("int8", OmgType::Int32),
-        ("int16", OmgType::Int32),
+        ("int8", OmgType::Int(IntType::I8)),
+        ("int16", OmgType::Int(IntType::I16)),
         ("int32", OmgType::Int32),
-        ("int64", OmgType::Int32),
+        ("int64", OmgType::Int(IntType::I64)),
+        ("uint8", OmgType::Int(IntType::U8)),
+        ("uint16", OmgType::Int(IntType::U16)),
+        ("uint32", OmgType::Int(IntType::U32)),
+        ("uint64", OmgType::Int(IntType::U64)),
         ("float32", OmgType::F64),
         ("float64", OmgType::F64),
+        ("string", OmgType::String),
         ("URI", OmgType::Uri),
     ];
 
@@ -329,5 +329,35 @@ fn parse_predicates(
                 u,
             ))
         }
+        Pmtl::WeakSince(args, l, u) => {
+            let (lhs, rhs) = *args;
+            Ok(Pmtl::WeakSince(
+                Box::new((
+                    parse_predicates(lhs, predicates, interner)?,
+                    parse_predicates(rhs, predicates, interner)?,
+                )),
+                l,
+                u,
+            ))
+        }
+        Pmtl::Trigger(args, l, u) => {
+            let (lhs, rhs) = *args;
+            Ok(Pmtl::Trigger(
+                Box::new((
+                    parse_predicates(lhs, predicates, interner)?,
+                    parse_predicates(rhs, predicates, interner)?,
+                )),
+                l,
+                u,
+            ))
+        }
+        Pmtl::Previous(pmtl) => {
+            parse_predicates(*pmtl, predicates, interner).map(|f| Pmtl::Previous(Box::new(f)))
+        }
+        Pmtl::WeakPrevious(pmtl) => {
+            parse_predicates(*pmtl, predicates, interner).map(|f| Pmtl::WeakPrevious(Box::new(f)))
+        }
+        Pmtl::Count(pmtl, cmp, n, l, u) => parse_predicates(*pmtl, predicates, interner)
+            .map(|f| Pmtl::Count(Box::new(f), cmp, n, l, u)),
     }
 }
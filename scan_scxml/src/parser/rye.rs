@@ -1,7 +1,7 @@
 use anyhow::{anyhow, bail};
 use chumsky::{IterParser, Parser, prelude::*, select};
 use logos::Logos;
-use scan_core::{Pmtl, Time};
+use scan_core::{Comparison, Pmtl, Time};
 
 #[derive(Logos, Debug, PartialEq, Eq, Hash, Clone)]
 #[logos(skip r"[ \t\n]+")]
@@ -27,6 +27,26 @@ pub enum Token {
     #[token("since")]
     Since,
 
+    #[token("B")]
+    #[token("weak_since")]
+    WeakSince,
+
+    #[token("T")]
+    #[token("trigger")]
+    Trigger,
+
+    #[token("Y")]
+    #[token("previous")]
+    Previous,
+
+    #[token("Z")]
+    #[token("weak_previous")]
+    WeakPrevious,
+
+    #[token("C")]
+    #[token("count")]
+    Count,
+
     #[token("&&")]
     #[token("and")]
     And,
@@ -43,6 +63,21 @@ pub enum Token {
     #[token("implies")]
     Implies,
 
+    #[token("<")]
+    Less,
+
+    #[token("<=")]
+    LessEq,
+
+    #[token("==")]
+    Equal,
+
+    #[token(">=")]
+    GreaterEq,
+
+    #[token(">")]
+    Greater,
+
     #[token("(")]
     LParen,
 
@@ -73,6 +108,18 @@ fn parser<'src>() -> impl Parser<'src, &'src [Token], Pmtl<String>, extra::Err<S
         Token::Integer(n) => n as u32,
     };
 
+    let count = select! {
+        Token::Integer(n) => n,
+    };
+
+    let comparison = select! {
+        Token::Less => Comparison::Less,
+        Token::LessEq => Comparison::LessEq,
+        Token::Equal => Comparison::Equal,
+        Token::GreaterEq => Comparison::GreaterEq,
+        Token::Greater => Comparison::Greater,
+    };
+
     let bounds = just(Token::BracketOpen)
         .ignore_then(integer.or_not().map(|p| p.unwrap_or(Time::MIN)))
         .then_ignore(just(Token::Colon))
@@ -91,15 +138,32 @@ fn parser<'src>() -> impl Parser<'src, &'src [Token], Pmtl<String>, extra::Err<S
                 Token::False => Pmtl::False,
             };
 
-            parenthesized.or(predicate)
+            // Counting formulae, e.g., `count[0:60]({ retry }) <= 3`
+            let counting = just(Token::Count)
+                .ignore_then(
+                    bounds
+                        .clone()
+                        .or_not()
+                        .map(|b| b.unwrap_or((Time::MIN, Time::MAX))),
+                )
+                .then(parenthesized.clone().or(predicate))
+                .then(comparison)
+                .then(count)
+                .map(|((((l, u), arg), cmp), n)| Pmtl::Count(Box::new(arg), cmp, n, l, u));
+
+            counting.or(parenthesized).or(predicate)
         };
 
         let unary = just(Token::Not)
             .or(just(Token::Once))
             .or(just(Token::Historically))
+            .or(just(Token::Previous))
+            .or(just(Token::WeakPrevious))
             .repeated()
             .foldr(atom, |op, rhs| match op {
                 Token::Not => Pmtl::Not(Box::new(rhs)),
+                Token::Previous => Pmtl::Previous(Box::new(rhs)),
+                Token::WeakPrevious => Pmtl::WeakPrevious(Box::new(rhs)),
                 Token::Once => Pmtl::Once(Box::new(rhs), Time::MIN, Time::MAX),
                 Token::Historically => Pmtl::Historically(Box::new(rhs), Time::MIN, Time::MAX),
                 _ => unreachable!(),
@@ -120,6 +184,8 @@ fn parser<'src>() -> impl Parser<'src, &'src [Token], Pmtl<String>, extra::Err<S
                 .or(just(Token::Or))
                 .or(just(Token::Implies))
                 .or(just(Token::Since))
+                .or(just(Token::WeakSince))
+                .or(just(Token::Trigger))
                 .then(temp_unary)
                 .repeated(),
             |lhs, (op, rhs)| match op {
@@ -127,13 +193,25 @@ fn parser<'src>() -> impl Parser<'src, &'src [Token], Pmtl<String>, extra::Err<S
                 Token::Or => Pmtl::Or(vec![lhs, rhs]),
                 Token::Implies => Pmtl::Implies(Box::new((lhs, rhs))),
                 Token::Since => Pmtl::Since(Box::new((lhs, rhs)), Time::MIN, Time::MAX),
+                Token::WeakSince => Pmtl::WeakSince(Box::new((lhs, rhs)), Time::MIN, Time::MAX),
+                Token::Trigger => Pmtl::Trigger(Box::new((lhs, rhs)), Time::MIN, Time::MAX),
                 _ => unreachable!(),
             },
         );
 
         binary.clone().foldl(
-            just(Token::Since).then(bounds).then(binary).repeated(),
-            |lhs, ((_op, (l, u)), rhs)| Pmtl::Since(Box::new((lhs, rhs)), l, u),
+            just(Token::Since)
+                .or(just(Token::WeakSince))
+                .or(just(Token::Trigger))
+                .then(bounds)
+                .then(binary)
+                .repeated(),
+            |lhs, ((op, (l, u)), rhs)| match op {
+                Token::Since => Pmtl::Since(Box::new((lhs, rhs)), l, u),
+                Token::WeakSince => Pmtl::WeakSince(Box::new((lhs, rhs)), l, u),
+                Token::Trigger => Pmtl::Trigger(Box::new((lhs, rhs)), l, u),
+                _ => unreachable!(),
+            },
         )
     })
     .then_ignore(end())
@@ -299,4 +377,60 @@ mod test {
             assert!(matches!(rhs, Pmtl::Atom(_)));
         }
     }
+
+    #[test]
+    fn bounded_count() {
+        let count = parse("count[0:60]({ retry }) <= 3").expect("parse formula");
+        assert!(matches!(
+            count,
+            Pmtl::Count(_, Comparison::LessEq, 3, 0, 60)
+        ));
+        if let Pmtl::Count(atom, _, _, _, _) = count {
+            assert!(matches!(*atom, Pmtl::Atom(_)));
+        }
+    }
+
+    #[test]
+    fn unbounded_count() {
+        let count = parse("C { retry } > 1 -> { failed }").expect("parse formula");
+        assert!(matches!(count, Pmtl::Implies(_)));
+        if let Pmtl::Implies(args) = count {
+            let (lhs, rhs) = *args;
+            assert!(matches!(
+                lhs,
+                Pmtl::Count(_, Comparison::Greater, 1, Time::MIN, Time::MAX)
+            ));
+            assert!(matches!(rhs, Pmtl::Atom(_)));
+        }
+    }
+
+    #[test]
+    fn previous() {
+        let previous = parse("Y Z { var > 10 }").expect("parse formula");
+        assert!(matches!(previous, Pmtl::Previous(_)));
+        if let Pmtl::Previous(weak) = previous {
+            assert!(matches!(*weak, Pmtl::WeakPrevious(_)));
+        }
+    }
+
+    #[test]
+    fn trigger() {
+        let trigger =
+            parse("{ var > 10 } trigger[0:5] previous { other_var == 1 }").expect("parse formula");
+        assert!(matches!(trigger, Pmtl::Trigger(_, 0, 5)));
+        if let Pmtl::Trigger(args, _, _) = trigger {
+            let (lhs, rhs) = *args;
+            assert!(matches!(lhs, Pmtl::Atom(_)));
+            assert!(matches!(rhs, Pmtl::Previous(_)));
+        }
+    }
+
+    #[test]
+    fn weak_since() {
+        let weak_since = parse("{ var > 10 } B { other_var == 1 }").expect("parse formula");
+        assert!(matches!(
+            weak_since,
+            Pmtl::WeakSince(_, Time::MIN, Time::MAX)
+        ));
+    }
 }
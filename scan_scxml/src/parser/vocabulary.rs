@@ -10,6 +10,8 @@ pub const TAG_STRUCT: &str = "structure";
 pub const TAG_FIELD: &str = "field";
 pub const TAG_PROCESS: &str = "process";
 pub const TAG_PROCESS_LIST: &str = "processList";
+pub const TAG_FAULT_LIST: &str = "faultList";
+pub const TAG_FAULT: &str = "fault";
 pub const TAG_STATE: &str = "state";
 pub const TAG_SCXML: &str = "scxml";
 pub const ATTR_INITIAL: &str = "initial";
@@ -23,6 +25,10 @@ pub const ATTR_TYPE: &str = "type";
 pub const ATTR_PARAM: &str = "param";
 pub const ATTR_MOC: &str = "moc";
 pub const ATTR_PATH: &str = "path";
+pub const ATTR_PROCESS: &str = "process";
+pub const ATTR_DROP: &str = "drop";
+pub const ATTR_DUPLICATE: &str = "duplicate";
+pub const ATTR_REORDER: &str = "reorder";
 // pub const TAG_VERSION: &str = "version";
 // pub const TAG_NAME: &str = "name";
 // pub const TAG_XMLNS: &str = "xmlns";
@@ -44,10 +50,12 @@ pub const ATTR_COND: &str = "cond";
 pub const TAG_ONENTRY: &str = "onentry";
 pub const TAG_ONEXIT: &str = "onexit";
 // pub const TAG_NULL: &str = "NULL";
-// pub const TAG_SCRIPT: &str = "script";
+pub const TAG_SCRIPT: &str = "script";
 pub const TAG_ASSIGN: &str = "assign";
 pub const ATTR_LOCATION: &str = "location";
 pub const ATTR_EXPR: &str = "expr";
+pub const ATTR_LOWER_BOUND: &str = "lower_bound";
+pub const ATTR_UPPER_BOUND: &str = "upper_bound";
 pub const TAG_RAISE: &str = "raise";
 pub const TAG_SEND: &str = "send";
 pub const TAG_PARAM: &str = "param";
@@ -95,7 +95,7 @@ impl Tracer<Event> for TracePrinter {
                     self.model
                         .ports
                         .iter()
-                        .map(|(name, t)| format!("{name}: {t:?}")),
+                        .map(|(name, _, t)| format!("{name}: {t:?}")),
                 ),
             )
             .expect("write header");
@@ -103,6 +103,18 @@ impl Tracer<Event> for TracePrinter {
     }
 
     fn trace<'a, I: IntoIterator<Item = &'a Val>>(&mut self, event: &Event, time: Time, ports: I) {
+        // Broadcasts are reported as the messages sent to each subscriber.
+        if let Some(subscribers) = self.model.broadcasts.get(&event.channel).cloned() {
+            let ports = ports.into_iter().collect::<Vec<_>>();
+            for channel in subscribers {
+                let event = Event {
+                    channel,
+                    ..event.clone()
+                };
+                self.trace(&event, time, ports.iter().copied());
+            }
+            return;
+        }
         let mut fields = Vec::new();
         let time = time.to_string();
         let mut action = String::new();
@@ -112,13 +124,33 @@ impl Tracer<Event> for TracePrinter {
         let mut param_name = String::new();
         let mut param_value = String::new();
         fields.push(time.as_str());
+        // Faults are reported next to the kind of event,
+        // which is otherwise treated as the communication they affect.
+        let (fault, event_type) = match event.event_type {
+            EventType::Dropped(ref val) => {
+                (Some(String::from("dropped")), EventType::Send(val.clone()))
+            }
+            EventType::Duplicated(ref val) => (
+                Some(String::from("duplicated")),
+                EventType::Send(val.clone()),
+            ),
+            EventType::Delayed(ref val, delay) => (
+                Some(format!("delayed {delay}")),
+                EventType::Send(val.clone()),
+            ),
+            EventType::Reordered(ref val) => (
+                Some(String::from("reordered")),
+                EventType::Receive(val.clone()),
+            ),
+            ref event_type => (None, event_type.clone()),
+        };
 
         if let Some((src, trg, event_idx, param)) = self.model.parameters.get(&event.channel) {
             origin_name = self.model.fsm_names.get(src).unwrap().to_owned();
             target_name = self.model.fsm_names.get(trg).unwrap().to_owned();
             event_name = self.model.events.get(*event_idx).unwrap().clone();
             param_name = param.to_owned();
-            match event.event_type {
+            match event_type {
                 EventType::Send(ref val) => {
                     action = "S".to_string();
                     param_value = format!("{val:?}");
@@ -127,11 +159,22 @@ impl Tracer<Event> for TracePrinter {
                     action = "R".to_string();
                     param_value = format!("{val:?}");
                 }
-                EventType::ProbeEmptyQueue | EventType::ProbeFullQueue => return,
+                EventType::Handshake(..)
+                | EventType::ProbeEmptyQueue
+                | EventType::ProbeFullQueue
+                | EventType::Dropped(_)
+                | EventType::Duplicated(_)
+                | EventType::Delayed(..)
+                | EventType::Reordered(_) => return,
             }
-        } else if let Some(trg) = self.model.ext_queues.get(&event.channel) {
+        } else if let Some(trg) = self.model.ext_queues.get(&event.channel).or_else(|| {
+            self.model
+                .fault_queues
+                .get(&event.channel)
+                .map(|(trg, _)| trg)
+        }) {
             target_name = self.model.fsm_names.get(trg).unwrap().to_owned();
-            match event.event_type {
+            match event_type {
                 EventType::Send(ref val) => {
                     action = "S".to_string();
                     if let Val::Tuple(e) = val {
@@ -170,12 +213,18 @@ impl Tracer<Event> for TracePrinter {
                         panic!("events should be pairs");
                     }
                 }
-                EventType::ProbeEmptyQueue | EventType::ProbeFullQueue => return,
+                EventType::Handshake(..)
+                | EventType::ProbeEmptyQueue
+                | EventType::ProbeFullQueue
+                | EventType::Dropped(_)
+                | EventType::Duplicated(_)
+                | EventType::Delayed(..)
+                | EventType::Reordered(_) => return,
             }
         } else if self.model.int_queues.contains(&event.channel) {
             origin_name = self.model.fsm_names.get(&event.pg_id).unwrap().to_owned();
             target_name = origin_name.clone();
-            match event.event_type {
+            match event_type {
                 EventType::Send(ref val) => {
                     action = "S".to_string();
                     if let Val::Integer(sent_event) = val {
@@ -192,12 +241,18 @@ impl Tracer<Event> for TracePrinter {
                         panic!("events should be indexed by integer");
                     }
                 }
-                EventType::ProbeEmptyQueue | EventType::ProbeFullQueue => return,
+                EventType::Handshake(..)
+                | EventType::ProbeEmptyQueue
+                | EventType::ProbeFullQueue
+                | EventType::Dropped(_)
+                | EventType::Duplicated(_)
+                | EventType::Delayed(..)
+                | EventType::Reordered(_) => return,
             }
         } else {
             event_name = String::new();
             param_name = String::new();
-            match event.event_type {
+            match event_type {
                 EventType::Send(ref val) => {
                     origin_name = self.model.fsm_names.get(&event.pg_id).unwrap().to_owned();
                     target_name = format!("{:?}", event.channel);
@@ -208,9 +263,22 @@ impl Tracer<Event> for TracePrinter {
                     target_name = self.model.fsm_names.get(&event.pg_id).unwrap().to_owned();
                     param_value = format!("{val:?}");
                 }
-                EventType::ProbeEmptyQueue | EventType::ProbeFullQueue => return,
+                EventType::Handshake(receiver, ref val) => {
+                    origin_name = self.model.fsm_names.get(&event.pg_id).unwrap().to_owned();
+                    target_name = self.model.fsm_names.get(&receiver).unwrap().to_owned();
+                    param_value = format!("{val:?}");
+                }
+                EventType::ProbeEmptyQueue
+                | EventType::ProbeFullQueue
+                | EventType::Dropped(_)
+                | EventType::Duplicated(_)
+                | EventType::Delayed(..)
+                | EventType::Reordered(_) => return,
             }
         }
+        if let Some(fault) = fault {
+            action = format!("{action} ({fault})").trim_start().to_owned();
+        }
 
         self.writer
             .as_mut()
@@ -271,13 +339,28 @@ fn format_val(val: &Val) -> String {
         Val::Boolean(true) => "true".to_string(),
         Val::Boolean(false) => "false".to_string(),
         Val::Integer(i) => i.to_string(),
+        Val::Int(_, i) => i.to_string(),
         Val::Float(ordered_float) => ordered_float.to_string(),
+        Val::String(s) => s.clone(),
+        Val::Enum(r#enum, idx) => r#enum
+            .label(*idx)
+            .map_or_else(|| idx.to_string(), str::to_owned),
         Val::Tuple(vec) => {
             vec.iter()
                 .fold("(".to_string(), |acc, v| acc + format_val(v).as_str())
                 + ")"
         }
-        Val::List(_, vec) => {
+        Val::Record(record, vals) => {
+            let fields = record
+                .fields()
+                .iter()
+                .zip(vals)
+                .map(|((name, _), val)| format!("{name}: {}", format_val(val)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{{{fields}}}")
+        }
+        Val::List(_, vec) | Val::Array(_, vec) => {
             vec.iter()
                 .fold("[".to_string(), |acc, v| acc + format_val(v).as_str())
                 + "]"
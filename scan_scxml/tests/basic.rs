@@ -1,3 +1,5 @@
+use scan_core::{Overflow, RunOutcome};
+use scan_scxml::ColumnMapping;
 use std::path::Path;
 
 #[test]
@@ -55,6 +57,11 @@ fn param_tennis() -> anyhow::Result<()> {
     test(Path::new("./tests/assets/test_param_tennis/model.xml"))
 }
 
+#[test]
+fn broadcast() -> anyhow::Result<()> {
+    test(Path::new("./tests/assets/test_broadcast/model.xml"))
+}
+
 #[test]
 fn conditional() -> anyhow::Result<()> {
     test(Path::new("./tests/assets/test_if/model.xml"))
@@ -65,8 +72,65 @@ fn elif() -> anyhow::Result<()> {
     test(Path::new("./tests/assets/test_elif/model.xml"))
 }
 
+#[test]
+fn script() -> anyhow::Result<()> {
+    test(Path::new("./tests/assets/test_script/model.xml"))
+}
+
+#[test]
+fn faults() -> anyhow::Result<()> {
+    test(Path::new("./tests/assets/test_faults/model.xml"))
+}
+
+#[test]
+fn distributions() -> anyhow::Result<()> {
+    let path = Path::new("./tests/assets/test_distributions/model.xml");
+    let (scan, ..) = scan_scxml::load(path, Overflow::default(), false)?;
+    scan.adaptive::<scan_scxml::TracePrinter>(0.95, 0.01, 100, None);
+    // Samples are always within the support of their distribution
+    assert!(scan.successes() > 0);
+    assert_eq!(scan.failures(), 0);
+    Ok(())
+}
+
+#[test]
+fn type_error() {
+    let Err(err) = scan_scxml::load(
+        Path::new("./tests/assets/test_type_error/model.xml"),
+        Overflow::default(),
+        false,
+    ) else {
+        panic!("badly typed model must not load");
+    };
+    // Variables are named as in the model
+    let msg = format!("{err:#}");
+    assert!(msg.contains("in expression `counter`"), "{msg}");
+    assert!(
+        msg.contains("type mismatch: expected bool, found int"),
+        "{msg}"
+    );
+}
+
+#[test]
+fn check_trace() -> anyhow::Result<()> {
+    let model = Path::new("./tests/assets/test_check_trace/model.xml");
+    let checker = scan_scxml::load_checker(model, ColumnMapping::default(), Overflow::default())?;
+    let success = checker.check(Path::new("./tests/assets/test_check_trace/success.csv"))?;
+    assert!(matches!(success, RunOutcome::Success));
+    let fail = checker.check(Path::new("./tests/assets/test_check_trace/fail.csv.gz"))?;
+    assert!(matches!(fail, RunOutcome::Fail(0)));
+    // The port's column has a different name and has to be mapped explicitly
+    let renamed = Path::new("./tests/assets/test_check_trace/renamed.csv");
+    assert!(checker.check(renamed).is_err());
+    let mut mapping = ColumnMapping::default();
+    mapping.map("result", "val".into());
+    let checker = scan_scxml::load_checker(model, mapping, Overflow::default())?;
+    assert!(matches!(checker.check(renamed)?, RunOutcome::Success));
+    Ok(())
+}
+
 fn test(path: &Path) -> anyhow::Result<()> {
-    let (scan, ..) = scan_scxml::load(path)?;
+    let (scan, ..) = scan_scxml::load(path, Overflow::default(), false)?;
     scan.adaptive::<scan_scxml::TracePrinter>(0.95, 0.01, 100, None);
     Ok(())
 }
@@ -1,11 +1,17 @@
 use anyhow::Context;
 use anyhow::anyhow;
 use anyhow::bail;
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use scan_core::{Oracle, Scan, adaptive_bound, okamoto_bound};
+use scan_core::{
+    Oracle, Overflow, RunOutcome, Scan, SourceLocation, adaptive_bound, okamoto_bound,
+};
 use serde::Serialize;
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 /// Supported model specification formats
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -34,6 +40,27 @@ enum Bar {
     Ascii,
 }
 
+/// Semantics of sized integers overflowing their type
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum OverflowSemantics {
+    /// Values wrap around, as in two's complement arithmetic
+    Wrap,
+    /// Values are clamped to the bounds of their type
+    Saturate,
+    /// Overflowing ends the run with an error
+    Error,
+}
+
+impl From<OverflowSemantics> for Overflow {
+    fn from(value: OverflowSemantics) -> Self {
+        match value {
+            OverflowSemantics::Wrap => Overflow::Wrap,
+            OverflowSemantics::Saturate => Overflow::Saturate,
+            OverflowSemantics::Error => Overflow::Error,
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct Report {
     precision: f64,
@@ -46,6 +73,67 @@ struct Report {
     property_failures: HashMap<String, u32>,
 }
 
+#[derive(Serialize)]
+struct TraceReport {
+    path: String,
+    verdict: Verdict,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Verdict {
+    Success,
+    Failure(String),
+    Inconclusive,
+    Error(String),
+}
+
+#[derive(Serialize)]
+struct CheckReport {
+    traces: Vec<TraceReport>,
+    successes: u32,
+    failures: u32,
+    inconclusive: u32,
+    errors: u32,
+    property_failures: HashMap<String, u32>,
+}
+
+#[derive(Serialize)]
+struct AnalysisReport {
+    diagnostics: Vec<String>,
+}
+
+/// Commands other than statistical model checking
+#[derive(Clone, Subcommand)]
+enum Command {
+    /// Check the model's properties over recorded execution traces
+    Check {
+        /// Paths of the traces (csv files, optionally gz-compressed) or of folders containing them
+        #[arg(value_hint = clap::ValueHint::AnyPath, required = true)]
+        traces: Vec<PathBuf>,
+        /// Map a trace field (time, action, origin, target, event) or a port to a csv column,
+        /// as FIELD=COLUMN (by default, traces are in SCAN's own format)
+        #[arg(short, long = "map", value_parser = parse_mapping)]
+        map: Vec<(String, String)>,
+    },
+    /// Statically analyze the model, reporting potential modelling mistakes
+    /// (e.g., unreachable locations or channels that are never received from)
+    Analyze,
+    /// Write the model's automata to a file in Graphviz DOT format
+    Dot {
+        /// Path of the DOT file to write
+        #[arg(value_hint = clap::ValueHint::FilePath, default_value = "model.dot")]
+        output: PathBuf,
+    },
+}
+
+fn parse_mapping(arg: &str) -> anyhow::Result<(String, String)> {
+    let (field, column) = arg
+        .split_once('=')
+        .ok_or(anyhow!("mapping '{arg}' is not of the form FIELD=COLUMN"))?;
+    Ok((field.trim().to_owned(), column.trim().to_owned()))
+}
+
 /// A statistical model checker for large concurrent systems
 #[derive(Clone, Parser)]
 #[deny(missing_docs)]
@@ -69,16 +157,37 @@ pub struct Cli {
     /// Saves execution traces in gz-compressed csv format
     #[arg(long = "traces", default_value = "false")]
     traces: bool,
+    /// Counts runs ending with an error (e.g., an out-of-bounds assignment) as failures
+    #[arg(long = "fail-on-error", default_value = "false")]
+    fail_on_error: bool,
+    /// Semantics of sized integers overflowing their type
+    #[arg(value_enum, long, default_value = "wrap")]
+    overflow: OverflowSemantics,
+    /// Compresses the chains of internal steps of the FSMs (SCXML only)
+    #[arg(long = "compress", default_value = "false")]
+    compress: bool,
     /// Output format of verification report
     #[arg(short, long, default_value = "human")]
     out: Output,
     /// Progress bar during verification
     #[arg(value_enum, short, long)]
     bar: Option<Bar>,
+    /// Command to execute instead of verifying the model
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
 impl Cli {
     pub fn run(self) -> anyhow::Result<()> {
+        if let Some(Command::Check { traces, map }) = &self.command {
+            return self.run_check(traces, map);
+        }
+        if let Some(Command::Analyze) = &self.command {
+            return self.run_analyze();
+        }
+        if let Some(Command::Dot { output }) = &self.command {
+            return self.run_dot(output);
+        }
         if let Some(format) = self.format {
             match format {
                 Format::Scxml => self.run_scxml(),
@@ -106,7 +215,8 @@ impl Cli {
     fn run_scxml(self) -> anyhow::Result<()> {
         use scan_scxml::*;
 
-        let (scan, scxml_model) = load(&self.path)?;
+        let (scan, scxml_model) =
+            load(&self.path, self.overflow.into(), self.compress).map_err(located)?;
         let scxml_model = Arc::new(scxml_model);
         let guarantees = scxml_model.guarantees.clone();
         let tracer = self.traces.then(|| TracePrinter::new(scxml_model));
@@ -116,7 +226,7 @@ impl Cli {
     fn run_jani(self) -> anyhow::Result<()> {
         use scan_jani::*;
 
-        let (scan, jani_model) = load(&self.path)?;
+        let (scan, jani_model) = load(&self.path, self.overflow.into()).map_err(located)?;
         let jani_model = Arc::new(jani_model);
         let guarantees = jani_model.guarantees.clone();
         let tracer = self.traces.then(|| TracePrinter::new(jani_model));
@@ -127,7 +237,8 @@ impl Cli {
     fn run_scxml_p2(&self) -> anyhow::Result<()> {
         use scan_scxml::*;
         
-        let (scan, scxml_model) = load(&self.path)?;
+        let (scan, scxml_model) =
+            load(&self.path, self.overflow.into(), self.compress).map_err(located)?;
         let scxml_model = Arc::new(scxml_model);
         let guarantees = scxml_model.guarantees.clone();
         let tracer = self.traces.then(|| TracePrinter::new(scxml_model));
@@ -135,10 +246,122 @@ impl Cli {
     }
 
 
+    fn run_check(&self, traces: &[PathBuf], map: &[(String, String)]) -> anyhow::Result<()> {
+        use scan_scxml::*;
+
+        if self.format.is_some_and(|format| format == Format::Jani) {
+            bail!("checking traces is only supported for SCXML models");
+        }
+        let mut mapping = ColumnMapping::default();
+        for (field, column) in map {
+            mapping.map(field, column.to_owned());
+        }
+        let checker = load_checker(&self.path, mapping, self.overflow.into()).map_err(located)?;
+        let guarantees = &checker.scxml_model().guarantees;
+        let mut files = Vec::new();
+        for path in traces {
+            collect_traces(path, &mut files)?;
+        }
+
+        let mut report = CheckReport {
+            traces: Vec::new(),
+            successes: 0,
+            failures: 0,
+            inconclusive: 0,
+            errors: 0,
+            property_failures: guarantees.iter().map(|g| (g.clone(), 0)).collect(),
+        };
+        for file in files {
+            let verdict = match checker.check(&file) {
+                Ok(RunOutcome::Success) => {
+                    report.successes += 1;
+                    Verdict::Success
+                }
+                Ok(RunOutcome::Fail(violation)) => {
+                    report.failures += 1;
+                    let property = guarantees[violation].clone();
+                    *report
+                        .property_failures
+                        .get_mut(&property)
+                        .expect("property") += 1;
+                    Verdict::Failure(property)
+                }
+                Ok(RunOutcome::Incomplete) => {
+                    report.inconclusive += 1;
+                    Verdict::Inconclusive
+                }
+                Err(err) => {
+                    report.errors += 1;
+                    Verdict::Error(format!("{err:#}"))
+                }
+            };
+            report.traces.push(TraceReport {
+                path: file.display().to_string(),
+                verdict,
+            });
+        }
+
+        match self.out {
+            Output::Human => print_check_report(&report, guarantees),
+            Output::Json => {
+                let report = serde_json::ser::to_string_pretty(&report)
+                    .context(anyhow!("failed report serialization"))?;
+                println!("{report}");
+            }
+        }
+        Ok(())
+    }
+
+    fn run_analyze(&self) -> anyhow::Result<()> {
+        let jani = match self.format {
+            Some(format) => format == Format::Jani,
+            None => self.path.extension().is_some_and(|ext| ext == "jani"),
+        };
+        let diagnostics = if jani {
+            scan_jani::analyze(&self.path)
+        } else {
+            scan_scxml::analyze(&self.path)
+        }
+        .map_err(located)?;
+        let report = AnalysisReport { diagnostics };
+
+        match self.out {
+            Output::Human => {
+                for diagnostic in &report.diagnostics {
+                    println!("{diagnostic}");
+                }
+                println!("Found {} potential issues", report.diagnostics.len());
+            }
+            Output::Json => {
+                let report = serde_json::ser::to_string_pretty(&report)
+                    .context(anyhow!("failed report serialization"))?;
+                println!("{report}");
+            }
+        }
+        Ok(())
+    }
+
+    fn run_dot(&self, output: &Path) -> anyhow::Result<()> {
+        let jani = match self.format {
+            Some(format) => format == Format::Jani,
+            None => self.path.extension().is_some_and(|ext| ext == "jani"),
+        };
+        let dot = if jani {
+            scan_jani::dot(&self.path)
+        } else {
+            scan_scxml::dot(&self.path)
+        }
+        .map_err(located)?;
+        std::fs::write(output, dot)
+            .with_context(|| format!("failed to write DOT file '{}'", output.display()))?;
+        println!("Model written to '{}'", output.display());
+        Ok(())
+    }
+
     fn run_scan<E, Err, Ts, Tr, O>(
         self,
-        scan: Scan<E, Err, Ts, O>,
-        guarantees: Vec<String>,
+        mut scan: Scan<E, Err, Ts, O>,
+        mut guarantees: Vec<String>,
         tracer: Option<Tr>,
     ) -> anyhow::Result<()>
     where
@@ -148,6 +371,11 @@ impl Cli {
         E: Clone + Send + Sync + 'static,
         O: Oracle + 'static,
     {
+        if self.fail_on_error {
+            // Run errors are reported as violations of an additional property.
+            scan.set_error_property(guarantees.len());
+            guarantees.push(String::from("run errors"));
+        }
         let mut handle = None;
         let model_name = self
             .path
@@ -261,6 +489,94 @@ impl Cli {
     }
 }
 
+// Prefixes model errors with the source location they refer to, if known,
+// so that they are rendered as `FILE:LINE:COLUMN: ERROR`.
+// The location replaces its own context in the chain of causes, so that it is reported only once.
+fn located(err: anyhow::Error) -> anyhow::Error {
+    let Some(location) = err.downcast_ref::<SourceLocation>().map(ToString::to_string) else {
+        return err;
+    };
+    let chain = err.chain().map(ToString::to_string).collect::<Vec<_>>();
+    let Some(idx) = chain.iter().position(|msg| *msg == location) else {
+        return err;
+    };
+    let (outer, inner) = chain.split_at(idx);
+    let Some((cause, contexts)) = inner[1..].split_last() else {
+        return err;
+    };
+    // Rebuild the chain from the root cause, prefixing the location to the outermost of its causes
+    let mut located = if let Some((first, contexts)) = contexts.split_first() {
+        contexts
+            .iter()
+            .rev()
+            .fold(anyhow!("{cause}"), |err, context| err.context(context.clone()))
+            .context(format!("{location}: {first}"))
+    } else {
+        anyhow!("{location}: {cause}")
+    };
+    for context in outer.iter().rev() {
+        located = located.context(context.clone());
+    }
+    located
+}
+
+// Collects csv traces (optionally gz-compressed) in the given path, searching folders recursively.
+fn collect_traces(path: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    if path.is_dir() {
+        let mut entries = std::fs::read_dir(path)
+            .with_context(|| format!("failed to read folder '{}'", path.display()))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort_unstable();
+        for entry in entries {
+            let hidden = entry
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with('.'));
+            let is_trace = entry
+                .to_str()
+                .is_some_and(|name| name.ends_with(".csv") || name.ends_with(".csv.gz"));
+            if !hidden && (entry.is_dir() || is_trace) {
+                collect_traces(&entry, files)?;
+            }
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+fn print_check_report(report: &CheckReport, guarantees: &[String]) {
+    for trace in report.traces.iter() {
+        match &trace.verdict {
+            Verdict::Success => println!("{}: success", trace.path),
+            Verdict::Failure(property) => println!("{}: {property} fails", trace.path),
+            Verdict::Inconclusive => println!("{}: inconclusive (assume violated)", trace.path),
+            Verdict::Error(err) => println!("{}: error: {err}", trace.path),
+        }
+    }
+    let checked = report.successes + report.failures + report.inconclusive;
+    println!(
+        "Checked {checked} traces with {} successes, {} failures, {} inconclusive ({} errors)",
+        report.successes, report.failures, report.inconclusive, report.errors
+    );
+    // Inconclusive traces do not count towards success rates
+    let runs = report.successes + report.failures;
+    if runs > 0 {
+        for property in guarantees {
+            let violations = report.property_failures.get(property).copied().unwrap_or(0);
+            println!(
+                "{property} success rate: {:.2}",
+                (runs - violations) as f64 / runs as f64
+            );
+        }
+        println!(
+            "Overall success rate: {:.2}",
+            report.successes as f64 / runs as f64
+        );
+    }
+}
+
 fn print_progress_bar<E, Err, Ts, O>(
     bar: Bar,
     confidence: f64,